@@ -5,6 +5,7 @@ use apparatus::engine::apparatus::{Apparatus, ApparatusSettings};
 use apparatus::engine::game::Game;
 use apparatus::engine::key::Key;
 use apparatus::errors::ApparatusError;
+use apparatus::launcher::LaunchOptions;
 
 struct Geometry {
     option: u32,
@@ -68,6 +69,7 @@ fn main() -> Result<()> {
     let settings = ApparatusSettings::default()
         .with_pixel_size(4, 4)
         .with_screen_size(320, 180);
+    let settings = LaunchOptions::from_env()?.apply(settings);
     let app = Apparatus::new("Geometry", settings)?;
     app.run::<Geometry>()?;
 
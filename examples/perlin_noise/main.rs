@@ -38,11 +38,12 @@ use anyhow::Result;
 use rand::prelude::*;
 
 use apparatus::color;
-use apparatus::color::Color;
 use apparatus::engine::apparatus::{Apparatus, ApparatusSettings};
 use apparatus::engine::game::Game;
 use apparatus::engine::key::Key;
+use apparatus::engine::sprite::Sprite;
 use apparatus::errors::ApparatusError;
+use apparatus::launcher::LaunchOptions;
 use apparatus::maths::lerp;
 
 fn generate_noise_seed(output_size: usize, noise_seed: &mut Vec<f32>, rng: &mut ThreadRng) {
@@ -57,7 +58,7 @@ fn generate_perlin_noise_1d(
     octaves: usize,
     bias: f32,
     seed: &[f32],
-    output: &mut Vec<f32>,
+    output: &mut [f32],
 ) {
     let bias = 1.0 / bias;
 
@@ -88,7 +89,7 @@ fn generate_perlin_noise_2d(
     octaves: usize,
     bias: f32,
     seed: &[f32],
-    output: &mut Vec<f32>,
+    output: &mut [f32],
 ) {
     let count = width;
     let bias = 1.0 / bias;
@@ -275,14 +276,20 @@ impl Game for PerlinNoise {
                 }
             }
             Mode::TwoDimensionsGreyscale => {
-                for x in 0..self.output_width {
-                    for y in 0..self.output_height {
-                        let noise = self.perlin_noise_2d[y * self.output_width + x];
+                let data = self
+                    .perlin_noise_2d
+                    .iter()
+                    .flat_map(|noise| {
                         let channel = (noise * 255.0) as u8;
-                        let color = Color::rgba(channel, channel, channel, 255);
-                        app.draw(x as f32, y as f32, color);
-                    }
-                }
+                        [channel, channel, channel, 255]
+                    })
+                    .collect();
+                let texture = Sprite::from_raw_rgba(
+                    self.output_width as u32,
+                    self.output_height as u32,
+                    data,
+                );
+                app.draw_sprite(0.0, 0.0, &texture);
             }
         }
     }
@@ -292,6 +299,7 @@ fn main() -> Result<()> {
     let settings = ApparatusSettings::default()
         .with_screen_size(250, 180)
         .with_pixel_size(2, 2);
+    let settings = LaunchOptions::from_env()?.apply(settings);
     let app = Apparatus::new("Perlin Noise", settings)?;
     app.run::<PerlinNoise>()?;
 
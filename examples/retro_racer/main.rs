@@ -27,9 +27,6 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY
 // WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::collections::VecDeque;
-use std::time::Duration;
-
 use anyhow::Result;
 
 use apparatus::color;
@@ -38,22 +35,11 @@ use apparatus::engine::apparatus::{Apparatus, ApparatusSettings};
 use apparatus::engine::game::Game;
 use apparatus::engine::key::Key;
 use apparatus::engine::sprite::Sprite;
+use apparatus::engine::track::{format_lap_time, LapTimer, Track};
 use apparatus::errors::ApparatusError;
-use apparatus::maths::clamp;
-
-struct TrackSegment {
-    curvature: f32,
-    distance: f32,
-}
-
-impl From<(f32, f32)> for TrackSegment {
-    fn from((curvature, distance): (f32, f32)) -> Self {
-        Self {
-            curvature,
-            distance,
-        }
-    }
-}
+use apparatus::launcher::LaunchOptions;
+use apparatus::maths::{clamp, exp_decay};
+use apparatus::renderer::software_2d::{Flip, TextStyle};
 
 enum Direction {
     Forward,
@@ -70,11 +56,9 @@ struct RetroRacer {
     target_curvature: f32,
     track_curvature: f32,
     player_curvature: f32,
-    track_distance: f32,
-    current_lap_time: Duration,
-    track: Vec<TrackSegment>,
+    track: Track,
     track_segment: usize,
-    lap_times: VecDeque<Duration>,
+    lap_timer: LapTimer,
 }
 
 impl Game for RetroRacer {
@@ -82,38 +66,16 @@ impl Game for RetroRacer {
         let mut sprites = Vec::new();
 
         let car_sprite_bytes = include_bytes!("assets/red_racer_32x32.png");
-        let car_sprite = Sprite::from_bytes(car_sprite_bytes);
+        let car_sprite = Sprite::try_from_bytes(car_sprite_bytes)?;
         sprites.push(car_sprite);
 
         let car_left_sprite_bytes = include_bytes!("assets/red_racer_left_32x32.png");
-        let car_left_sprite = Sprite::from_bytes(car_left_sprite_bytes);
+        let car_left_sprite = Sprite::try_from_bytes(car_left_sprite_bytes)?;
         sprites.push(car_left_sprite);
 
-        let car_right_sprite_bytes = include_bytes!("assets/red_racer_right_32x32.png");
-        let car_right_sprite = Sprite::from_bytes(car_right_sprite_bytes);
-        sprites.push(car_right_sprite);
-
-        let track: Vec<TrackSegment> = [
-            (0.0, 10.0),
-            (0.0, 200.0),
-            (1.0, 200.0),
-            (0.0, 400.0),
-            (-1.0, 100.0),
-            (0.0, 200.0),
-            (-1.0, 200.0),
-            (1.0, 200.0),
-            (0.0, 200.0),
-            (0.2, 500.0),
-            (0.0, 200.0),
-        ]
-        .into_iter()
-        .map(|t| t.into())
-        .collect();
-
-        let track_distance = track
-            .iter()
-            .map(|TrackSegment { distance, .. }| distance)
-            .sum();
+        let track = Track::load_str(include_str!("assets/track.toml"))
+            .map_err(|e| ApparatusError::Game(Box::new(e)))?;
+        let lap_timer = LapTimer::new(&track);
 
         let retro_racer = Self {
             sprites,
@@ -124,11 +86,9 @@ impl Game for RetroRacer {
             target_curvature: 0.0,
             track_curvature: 0.0,
             player_curvature: 0.0,
-            track_distance,
-            current_lap_time: Duration::from_millis(0),
             track,
             track_segment: 0,
-            lap_times: VecDeque::from([Duration::from_millis(0); 5]),
+            lap_timer,
         };
 
         Ok(retro_racer)
@@ -162,35 +122,25 @@ impl Game for RetroRacer {
 
         self.speed = clamp(0.0, self.speed, 1.0);
         self.distance += 70.0 * self.speed * dt.as_secs_f32();
-        self.current_lap_time += dt;
 
-        if self.distance > self.track_distance {
-            self.distance -= self.track_distance;
-            self.lap_times.push_front(self.current_lap_time);
-            self.lap_times.pop_back();
-            self.current_lap_time = Duration::from_millis(0);
-        }
-
-        let mut offset = 0.0;
-        let mut track_segment = 0;
-
-        // TODO: Should keep track of these in the game state.
-        while track_segment < self.track.len() && offset < self.distance {
-            if let Some(segment) = self.track.get(track_segment) {
-                offset += segment.distance;
-                track_segment += 1;
-            }
+        let track_distance = self.track.length();
+        if self.distance > track_distance {
+            self.distance -= track_distance;
         }
+        self.lap_timer.update(self.distance, dt);
 
+        let (track_segment, target_curvature) = match self.track.segment_at(self.distance) {
+            Some((index, segment, _)) => (index, segment.curvature),
+            None => (0, 0.0),
+        };
         self.track_segment = track_segment;
 
-        let target_curvature = match self.track.get(track_segment) {
-            Some(&TrackSegment { curvature, .. }) => curvature,
-            _ => 0.0,
-        };
-        let track_curvature_difference =
-            (target_curvature - self.target_curvature) * dt.as_secs_f32() * self.speed;
-        self.target_curvature += track_curvature_difference;
+        self.target_curvature = exp_decay(
+            self.target_curvature,
+            target_curvature,
+            self.speed,
+            dt.as_secs_f32(),
+        );
         self.track_curvature += self.target_curvature * dt.as_secs_f32() * self.speed;
 
         self.car_pos = self.player_curvature - self.track_curvature;
@@ -227,7 +177,8 @@ impl Game for RetroRacer {
         }
 
         // Draw track.
-        let road = if self.track_segment == 0 || self.track_segment == 1 {
+        // The short first segment is the start line.
+        let road = if self.track_segment == 0 {
             color::css::WHITE
         } else {
             color::css::LIGHTGREY
@@ -285,10 +236,11 @@ impl Game for RetroRacer {
         }
 
         // Draw car.
-        let sprite_idx = match self.direction {
-            Direction::Forward => 0,
-            Direction::Left => 1,
-            Direction::Right => 2,
+        // Turning right is drawn as turning left, mirrored.
+        let (sprite_idx, flip) = match self.direction {
+            Direction::Forward => (0, Flip::NONE),
+            Direction::Left => (1, Flip::NONE),
+            Direction::Right => (1, Flip::HORIZONTAL),
         };
 
         if let Some(car_sprite) = self.sprites.get(sprite_idx) {
@@ -299,68 +251,72 @@ impl Game for RetroRacer {
                 - (car_sprite.width() as f32 / 2.0);
             let car_y = 30.0 * scale;
 
-            app.draw_sprite(car_x as f32, car_y as f32, car_sprite);
+            app.draw_sprite_flipped(car_x, car_y, car_sprite, flip);
         }
 
-        // Draw stats.
-        app.draw_string(
+        // Draw stats, outlined to stay readable over the sky and road.
+        let hud = TextStyle::default().with_outline(color::css::BLACK, 1.0);
+        app.draw_styled_string(
             format!("Distance: {:.2}", self.distance),
             10.0,
             app.window_height() - 20.0,
             color::css::WHITE,
             12.0,
+            &hud,
         );
-        app.draw_string(
+        app.draw_styled_string(
             format!("Speed: {:.2}", self.speed),
             10.0,
             app.window_height() - 30.0,
             color::css::WHITE,
             12.0,
+            &hud,
         );
-        app.draw_string(
+        app.draw_styled_string(
             format!("Target curvature:: {:.2}", self.target_curvature),
             10.0,
             app.window_height() - 40.0,
             color::css::WHITE,
             12.0,
+            &hud,
         );
-        app.draw_string(
+        app.draw_styled_string(
             format!("Player curvature: {:.2}", self.player_curvature),
             10.0,
             app.window_height() - 50.0,
             color::css::WHITE,
             12.0,
+            &hud,
         );
-        app.draw_string(
+        app.draw_styled_string(
             format!("Track curvature: {:.2}", self.track_curvature),
             10.0,
             app.window_height() - 60.0,
             color::css::WHITE,
             12.0,
+            &hud,
         );
 
-        fn format_lap_time(lap_time: &Duration) -> String {
-            let minutes = lap_time.as_secs() / 60;
-            let seconds = lap_time.as_secs() - (minutes * 60);
-            let millis = lap_time.subsec_millis();
-            format!("{:0>2}:{:>02}:{:3}", minutes, seconds, millis)
-        }
-
-        app.draw_string(
-            format!("Lap 0: {}", format_lap_time(&self.current_lap_time)),
+        app.draw_styled_string(
+            format!(
+                "Lap 0: {}",
+                format_lap_time(self.lap_timer.current_lap_time())
+            ),
             10.0,
             app.window_height() - 80.0,
             color::css::WHITE,
             12.0,
+            &hud,
         );
 
-        for (lap, lap_time) in self.lap_times.iter().enumerate() {
-            app.draw_string(
-                format!("Lap {}: {}", lap + 1, format_lap_time(lap_time)),
+        for (lap, lap_time) in self.lap_timer.lap_times().iter().rev().take(5).enumerate() {
+            app.draw_styled_string(
+                format!("Lap {}: {}", lap + 1, format_lap_time(*lap_time)),
                 10.0,
-                app.window_height() as f32 - (90.0 + 10.0 * lap as f32),
+                app.window_height() - (90.0 + 10.0 * lap as f32),
                 color::css::WHITE,
                 12.0,
+                &hud,
             );
         }
     }
@@ -370,6 +326,7 @@ fn main() -> Result<()> {
     let settings = ApparatusSettings::default()
         .with_screen_size(320, 180)
         .with_pixel_size(4, 4);
+    let settings = LaunchOptions::from_env()?.apply(settings);
 
     let app = Apparatus::new("Retro Racer!", settings)?;
     app.run::<RetroRacer>()?;
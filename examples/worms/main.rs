@@ -5,16 +5,24 @@ use rand::prelude::ThreadRng;
 use rand::Rng;
 use std::any::Any;
 use std::f32::consts::PI;
+use std::sync::OnceLock;
 
 use apparatus::engine::apparatus::{Apparatus, ApparatusSettings};
+use apparatus::engine::camera::{Camera, Camera2D, Follow};
 use apparatus::engine::game::Game;
 use apparatus::engine::key::Key;
 use apparatus::engine::mouse::MouseButton;
 use apparatus::engine::sprite::Sprite;
+use apparatus::engine::vector_model::VectorModel;
 use apparatus::engine::Point;
 use apparatus::errors::ApparatusError;
-use apparatus::maths::{clamp, lerp};
+use apparatus::launcher::LaunchOptions;
+use apparatus::maths::{clamp, Rect, Vec2};
+use apparatus::procgen::noise::{noise_seed, perlin_noise_1d};
+use apparatus::procgen::seed::WorldSeed;
 use apparatus::renderer::bresenham::BresenhamLine;
+use apparatus::renderer::upscale::{RenderScale, UpscaleFilter};
+use apparatus::ui::progress_bar::ProgressBar;
 
 // Implementation notes:
 // - All units (worms) have circular collision boxes.
@@ -23,20 +31,6 @@ use apparatus::renderer::bresenham::BresenhamLine;
 const SKY: Color = color::css::CYAN;
 const LAND: Color = color::css::DARKGREEN;
 
-static mut NEXT_PHYSICS_ID: u128 = 0;
-
-fn get_physics_id() -> u128 {
-    unsafe {
-        let id = NEXT_PHYSICS_ID;
-        let (next, overflow) = NEXT_PHYSICS_ID.overflowing_add(1);
-        if overflow {
-            panic!("We ran out of physics ID's, fix this");
-        }
-        NEXT_PHYSICS_ID = next;
-        id
-    }
-}
-
 #[derive(Debug, Copy, Clone)]
 enum GameState {
     Reset,
@@ -52,15 +46,12 @@ struct Worms {
     map_width: u32,
     map_height: u32,
     map: Vec<u8>,
-    camera_pos_x: f32,
-    camera_pos_y: f32,
-    target_camera_pos_x: f32,
-    target_camera_pos_y: f32,
+    camera: Camera,
     rng: ThreadRng,
 
     physics_things: Vec<Box<dyn Physics>>,
-    object_under_control: Option<u128>,
-    camera_tracking_object: Option<u128>,
+    object_under_control: Option<u64>,
+    camera_tracking_object: Option<u64>,
     is_energising: bool,
     energy_level: f32,
     fire_weapon: bool,
@@ -74,15 +65,15 @@ struct Worms {
 }
 
 impl Worms {
-    fn new(rng: ThreadRng) -> Self {
+    fn new(app: &Apparatus, rng: ThreadRng) -> Self {
         let map_width = 1024;
         let map_height = 512;
         let map = vec![0; map_width as usize * map_height as usize];
 
-        let camera_pos_x = 0.0;
-        let camera_pos_y = map_height as f32;
-        let target_camera_pos_x = 0.0;
-        let target_camera_pos_y = map_height as f32;
+        let mut camera = Camera::new(app.screen_width() as f32, app.screen_height() as f32)
+            .with_bounds(Rect::new(0.0, 0.0, map_width as f32, map_height as f32))
+            .with_follow(Follow::smooth(5.0));
+        camera.set_position(0.0, map_height as f32);
 
         let physics_things = Vec::new();
 
@@ -104,10 +95,7 @@ impl Worms {
             map_width,
             map_height,
             map,
-            camera_pos_x,
-            camera_pos_y,
-            target_camera_pos_x,
-            target_camera_pos_y,
+            camera,
             rng,
             physics_things,
             object_under_control,
@@ -123,22 +111,19 @@ impl Worms {
         }
     }
 
-    fn create_map(&mut self) {
-        let mut noise_seed = vec![0.0; self.map_width as usize];
+    /// Generate terrain from a new seed, which is shown in the debug overlay.
+    fn create_map(&mut self, app: &mut Apparatus) {
+        let seed = WorldSeed::random();
+        app.set_world_seed(seed);
+
         let mut surface = vec![0.0; self.map_width as usize];
 
-        generate_noise_seed(self.map_width, &mut noise_seed, &mut self.rng);
+        let mut noise_seed = noise_seed(&mut seed.rng("terrain"), self.map_width as usize);
         noise_seed[0] = 0.5; // Hack to set initial and final octave seed to be half way up the map.
 
         let octaves = 8;
         let scaling_bias = 2.0;
-        generate_perlin_noise_1d(
-            self.map_width,
-            octaves,
-            scaling_bias,
-            &noise_seed,
-            &mut surface,
-        );
+        perlin_noise_1d(octaves, scaling_bias, &noise_seed, &mut surface);
 
         for x in 0..self.map_width {
             for y in 0..self.map_height {
@@ -151,13 +136,14 @@ impl Worms {
         }
     }
 
-    fn get_object(&self, id: u128) -> Option<&Box<dyn Physics>> {
+    fn get_object(&self, id: u64) -> Option<&dyn Physics> {
         self.physics_things
             .iter()
             .find(|p| p.physics_object().id == id)
+            .map(|p| p.as_ref())
     }
 
-    fn get_object_mut(&mut self, id: u128) -> Option<&mut Box<dyn Physics>> {
+    fn get_object_mut(&mut self, id: u64) -> Option<&mut Box<dyn Physics>> {
         self.physics_things
             .iter_mut()
             .find(|p| p.physics_object().id == id)
@@ -165,25 +151,40 @@ impl Worms {
 }
 
 impl Game for Worms {
-    fn on_create(_app: &Apparatus) -> std::result::Result<Self, ApparatusError> {
+    fn on_create(app: &Apparatus) -> std::result::Result<Self, ApparatusError> {
         let rng = rand::thread_rng();
-        let worms = Worms::new(rng);
+        let worms = Worms::new(app, rng);
 
         Ok(worms)
     }
 
+    /// Options in the pause menu cycles through render scales, to trade sharpness for frame rate.
+    fn on_pause_menu_options(&mut self, app: &mut Apparatus) {
+        let settings = [
+            (RenderScale::Full, UpscaleFilter::Nearest),
+            (RenderScale::Half, UpscaleFilter::Nearest),
+            (RenderScale::Half, UpscaleFilter::Scale2x),
+            (RenderScale::Half, UpscaleFilter::Bilinear),
+        ];
+        let current = settings
+            .iter()
+            .position(|&setting| setting == (app.render_scale(), app.upscale_filter()))
+            .unwrap_or(0);
+        let (scale, filter) = settings[(current + 1) % settings.len()];
+        app.set_render_scale(scale, filter);
+    }
+
     fn on_update(&mut self, app: &mut Apparatus) {
         if app.was_key_released(Key::M) {
-            self.create_map();
+            self.create_map(app);
         }
 
         if app.is_key_held(Key::E) && app.was_mouse_button_released(MouseButton::Left) {
+            let mouse = app.mouse_world_pos();
             if app.is_key_held(Key::Num1) {
                 explosion(
-                    Point::new(
-                        app.mouse_pos_x() + self.camera_pos_x,
-                        app.mouse_pos_y() + self.camera_pos_y,
-                    ),
+                    app,
+                    Point::new(mouse.x, mouse.y),
                     10.0,
                     self.map_width,
                     self.map_height,
@@ -195,19 +196,15 @@ impl Game for Worms {
 
             if app.is_key_held(Key::Num2) {
                 let dummy = Missile::new(
-                    Point::new(
-                        app.mouse_pos_x() + self.camera_pos_x,
-                        app.mouse_pos_y() + self.camera_pos_y,
-                    ),
+                    app.next_id(),
+                    Point::new(mouse.x, mouse.y),
                     Point::new(0.0, 0.0),
                 );
                 self.physics_things.push(Box::new(dummy));
             }
 
             if app.is_key_held(Key::Num3) {
-                let x1 = app.mouse_pos_x() + self.camera_pos_x;
-                let y1 = app.mouse_pos_y() + self.camera_pos_y;
-                let worm = Worm::new(Point::new(x1, y1));
+                let worm = Worm::new(app.next_id(), Point::new(mouse.x, mouse.y));
                 let id = worm.physics_object.id;
                 self.physics_things.push(Box::new(worm));
                 self.object_under_control = Some(id);
@@ -215,10 +212,7 @@ impl Game for Worms {
             }
 
             if app.is_key_held(Key::Num9) {
-                let dummy = Dummy::new(
-                    app.mouse_pos_x() + self.camera_pos_x,
-                    app.mouse_pos_y() + self.camera_pos_y,
-                );
+                let dummy = Dummy::new(app.next_id(), mouse.x, mouse.y);
                 self.physics_things.push(Box::new(dummy));
             }
         }
@@ -226,17 +220,22 @@ impl Game for Worms {
         // Map scroll.
         let map_scroll_speed = 400.0;
         let dt = app.elapsed_time().as_secs_f32();
+        let mut scroll = Vec2::new(0.0, 0.0);
         if app.mouse_pos_x() < 5.0 {
-            self.camera_pos_x -= map_scroll_speed * dt;
+            scroll.x -= map_scroll_speed * dt;
         }
         if app.mouse_pos_x() > app.screen_width() as f32 - 5.0 {
-            self.camera_pos_x += map_scroll_speed * dt;
+            scroll.x += map_scroll_speed * dt;
         }
         if app.mouse_pos_y() < 5.0 {
-            self.camera_pos_y -= map_scroll_speed * dt;
+            scroll.y -= map_scroll_speed * dt;
         }
         if app.mouse_pos_y() > app.screen_height() as f32 - 5.0 {
-            self.camera_pos_y += map_scroll_speed * dt;
+            scroll.y += map_scroll_speed * dt;
+        }
+        if scroll != Vec2::new(0.0, 0.0) {
+            let camera = self.camera.position() + scroll;
+            self.camera.set_position(camera.x, camera.y);
         }
 
         // Game state management.
@@ -247,7 +246,7 @@ impl Game for Worms {
             }
             GameState::GenerateTerrain => {
                 self.player_has_control = false;
-                self.create_map();
+                self.create_map(app);
                 self.next_state = GameState::GeneratingTerrain;
             }
             GameState::GeneratingTerrain => {
@@ -257,7 +256,7 @@ impl Game for Worms {
             GameState::AllocateUnits => {
                 self.player_has_control = false;
 
-                let worm = Worm::new((32.0, self.map_height as f32).into());
+                let worm = Worm::new(app.next_id(), (32.0, self.map_height as f32).into());
                 self.object_under_control = Some(worm.physics_object.id);
                 self.camera_tracking_object = self.object_under_control;
                 self.physics_things.push(Box::new(worm));
@@ -355,6 +354,7 @@ impl Game for Worms {
                                 let velocity_y = worm.shoot_angle.sin() * 40.0 * self.energy_level;
 
                                 let missile = Missile::new(
+                                    app.next_id(),
                                     Point::new(origin_x, origin_y),
                                     Point::new(velocity_x, velocity_y),
                                 );
@@ -373,35 +373,15 @@ impl Game for Worms {
             }
         }
 
-        let (x, y) = if let Some(id) = self.camera_tracking_object {
-            if let Some(camera_tracking_object) = self.get_object(id) {
-                let p = camera_tracking_object.physics_object();
-                (
-                    p.position_x - app.screen_width() as f32 / 2.0,
-                    p.position_y - app.screen_height() as f32 / 2.0,
-                )
-            } else {
-                (self.camera_pos_x, self.camera_pos_y)
-            }
-        } else {
-            (self.camera_pos_x, self.camera_pos_y)
-        };
-
-        self.target_camera_pos_x = x;
-        self.target_camera_pos_y = y;
-        self.camera_pos_x += (self.target_camera_pos_x - self.camera_pos_x) * 5.0 * dt;
-        self.camera_pos_y += (self.target_camera_pos_y - self.camera_pos_y) * 5.0 * dt;
-
-        self.camera_pos_x = clamp(
-            0.0,
-            self.camera_pos_x,
-            (self.map_width - app.screen_width() as u32) as f32,
-        );
-        self.camera_pos_y = clamp(
-            0.0,
-            self.camera_pos_y,
-            (self.map_height - app.screen_height() as u32) as f32,
-        );
+        let target = self
+            .camera_tracking_object
+            .and_then(|id| self.get_object(id))
+            .map(|object| {
+                let p = object.physics_object();
+                Vec2::new(p.position_x, p.position_y)
+            });
+        self.camera.update(dt, target);
+        app.set_camera(&Camera2D::from(&self.camera));
 
         // Update physics - 10 times per 1 render cycle. How does this work?
         for _ in 0..10 {
@@ -492,6 +472,7 @@ impl Game for Worms {
                 let p = pt.physics_object();
                 if let DeathAction::Explode(radius) = p.bounce_death_action() {
                     explosion(
+                        app,
                         Point::new(p.position_x, p.position_y),
                         *radius,
                         self.map_width,
@@ -507,30 +488,32 @@ impl Game for Worms {
             self.physics_things.retain(|p| !p.physics_object().is_dead);
         }
 
-        // Draw landscape.
-        for x in 0..app.screen_width() {
-            for y in 0..app.screen_height() {
-                match self.map[(y + self.camera_pos_y as usize) * self.map_width as usize
-                    + (x + self.camera_pos_x as usize)]
-                {
-                    0 => app.draw(x as f32, y as f32, SKY),
-                    1 => app.draw(x as f32, y as f32, LAND),
-                    _ => unreachable!("Tried to draw an unknown pixel type"),
+        // Draw the visible part of the landscape a screen pixel at a time.
+        let camera = self.camera.position();
+        app.in_screen_space(|app| {
+            for x in 0..app.screen_width() {
+                for y in 0..app.screen_height() {
+                    match self.map[(y + camera.y as usize) * self.map_width as usize
+                        + (x + camera.x as usize)]
+                    {
+                        0 => app.draw(x as f32, y as f32, SKY),
+                        1 => app.draw(x as f32, y as f32, LAND),
+                        _ => unreachable!("Tried to draw an unknown pixel type"),
+                    }
                 }
             }
-        }
+        });
 
+        // Everything else is drawn at its world position, through the camera.
         for p in &self.physics_things {
-            p.draw(app, self.camera_pos_x, self.camera_pos_y);
+            p.draw(app);
 
             if let Some(id) = self.object_under_control {
                 if p.physics_object().id == id {
                     if let Some(worm) = p.as_any().downcast_ref::<Worm>() {
                         let po = &worm.physics_object;
-                        let center_x =
-                            po.position_x + 8.0 * worm.shoot_angle.cos() - self.camera_pos_x;
-                        let center_y =
-                            po.position_y + 8.0 * worm.shoot_angle.sin() - self.camera_pos_y;
+                        let center_x = po.position_x + 8.0 * worm.shoot_angle.cos();
+                        let center_y = po.position_y + 8.0 * worm.shoot_angle.sin();
 
                         // Direction cursor.
                         app.draw(center_x, center_y, color::css::BLACK);
@@ -541,18 +524,14 @@ impl Game for Worms {
 
                         // Weapon energising energy level.
                         if self.is_energising {
-                            for i in 0..=(10.0 * self.energy_level) as u32 {
-                                app.draw(
-                                    po.position_x - 5.0 + i as f32 - self.camera_pos_x,
-                                    po.position_y + 12.0 - self.camera_pos_y,
-                                    color::css::GREEN,
-                                );
-                                app.draw(
-                                    po.position_x - 5.0 + i as f32 - self.camera_pos_x,
-                                    po.position_y + 11.0 - self.camera_pos_y,
-                                    color::css::RED,
-                                );
-                            }
+                            let power_meter = ProgressBar::new(Rect::new(
+                                po.position_x - 5.0,
+                                po.position_y + 11.0,
+                                11.0,
+                                2.0,
+                            ))
+                            .with_gradient(color::css::GREEN, color::css::RED);
+                            power_meter.draw(app, self.energy_level, 1.0);
                         }
                     }
                 }
@@ -569,13 +548,15 @@ impl Game for Worms {
         }
 
         if self.is_game_stable {
-            app.draw_filled_rectangle(
-                2.0,
-                app.screen_height() as f32 - 8.0,
-                6.0,
-                6.0,
-                color::css::RED,
-            );
+            app.in_screen_space(|app| {
+                app.draw_filled_rectangle(
+                    2.0,
+                    app.screen_height() as f32 - 8.0,
+                    6.0,
+                    6.0,
+                    color::css::RED,
+                )
+            });
         }
 
         self.game_state = self.next_state;
@@ -585,57 +566,23 @@ impl Game for Worms {
 fn main() -> Result<()> {
     let settings = ApparatusSettings::default()
         .with_screen_size(256, 160)
-        .with_pixel_size(6, 6);
+        .with_pixel_size(6, 6)
+        .with_pause_menu(true);
+    let settings = LaunchOptions::from_env()?.apply(settings);
     let engine = Apparatus::new("Worms", settings)?;
     engine.run::<Worms>()?;
 
     Ok(())
 }
 
-fn generate_noise_seed(output_size: u32, noise_seed: &mut Vec<f32>, rng: &mut ThreadRng) {
-    unsafe { noise_seed.set_len(output_size as usize) };
-    for i in noise_seed.iter_mut() {
-        *i = rng.gen_range(0.0..=1.0);
-    }
-}
-
-fn generate_perlin_noise_1d(
-    count: u32,
-    octaves: u32,
-    bias: f32,
-    seed: &[f32],
-    output: &mut Vec<f32>,
-) {
-    let (count, octaves) = (count as usize, octaves as usize);
-    let bias = 1.0 / bias;
-
-    for (i, x) in output.iter_mut().enumerate() {
-        let mut noise = 0.0;
-        let mut scale = 1.0;
-        let mut scale_accumulator = 0.0;
-
-        for octave in 0..octaves {
-            let pitch = count >> octave;
-            let sample_1 = (i / pitch) * pitch;
-            let sample_2 = (sample_1 + pitch) % count;
-
-            let blend = (i - sample_1) as f32 / pitch as f32;
-            let sample = lerp(seed[sample_2], seed[sample_1], blend);
-            noise += sample * scale;
-            scale_accumulator += scale;
-            scale *= bias;
-        }
-
-        *x = noise / scale_accumulator;
-    }
-}
-
+#[allow(clippy::too_many_arguments)]
 fn explosion(
+    app: &mut Apparatus,
     position: Point,
     radius: f32,
     map_width: u32,
     map_height: u32,
-    map: &mut Vec<u8>,
+    map: &mut [u8],
     physics_things: &mut Vec<Box<dyn Physics>>,
     rng: &mut ThreadRng,
 ) {
@@ -646,21 +593,13 @@ fn explosion(
         radius: i32,
         map_width: u32,
         map_height: u32,
-        map: &mut Vec<u8>,
+        map: &mut [u8],
     ) {
         let mut x0 = 0;
         let mut y0 = radius;
         let mut d = 3 - 2 * radius;
 
-        fn draw_line(
-            x0: f32,
-            y0: f32,
-            x1: f32,
-            y1: f32,
-            width: u32,
-            height: u32,
-            map: &mut Vec<u8>,
-        ) {
+        fn draw_line(x0: f32, y0: f32, x1: f32, y1: f32, width: u32, height: u32, map: &mut [u8]) {
             let x0 = (clamp(0.0, x0.floor(), width as f32) + 0.5) as u32;
             let y0 = (clamp(0.0, y0.floor(), height as f32) + 0.5) as u32;
             let x1 = (clamp(0.0, x1.floor(), width as f32) + 0.5) as u32;
@@ -731,7 +670,7 @@ fn explosion(
 
     // Shockwave.
     physics_things.iter_mut().for_each(|p| {
-        let mut p = p.physics_object_mut();
+        let p = p.physics_object_mut();
         let dx = p.position_x - position.x();
         let dy = p.position_y - position.y();
         let mut distance = (dx * dx + dy * dy).sqrt(); // Or we could compare to radius squared and save the division.
@@ -748,7 +687,7 @@ fn explosion(
 
     // Launch debris.
     for _ in 0..radius as u32 {
-        let debris = Debris::new(position.x(), position.y(), rng);
+        let debris = Debris::new(app.next_id(), position.x(), position.y(), rng);
         physics_things.push(Box::new(debris));
     }
 }
@@ -761,7 +700,7 @@ enum DeathAction {
 
 #[derive(Debug)]
 struct PhysicsObject {
-    id: u128,
+    id: u64,
 
     position_x: f32,     // or just `x`?
     position_y: f32,     // or just `y`?
@@ -780,8 +719,7 @@ struct PhysicsObject {
 }
 
 impl PhysicsObject {
-    fn new(x: f32, y: f32) -> Self {
-        let id = get_physics_id();
+    fn new(id: u64, x: f32, y: f32) -> Self {
         Self {
             id,
             position_x: x,
@@ -809,7 +747,7 @@ trait Physics {
 
     fn physics_object_mut(&mut self) -> &mut PhysicsObject;
 
-    fn draw(&self, app: &mut Apparatus, camera_offset_x: f32, camera_offset_y: f32);
+    fn draw(&self, app: &mut Apparatus);
 
     fn as_any(&self) -> &dyn Any;
 
@@ -821,8 +759,8 @@ struct Dummy {
 }
 
 impl Dummy {
-    fn new(x: f32, y: f32) -> Self {
-        let physics_object = PhysicsObject::new(x, y);
+    fn new(id: u64, x: f32, y: f32) -> Self {
+        let physics_object = PhysicsObject::new(id, x, y);
 
         Self { physics_object }
     }
@@ -837,7 +775,7 @@ impl Physics for Dummy {
         &mut self.physics_object
     }
 
-    fn draw(&self, app: &mut Apparatus, camera_offset_x: f32, camera_offset_y: f32) {
+    fn draw(&self, app: &mut Apparatus) {
         let rotation = self
             .physics_object
             .velocity_y
@@ -850,16 +788,16 @@ impl Physics for Dummy {
         let direction_y = y + (rotation.cos() + radius * rotation.sin());
 
         app.draw_line(
-            self.physics_object.position_x - camera_offset_x,
-            self.physics_object.position_y - camera_offset_y,
-            direction_x - camera_offset_x,
-            direction_y - camera_offset_y,
+            self.physics_object.position_x,
+            self.physics_object.position_y,
+            direction_x,
+            direction_y,
             color::css::WHITE,
         );
 
         app.draw_wireframe_circle(
-            self.physics_object.position_x - camera_offset_x,
-            self.physics_object.position_y - camera_offset_y,
+            self.physics_object.position_x,
+            self.physics_object.position_y,
             radius,
             color::css::WHITE,
         );
@@ -886,8 +824,8 @@ impl Debris {
         Point::new(1.0, 0.0),
     ];
 
-    fn new(x: f32, y: f32, rng: &mut ThreadRng) -> Self {
-        let mut physics_object = PhysicsObject::new(x, y);
+    fn new(id: u64, x: f32, y: f32, rng: &mut ThreadRng) -> Self {
+        let mut physics_object = PhysicsObject::new(id, x, y);
         physics_object.velocity_x = 10.0 * (rng.gen_range(0.0..=1.0) * 2.0 * PI).cos();
         physics_object.velocity_y = 10.0 * (rng.gen_range(0.0..=1.0) * 2.0 * PI).sin();
         physics_object.radius = 1.0;
@@ -907,7 +845,7 @@ impl Physics for Debris {
         &mut self.physics_object
     }
 
-    fn draw(&self, app: &mut Apparatus, camera_offset_x: f32, camera_offset_y: f32) {
+    fn draw(&self, app: &mut Apparatus) {
         let rotation = self
             .physics_object
             .velocity_y
@@ -915,8 +853,8 @@ impl Physics for Debris {
 
         app.draw_wireframe_model(
             (
-                self.physics_object.position_x - camera_offset_x,
-                self.physics_object.position_y - camera_offset_y,
+                self.physics_object.position_x,
+                self.physics_object.position_y,
             )
                 .into(),
             rotation,
@@ -940,23 +878,17 @@ struct Missile {
 }
 
 impl Missile {
-    const MODEL: [Point; 12] = [
-        Point::new(0.0, 0.0),
-        Point::new(1.0, 1.0),
-        Point::new(2.0, 1.0),
-        Point::new(2.5, 0.0),
-        Point::new(2.0, -1.0),
-        Point::new(1.0, -1.0),
-        Point::new(0.0, 0.0),
-        Point::new(-1.0, -1.0),
-        Point::new(-2.5, -1.0),
-        Point::new(-2.0, 0.0),
-        Point::new(-2.5, 1.0),
-        Point::new(-1.0, 1.0),
-    ];
+    /// The model is parsed once and shared by every missile.
+    fn model() -> &'static VectorModel {
+        static MODEL: OnceLock<VectorModel> = OnceLock::new();
+        MODEL.get_or_init(|| {
+            VectorModel::load_str(include_str!("assets/missile.svg"))
+                .expect("missile model is valid")
+        })
+    }
 
-    fn new(position: Point, velocity: Point) -> Self {
-        let mut physics_object = PhysicsObject::new(position.x(), position.y());
+    fn new(id: u64, position: Point, velocity: Point) -> Self {
+        let mut physics_object = PhysicsObject::new(id, position.x(), position.y());
         physics_object.velocity_x = velocity.x();
         physics_object.velocity_y = velocity.y();
         physics_object.radius = 2.5;
@@ -977,20 +909,19 @@ impl Physics for Missile {
         &mut self.physics_object
     }
 
-    fn draw(&self, app: &mut Apparatus, camera_offset_x: f32, camera_offset_y: f32) {
+    fn draw(&self, app: &mut Apparatus) {
         // Negative y because we flipped the y axis when we draw.
         let rotation = (-self.physics_object.velocity_y).atan2(self.physics_object.velocity_x);
 
-        app.draw_wireframe_model(
+        app.draw_vector_model(
             (
-                self.physics_object.position_x - camera_offset_x,
-                self.physics_object.position_y - camera_offset_y,
+                self.physics_object.position_x,
+                self.physics_object.position_y,
             )
                 .into(),
             rotation,
             self.physics_object.radius * 0.4,
-            &Self::MODEL,
-            color::css::YELLOW,
+            Self::model(),
         )
     }
 
@@ -1010,11 +941,11 @@ struct Worm {
 }
 
 impl Worm {
-    fn new(position: Point) -> Self {
+    fn new(id: u64, position: Point) -> Self {
         let sprite_bytes = include_bytes!("assets/worm.png");
         let sprite = Sprite::from_bytes(sprite_bytes);
 
-        let mut physics_object = PhysicsObject::new(position.x(), position.y());
+        let mut physics_object = PhysicsObject::new(id, position.x(), position.y());
         physics_object.velocity_x = 0.0;
         physics_object.velocity_y = 0.0;
         physics_object.radius = 3.5;
@@ -1040,10 +971,10 @@ impl Physics for Worm {
         &mut self.physics_object
     }
 
-    fn draw(&self, app: &mut Apparatus, camera_offset_x: f32, camera_offset_y: f32) {
+    fn draw(&self, app: &mut Apparatus) {
         app.draw_sprite(
-            self.physics_object.position_x - camera_offset_x - self.physics_object.radius,
-            self.physics_object.position_y - camera_offset_y - self.physics_object.radius - 1.0,
+            self.physics_object.position_x - self.physics_object.radius,
+            self.physics_object.position_y - self.physics_object.radius - 1.0,
             &self.sprite,
         );
     }
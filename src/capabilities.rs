@@ -0,0 +1,86 @@
+/// The engine's crate version, e.g. "0.0.3".
+pub fn version() -> &'static str {
+    env!("CARGO_PKG_VERSION")
+}
+
+/// The windowing and rendering backend in use.
+pub fn backend_name() -> &'static str {
+    "minifb (software)"
+}
+
+/// Optional engine features a game can check for before relying on them.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Capability {
+    Audio,
+    Gamepads,
+    /// Drawing is done by the GPU rather than the software renderer.
+    HardwareRendering,
+    /// Per-pixel window transparency, see `ApparatusSettings::with_transparent_window`.
+    TransparentWindow,
+    /// The window can be moved programmatically, see `Apparatus::set_window_position`.
+    WindowPositioning,
+}
+
+impl Capability {
+    pub const ALL: [Capability; 5] = [
+        Capability::Audio,
+        Capability::Gamepads,
+        Capability::HardwareRendering,
+        Capability::TransparentWindow,
+        Capability::WindowPositioning,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Capability::Audio => "Audio",
+            Capability::Gamepads => "Gamepads",
+            Capability::HardwareRendering => "Hardware rendering",
+            Capability::TransparentWindow => "Transparent window",
+            Capability::WindowPositioning => "Window positioning",
+        }
+    }
+}
+
+/// Whether this build of the engine supports `capability` on the current platform.
+pub fn is_supported(capability: Capability) -> bool {
+    match capability {
+        Capability::Audio | Capability::Gamepads | Capability::HardwareRendering => false,
+        Capability::TransparentWindow => !cfg!(target_os = "macos"),
+        Capability::WindowPositioning => true,
+    }
+}
+
+/// A multi-line summary of the engine version, backend and capabilities, e.g. for a settings or
+/// diagnostics screen.
+pub fn summary() -> String {
+    let mut summary = format!("Apparatus {}\nBackend: {}\n", version(), backend_name());
+    for capability in Capability::ALL {
+        let status = if is_supported(capability) {
+            "yes"
+        } else {
+            "no"
+        };
+        summary.push_str(&format!("{}: {}\n", capability.name(), status));
+    }
+
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn version_matches_crate_version() {
+        assert_eq!(env!("CARGO_PKG_VERSION"), version());
+    }
+
+    #[test]
+    fn summary_lists_every_capability() {
+        let summary = summary();
+
+        for capability in Capability::ALL {
+            assert!(summary.contains(capability.name()));
+        }
+    }
+}
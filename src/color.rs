@@ -34,6 +34,56 @@ impl Color {
 
         Self::rgba(r, g, b, 255)
     }
+
+    /// `src` composited over `dst`, keeping the combined alpha, e.g. for stamping one image into
+    /// another.
+    pub fn blend_over(src: Self, dst: Self) -> Self {
+        let src_a = src.a() as f32 / 255.0;
+        let dst_a = dst.a() as f32 / 255.0;
+        let out_a = src_a + dst_a * (1.0 - src_a);
+        if out_a == 0.0 {
+            return Self::rgba(0, 0, 0, 0);
+        }
+
+        let channel = |src: u8, dst: u8| {
+            ((src as f32 * src_a + dst as f32 * dst_a * (1.0 - src_a)) / out_a).round() as u8
+        };
+
+        Self::rgba(
+            channel(src.r(), dst.r()),
+            channel(src.g(), dst.g()),
+            channel(src.b(), dst.b()),
+            (out_a * 255.0).round() as u8,
+        )
+    }
+
+    /// The color with red, green and blue multiplied by its alpha.
+    pub fn premultiplied(&self) -> Self {
+        let channel = |value: u8| (value as f32 * self.a() as f32 / 255.0).round() as u8;
+
+        Self::rgba(
+            channel(self.r()),
+            channel(self.g()),
+            channel(self.b()),
+            self.a(),
+        )
+    }
+
+    /// `src` composited over `dst` where both are premultiplied by alpha, keeping the combined
+    /// alpha, e.g. for surfaces that are composited later. A zero alpha source with color adds
+    /// light.
+    pub fn blend_premultiplied(src: Self, dst: Self) -> Self {
+        let remaining = 1.0 - src.a() as f32 / 255.0;
+        let channel =
+            |src: u8, dst: u8| (src as f32 + dst as f32 * remaining).round().min(255.0) as u8;
+
+        Self::rgba(
+            channel(src.r(), dst.r()),
+            channel(src.g(), dst.g()),
+            channel(src.b(), dst.b()),
+            channel(src.a(), dst.a()),
+        )
+    }
 }
 
 impl From<Color> for u32 {
@@ -42,6 +92,12 @@ impl From<Color> for u32 {
     }
 }
 
+impl From<u32> for Color {
+    fn from(argb: u32) -> Self {
+        Self(argb.to_be_bytes())
+    }
+}
+
 impl Debug for Color {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         Display::fmt(self, f)
@@ -65,6 +121,36 @@ impl Display for Color {
 mod tests {
     use super::*;
 
+    #[test]
+    fn blending_over_keeps_the_combined_alpha() {
+        let half_black = Color::rgba(0, 0, 0, 128);
+
+        assert_eq!(
+            Color::rgba(0, 0, 0, 192),
+            Color::blend_over(half_black, half_black)
+        );
+        assert_eq!(
+            Color::rgba(255, 0, 0, 255),
+            Color::blend_over(Color::rgba(255, 0, 0, 255), half_black)
+        );
+        assert_eq!(
+            half_black,
+            Color::blend_over(Color::rgba(0, 0, 0, 0), half_black)
+        );
+    }
+
+    #[test]
+    fn premultiplied_blending_keeps_the_combined_alpha() {
+        let src = Color::rgba(255, 0, 0, 128).premultiplied();
+        let dst = Color::rgba(0, 0, 255, 128).premultiplied();
+
+        assert_eq!(Color::rgba(128, 0, 0, 128), src);
+        assert_eq!(
+            Color::rgba(128, 0, 64, 192),
+            Color::blend_premultiplied(src, dst)
+        );
+    }
+
     #[test]
     fn color_has_rgba_components() {
         let color = Color::rgba(50, 100, 150, 200);
@@ -83,6 +169,13 @@ mod tests {
         assert_eq!(expected, Into::<u32>::into(color));
     }
 
+    #[test]
+    fn color_can_be_created_from_argb_u32() {
+        let argb = (255 << 24) | (64 << 16) | (128 << 8) | 192;
+
+        assert_eq!(Color::rgba(64, 128, 192, 255), Color::from(argb));
+    }
+
     #[test]
     fn linear_blend_red_color_full_opacity_onto_blue_color_is_red() {
         let red = css::RED;
@@ -1,27 +1,70 @@
+use std::panic::AssertUnwindSafe;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 
-use log::error;
+use image::{ImageFormat, RgbaImage};
+use log::{error, info, warn};
 
 use crate::color::Color;
+use crate::engine::camera::Camera2D;
 use crate::engine::clock::Clock;
+use crate::engine::crash::{CrashReporter, CrashSettings, FrameRecord};
+use crate::engine::diagnostics::Diagnostics;
+use crate::engine::floating_text::{FloatingText, FloatingTexts};
 use crate::engine::game::Game;
 use crate::engine::key::Key;
+use crate::engine::localization::Localization;
 use crate::engine::logger::Logger;
 use crate::engine::mouse::MouseButton;
-use crate::engine::sprite::Sprite;
+use crate::engine::palette::Palette;
+use crate::engine::pause_menu::{PauseMenu, PauseMenuAction, PauseMenuCommand};
+use crate::engine::plugin::Plugin;
+use crate::engine::recorder::{GifRecorder, RecorderSettings};
+use crate::engine::replay::Replay;
+use crate::engine::sprite::{Sprite, SpriteAtlas};
+use crate::engine::strict;
+use crate::engine::vector_model::VectorModel;
 use crate::engine::Point;
 use crate::errors::ApparatusError;
+use crate::font::GlyphAtlas;
+use crate::maths::spline::Spline;
+use crate::maths::{Obb, Rect, Vec2};
 use crate::platform::framebuffer::FrameBuffer;
-use crate::platform::input::Input;
-use crate::platform::window::Window;
-use crate::renderer::software_2d::Renderer;
+use crate::platform::input::{Input, InputAccessibility};
+use crate::platform::window::{FocusTracker, Window, WindowOptions};
+use crate::procgen::seed::WorldSeed;
+use crate::renderer::custom::DrawCommand;
+use crate::renderer::software_2d::{BlendMode, Flip, Interlace, Origin, Renderer, TextStyle};
+use crate::renderer::upscale::{RenderScale, UpscaleFilter};
+use crate::ui::text_grid::TextGrid;
+use crate::ui::{hit_test, selection};
 use crate::{color, util};
 
+/// How long frames last while idle, see `ApparatusSettings::with_idle_mode`.
+const IDLE_FRAME_DURATION: Duration = Duration::from_millis(100);
+
 pub struct ApparatusSettings {
     width: usize,
     height: usize,
     pixel_width: usize,
     pixel_height: usize,
+    pause_menu: bool,
+    localization: Localization,
+    window_options: WindowOptions,
+    crash: Option<CrashSettings>,
+    origin: Origin,
+    render_scale: RenderScale,
+    upscale_filter: UpscaleFilter,
+    interlace: Interlace,
+    dirty_tracking: bool,
+    input_accessibility: InputAccessibility,
+    diagnostics: bool,
+    strict_rendering: bool,
+    idle_mode: bool,
+    glyph_atlas: GlyphAtlas,
+    recorder: Option<RecorderSettings>,
+    headless: bool,
+    replay: Option<PathBuf>,
 }
 
 impl Default for ApparatusSettings {
@@ -31,6 +74,23 @@ impl Default for ApparatusSettings {
             height: 720,
             pixel_width: 1,
             pixel_height: 1,
+            pause_menu: false,
+            localization: Localization::default(),
+            window_options: WindowOptions::default(),
+            crash: None,
+            origin: Origin::default(),
+            render_scale: RenderScale::default(),
+            upscale_filter: UpscaleFilter::default(),
+            interlace: Interlace::default(),
+            dirty_tracking: false,
+            input_accessibility: InputAccessibility::default(),
+            diagnostics: false,
+            strict_rendering: false,
+            idle_mode: false,
+            glyph_atlas: GlyphAtlas::default(),
+            recorder: None,
+            headless: false,
+            replay: None,
         }
     }
 }
@@ -51,6 +111,174 @@ impl ApparatusSettings {
         self.height = height;
         self
     }
+
+    /// Enable the built-in pause menu, opened with Escape or when the window loses focus.
+    /// The game can respond to it through the pause callbacks on `Game`.
+    /// Defaults to disabled.
+    pub fn with_pause_menu(mut self, enabled: bool) -> Self {
+        self.pause_menu = enabled;
+        self
+    }
+
+    /// Provide the string tables used by `Apparatus::draw_localized_string` and the built-in
+    /// pause menu. The pause menu's keys are `apparatus.pause.title`, `.resume`, `.options`,
+    /// `.quit`, `.confirm_quit` and `.confirm_quit_hint`, shown in English when missing.
+    /// Defaults to no translations, so keys are drawn as-is.
+    pub fn with_localization(mut self, localization: Localization) -> Self {
+        self.localization = localization;
+        self
+    }
+
+    /// On panic, write a crash bundle to a new folder in `directory` containing the panic message
+    /// and backtrace, these settings, input from the last `frames` frames and a screenshot of the
+    /// current frame, then flush the log. Defaults to disabled.
+    pub fn with_crash_handler(mut self, directory: impl Into<PathBuf>, frames: usize) -> Self {
+        self.crash = Some(CrashSettings {
+            directory: directory.into(),
+            frames,
+        });
+        self
+    }
+
+    /// Keep the last `seconds` of gameplay, capturing one frame in `every`, and save it as an
+    /// animated GIF in a new file in `directory` when `key` is pressed. Frames are kept at the
+    /// screen size in virtual pixels. Defaults to disabled.
+    pub fn with_gif_recorder(
+        mut self,
+        directory: impl Into<PathBuf>,
+        seconds: f32,
+        every: usize,
+        key: Key,
+    ) -> Self {
+        self.recorder = Some(RecorderSettings {
+            directory: directory.into(),
+            seconds,
+            every,
+            key,
+        });
+        self
+    }
+
+    /// Choose where (0, 0) is and which way y increases for drawing and mouse coordinates.
+    /// Shapes, text and sprites are anchored at their bottom left corner with a bottom left
+    /// origin and at their top left corner with a top left origin. Angles and anything else
+    /// expressed in screen axes, such as floating text velocities and the ui layout helpers,
+    /// follow the chosen axes. Defaults to bottom left.
+    pub fn with_origin(mut self, origin: Origin) -> Self {
+        self.origin = origin;
+        self
+    }
+
+    /// Render at a fraction of the window resolution and upscale with `filter`, trading
+    /// sharpness for frame rate. Can be changed while running with
+    /// `Apparatus::set_render_scale`. Defaults to full scale. The pixel size must divide by the
+    /// scale's divisor, so a pixel size of 1 always renders at full scale.
+    pub fn with_render_scale(mut self, scale: RenderScale, filter: UpscaleFilter) -> Self {
+        self.render_scale = scale;
+        self.upscale_filter = filter;
+        self
+    }
+
+    /// Draw alternate scanlines or a checkerboard of pixels each frame, keeping the rest from the
+    /// previous frame, to halve fill cost in slow scenes. Can be changed while running with
+    /// `Apparatus::set_interlace`. Defaults to off.
+    pub fn with_interlace(mut self, interlace: Interlace) -> Self {
+        self.interlace = interlace;
+        self
+    }
+
+    /// Track the area drawn each frame and don't display frames where nothing was drawn, for
+    /// mostly static screens that aren't cleared every frame, e.g. board games. Can be changed
+    /// while running with `Apparatus::set_dirty_tracking`. Defaults to false.
+    pub fn with_dirty_tracking(mut self, enabled: bool) -> Self {
+        self.dirty_tracking = enabled;
+        self
+    }
+
+    /// Hold-as-toggle keys, key repeat and sticky modifiers, applied to all keyboard input.
+    /// Can be changed while running with `Apparatus::set_input_accessibility`. Defaults to none.
+    pub fn with_input_accessibility(mut self, accessibility: InputAccessibility) -> Self {
+        self.input_accessibility = accessibility;
+        self
+    }
+
+    /// Remove the window's title bar and border. Defaults to false.
+    pub fn with_borderless_window(mut self, borderless: bool) -> Self {
+        self.window_options.borderless = borderless;
+        self
+    }
+
+    /// Keep the window above all others. Defaults to false.
+    pub fn with_topmost_window(mut self, topmost: bool) -> Self {
+        self.window_options.topmost = topmost;
+        self
+    }
+
+    /// Cover the screen with a borderless window kept above all others, scaled up to the
+    /// largest whole multiple of its size that fits. minifb can't change the display mode, so
+    /// the window may not fill the screen exactly. Defaults to false.
+    pub fn with_fullscreen_window(mut self, fullscreen: bool) -> Self {
+        self.window_options.fullscreen = fullscreen;
+        self
+    }
+
+    /// Show the desktop through pixels with zero alpha, e.g. for sprites walking across the
+    /// desktop. Clear with a fully transparent color to see through the window. Translucent
+    /// pixels are blended onto the frame and so become opaque, unless drawn with
+    /// `BlendMode::Over`. Implies a borderless window and is not supported on macOS. Defaults to
+    /// false.
+    pub fn with_transparent_window(mut self, transparent: bool) -> Self {
+        self.window_options.transparent = transparent;
+        self
+    }
+
+    /// Log a warning when drawing is done in a way that has a faster alternative, e.g. drawing
+    /// the same string every frame, drawing sprites entirely off-screen or clearing the screen
+    /// more than once a frame. Each problem is logged at most once every ten seconds. Defaults
+    /// to false.
+    pub fn with_diagnostics(mut self, enabled: bool) -> Self {
+        self.diagnostics = enabled;
+        self
+    }
+
+    /// Panic when a draw call is given a NaN or infinite coordinate, a negative size or radius,
+    /// or a text size of zero, instead of silently drawing nothing or clamping. The panic
+    /// reports the game's call site. Intended for debug builds. Defaults to false.
+    pub fn with_strict_rendering(mut self, strict: bool) -> Self {
+        self.strict_rendering = strict;
+        self
+    }
+
+    /// Draw strings with glyphs baked with `Apparatus::bake_glyph_atlas`, rather than
+    /// rasterizing them on first use. Defaults to none.
+    pub fn with_glyph_atlas(mut self, atlas: GlyphAtlas) -> Self {
+        self.glyph_atlas = atlas;
+        self
+    }
+
+    /// Stop presenting frames and sleep for longer between them while the player does nothing
+    /// and the game draws the same frame, e.g. in tools or paused games, instead of keeping a
+    /// CPU core busy. The game is still updated, ten times a second, so it wakes as soon as
+    /// anything changes. Defaults to false.
+    pub fn with_idle_mode(mut self, enabled: bool) -> Self {
+        self.idle_mode = enabled;
+        self
+    }
+
+    /// Run without opening a window or reading input, as fast as the game updates, e.g. to
+    /// play back replays or run soak tests on a build server. The game runs until it calls
+    /// `Apparatus::quit` or the replay ends. Defaults to false.
+    pub fn with_headless(mut self, headless: bool) -> Self {
+        self.headless = headless;
+        self
+    }
+
+    /// Play back the replay saved at `path` instead of reading the player's input, starting
+    /// from the replay's world seed, and stop when it ends. Defaults to none.
+    pub fn with_replay(mut self, path: impl Into<PathBuf>) -> Self {
+        self.replay = Some(path.into());
+        self
+    }
 }
 
 pub struct Apparatus {
@@ -68,6 +296,28 @@ pub struct Apparatus {
     input: Input,
     target_frame_duration: Duration,
     running: bool,
+
+    pause_menu_enabled: bool,
+    pause_menu: PauseMenu,
+    paused: bool,
+    paused_frame: Vec<u32>,
+    focus: FocusTracker,
+
+    localization: Localization,
+    floating_texts: FloatingTexts,
+    world_seed: Option<WorldSeed>,
+
+    crash_reporter: Option<CrashReporter>,
+    recorder: Option<GifRecorder>,
+    headless: bool,
+    replay: Option<Replay>,
+    diagnostics: Option<Diagnostics>,
+    strict_rendering: bool,
+    idle_mode: bool,
+    idle_frame: Vec<u32>,
+    frame: u64,
+    next_id: u64,
+    plugins: Vec<Box<dyn Plugin>>,
 }
 
 impl Apparatus {
@@ -84,21 +334,70 @@ impl Apparatus {
         let mut clock = Clock::default();
         clock.tick();
 
-        let window = Window::new(name, window_width, window_height)?;
+        let window = if settings.headless {
+            Window::headless(name, window_width, window_height)
+        } else {
+            Window::new(name, window_width, window_height, settings.window_options)?
+        };
         let frame_buffer = FrameBuffer::new(window_width as usize, window_height as usize);
-        let renderer = Renderer::new(
+        let mut renderer = Renderer::new(
             window_width,
             window_height,
             pixel_width,
             pixel_height,
             frame_buffer,
         );
-        let input = Input::new();
+        renderer.set_origin(settings.origin);
+        renderer.set_render_scale(settings.render_scale, settings.upscale_filter);
+        renderer.set_interlace(settings.interlace);
+        renderer.set_dirty_tracking(settings.dirty_tracking);
+        renderer.add_glyph_atlas(settings.glyph_atlas);
+        let mut input = Input::new();
+        input.set_accessibility(settings.input_accessibility);
 
         let target_frame_duration = Duration::from_secs_f32(1.0 / 60.0);
 
         let running = false;
 
+        let pause_menu_enabled = settings.pause_menu;
+        let pause_menu = PauseMenu::default();
+
+        let recorder = settings
+            .recorder
+            .clone()
+            .map(|recorder| GifRecorder::new(recorder, target_frame_duration));
+
+        let replay = settings
+            .replay
+            .as_ref()
+            .map(Replay::load_file)
+            .transpose()
+            .map_err(|e| ApparatusError::Initialisation(e.into()))?;
+        let world_seed = replay.as_ref().and_then(Replay::seed);
+
+        let crash_reporter = settings.crash.clone().map(|crash| {
+            let summary = format!(
+                "screen size: {} x {}\npixel size: {} x {}\norigin: {:?}\nrender scale: {:?} ({:?})\n\
+                 interlace: {:?}\ninput accessibility: {:?}\npause menu: {}\nwindow: {:?}\n",
+                screen_width,
+                screen_height,
+                pixel_width,
+                pixel_height,
+                settings.origin,
+                settings.render_scale,
+                settings.upscale_filter,
+                settings.interlace,
+                input.accessibility(),
+                settings.pause_menu,
+                settings.window_options,
+            );
+            let reporter = CrashReporter::new(crash, summary);
+            reporter.install(_logger.handle());
+            reporter
+        });
+
+        let localization = settings.localization;
+
         let app = Self {
             pixel_width,
             pixel_height,
@@ -114,17 +413,71 @@ impl Apparatus {
             input,
             target_frame_duration,
             running,
+
+            pause_menu_enabled,
+            pause_menu,
+            paused: false,
+            paused_frame: Vec::new(),
+            focus: FocusTracker::default(),
+
+            localization,
+            floating_texts: FloatingTexts::default(),
+            world_seed,
+
+            crash_reporter,
+            recorder,
+            headless: settings.headless,
+            replay,
+            diagnostics: settings.diagnostics.then(Diagnostics::default),
+            strict_rendering: settings.strict_rendering,
+            idle_mode: settings.idle_mode,
+            idle_frame: Vec::new(),
+            frame: 0,
+            next_id: 0,
+            plugins: Vec::new(),
         };
 
         Ok(app)
     }
 
-    pub fn run<G>(mut self) -> Result<(), ApparatusError>
+    pub fn run<G>(self) -> Result<(), ApparatusError>
     where
         G: Game,
     {
-        let mut game = G::on_create(&self)?;
+        let game = G::on_create(&self)?;
+        self.run_game(game)
+    }
+
+    /// Run a game exported from the dynamic library at `path` with `export_hot_reload_game!`,
+    /// reloading it whenever the library is rebuilt.
+    #[cfg(feature = "hot-reload")]
+    pub fn run_hot_reload(self, path: impl Into<std::path::PathBuf>) -> Result<(), ApparatusError> {
+        let game = crate::engine::hot_reload::HotReloader::new(&self, path)?;
+        self.run_game(game)
+    }
 
+    fn run_game<G>(mut self, mut game: G) -> Result<(), ApparatusError>
+    where
+        G: Game,
+    {
+        // Catch a panic to add a screenshot of the frame to the crash bundle, then carry on
+        // unwinding.
+        match std::panic::catch_unwind(AssertUnwindSafe(|| self.game_loop(&mut game))) {
+            Ok(result) => result,
+            Err(panic) => {
+                if let Some(reporter) = &self.crash_reporter {
+                    reporter.write_screenshot(&self.renderer.to_image());
+                }
+                std::panic::resume_unwind(panic)
+            }
+        }
+    }
+
+    fn game_loop<G>(&mut self, game: &mut G) -> Result<(), ApparatusError>
+    where
+        G: Game,
+    {
+        self.run_plugins(|plugin, app| plugin.on_engine_start(app));
         self.clock.tick();
 
         self.running = true;
@@ -133,12 +486,56 @@ impl Apparatus {
                 self.running = false;
             }
 
-            self.input.process_input(&self.window);
+            if let Some(replay) = &self.replay {
+                // Replays step by whole frames, so key repeat plays back the same however
+                // long the frame took.
+                match replay.frames().get(self.frame as usize) {
+                    Some(frame) => self
+                        .input
+                        .process_replay_frame(frame, self.target_frame_duration.as_secs_f32()),
+                    None => {
+                        info!("replay finished after {} frames", self.frame);
+                        break;
+                    }
+                }
+            } else {
+                self.input
+                    .process_input(&self.window, self.clock.delta().as_secs_f32());
+            }
+
+            if let Some(recorder) = &self.recorder {
+                if self.input.is_key_pressed(recorder.key()) {
+                    recorder.save();
+                }
+            }
+
+            if self.pause_menu_enabled {
+                self.update_pause_menu(game);
+            }
+
+            if !self.paused {
+                self.run_plugins(|plugin, app| plugin.pre_update(app));
+                game.on_update(self);
+                self.run_plugins(|plugin, app| plugin.post_update(app));
 
-            game.on_update(&mut self);
+                self.floating_texts.update(self.target_frame_duration);
+                self.floating_texts
+                    .draw(&mut self.renderer, self.pixel_width, self.pixel_height);
+            }
 
+            // Flatten the layers first, so overlays go on top and idle frames compare the
+            // whole picture.
+            self.renderer.composite_layers();
+            let idle = self.idle_mode && self.is_idle();
             let elapsed = self.clock.elapsed();
-            if elapsed < self.target_frame_duration {
+            if self.headless {
+                // Nobody is watching, so don't wait for the next frame.
+            } else if idle {
+                // Sleep without busy-waiting; the next frame doesn't need to be on time.
+                if elapsed < IDLE_FRAME_DURATION {
+                    std::thread::sleep(IDLE_FRAME_DURATION - elapsed);
+                }
+            } else if elapsed < self.target_frame_duration {
                 if let Err(e) = util::sleep(self.target_frame_duration - elapsed) {
                     error!("{}", e);
                 }
@@ -146,53 +543,221 @@ impl Apparatus {
 
             self.clock.tick();
 
-            // Stats.
-            #[cfg(debug_assertions)]
-            {
-                let fps = 1.0 / self.clock.delta().as_secs_f32();
-                let debug_box_width = 190.0;
-                let debug_box_left = self.window_width - debug_box_width;
-                let debug_box_height = 50.0;
-                let debug_box_bottom = self.window_height - debug_box_height;
-                self.renderer.draw_filled_rectangle_unscaled(
-                    debug_box_left,
-                    debug_box_bottom,
-                    debug_box_width,
-                    debug_box_height,
-                    color::css::SILVER,
-                );
-                self.renderer.draw_string(
-                    format!("ms/F: {:.2}", self.clock.delta().as_secs_f32() * 1_000.0),
-                    debug_box_left + 10.0,
-                    debug_box_bottom + debug_box_height - 20.0,
-                    color::css::BLACK,
-                    12.0,
-                );
-                self.renderer.draw_string(
-                    format!("FPS: {:.2}", fps),
-                    debug_box_left + 10.0,
-                    debug_box_bottom + debug_box_height - 30.0,
-                    color::css::BLACK,
-                    12.0,
-                );
-                self.renderer.draw_string(
-                    format!(
-                        "Sleep tolerance (ms): {}",
-                        util::get_sleep_tolerance().as_micros() as f32 / 1_000.0
-                    ),
-                    debug_box_left + 10.0,
-                    debug_box_bottom + debug_box_height - 40.0,
-                    color::css::BLACK,
-                    12.0,
+            if idle {
+                self.window.poll_events();
+            } else {
+                self.run_plugins(|plugin, app| plugin.pre_present(app));
+
+                #[cfg(debug_assertions)]
+                self.draw_stats();
+
+                let changed = self.renderer.is_dirty();
+                let frame = self.renderer.present();
+                if changed {
+                    self.window.display(frame)?;
+                } else {
+                    self.window.poll_events();
+                }
+            }
+
+            if let Some(recorder) = &mut self.recorder {
+                recorder.capture(
+                    &self.renderer.frame().data,
+                    self.window_width as usize,
+                    self.window_height as usize,
+                    self.pixel_width,
+                    self.pixel_height,
                 );
             }
 
-            self.window.display(self.renderer.buffer())?;
+            if let Some(reporter) = &self.crash_reporter {
+                let record = FrameRecord {
+                    frame: self.frame,
+                    keys: self.input.held_keys(),
+                    mouse: (self.mouse_pos_x(), self.mouse_pos_y()),
+                    buttons: self.input.held_mouse_buttons(),
+                };
+                reporter.record(record);
+            }
+            if let Some(diagnostics) = &mut self.diagnostics {
+                for diagnostic in diagnostics.end_frame() {
+                    warn!("{}", diagnostic);
+                }
+            }
+            self.frame += 1;
         }
 
         Ok(())
     }
 
+    fn run_plugins(&mut self, hook: impl Fn(&mut dyn Plugin, &mut Apparatus)) {
+        // Plugins are taken out while they run so each can be handed the engine.
+        let mut plugins = std::mem::take(&mut self.plugins);
+        for plugin in &mut plugins {
+            hook(plugin.as_mut(), self);
+        }
+        plugins.append(&mut self.plugins);
+        self.plugins = plugins;
+    }
+
+    /// Whether the player did nothing and the frame is the same as the last one, so it needn't
+    /// be presented.
+    fn is_idle(&mut self) -> bool {
+        if self.renderer.dirty_tracking() {
+            return !self.renderer.is_dirty() && self.input.is_idle();
+        }
+
+        let frame = &self.renderer.buffer().data;
+        let unchanged = *frame == self.idle_frame;
+        if !unchanged {
+            self.idle_frame.clone_from(frame);
+        }
+
+        unchanged && self.input.is_idle()
+    }
+
+    #[cfg(debug_assertions)]
+    fn draw_stats(&mut self) {
+        // Engine overlays are laid out on screen with a bottom left origin, alpha blended and
+        // drawn in full, so they don't flicker when interlaced.
+        let camera = self.renderer.camera();
+        self.renderer.clear_camera();
+        let origin = self.renderer.origin();
+        self.renderer.set_origin(Origin::BottomLeft);
+        let interlace = self.renderer.interlace();
+        self.renderer.set_interlace(Interlace::Off);
+        let blend_mode = self.renderer.blend_mode();
+        self.renderer.set_blend_mode(BlendMode::Alpha);
+
+        let fps = 1.0 / self.clock.delta().as_secs_f32();
+        let debug_box_width = 190.0;
+        let debug_box_left = self.window_width - debug_box_width;
+        let debug_box_height = if self.world_seed.is_some() {
+            60.0
+        } else {
+            50.0
+        };
+        let debug_box_bottom = self.window_height - debug_box_height;
+        self.renderer.draw_filled_rectangle_unscaled(
+            debug_box_left,
+            debug_box_bottom,
+            debug_box_width,
+            debug_box_height,
+            color::css::SILVER,
+        );
+        self.renderer.draw_string(
+            format!("ms/F: {:.2}", self.clock.delta().as_secs_f32() * 1_000.0),
+            debug_box_left + 10.0,
+            debug_box_bottom + debug_box_height - 20.0,
+            color::css::BLACK,
+            12.0,
+        );
+        self.renderer.draw_string(
+            format!("FPS: {:.2}", fps),
+            debug_box_left + 10.0,
+            debug_box_bottom + debug_box_height - 30.0,
+            color::css::BLACK,
+            12.0,
+        );
+        self.renderer.draw_string(
+            format!(
+                "Sleep tolerance (ms): {}",
+                util::get_sleep_tolerance().as_micros() as f32 / 1_000.0
+            ),
+            debug_box_left + 10.0,
+            debug_box_bottom + debug_box_height - 40.0,
+            color::css::BLACK,
+            12.0,
+        );
+        if let Some(seed) = self.world_seed {
+            self.renderer.draw_string(
+                format!("Seed: {}", seed),
+                debug_box_left + 10.0,
+                debug_box_bottom + debug_box_height - 50.0,
+                color::css::BLACK,
+                12.0,
+            );
+        }
+
+        self.renderer.set_origin(origin);
+        self.renderer.set_interlace(interlace);
+        self.renderer.set_blend_mode(blend_mode);
+        if let Some(camera) = camera {
+            self.renderer.set_camera(&camera);
+        }
+    }
+
+    fn update_pause_menu<G>(&mut self, game: &mut G)
+    where
+        G: Game,
+    {
+        let is_active = self.window.is_active();
+        let lost_focus = self.focus.lost_focus(is_active);
+
+        if self.paused {
+            let command = if self.input.is_key_pressed(Key::Up) {
+                Some(PauseMenuCommand::Up)
+            } else if self.input.is_key_pressed(Key::Down) {
+                Some(PauseMenuCommand::Down)
+            } else if self.input.is_key_pressed(Key::Enter) {
+                Some(PauseMenuCommand::Select)
+            } else if self.input.is_key_pressed(Key::Escape) {
+                Some(PauseMenuCommand::Back)
+            } else {
+                None
+            };
+
+            match command.and_then(|command| self.pause_menu.handle(command)) {
+                Some(PauseMenuAction::Resume) => {
+                    self.paused = false;
+                    game.on_resume(self);
+                }
+                Some(PauseMenuAction::Options) => game.on_pause_menu_options(self),
+                Some(PauseMenuAction::Quit) => {
+                    if game.on_quit_requested(self) {
+                        self.running = false;
+                    } else {
+                        self.pause_menu.reset();
+                    }
+                }
+                None => {}
+            }
+        } else if lost_focus || self.input.is_key_pressed(Key::Escape) {
+            self.paused = true;
+            self.pause_menu.reset();
+            self.paused_frame.clone_from(&self.renderer.buffer().data);
+            game.on_pause(self);
+        }
+
+        if self.paused {
+            // Redraw the overlay over the frame the game was paused on, rather than stacking it.
+            self.renderer
+                .buffer_mut()
+                .data
+                .copy_from_slice(&self.paused_frame);
+            let camera = self.renderer.camera();
+            let origin = self.renderer.origin();
+            let interlace = self.renderer.interlace();
+            let blend_mode = self.renderer.blend_mode();
+            self.renderer.clear_camera();
+            self.renderer.set_origin(Origin::BottomLeft);
+            self.renderer.set_interlace(Interlace::Off);
+            self.renderer.set_blend_mode(BlendMode::Alpha);
+            self.pause_menu.draw(
+                &mut self.renderer,
+                &self.localization,
+                self.window_width,
+                self.window_height,
+            );
+            self.renderer.set_origin(origin);
+            self.renderer.set_interlace(interlace);
+            self.renderer.set_blend_mode(blend_mode);
+            if let Some(camera) = camera {
+                self.renderer.set_camera(&camera);
+            }
+        }
+    }
+
     // ----- Info -----
     pub fn pixel_width(&self) -> usize {
         self.pixel_width
@@ -218,11 +783,255 @@ impl Apparatus {
         self.window_height
     }
 
+    /// Change the screen size and pixel size while running, e.g. from a graphics settings menu.
+    /// The window is reopened at the new size and the current frame is stretched to fit until
+    /// the game next draws.
+    pub fn set_virtual_resolution(
+        &mut self,
+        screen_width: usize,
+        screen_height: usize,
+        pixel_width: usize,
+        pixel_height: usize,
+    ) -> Result<(), ApparatusError> {
+        let window_width = (screen_width * pixel_width) as f32;
+        let window_height = (screen_height * pixel_height) as f32;
+        self.window.resize(window_width, window_height)?;
+        self.focus.reset();
+
+        if self.paused {
+            self.renderer
+                .buffer_mut()
+                .data
+                .copy_from_slice(&self.paused_frame);
+        }
+        self.renderer
+            .resize(window_width, window_height, pixel_width, pixel_height);
+        if self.paused {
+            self.paused_frame.clone_from(&self.renderer.buffer().data);
+        }
+
+        self.screen_width = screen_width;
+        self.screen_height = screen_height;
+        self.pixel_width = pixel_width;
+        self.pixel_height = pixel_height;
+        self.window_width = window_width;
+        self.window_height = window_height;
+
+        Ok(())
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// The number of frames run so far, which is also the index of the current frame.
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    /// Stop the game at the end of this frame, as if the window was closed.
+    pub fn quit(&mut self) {
+        self.running = false;
+    }
+
+    /// Move the window so its top left corner is at (x, y) on the desktop, in screen pixels.
+    pub fn set_window_position(&mut self, x: isize, y: isize) {
+        self.window.set_position(x, y);
+    }
+
+    // ----- Localization -----
+    pub fn localization(&self) -> &Localization {
+        &self.localization
+    }
+
+    pub fn localization_mut(&mut self) -> &mut Localization {
+        &mut self.localization
+    }
+
     // ----- Timing -----
     pub fn elapsed_time(&self) -> Duration {
         self.target_frame_duration
     }
 
+    // ----- Effects -----
+    /// Spawn text that the engine moves, fades and draws on top of each frame until it expires.
+    pub fn spawn_floating_text(&mut self, text: FloatingText) {
+        self.floating_texts.spawn(text);
+    }
+
+    pub fn clear_floating_text(&mut self) {
+        self.floating_texts.clear();
+    }
+
+    // ----- World -----
+    /// The seed the current world was generated from. Shown in the debug overlay so it can be
+    /// shared when reporting a problem with a generated level.
+    pub fn world_seed(&self) -> Option<WorldSeed> {
+        self.world_seed
+    }
+
+    pub fn set_world_seed(&mut self, seed: WorldSeed) {
+        self.world_seed = Some(seed);
+    }
+
+    /// The replay being played back, e.g. to check the game state with
+    /// `Replay::check_state_hash`.
+    pub fn replay(&self) -> Option<&Replay> {
+        self.replay.as_ref()
+    }
+
+    /// Add a plugin whose hooks run alongside the game. Plugins added from a hook start running
+    /// from the next hook.
+    pub fn add_plugin(&mut self, plugin: impl Plugin + 'static) {
+        self.plugins.push(Box::new(plugin));
+    }
+
+    /// A new ID, unique for as long as the engine runs, e.g. to tell game objects apart.
+    pub fn next_id(&mut self) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+
+    /// Where (0, 0) is and which way y increases for draw calls, as set with
+    /// `ApparatusSettings::with_origin`.
+    pub fn origin(&self) -> Origin {
+        self.renderer.origin()
+    }
+
+    pub fn interlace(&self) -> Interlace {
+        self.renderer.interlace()
+    }
+
+    /// Switch interlaced drawing on or off while running.
+    pub fn set_interlace(&mut self, interlace: Interlace) {
+        self.renderer.set_interlace(interlace);
+    }
+
+    pub fn layer(&self) -> u8 {
+        self.renderer.layer()
+    }
+
+    /// Draw into `layer` from now on, e.g. 0 for the background, 1 for terrain, 2 for entities
+    /// and 3 for the UI, in whatever order the game draws them. Higher layers are stacked on top
+    /// when the frame is presented. Drawing returns to layer 0 at the start of every frame.
+    pub fn set_layer(&mut self, layer: u8) {
+        self.renderer.set_layer(layer);
+    }
+
+    pub fn camera(&self) -> Option<Camera2D> {
+        self.renderer.camera()
+    }
+
+    /// Draw world-space shapes and sprites through `camera` from now on, so positions are given
+    /// in the world. Text is still drawn in window pixels; draw the HUD with `in_screen_space`.
+    /// The engine draws world effects such as floating text through the camera set when the
+    /// frame's update ends.
+    pub fn set_camera(&mut self, camera: &Camera2D) {
+        self.renderer.set_camera(camera);
+    }
+
+    pub fn clear_camera(&mut self) {
+        self.renderer.clear_camera();
+    }
+
+    /// Run `draw` with the camera cleared, so its positions are on screen, e.g. for the HUD or
+    /// a marker placed with `world_to_screen`. The camera is set again afterwards.
+    pub fn in_screen_space<R>(&mut self, draw: impl FnOnce(&mut Self) -> R) -> R {
+        let camera = self.renderer.camera();
+        self.renderer.clear_camera();
+        let result = draw(self);
+        if let Some(camera) = camera {
+            self.renderer.set_camera(&camera);
+        }
+        result
+    }
+
+    /// Where a world position is drawn on screen, in virtual pixels, through the camera. With no
+    /// camera set the position is already on screen.
+    pub fn world_to_screen(&self, point: Vec2) -> Vec2 {
+        self.renderer.world_to_screen(point)
+    }
+
+    /// The world position drawn at a screen position through the camera.
+    pub fn screen_to_world(&self, point: Vec2) -> Vec2 {
+        self.renderer.screen_to_world(point)
+    }
+
+    pub fn dirty_tracking(&self) -> bool {
+        self.renderer.dirty_tracking()
+    }
+
+    /// Switch dirty tracking on or off while running, see
+    /// `ApparatusSettings::with_dirty_tracking`.
+    pub fn set_dirty_tracking(&mut self, enabled: bool) {
+        self.renderer.set_dirty_tracking(enabled);
+    }
+
+    pub fn blend_mode(&self) -> BlendMode {
+        self.renderer.blend_mode()
+    }
+
+    /// Set how everything drawn from now on is combined with the screen, e.g. `BlendMode::Add`
+    /// for an explosion, then back to `BlendMode::Alpha`.
+    pub fn set_blend_mode(&mut self, blend_mode: BlendMode) {
+        self.renderer.set_blend_mode(blend_mode);
+    }
+
+    /// The most recently presented frame at window resolution, e.g. to make a save thumbnail.
+    pub fn frame_image(&self) -> RgbaImage {
+        self.renderer.to_image()
+    }
+
+    /// Save the most recently presented frame as a PNG at window resolution, e.g. for a bug
+    /// report.
+    pub fn screenshot(&self, path: impl AsRef<Path>) -> Result<(), ApparatusError> {
+        self.renderer
+            .to_image()
+            .save_with_format(path, ImageFormat::Png)
+            .map_err(|e| ApparatusError::Screenshot(Box::new(e)))
+    }
+
+    /// Rasterize `characters` at each text size in `sizes`, e.g. to save with
+    /// `GlyphAtlas::save_file` and ship with the game.
+    pub fn bake_glyph_atlas(&self, sizes: &[f32], characters: &str) -> GlyphAtlas {
+        self.renderer.bake_glyph_atlas(sizes, characters)
+    }
+
+    /// Draw strings with glyphs from a baked atlas as well as any already added, rather than
+    /// rasterizing them on first use. Atlases are baked for the current render scale.
+    pub fn add_glyph_atlas(&mut self, atlas: GlyphAtlas) {
+        self.renderer.add_glyph_atlas(atlas);
+    }
+
+    pub fn render_scale(&self) -> RenderScale {
+        self.renderer.render_scale()
+    }
+
+    pub fn upscale_filter(&self) -> UpscaleFilter {
+        self.renderer.upscale_filter()
+    }
+
+    /// Change the render scale and upscale filter, e.g. from a settings menu in
+    /// `Game::on_pause_menu_options`. The scale is raised if the pixel size doesn't divide by it;
+    /// the scale in effect is returned.
+    pub fn set_render_scale(&mut self, scale: RenderScale, filter: UpscaleFilter) -> RenderScale {
+        if self.paused {
+            self.renderer
+                .buffer_mut()
+                .data
+                .copy_from_slice(&self.paused_frame);
+        }
+
+        let scale = self.renderer.set_render_scale(scale, filter);
+
+        if self.paused {
+            self.paused_frame.clone_from(&self.renderer.buffer().data);
+        }
+
+        scale
+    }
+
     // ----- Input -----
     pub fn is_key_pressed(&self, key: Key) -> bool {
         self.input.is_key_pressed(key)
@@ -236,12 +1045,45 @@ impl Apparatus {
         self.input.was_key_released(key)
     }
 
+    /// Printable characters typed since the last frame, for text entry.
+    pub fn typed_text(&self) -> &str {
+        self.input.typed_text()
+    }
+
+    /// The mouse x position in virtual pixels, matching the coordinates given to draw calls.
     pub fn mouse_pos_x(&self) -> f32 {
-        self.input.mouse_pos_x() / self.pixel_width as f32
+        self.mouse_virtual_pos().x
     }
 
+    /// The mouse y position in virtual pixels, matching the coordinates given to draw calls, so
+    /// it increases upwards with a bottom left origin and downwards with a top left origin.
     pub fn mouse_pos_y(&self) -> f32 {
-        self.input.mouse_pos_y() / self.pixel_height as f32
+        self.mouse_virtual_pos().y
+    }
+
+    /// The mouse position in window pixels from the bottom left of the window, whatever the
+    /// origin, as used by `draw_string`.
+    pub fn mouse_window_pos(&self) -> Vec2 {
+        Vec2::new(self.input.mouse_pos_x(), self.input.mouse_pos_y())
+    }
+
+    /// The mouse position in virtual pixels, matching the coordinates given to draw calls. The
+    /// fractional part is how far across the virtual pixel the cursor is, so flooring gives the
+    /// pixel under the cursor.
+    pub fn mouse_virtual_pos(&self) -> Vec2 {
+        window_to_virtual(
+            self.mouse_window_pos(),
+            self.renderer.origin(),
+            self.window_height,
+            self.pixel_width,
+            self.pixel_height,
+        )
+    }
+
+    /// The mouse position in the world, seen through the camera set with `set_camera`. With no
+    /// camera set it is the same as `mouse_virtual_pos`.
+    pub fn mouse_world_pos(&self) -> Vec2 {
+        self.screen_to_world(self.mouse_virtual_pos())
     }
 
     pub fn mouse_in_window(&self) -> bool {
@@ -251,6 +1093,31 @@ impl Apparatus {
             && self.input.mouse_pos_y() <= self.window_height()
     }
 
+    pub fn input_accessibility(&self) -> &InputAccessibility {
+        self.input.accessibility()
+    }
+
+    /// Change the input accessibility options, e.g. from a settings menu. Keys held by toggling
+    /// or sticky modifiers are let go.
+    pub fn set_input_accessibility(&mut self, accessibility: InputAccessibility) {
+        self.input.set_accessibility(accessibility);
+    }
+
+    /// Whether the mouse is over `rect`, given in virtual pixels on screen, e.g. for a button.
+    pub fn is_mouse_over(&self, rect: &Rect) -> bool {
+        selection::contains(rect, self.mouse_virtual_pos())
+    }
+
+    /// The topmost of `sprites`, given in draw order with their world positions, that has a
+    /// visible pixel under the mouse, seen through the camera as with `mouse_world_pos`.
+    pub fn sprite_under_mouse<'a, T>(
+        &self,
+        sprites: impl IntoIterator<Item = (T, &'a Sprite, Vec2)>,
+    ) -> Option<T> {
+        let point = self.mouse_world_pos();
+        hit_test::sprite_at(sprites, self.renderer.origin(), point)
+    }
+
     pub fn is_mouse_button_held(&self, button: MouseButton) -> bool {
         self.input.is_mouse_button_held(button)
     }
@@ -261,18 +1128,33 @@ impl Apparatus {
 
     // ----- Graphics -----
     pub fn clear(&mut self, color: Color) {
+        if let Some(diagnostics) = &mut self.diagnostics {
+            diagnostics.cleared();
+        }
         self.renderer.clear(color);
     }
 
+    #[track_caller]
     pub fn draw(&mut self, x: f32, y: f32, color: Color) {
+        if self.strict_rendering {
+            strict::finite("draw", &[("x", x), ("y", y)]);
+        }
         self.renderer.draw(x, y, color);
     }
 
+    #[track_caller]
     pub fn draw_line(&mut self, x0: f32, y0: f32, x1: f32, y1: f32, color: Color) {
+        if self.strict_rendering {
+            strict::finite(
+                "draw_line",
+                &[("x0", x0), ("y0", y0), ("x1", x1), ("y1", y1)],
+            );
+        }
         self.renderer.draw_line(x0, y0, x1, y1, color);
     }
 
     #[allow(clippy::too_many_arguments)]
+    #[track_caller]
     pub fn draw_wireframe_triangle(
         &mut self,
         x0: f32,
@@ -283,11 +1165,25 @@ impl Apparatus {
         y2: f32,
         color: Color,
     ) {
+        if self.strict_rendering {
+            strict::finite(
+                "draw_wireframe_triangle",
+                &[
+                    ("x0", x0),
+                    ("y0", y0),
+                    ("x1", x1),
+                    ("y1", y1),
+                    ("x2", x2),
+                    ("y2", y2),
+                ],
+            );
+        }
         self.renderer
             .draw_wireframe_triangle(x0, y0, x1, y1, x2, y2, color);
     }
 
     #[allow(clippy::too_many_arguments)]
+    #[track_caller]
     pub fn draw_filled_triangle(
         &mut self,
         x0: f32,
@@ -298,10 +1194,24 @@ impl Apparatus {
         y2: f32,
         color: Color,
     ) {
+        if self.strict_rendering {
+            strict::finite(
+                "draw_filled_triangle",
+                &[
+                    ("x0", x0),
+                    ("y0", y0),
+                    ("x1", x1),
+                    ("y1", y1),
+                    ("x2", x2),
+                    ("y2", y2),
+                ],
+            );
+        }
         self.renderer
             .draw_filled_triangle(x0, y0, x1, y1, x2, y2, color);
     }
 
+    #[track_caller]
     pub fn draw_wireframe_rectangle(
         &mut self,
         x: f32,
@@ -310,23 +1220,187 @@ impl Apparatus {
         height: f32,
         color: Color,
     ) {
+        if self.strict_rendering {
+            strict::finite("draw_wireframe_rectangle", &[("x", x), ("y", y)]);
+            strict::non_negative(
+                "draw_wireframe_rectangle",
+                &[("width", width), ("height", height)],
+            );
+        }
         self.renderer
             .draw_wireframe_rectangle(x, y, width, height, color);
     }
 
+    #[track_caller]
     pub fn draw_filled_rectangle(&mut self, x: f32, y: f32, width: f32, height: f32, color: Color) {
+        if self.strict_rendering {
+            strict::finite("draw_filled_rectangle", &[("x", x), ("y", y)]);
+            strict::non_negative(
+                "draw_filled_rectangle",
+                &[("width", width), ("height", height)],
+            );
+        }
         self.renderer
             .draw_filled_rectangle(x, y, width, height, color);
     }
 
+    #[track_caller]
+    pub fn draw_wireframe_obb(&mut self, obb: &Obb, color: Color) {
+        if self.strict_rendering {
+            strict::finite(
+                "draw_wireframe_obb",
+                &[
+                    ("center.x", obb.center.x),
+                    ("center.y", obb.center.y),
+                    ("rotation", obb.rotation),
+                ],
+            );
+            strict::non_negative(
+                "draw_wireframe_obb",
+                &[
+                    ("half_extents.x", obb.half_extents.x),
+                    ("half_extents.y", obb.half_extents.y),
+                ],
+            );
+        }
+        self.renderer.draw_wireframe_obb(obb, color);
+    }
+
+    #[track_caller]
+    pub fn draw_filled_obb(&mut self, obb: &Obb, color: Color) {
+        if self.strict_rendering {
+            strict::finite(
+                "draw_filled_obb",
+                &[
+                    ("center.x", obb.center.x),
+                    ("center.y", obb.center.y),
+                    ("rotation", obb.rotation),
+                ],
+            );
+            strict::non_negative(
+                "draw_filled_obb",
+                &[
+                    ("half_extents.x", obb.half_extents.x),
+                    ("half_extents.y", obb.half_extents.y),
+                ],
+            );
+        }
+        self.renderer.draw_filled_obb(obb, color);
+    }
+
+    #[track_caller]
+    pub fn draw_spline(&mut self, spline: &Spline, color: Color) {
+        if self.strict_rendering {
+            for segment in spline.segments() {
+                strict::finite(
+                    "draw_spline",
+                    &[
+                        ("p0.x", segment.p0.x),
+                        ("p0.y", segment.p0.y),
+                        ("p1.x", segment.p1.x),
+                        ("p1.y", segment.p1.y),
+                        ("p2.x", segment.p2.x),
+                        ("p2.y", segment.p2.y),
+                        ("p3.x", segment.p3.x),
+                        ("p3.y", segment.p3.y),
+                    ],
+                );
+            }
+        }
+        self.renderer.draw_spline(spline, color);
+    }
+
+    #[track_caller]
     pub fn draw_wireframe_circle(&mut self, x: f32, y: f32, radius: f32, color: Color) {
+        if self.strict_rendering {
+            strict::finite("draw_wireframe_circle", &[("x", x), ("y", y)]);
+            strict::non_negative("draw_wireframe_circle", &[("radius", radius)]);
+        }
         self.renderer.draw_wireframe_circle(x, y, radius, color);
     }
 
+    #[track_caller]
     pub fn draw_filled_circle(&mut self, x: f32, y: f32, radius: f32, color: Color) {
+        if self.strict_rendering {
+            strict::finite("draw_filled_circle", &[("x", x), ("y", y)]);
+            strict::non_negative("draw_filled_circle", &[("radius", radius)]);
+        }
         self.renderer.draw_filled_circle(x, y, radius, color);
     }
 
+    #[track_caller]
+    pub fn draw_wireframe_arc(
+        &mut self,
+        x: f32,
+        y: f32,
+        radius: f32,
+        start_angle: f32,
+        end_angle: f32,
+        color: Color,
+    ) {
+        if self.strict_rendering {
+            strict::finite(
+                "draw_wireframe_arc",
+                &[
+                    ("x", x),
+                    ("y", y),
+                    ("start_angle", start_angle),
+                    ("end_angle", end_angle),
+                ],
+            );
+            strict::non_negative("draw_wireframe_arc", &[("radius", radius)]);
+        }
+        self.renderer
+            .draw_wireframe_arc(x, y, radius, start_angle, end_angle, color);
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    #[track_caller]
+    pub fn draw_filled_arc(
+        &mut self,
+        x: f32,
+        y: f32,
+        inner_radius: f32,
+        outer_radius: f32,
+        start_angle: f32,
+        end_angle: f32,
+        color: Color,
+    ) {
+        if self.strict_rendering {
+            strict::finite(
+                "draw_filled_arc",
+                &[
+                    ("x", x),
+                    ("y", y),
+                    ("start_angle", start_angle),
+                    ("end_angle", end_angle),
+                ],
+            );
+            strict::non_negative(
+                "draw_filled_arc",
+                &[
+                    ("inner_radius", inner_radius),
+                    ("outer_radius", outer_radius),
+                ],
+            );
+            strict::ordered(
+                "draw_filled_arc",
+                ("inner_radius", inner_radius),
+                ("outer_radius", outer_radius),
+            );
+        }
+        self.renderer.draw_filled_arc(
+            x,
+            y,
+            inner_radius,
+            outer_radius,
+            start_angle,
+            end_angle,
+            color,
+        );
+    }
+
+    #[track_caller]
     pub fn draw_wireframe_model(
         &mut self,
         position: Point,
@@ -335,15 +1409,360 @@ impl Apparatus {
         model: &[Point],
         color: Color,
     ) {
+        if self.strict_rendering {
+            strict::finite(
+                "draw_wireframe_model",
+                &[
+                    ("position.x", position.x()),
+                    ("position.y", position.y()),
+                    ("rotation", rotation),
+                    ("scale", scale),
+                ],
+            );
+        }
         self.renderer
             .draw_wireframe_model(position, rotation, scale, model, color);
     }
 
+    #[track_caller]
+    pub fn draw_vector_model(
+        &mut self,
+        position: Point,
+        rotation: f32,
+        scale: f32,
+        model: &VectorModel,
+    ) {
+        if self.strict_rendering {
+            strict::finite(
+                "draw_vector_model",
+                &[
+                    ("position.x", position.x()),
+                    ("position.y", position.y()),
+                    ("rotation", rotation),
+                    ("scale", scale),
+                ],
+            );
+        }
+        self.renderer
+            .draw_vector_model(position, rotation, scale, model);
+    }
+
+    #[track_caller]
+    pub fn draw_filled_polygon(&mut self, vertices: &[Point], color: Color) {
+        if self.strict_rendering {
+            for vertex in vertices {
+                strict::finite(
+                    "draw_filled_polygon",
+                    &[("vertex.x", vertex.x()), ("vertex.y", vertex.y())],
+                );
+            }
+        }
+        self.renderer.draw_filled_polygon(vertices, color);
+    }
+
+    #[track_caller]
     pub fn draw_string(&mut self, value: impl AsRef<str>, x: f32, y: f32, color: Color, size: f32) {
+        if self.strict_rendering {
+            strict::finite("draw_string", &[("x", x), ("y", y)]);
+            strict::positive("draw_string", &[("size", size)]);
+        }
+        self.check_string(value.as_ref());
         self.renderer.draw_string(value, x, y, color, size);
     }
 
+    /// Draw a string with an outline and/or drop shadow so it stays readable over any background.
+    #[allow(clippy::too_many_arguments)]
+    #[track_caller]
+    pub fn draw_styled_string(
+        &mut self,
+        value: impl AsRef<str>,
+        x: f32,
+        y: f32,
+        color: Color,
+        size: f32,
+        style: &TextStyle,
+    ) {
+        if self.strict_rendering {
+            strict::finite("draw_styled_string", &[("x", x), ("y", y)]);
+            strict::positive("draw_styled_string", &[("size", size)]);
+        }
+        self.check_string(value.as_ref());
+        self.renderer
+            .draw_styled_string(value, x, y, color, size, style);
+    }
+
+    /// Draw the string for `key` in the current language, substituting any `{name}` parameters.
+    #[allow(clippy::too_many_arguments)]
+    #[track_caller]
+    pub fn draw_localized_string(
+        &mut self,
+        key: &str,
+        args: &[(&str, &str)],
+        x: f32,
+        y: f32,
+        color: Color,
+        size: f32,
+    ) {
+        if self.strict_rendering {
+            strict::finite("draw_localized_string", &[("x", x), ("y", y)]);
+            strict::positive("draw_localized_string", &[("size", size)]);
+        }
+        let value = self.localization.format(key, args);
+        self.check_string(&value);
+        self.renderer.draw_string(value, x, y, color, size);
+    }
+
+    /// How wide `value` is drawn at `size`, both in window pixels, e.g. to right-align a string.
+    pub fn string_width(&self, value: &str, size: f32) -> f32 {
+        self.renderer.string_width(value, size)
+    }
+
+    #[track_caller]
+    pub fn draw_text_grid(&mut self, x: f32, y: f32, grid: &TextGrid) {
+        if self.strict_rendering {
+            strict::finite("draw_text_grid", &[("x", x), ("y", y)]);
+        }
+        self.renderer.draw_text_grid(x, y, grid);
+    }
+
+    #[track_caller]
     pub fn draw_sprite(&mut self, x: f32, y: f32, sprite: &Sprite) {
+        if self.strict_rendering {
+            strict::finite("draw_sprite", &[("x", x), ("y", y)]);
+        }
+        self.check_sprite(x, y, sprite.width(), sprite.height());
         self.renderer.draw_sprite(x, y, sprite);
     }
+
+    #[track_caller]
+    pub fn draw_sprite_flipped(&mut self, x: f32, y: f32, sprite: &Sprite, flip: Flip) {
+        if self.strict_rendering {
+            strict::finite("draw_sprite_flipped", &[("x", x), ("y", y)]);
+        }
+        self.check_sprite(x, y, sprite.width(), sprite.height());
+        self.renderer.draw_sprite_flipped(x, y, sprite, flip);
+    }
+
+    /// Draw a sprite with its colors moved towards `tint` by the tint's alpha, e.g. to flash it
+    /// white when hit or show a team color, and its opacity multiplied by `alpha` to fade it.
+    #[track_caller]
+    pub fn draw_sprite_tinted(&mut self, x: f32, y: f32, sprite: &Sprite, tint: Color, alpha: f32) {
+        if self.strict_rendering {
+            strict::finite("draw_sprite_tinted", &[("x", x), ("y", y)]);
+            strict::non_negative("draw_sprite_tinted", &[("alpha", alpha)]);
+        }
+        self.check_sprite(x, y, sprite.width(), sprite.height());
+        self.renderer.draw_sprite_tinted(x, y, sprite, tint, alpha);
+    }
+
+    /// Run a custom rasterizer, e.g. a raycaster's column renderer, with the current camera. `clip` is in virtual pixels; pass `None` to draw anywhere on screen.
+    #[track_caller]
+    pub fn draw_custom(&mut self, command: &dyn DrawCommand, clip: Option<Rect>) {
+        if let (true, Some(clip)) = (self.strict_rendering, clip) {
+            strict::finite("draw_custom", &[("x", clip.x), ("y", clip.y)]);
+            strict::non_negative(
+                "draw_custom",
+                &[("width", clip.width), ("height", clip.height)],
+            );
+        }
+        self.renderer.draw_custom(command, clip);
+    }
+
+    #[track_caller]
+    pub fn draw_sprite_with_palette(&mut self, x: f32, y: f32, sprite: &Sprite, palette: &Palette) {
+        if self.strict_rendering {
+            strict::finite("draw_sprite_with_palette", &[("x", x), ("y", y)]);
+        }
+        self.check_sprite(x, y, sprite.width(), sprite.height());
+        self.renderer
+            .draw_sprite_with_palette(x, y, sprite, palette);
+    }
+
+    #[track_caller]
+    pub fn draw_sprite_scaled(
+        &mut self,
+        x: f32,
+        y: f32,
+        sprite: &Sprite,
+        scale_x: f32,
+        scale_y: f32,
+    ) {
+        if self.strict_rendering {
+            strict::finite(
+                "draw_sprite_scaled",
+                &[
+                    ("x", x),
+                    ("y", y),
+                    ("scale_x", scale_x),
+                    ("scale_y", scale_y),
+                ],
+            );
+        }
+        self.check_sprite_area(Rect::new(
+            x,
+            y,
+            sprite.width() as f32 * scale_x,
+            sprite.height() as f32 * scale_y,
+        ));
+        self.renderer
+            .draw_sprite_scaled(x, y, sprite, scale_x, scale_y);
+    }
+
+    /// Draw the frame of `atlas` named `name`. Unknown names draw nothing.
+    #[track_caller]
+    pub fn draw_atlas_frame(&mut self, x: f32, y: f32, atlas: &SpriteAtlas, name: &str) {
+        if self.strict_rendering {
+            strict::finite("draw_atlas_frame", &[("x", x), ("y", y)]);
+        }
+        if let Some(frame) = atlas.frame(name) {
+            self.check_sprite(x, y, frame.width, frame.height);
+            self.renderer.draw_sprite_region(
+                x,
+                y,
+                atlas.sprite(),
+                frame.x,
+                frame.y,
+                frame.width,
+                frame.height,
+            );
+        }
+    }
+
+    /// Draw the region of a sprite sheet with its top left corner at (src_x, src_y).
+    #[allow(clippy::too_many_arguments)]
+    #[track_caller]
+    pub fn draw_sprite_region(
+        &mut self,
+        x: f32,
+        y: f32,
+        sprite: &Sprite,
+        src_x: u32,
+        src_y: u32,
+        src_width: u32,
+        src_height: u32,
+    ) {
+        if self.strict_rendering {
+            strict::finite("draw_sprite_region", &[("x", x), ("y", y)]);
+        }
+        self.check_sprite(x, y, src_width, src_height);
+        self.renderer
+            .draw_sprite_region(x, y, sprite, src_x, src_y, src_width, src_height);
+    }
+
+    /// Draw a sprite rotated counter-clockwise by `rotation` radians and scaled about `origin`,
+    /// given in sprite pixels from the corner `draw_sprite` anchors.
+    #[allow(clippy::too_many_arguments)]
+    #[track_caller]
+    pub fn draw_sprite_ex(
+        &mut self,
+        x: f32,
+        y: f32,
+        sprite: &Sprite,
+        rotation: f32,
+        scale_x: f32,
+        scale_y: f32,
+        origin: Vec2,
+    ) {
+        if self.strict_rendering {
+            strict::finite(
+                "draw_sprite_ex",
+                &[
+                    ("x", x),
+                    ("y", y),
+                    ("rotation", rotation),
+                    ("scale_x", scale_x),
+                    ("scale_y", scale_y),
+                    ("origin.x", origin.x),
+                    ("origin.y", origin.y),
+                ],
+            );
+        }
+        let bounds = self.renderer.sprite_ex_bounds(
+            x,
+            y,
+            sprite,
+            rotation,
+            Vec2::new(scale_x, scale_y),
+            origin,
+        );
+        self.check_sprite_area(bounds);
+        self.renderer
+            .draw_sprite_ex(x, y, sprite, rotation, scale_x, scale_y, origin);
+    }
+
+    #[track_caller]
+    pub fn draw_sprite_tiled(&mut self, rect: Rect, sprite: &Sprite, offset: Vec2) {
+        if self.strict_rendering {
+            strict::finite(
+                "draw_sprite_tiled",
+                &[
+                    ("rect.x", rect.x),
+                    ("rect.y", rect.y),
+                    ("offset.x", offset.x),
+                    ("offset.y", offset.y),
+                ],
+            );
+            strict::non_negative(
+                "draw_sprite_tiled",
+                &[("rect.width", rect.width), ("rect.height", rect.height)],
+            );
+        }
+        // Tiled sprites are drawn on screen, whether or not a camera is set.
+        if let Some(diagnostics) = &mut self.diagnostics {
+            diagnostics.sprite_drawn(self.renderer.is_screen_area_visible(rect));
+        }
+        self.renderer.draw_sprite_tiled(rect, sprite, offset);
+    }
+
+    fn check_string(&mut self, value: &str) {
+        if let Some(diagnostics) = &mut self.diagnostics {
+            diagnostics.string_drawn(value);
+        }
+    }
+
+    fn check_sprite(&mut self, x: f32, y: f32, width: u32, height: u32) {
+        self.check_sprite_area(Rect::new(x, y, width as f32, height as f32));
+    }
+
+    /// Count a sprite drawn over `area`, in the world, as seen through the camera.
+    fn check_sprite_area(&mut self, area: Rect) {
+        if let Some(diagnostics) = &mut self.diagnostics {
+            diagnostics.sprite_drawn(self.renderer.is_area_visible(area));
+        }
+    }
+}
+
+/// Convert a position in window pixels from the bottom left of the window to virtual pixels.
+fn window_to_virtual(
+    position: Vec2,
+    origin: Origin,
+    window_height: f32,
+    pixel_width: usize,
+    pixel_height: usize,
+) -> Vec2 {
+    let y = match origin {
+        Origin::BottomLeft => position.y,
+        Origin::TopLeft => window_height - position.y,
+    };
+
+    Vec2::new(position.x / pixel_width as f32, y / pixel_height as f32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn virtual_mouse_position_is_scaled_by_pixel_size() {
+        let position = window_to_virtual(Vec2::new(25.0, 30.0), Origin::BottomLeft, 100.0, 2, 4);
+
+        assert_eq!(Vec2::new(12.5, 7.5), position);
+    }
+
+    #[test]
+    fn virtual_mouse_position_follows_the_origin() {
+        let position = window_to_virtual(Vec2::new(10.0, 30.0), Origin::TopLeft, 100.0, 2, 2);
+
+        assert_eq!(Vec2::new(5.0, 35.0), position);
+    }
 }
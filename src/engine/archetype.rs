@@ -0,0 +1,421 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::engine::sprite::Sprite;
+use crate::maths::Vec2;
+
+#[derive(Debug, Error)]
+pub enum ArchetypeError {
+    #[error("unable to read archetype definitions")]
+    Io(#[from] std::io::Error),
+    #[error("invalid archetype definitions")]
+    Parse(#[from] toml::de::Error),
+    #[error("no archetype named '{0}'")]
+    Unknown(String),
+    #[error("unable to load sprite '{path}'")]
+    Sprite {
+        path: String,
+        source: image::ImageError,
+    },
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CollisionShape {
+    Circle { radius: f32 },
+    Rectangle { width: f32, height: f32 },
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct PhysicsProperties {
+    pub mass: f32,
+    pub elasticity: f32,
+    pub friction: f32,
+    pub gravity_scale: f32,
+}
+
+impl Default for PhysicsProperties {
+    fn default() -> Self {
+        Self {
+            mass: 1.0,
+            elasticity: 0.0,
+            friction: 0.0,
+            gravity_scale: 1.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct AnimationDefinition {
+    /// Sprite paths, one per frame.
+    pub frames: Vec<String>,
+    /// Seconds each frame is shown for.
+    pub frame_time: f32,
+    #[serde(default = "default_looping")]
+    pub looping: bool,
+    /// Events fired when frames are reached, e.g.
+    /// `events = [{ frame = 2, name = "footstep" }]`.
+    #[serde(default)]
+    pub events: Vec<FrameEvent>,
+}
+
+/// A named event on an animation frame, e.g. "hit" on the frame a sword connects.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct FrameEvent {
+    /// The index of the frame, from 0.
+    pub frame: usize,
+    pub name: String,
+}
+
+fn default_looping() -> bool {
+    true
+}
+
+/// A template describing how to build one kind of entity.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+pub struct Archetype {
+    pub sprite: Option<String>,
+    pub animation: Option<AnimationDefinition>,
+    pub physics: Option<PhysicsProperties>,
+    pub shape: Option<CollisionShape>,
+    /// Game specific values, e.g. `health = 100`.
+    #[serde(default)]
+    pub fields: HashMap<String, toml::Value>,
+}
+
+impl Archetype {
+    pub fn field_f32(&self, name: &str) -> Option<f32> {
+        match self.fields.get(name)? {
+            toml::Value::Float(value) => Some(*value as f32),
+            toml::Value::Integer(value) => Some(*value as f32),
+            _ => None,
+        }
+    }
+
+    pub fn field_i64(&self, name: &str) -> Option<i64> {
+        self.fields.get(name)?.as_integer()
+    }
+
+    pub fn field_bool(&self, name: &str) -> Option<bool> {
+        self.fields.get(name)?.as_bool()
+    }
+
+    pub fn field_str(&self, name: &str) -> Option<&str> {
+        self.fields.get(name)?.as_str()
+    }
+}
+
+/// Frames loaded from an `AnimationDefinition`.
+pub struct Animation {
+    pub frames: Vec<Sprite>,
+    pub frame_time: f32,
+    pub looping: bool,
+    pub events: Vec<FrameEvent>,
+}
+
+impl Animation {
+    /// The frame to show `elapsed` seconds into the animation. Animations that don't loop hold
+    /// their last frame.
+    pub fn frame(&self, elapsed: f32) -> Option<&Sprite> {
+        let index = if self.frame_time > 0.0 {
+            (elapsed / self.frame_time).max(0.0) as usize
+        } else {
+            0
+        };
+        let index = match (self.looping, self.frames.len()) {
+            (_, 0) => return None,
+            (true, len) => index % len,
+            (false, len) => index.min(len - 1),
+        };
+
+        self.frames.get(index)
+    }
+
+    /// The names of events on frames reached from `previous` up to but not including `elapsed`
+    /// seconds, in order. Call it each frame with the last and current elapsed time, e.g. to
+    /// play a footstep sound, and each event fires once per pass through the animation.
+    pub fn events(&self, previous: f32, elapsed: f32) -> Vec<&str> {
+        let len = self.frames.len();
+        if len == 0 || self.frame_time <= 0.0 || elapsed <= previous {
+            return Vec::new();
+        }
+
+        // Frame starts are numbered across loops, so frame `start` begins at start * frame_time.
+        let first = (previous.max(0.0) / self.frame_time).ceil() as usize;
+        let last = (elapsed / self.frame_time).ceil() as usize;
+        let last = if self.looping { last } else { last.min(len) };
+
+        (first..last)
+            .flat_map(|start| {
+                self.events
+                    .iter()
+                    .filter(move |event| event.frame == start % len)
+                    .map(|event| event.name.as_str())
+            })
+            .collect()
+    }
+}
+
+/// An entity built from an archetype, with its sprites loaded, ready for a game to add to its
+/// world or physics.
+pub struct ArchetypeInstance {
+    /// The name of the archetype it was built from.
+    pub archetype: String,
+    pub position: Vec2,
+    pub velocity: Vec2,
+    pub sprite: Option<Sprite>,
+    pub animation: Option<Animation>,
+    pub physics: Option<PhysicsProperties>,
+    pub shape: Option<CollisionShape>,
+    pub fields: HashMap<String, toml::Value>,
+}
+
+/// A named collection of archetypes, loaded from TOML where each table is one archetype:
+///
+/// ```toml
+/// [worm]
+/// sprite = "assets/worm.png"
+/// physics = { mass = 1.0, elasticity = 0.2 }
+/// shape = { type = "circle", radius = 3.5 }
+/// fields = { health = 100 }
+/// ```
+#[derive(Debug, Default)]
+pub struct Archetypes {
+    archetypes: HashMap<String, Archetype>,
+}
+
+impl Archetypes {
+    /// Load archetypes from a TOML string, replacing any existing archetypes with the same name.
+    pub fn load_str(&mut self, source: &str) -> Result<(), ArchetypeError> {
+        let archetypes: HashMap<String, Archetype> = toml::from_str(source)?;
+        self.archetypes.extend(archetypes);
+
+        Ok(())
+    }
+
+    pub fn load_file(&mut self, path: impl AsRef<Path>) -> Result<(), ArchetypeError> {
+        let source = std::fs::read_to_string(path)?;
+        self.load_str(&source)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Archetype> {
+        self.archetypes.get(name)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.archetypes.keys().map(String::as_str)
+    }
+
+    /// Build an entity from the archetype `name` at (x, y), loading its sprites. Sprite paths
+    /// are relative to the working directory.
+    pub fn instantiate(
+        &self,
+        name: &str,
+        x: f32,
+        y: f32,
+    ) -> Result<ArchetypeInstance, ArchetypeError> {
+        let archetype = self
+            .get(name)
+            .ok_or_else(|| ArchetypeError::Unknown(name.to_string()))?;
+
+        let sprite = archetype.sprite.as_deref().map(load_sprite).transpose()?;
+        let animation = match &archetype.animation {
+            Some(animation) => Some(Animation {
+                frames: animation
+                    .frames
+                    .iter()
+                    .map(|path| load_sprite(path))
+                    .collect::<Result<_, _>>()?,
+                frame_time: animation.frame_time,
+                looping: animation.looping,
+                events: animation.events.clone(),
+            }),
+            None => None,
+        };
+
+        Ok(ArchetypeInstance {
+            archetype: name.to_string(),
+            position: Vec2::new(x, y),
+            velocity: Vec2::new(0.0, 0.0),
+            sprite,
+            animation,
+            physics: archetype.physics.clone(),
+            shape: archetype.shape.clone(),
+            fields: archetype.fields.clone(),
+        })
+    }
+}
+
+fn load_sprite(path: &str) -> Result<Sprite, ArchetypeError> {
+    let image = image::open(path).map_err(|source| ArchetypeError::Sprite {
+        path: path.to_string(),
+        source,
+    })?;
+    let image = image.to_rgba8();
+    let (width, height) = image.dimensions();
+
+    Ok(Sprite::from_raw_rgba(width, height, image.into_raw()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEFINITIONS: &str = r#"
+        [worm]
+        sprite = "assets/worm.png"
+        physics = { mass = 2.0, elasticity = 0.2 }
+        shape = { type = "circle", radius = 3.5 }
+        fields = { health = 100, name = "Boggy" }
+
+        [crate]
+        shape = { type = "rectangle", width = 8.0, height = 8.0 }
+        animation = { frames = ["crate_0.png", "crate_1.png"], frame_time = 0.1 }
+    "#;
+
+    #[test]
+    fn archetypes_are_loaded_by_name() {
+        let mut archetypes = Archetypes::default();
+        archetypes.load_str(DEFINITIONS).unwrap();
+
+        let worm = archetypes.get("worm").unwrap();
+
+        assert_eq!(Some("assets/worm.png"), worm.sprite.as_deref());
+        assert_eq!(Some(CollisionShape::Circle { radius: 3.5 }), worm.shape);
+    }
+
+    #[test]
+    fn unspecified_physics_properties_use_defaults() {
+        let mut archetypes = Archetypes::default();
+        archetypes.load_str(DEFINITIONS).unwrap();
+
+        let physics = archetypes.get("worm").unwrap().physics.clone().unwrap();
+
+        assert_eq!(2.0, physics.mass);
+        assert_eq!(1.0, physics.gravity_scale);
+    }
+
+    #[test]
+    fn animation_loops_by_default() {
+        let mut archetypes = Archetypes::default();
+        archetypes.load_str(DEFINITIONS).unwrap();
+
+        let animation = archetypes.get("crate").unwrap().animation.clone().unwrap();
+
+        assert_eq!(2, animation.frames.len());
+        assert!(animation.looping);
+    }
+
+    #[test]
+    fn frame_events_fire_once_per_pass() {
+        let animation = Animation {
+            frames: vec![
+                Sprite::from_raw_rgba(1, 1, vec![0; 4]),
+                Sprite::from_raw_rgba(1, 1, vec![0; 4]),
+            ],
+            frame_time: 0.1,
+            looping: true,
+            events: vec![
+                FrameEvent {
+                    frame: 0,
+                    name: "step".into(),
+                },
+                FrameEvent {
+                    frame: 1,
+                    name: "hit".into(),
+                },
+            ],
+        };
+
+        assert_eq!(vec!["step"], animation.events(0.0, 0.05));
+        assert!(animation.events(0.05, 0.1).is_empty());
+        assert_eq!(vec!["hit", "step", "hit"], animation.events(0.1, 0.35));
+
+        let once = Animation {
+            looping: false,
+            ..animation
+        };
+        assert_eq!(vec!["hit"], once.events(0.05, 1.0));
+    }
+
+    #[test]
+    fn custom_fields_are_accessible_by_type() {
+        let mut archetypes = Archetypes::default();
+        archetypes.load_str(DEFINITIONS).unwrap();
+
+        let worm = archetypes.get("worm").unwrap();
+
+        assert_eq!(Some(100), worm.field_i64("health"));
+        assert_eq!(Some(100.0), worm.field_f32("health"));
+        assert_eq!(Some("Boggy"), worm.field_str("name"));
+        assert_eq!(None, worm.field_bool("name"));
+    }
+
+    #[test]
+    fn instances_copy_their_archetype() {
+        let mut archetypes = Archetypes::default();
+        archetypes
+            .load_str(
+                "[mine]\nshape = { type = \"circle\", radius = 2.0 }\nfields = { damage = 5 }",
+            )
+            .unwrap();
+
+        let mine = archetypes.instantiate("mine", 10.0, 20.0).unwrap();
+
+        assert_eq!("mine", mine.archetype);
+        assert_eq!(Vec2::new(10.0, 20.0), mine.position);
+        assert_eq!(Some(CollisionShape::Circle { radius: 2.0 }), mine.shape);
+        assert_eq!(Some(&toml::Value::Integer(5)), mine.fields.get("damage"));
+        assert!(mine.sprite.is_none());
+    }
+
+    #[test]
+    fn instantiating_fails_for_unknown_archetypes_and_missing_sprites() {
+        let mut archetypes = Archetypes::default();
+        archetypes.load_str(DEFINITIONS).unwrap();
+
+        assert!(matches!(
+            archetypes.instantiate("dragon", 0.0, 0.0),
+            Err(ArchetypeError::Unknown(_))
+        ));
+        assert!(matches!(
+            archetypes.instantiate("worm", 0.0, 0.0),
+            Err(ArchetypeError::Sprite { .. })
+        ));
+    }
+
+    #[test]
+    fn animation_frames_loop_or_hold() {
+        let frame = || Sprite::from_raw_rgba(1, 1, vec![0; 4]);
+        let mut animation = Animation {
+            frames: vec![frame(), frame()],
+            frame_time: 0.1,
+            looping: true,
+            events: Vec::new(),
+        };
+
+        assert!(std::ptr::eq(
+            &animation.frames[0],
+            animation.frame(0.25).unwrap()
+        ));
+        animation.looping = false;
+        assert!(std::ptr::eq(
+            &animation.frames[1],
+            animation.frame(0.25).unwrap()
+        ));
+    }
+
+    #[test]
+    fn invalid_definitions_are_an_error() {
+        let mut archetypes = Archetypes::default();
+
+        assert!(matches!(
+            archetypes.load_str("[worm]\nshape = { type = \"hexagon\" }"),
+            Err(ArchetypeError::Parse(_))
+        ));
+    }
+}
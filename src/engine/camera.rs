@@ -0,0 +1,828 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+
+use log::warn;
+
+use crate::maths::{clamp, exp_decay_vec2, Easing, Rect, Vec2};
+
+/// How a camera keeps a moving target in view.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Follow {
+    rate: f32,
+    deadzone: Vec2,
+    lookahead: f32,
+}
+
+impl Default for Follow {
+    fn default() -> Self {
+        Self::locked()
+    }
+}
+
+impl Follow {
+    /// Keep the target centered.
+    pub fn locked() -> Self {
+        Self {
+            rate: 0.0,
+            deadzone: Vec2::new(0.0, 0.0),
+            lookahead: 0.0,
+        }
+    }
+
+    /// Ease towards the target, closing the gap at `rate` as with `maths::exp_decay`.
+    pub fn smooth(rate: f32) -> Self {
+        Self {
+            rate,
+            ..Self::locked()
+        }
+    }
+
+    /// Only move once the target leaves a `width` x `height` box around the center of the view,
+    /// so small movements don't shake the view.
+    pub fn with_deadzone(mut self, width: f32, height: f32) -> Self {
+        self.deadzone = Vec2::new(width, height);
+        self
+    }
+
+    /// Aim where the target will be in `seconds` at its current speed, so more of what it is
+    /// heading towards is shown.
+    pub fn with_lookahead(mut self, seconds: f32) -> Self {
+        self.lookahead = seconds;
+        self
+    }
+}
+
+/// A framing of the world that a camera can be moved to.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Shot {
+    pub center: Vec2,
+    pub zoom: f32,
+}
+
+impl Shot {
+    pub fn new(center: Vec2, zoom: f32) -> Self {
+        Self { center, zoom }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum CameraStep {
+    Hold(f32),
+    Move {
+        shot: String,
+        duration: Duration,
+        easing: Easing,
+    },
+    Cut(String),
+}
+
+/// Camera moves between named shots, played in order with `Camera::play`, e.g. an intro
+/// fly-over of the level:
+///
+/// ```text
+/// CameraSequence::new()
+///     .cut("castle")
+///     .hold(Duration::from_secs(2))
+///     .move_to("village", Duration::from_secs(3), Easing::EaseInOut)
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CameraSequence {
+    steps: VecDeque<CameraStep>,
+}
+
+impl CameraSequence {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep the current framing for `duration`.
+    pub fn hold(mut self, duration: Duration) -> Self {
+        self.steps
+            .push_back(CameraStep::Hold(duration.as_secs_f32()));
+        self
+    }
+
+    /// Move to the shot named `shot` over `duration`.
+    pub fn move_to(mut self, shot: impl Into<String>, duration: Duration, easing: Easing) -> Self {
+        self.steps.push_back(CameraStep::Move {
+            shot: shot.into(),
+            duration,
+            easing,
+        });
+        self
+    }
+
+    /// Jump straight to the shot named `shot`.
+    pub fn cut(mut self, shot: impl Into<String>) -> Self {
+        self.steps.push_back(CameraStep::Cut(shot.into()));
+        self
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Playback {
+    steps: VecDeque<CameraStep>,
+    hold: f32,
+}
+
+#[derive(Clone, Debug)]
+struct Transition {
+    from_center: Vec2,
+    to_center: Vec2,
+    from_zoom: f32,
+    to_zoom: f32,
+    duration: f32,
+    elapsed: f32,
+    easing: Easing,
+}
+
+/// A view of `width` x `height` virtual pixels onto the world, which can follow a target, pan
+/// between positions and zoom. Subtract `position` from world positions when drawing, or use
+/// `world_to_screen` when zoomed.
+#[derive(Clone, Debug)]
+pub struct Camera {
+    center: Vec2,
+    zoom: f32,
+    viewport: Vec2,
+    bounds: Option<Rect>,
+    follow: Follow,
+    transition: Option<Transition>,
+    last_target: Option<Vec2>,
+    shots: HashMap<String, Shot>,
+    playback: Option<Playback>,
+}
+
+impl Camera {
+    /// Create a camera looking at the area from (0, 0) to (width, height).
+    pub fn new(width: f32, height: f32) -> Self {
+        let viewport = Vec2::new(width, height);
+
+        Self {
+            center: viewport * 0.5,
+            zoom: 1.0,
+            viewport,
+            bounds: None,
+            follow: Follow::default(),
+            transition: None,
+            last_target: None,
+            shots: HashMap::new(),
+            playback: None,
+        }
+    }
+
+    /// Keep the view inside `bounds`, e.g. the level. Defaults to unbounded.
+    pub fn with_bounds(mut self, bounds: Rect) -> Self {
+        self.set_bounds(Some(bounds));
+        self
+    }
+
+    pub fn set_bounds(&mut self, bounds: Option<Rect>) {
+        self.bounds = bounds;
+        self.clamp_to_bounds();
+    }
+
+    /// How `update` follows a target. Defaults to `Follow::locked`.
+    pub fn with_follow(mut self, follow: Follow) -> Self {
+        self.follow = follow;
+        self
+    }
+
+    pub fn set_follow(&mut self, follow: Follow) {
+        self.follow = follow;
+    }
+
+    /// The world position of the view's corner at (0, 0), which is subtracted when drawing.
+    pub fn position(&self) -> Vec2 {
+        self.center - self.view_size() * 0.5
+    }
+
+    /// Move the view's corner to (x, y), stopping any pan.
+    pub fn set_position(&mut self, x: f32, y: f32) {
+        let center = Vec2::new(x, y) + self.view_size() * 0.5;
+        self.set_center(center.x, center.y);
+    }
+
+    pub fn center(&self) -> Vec2 {
+        self.center
+    }
+
+    /// Look at (x, y), stopping any pan.
+    pub fn set_center(&mut self, x: f32, y: f32) {
+        self.transition = None;
+        self.center = Vec2::new(x, y);
+        self.clamp_to_bounds();
+    }
+
+    pub fn zoom(&self) -> f32 {
+        self.zoom
+    }
+
+    /// Show the world `zoom` times larger, keeping the same center.
+    pub fn set_zoom(&mut self, zoom: f32) {
+        self.transition = None;
+        self.zoom = zoom.max(f32::EPSILON);
+        self.clamp_to_bounds();
+    }
+
+    /// The size of the world area shown, which shrinks as the camera zooms in.
+    pub fn view_size(&self) -> Vec2 {
+        self.viewport * (1.0 / self.zoom)
+    }
+
+    /// The world area shown.
+    pub fn visible_rect(&self) -> Rect {
+        let position = self.position();
+        let size = self.view_size();
+
+        Rect::new(position.x, position.y, size.x, size.y)
+    }
+
+    pub fn world_to_screen(&self, point: Vec2) -> Vec2 {
+        (point - self.position()) * self.zoom
+    }
+
+    pub fn screen_to_world(&self, point: Vec2) -> Vec2 {
+        point * (1.0 / self.zoom) + self.position()
+    }
+
+    /// Move the view, its bounds, any pan in progress and its shots by `-shift`, so the same part
+    /// of the world stays in view when a `FloatingOrigin` rebases.
+    pub fn shift_origin(&mut self, shift: Vec2) {
+        self.center = self.center - shift;
+        if let Some(bounds) = &mut self.bounds {
+            bounds.x -= shift.x;
+            bounds.y -= shift.y;
+        }
+        if let Some(transition) = &mut self.transition {
+            transition.from_center = transition.from_center - shift;
+            transition.to_center = transition.to_center - shift;
+        }
+        if let Some(target) = &mut self.last_target {
+            *target = *target - shift;
+        }
+        for shot in self.shots.values_mut() {
+            shot.center = shot.center - shift;
+        }
+    }
+
+    /// Move the center of the view to `target` over `duration`. Following is suspended until
+    /// the pan finishes.
+    pub fn pan_to(&mut self, target: Vec2, duration: Duration, easing: Easing) {
+        self.transition_to(target, self.zoom, duration, easing);
+    }
+
+    /// Pan and zoom so `rect` fills as much of the view as it can without being cropped.
+    pub fn zoom_to_fit(&mut self, rect: Rect, duration: Duration, easing: Easing) {
+        let zoom = if rect.width > 0.0 && rect.height > 0.0 {
+            (self.viewport.x / rect.width).min(self.viewport.y / rect.height)
+        } else {
+            self.zoom
+        };
+        let center = Vec2::new(rect.x + rect.width / 2.0, rect.y + rect.height / 2.0);
+
+        self.transition_to(center, zoom, duration, easing);
+    }
+
+    /// Whether a pan or zoom is in progress.
+    pub fn is_transitioning(&self) -> bool {
+        self.transition.is_some()
+    }
+
+    /// The current framing.
+    pub fn current_shot(&self) -> Shot {
+        Shot::new(self.center, self.zoom)
+    }
+
+    /// Remember the current framing as `name`, replacing any shot with the same name.
+    pub fn bookmark(&mut self, name: impl Into<String>) {
+        self.set_shot(name, self.current_shot());
+    }
+
+    pub fn set_shot(&mut self, name: impl Into<String>, shot: Shot) {
+        self.shots.insert(name.into(), shot);
+    }
+
+    pub fn shot(&self, name: &str) -> Option<Shot> {
+        self.shots.get(name).copied()
+    }
+
+    /// Play `sequence` from the start, replacing any sequence or pan in progress. Following is
+    /// suspended until it finishes. Steps naming unknown shots are skipped.
+    pub fn play(&mut self, sequence: &CameraSequence) {
+        self.transition = None;
+        self.playback = Some(Playback {
+            steps: sequence.steps.clone(),
+            hold: 0.0,
+        });
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playback.is_some()
+    }
+
+    /// Finish the sequence in progress straight away, framing its last shot.
+    pub fn skip(&mut self) {
+        let playback = match self.playback.take() {
+            Some(playback) => playback,
+            None => return,
+        };
+
+        let mut last = self
+            .transition
+            .take()
+            .map(|transition| Shot::new(transition.to_center, transition.to_zoom));
+        for step in &playback.steps {
+            if let CameraStep::Move { shot, .. } | CameraStep::Cut(shot) = step {
+                last = self.shot(shot).or(last);
+            }
+        }
+
+        if let Some(shot) = last {
+            self.cut_to(shot);
+        }
+    }
+
+    fn cut_to(&mut self, shot: Shot) {
+        self.center = shot.center;
+        self.zoom = shot.zoom.max(f32::EPSILON);
+        self.clamp_to_bounds();
+    }
+
+    fn transition_to(&mut self, center: Vec2, zoom: f32, duration: Duration, easing: Easing) {
+        self.transition = Some(Transition {
+            from_center: self.center,
+            to_center: center,
+            from_zoom: self.zoom,
+            to_zoom: zoom.max(f32::EPSILON),
+            duration: duration.as_secs_f32(),
+            elapsed: 0.0,
+            easing,
+        });
+        self.update_transition(0.0);
+    }
+
+    /// Call every frame with the position of the target to follow, if any, to advance pans and
+    /// follow the target.
+    pub fn update(&mut self, dt: f32, target: Option<Vec2>) {
+        if self.playback.is_some() {
+            self.update_playback(dt);
+        } else if self.transition.is_some() {
+            self.update_transition(dt);
+        } else if let Some(target) = target {
+            self.update_follow(dt, target);
+        }
+        self.last_target = target;
+
+        self.clamp_to_bounds();
+    }
+
+    /// Advance the sequence, spending `dt` on its current step.
+    fn update_playback(&mut self, dt: f32) {
+        loop {
+            if self.transition.is_some() {
+                self.update_transition(dt);
+                return;
+            }
+
+            let playback = match &mut self.playback {
+                Some(playback) => playback,
+                None => return,
+            };
+            if playback.hold > 0.0 {
+                playback.hold -= dt;
+                return;
+            }
+
+            match playback.steps.pop_front() {
+                Some(CameraStep::Hold(duration)) => playback.hold = duration,
+                Some(CameraStep::Move {
+                    shot,
+                    duration,
+                    easing,
+                }) => match self.shot(&shot) {
+                    Some(shot) => self.transition_to(shot.center, shot.zoom, duration, easing),
+                    None => warn!("camera sequence skipped unknown shot '{}'", shot),
+                },
+                Some(CameraStep::Cut(shot)) => match self.shot(&shot) {
+                    Some(shot) => self.cut_to(shot),
+                    None => warn!("camera sequence skipped unknown shot '{}'", shot),
+                },
+                None => {
+                    self.playback = None;
+                    return;
+                }
+            }
+        }
+    }
+
+    fn update_transition(&mut self, dt: f32) {
+        let transition = match &mut self.transition {
+            Some(transition) => transition,
+            None => return,
+        };
+
+        transition.elapsed += dt;
+        let t = if transition.duration > 0.0 {
+            transition
+                .easing
+                .apply(transition.elapsed / transition.duration)
+        } else {
+            1.0
+        };
+        self.center = transition.from_center + (transition.to_center - transition.from_center) * t;
+        self.zoom = transition.from_zoom + (transition.to_zoom - transition.from_zoom) * t;
+
+        if t >= 1.0 {
+            self.transition = None;
+        }
+        self.clamp_to_bounds();
+    }
+
+    fn update_follow(&mut self, dt: f32, target: Vec2) {
+        let velocity = match self.last_target {
+            Some(last) if dt > 0.0 => (target - last) * (1.0 / dt),
+            _ => Vec2::new(0.0, 0.0),
+        };
+        let aim = target + velocity * self.follow.lookahead;
+
+        let offset = aim - self.center;
+        let half = self.follow.deadzone * 0.5;
+        let desired = self.center
+            + Vec2::new(
+                outside_deadzone(offset.x, half.x),
+                outside_deadzone(offset.y, half.y),
+            );
+
+        self.center = if self.follow.rate > 0.0 {
+            exp_decay_vec2(self.center, desired, self.follow.rate, dt)
+        } else {
+            desired
+        };
+    }
+
+    fn clamp_to_bounds(&mut self) {
+        let bounds = match self.bounds {
+            Some(bounds) => bounds,
+            None => return,
+        };
+
+        let half = self.view_size() * 0.5;
+        let clamp_axis = |center: f32, min: f32, size: f32, half: f32| {
+            if half * 2.0 >= size {
+                min + size / 2.0
+            } else {
+                clamp(min + half, center, min + size - half)
+            }
+        };
+        self.center = Vec2::new(
+            clamp_axis(self.center.x, bounds.x, bounds.width, half.x),
+            clamp_axis(self.center.y, bounds.y, bounds.height, half.y),
+        );
+    }
+}
+
+/// How far `offset` is beyond a deadzone extending `half` either side of zero.
+fn outside_deadzone(offset: f32, half: f32) -> f32 {
+    if offset > half {
+        offset - half
+    } else if offset < -half {
+        offset + half
+    } else {
+        0.0
+    }
+}
+
+/// A view onto the world that the renderer applies to world-space draw calls once set with
+/// `Apparatus::set_camera`, so games needn't subtract a camera position from every call. The
+/// center of the view is drawn at the center of the screen, magnified by the zoom and turned
+/// by the rotation, in radians counter-clockwise, which turns the world the other way.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Camera2D {
+    center: Vec2,
+    zoom: f32,
+    rotation: f32,
+}
+
+impl Default for Camera2D {
+    fn default() -> Self {
+        Self::new(Vec2::default())
+    }
+}
+
+impl Camera2D {
+    /// Look at `center`, unzoomed and unrotated.
+    pub fn new(center: Vec2) -> Self {
+        Self {
+            center,
+            zoom: 1.0,
+            rotation: 0.0,
+        }
+    }
+
+    /// Defaults to 1.
+    pub fn with_zoom(mut self, zoom: f32) -> Self {
+        self.set_zoom(zoom);
+        self
+    }
+
+    /// Defaults to 0.
+    pub fn with_rotation(mut self, rotation: f32) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    pub fn center(&self) -> Vec2 {
+        self.center
+    }
+
+    pub fn set_center(&mut self, center: Vec2) {
+        self.center = center;
+    }
+
+    /// Move the view by `offset` in world units.
+    pub fn pan(&mut self, offset: Vec2) {
+        self.center = self.center + offset;
+    }
+
+    pub fn zoom(&self) -> f32 {
+        self.zoom
+    }
+
+    /// Show the world `zoom` times larger.
+    pub fn set_zoom(&mut self, zoom: f32) {
+        self.zoom = zoom.max(f32::EPSILON);
+    }
+
+    pub fn rotation(&self) -> f32 {
+        self.rotation
+    }
+
+    pub fn set_rotation(&mut self, rotation: f32) {
+        self.rotation = rotation;
+    }
+
+    /// Where `point` in the world appears on a screen of `screen_size` virtual pixels.
+    pub fn world_to_screen(&self, point: Vec2, screen_size: Vec2) -> Vec2 {
+        let (sin, cos) = (-self.rotation).sin_cos();
+        let offset = point - self.center;
+        let turned = Vec2::new(
+            offset.x * cos - offset.y * sin,
+            offset.x * sin + offset.y * cos,
+        );
+
+        turned * self.zoom + screen_size * 0.5
+    }
+
+    /// The point in the world shown at `point` on a screen of `screen_size` virtual pixels,
+    /// e.g. under the mouse.
+    pub fn screen_to_world(&self, point: Vec2, screen_size: Vec2) -> Vec2 {
+        let (sin, cos) = self.rotation.sin_cos();
+        let offset = (point - screen_size * 0.5) * (1.0 / self.zoom);
+        let turned = Vec2::new(
+            offset.x * cos - offset.y * sin,
+            offset.x * sin + offset.y * cos,
+        );
+
+        turned + self.center
+    }
+
+    /// Whether drawing through the camera only moves things, so sprites can be copied rather
+    /// than resampled.
+    pub(crate) fn is_translation(&self) -> bool {
+        self.zoom == 1.0 && self.rotation == 0.0
+    }
+}
+
+/// The view a `Camera` shows, for a camera whose viewport is the size of the screen.
+impl From<&Camera> for Camera2D {
+    fn from(camera: &Camera) -> Self {
+        Camera2D::new(camera.center()).with_zoom(camera.zoom())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near(expected: Vec2, actual: Vec2) {
+        assert!(
+            (expected - actual).length() < 1e-3,
+            "expected {:?}, got {:?}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn pan_eases_to_the_target_over_its_duration() {
+        let mut camera = Camera::new(100.0, 100.0);
+
+        camera.pan_to(
+            Vec2::new(150.0, 50.0),
+            Duration::from_secs(1),
+            Easing::Linear,
+        );
+        camera.update(0.5, None);
+        assert_near(Vec2::new(100.0, 50.0), camera.center());
+
+        camera.update(0.5, None);
+        assert_near(Vec2::new(150.0, 50.0), camera.center());
+        assert!(!camera.is_transitioning());
+    }
+
+    #[test]
+    fn following_waits_for_the_pan_to_finish() {
+        let mut camera = Camera::new(100.0, 100.0);
+        camera.pan_to(
+            Vec2::new(150.0, 50.0),
+            Duration::from_secs(1),
+            Easing::EaseInOut,
+        );
+
+        camera.update(0.5, Some(Vec2::new(0.0, 0.0)));
+        assert_near(Vec2::new(100.0, 50.0), camera.center());
+
+        camera.update(0.5, Some(Vec2::new(0.0, 0.0)));
+        camera.update(0.1, Some(Vec2::new(0.0, 0.0)));
+        assert_near(Vec2::new(0.0, 0.0), camera.center());
+    }
+
+    #[test]
+    fn targets_inside_the_deadzone_do_not_move_the_camera() {
+        let mut camera =
+            Camera::new(100.0, 100.0).with_follow(Follow::locked().with_deadzone(20.0, 20.0));
+
+        camera.update(0.1, Some(Vec2::new(58.0, 45.0)));
+        assert_near(Vec2::new(50.0, 50.0), camera.center());
+
+        camera.update(0.1, Some(Vec2::new(70.0, 45.0)));
+        assert_near(Vec2::new(60.0, 50.0), camera.center());
+    }
+
+    #[test]
+    fn lookahead_leads_a_moving_target() {
+        let mut camera =
+            Camera::new(100.0, 100.0).with_follow(Follow::locked().with_lookahead(0.5));
+
+        camera.update(0.1, Some(Vec2::new(50.0, 50.0)));
+        camera.update(0.1, Some(Vec2::new(60.0, 50.0)));
+
+        assert_near(Vec2::new(110.0, 50.0), camera.center());
+    }
+
+    #[test]
+    fn view_is_kept_inside_the_bounds() {
+        let mut camera = Camera::new(100.0, 100.0).with_bounds(Rect::new(0.0, 0.0, 400.0, 80.0));
+
+        camera.set_position(-50.0, 0.0);
+        assert_near(Vec2::new(0.0, -10.0), camera.position());
+
+        camera.update(0.1, Some(Vec2::new(1000.0, 0.0)));
+        assert_near(Vec2::new(300.0, -10.0), camera.position());
+    }
+
+    #[test]
+    fn zoom_to_fit_shows_the_whole_rect() {
+        let mut camera = Camera::new(100.0, 50.0);
+
+        camera.zoom_to_fit(
+            Rect::new(10.0, 10.0, 50.0, 50.0),
+            Duration::ZERO,
+            Easing::Linear,
+        );
+
+        assert_eq!(1.0, camera.zoom());
+        assert_near(Vec2::new(35.0, 35.0), camera.center());
+        assert_near(Vec2::new(-15.0, 10.0), camera.position());
+        assert_near(
+            Vec2::new(50.0, 25.0),
+            camera.world_to_screen(Vec2::new(35.0, 35.0)),
+        );
+
+        camera.zoom_to_fit(
+            Rect::new(0.0, 0.0, 20.0, 10.0),
+            Duration::ZERO,
+            Easing::Linear,
+        );
+        assert_eq!(5.0, camera.zoom());
+        assert_near(
+            Vec2::new(20.0, 10.0),
+            camera.screen_to_world(Vec2::new(100.0, 50.0)),
+        );
+    }
+
+    fn shots() -> Camera {
+        let mut camera = Camera::new(100.0, 100.0);
+        camera.set_shot("castle", Shot::new(Vec2::new(0.0, 0.0), 1.0));
+        camera.set_shot("village", Shot::new(Vec2::new(100.0, 0.0), 2.0));
+        camera
+    }
+
+    #[test]
+    fn sequences_cut_hold_and_move_between_shots() {
+        let mut camera = shots();
+        let sequence = CameraSequence::new()
+            .cut("castle")
+            .hold(Duration::from_secs(1))
+            .move_to("village", Duration::from_secs(1), Easing::Linear);
+
+        camera.play(&sequence);
+        camera.update(0.5, Some(Vec2::new(500.0, 500.0)));
+        assert_eq!(Shot::new(Vec2::new(0.0, 0.0), 1.0), camera.current_shot());
+
+        camera.update(0.5, None);
+        camera.update(0.5, None);
+        assert_near(Vec2::new(50.0, 0.0), camera.center());
+        assert_eq!(1.5, camera.zoom());
+
+        camera.update(0.5, None);
+        camera.update(0.1, None);
+        assert_eq!(Shot::new(Vec2::new(100.0, 0.0), 2.0), camera.current_shot());
+        assert!(!camera.is_playing());
+    }
+
+    #[test]
+    fn skipping_frames_the_last_known_shot() {
+        let mut camera = shots();
+        let sequence = CameraSequence::new()
+            .move_to("village", Duration::from_secs(5), Easing::EaseInOut)
+            .hold(Duration::from_secs(1))
+            .cut("nowhere");
+
+        camera.play(&sequence);
+        camera.update(1.0, None);
+        camera.skip();
+
+        assert!(!camera.is_playing());
+        assert!(!camera.is_transitioning());
+        assert_eq!(Shot::new(Vec2::new(100.0, 0.0), 2.0), camera.current_shot());
+    }
+
+    #[test]
+    fn bookmarks_remember_the_current_framing() {
+        let mut camera = Camera::new(100.0, 100.0);
+        camera.set_center(30.0, 40.0);
+
+        camera.bookmark("start");
+        camera.set_center(0.0, 0.0);
+
+        assert_eq!(
+            Some(Shot::new(Vec2::new(30.0, 40.0), 1.0)),
+            camera.shot("start")
+        );
+    }
+
+    #[test]
+    fn shifting_the_origin_keeps_the_view_and_shots_in_place() {
+        let mut camera = Camera::new(100.0, 60.0).with_bounds(Rect::new(0.0, 0.0, 5000.0, 60.0));
+        camera.set_center(4000.0, 30.0);
+        camera.set_shot("boss", Shot::new(Vec2::new(4500.0, 30.0), 1.0));
+
+        camera.shift_origin(Vec2::new(4000.0, 0.0));
+        camera.update(0.1, None);
+
+        assert_near(Vec2::new(0.0, 30.0), camera.center());
+        assert_near(Vec2::new(500.0, 30.0), camera.shot("boss").unwrap().center);
+        // The bounds moved too, so the view can still reach their far end.
+        camera.set_center(2000.0, 30.0);
+        assert_near(Vec2::new(950.0, 30.0), camera.center());
+    }
+
+    #[test]
+    fn camera_2d_round_trips_through_the_screen() {
+        let screen = Vec2::new(100.0, 60.0);
+        let camera = Camera2D::new(Vec2::new(200.0, 40.0))
+            .with_zoom(2.0)
+            .with_rotation(std::f32::consts::FRAC_PI_2);
+
+        assert_near(
+            Vec2::new(50.0, 30.0),
+            camera.world_to_screen(Vec2::new(200.0, 40.0), screen),
+        );
+        // Turning the view a quarter counter-clockwise turns the world a quarter clockwise.
+        assert_near(
+            Vec2::new(50.0, 10.0),
+            camera.world_to_screen(Vec2::new(210.0, 40.0), screen),
+        );
+
+        let point = Vec2::new(12.0, 34.0);
+        assert_near(
+            point,
+            camera.screen_to_world(camera.world_to_screen(point, screen), screen),
+        );
+    }
+
+    #[test]
+    fn camera_2d_matches_an_unrotated_camera() {
+        let mut camera = Camera::new(100.0, 60.0);
+        camera.set_position(30.0, 20.0);
+        camera.set_zoom(2.0);
+        let view = Camera2D::from(&camera);
+        let point = Vec2::new(70.0, 45.0);
+
+        assert_near(
+            camera.world_to_screen(point),
+            view.world_to_screen(point, Vec2::new(100.0, 60.0)),
+        );
+    }
+}
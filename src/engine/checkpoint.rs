@@ -0,0 +1,293 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::color::Color;
+use crate::engine::apparatus::Apparatus;
+use crate::maths::{Rect, Vec2};
+use crate::procgen::seed::WorldSeed;
+
+#[derive(Debug, Error)]
+pub enum CheckpointError {
+    #[error("unable to access checkpoint save")]
+    Io(#[from] std::io::Error),
+    #[error("unable to write checkpoint save")]
+    Serialize(#[from] toml::ser::Error),
+    #[error("invalid checkpoint save")]
+    Deserialize(#[from] toml::de::Error),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Checkpoint {
+    pub name: String,
+    /// Where entities respawn.
+    pub position: Vec2,
+    /// Entering this area activates the checkpoint.
+    pub trigger: Rect,
+}
+
+#[derive(Default, Deserialize, Serialize)]
+struct SavedCheckpoints {
+    active: Option<String>,
+    seed: Option<WorldSeed>,
+}
+
+/// Tracks the checkpoints in a level and which one was activated last.
+pub struct Checkpoints {
+    start: Vec2,
+    checkpoints: Vec<Checkpoint>,
+    active: Option<usize>,
+    seed: Option<WorldSeed>,
+}
+
+impl Checkpoints {
+    /// Create an empty set of checkpoints, respawning at `start` until one is activated.
+    pub fn new(start: Vec2) -> Self {
+        Self {
+            start,
+            checkpoints: Vec::new(),
+            active: None,
+            seed: None,
+        }
+    }
+
+    /// Save the seed the level was generated from alongside the progress, so it can be
+    /// regenerated on load.
+    pub fn with_seed(mut self, seed: WorldSeed) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    pub fn seed(&self) -> Option<WorldSeed> {
+        self.seed
+    }
+
+    pub fn register(&mut self, name: impl Into<String>, position: Vec2, trigger: Rect) {
+        self.checkpoints.push(Checkpoint {
+            name: name.into(),
+            position,
+            trigger,
+        });
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Checkpoint> {
+        self.checkpoints
+            .iter()
+            .find(|checkpoint| checkpoint.name == name)
+    }
+
+    pub fn active(&self) -> Option<&Checkpoint> {
+        self.checkpoints.get(self.active?)
+    }
+
+    /// Activate the checkpoint called `name`, returning false if there isn't one.
+    pub fn activate(&mut self, name: &str) -> bool {
+        match self.checkpoints.iter().position(|c| c.name == name) {
+            Some(index) => {
+                self.active = Some(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Activate any checkpoint whose trigger contains `position`. Returns the checkpoint when it
+    /// wasn't already active, e.g. to show a "checkpoint reached" message.
+    pub fn update(&mut self, position: Vec2) -> Option<&Checkpoint> {
+        let index = self.checkpoints.iter().position(|checkpoint| {
+            let Rect {
+                x,
+                y,
+                width,
+                height,
+            } = checkpoint.trigger;
+            position.x >= x && position.x < x + width && position.y >= y && position.y < y + height
+        })?;
+
+        if self.active == Some(index) {
+            return None;
+        }
+        self.active = Some(index);
+
+        self.checkpoints.get(index)
+    }
+
+    /// Where to respawn: the last activated checkpoint, or the level start.
+    pub fn respawn_position(&self) -> Vec2 {
+        self.active()
+            .map_or(self.start, |checkpoint| checkpoint.position)
+    }
+
+    pub fn reset(&mut self) {
+        self.active = None;
+    }
+
+    pub fn save_str(&self) -> Result<String, CheckpointError> {
+        let saved = SavedCheckpoints {
+            active: self.active().map(|checkpoint| checkpoint.name.clone()),
+            seed: self.seed,
+        };
+
+        Ok(toml::to_string(&saved)?)
+    }
+
+    /// Restore the active checkpoint, and the seed if one was saved. Checkpoints that no longer
+    /// exist are ignored.
+    pub fn load_str(&mut self, source: &str) -> Result<(), CheckpointError> {
+        let saved: SavedCheckpoints = toml::from_str(source)?;
+        self.seed = saved.seed.or(self.seed);
+        self.active = None;
+        if let Some(name) = saved.active {
+            self.activate(&name);
+        }
+
+        Ok(())
+    }
+
+    pub fn save_file(&self, path: impl AsRef<Path>) -> Result<(), CheckpointError> {
+        std::fs::write(path, self.save_str()?)?;
+
+        Ok(())
+    }
+
+    pub fn load_file(&mut self, path: impl AsRef<Path>) -> Result<(), CheckpointError> {
+        let source = std::fs::read_to_string(path)?;
+        self.load_str(&source)
+    }
+}
+
+/// Fades the screen out and back in around a respawn.
+pub struct RespawnFade {
+    duration: f32,
+    elapsed: Option<f32>,
+    color: Color,
+}
+
+impl RespawnFade {
+    /// A fade lasting `duration` seconds in total, half out and half in.
+    pub fn new(duration: f32) -> Self {
+        Self {
+            duration,
+            elapsed: None,
+            color: Color::rgba(0, 0, 0, 255),
+        }
+    }
+
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn start(&mut self) {
+        self.elapsed = Some(0.0);
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.elapsed.is_some()
+    }
+
+    /// Advance the fade. Returns true on the frame the screen is fully covered, which is when
+    /// entities should be moved to their respawn position.
+    pub fn update(&mut self, dt: f32) -> bool {
+        let elapsed = match self.elapsed {
+            Some(elapsed) => elapsed,
+            None => return false,
+        };
+
+        let midpoint = self.duration / 2.0;
+        let next = elapsed + dt;
+        self.elapsed = if next >= self.duration {
+            None
+        } else {
+            Some(next)
+        };
+
+        elapsed < midpoint && next >= midpoint
+    }
+
+    /// How much of the screen is covered, from 0 to 1.
+    pub fn opacity(&self) -> f32 {
+        match self.elapsed {
+            Some(elapsed) if self.duration > 0.0 => {
+                1.0 - (elapsed / (self.duration / 2.0) - 1.0).abs()
+            }
+            _ => 0.0,
+        }
+    }
+
+    pub fn draw(&self, app: &mut Apparatus) {
+        let opacity = self.opacity();
+        if opacity <= 0.0 {
+            return;
+        }
+
+        let color = Color::rgba(
+            self.color.r(),
+            self.color.g(),
+            self.color.b(),
+            (self.color.a() as f32 * opacity) as u8,
+        );
+        let (width, height) = (app.screen_width() as f32, app.screen_height() as f32);
+        app.draw_filled_rectangle(0.0, 0.0, width, height, color);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn level() -> Checkpoints {
+        let mut checkpoints = Checkpoints::new(Vec2::new(0.0, 0.0));
+        checkpoints.register(
+            "cave",
+            Vec2::new(100.0, 10.0),
+            Rect::new(90.0, 0.0, 20.0, 40.0),
+        );
+        checkpoints.register(
+            "summit",
+            Vec2::new(200.0, 80.0),
+            Rect::new(190.0, 70.0, 20.0, 40.0),
+        );
+        checkpoints
+    }
+
+    #[test]
+    fn entering_trigger_activates_checkpoint_once() {
+        let mut checkpoints = level();
+        assert_eq!(Vec2::new(0.0, 0.0), checkpoints.respawn_position());
+
+        let reached = checkpoints
+            .update(Vec2::new(95.0, 5.0))
+            .map(|c| c.name.clone());
+
+        assert_eq!(Some("cave".to_string()), reached);
+        assert!(checkpoints.update(Vec2::new(96.0, 5.0)).is_none());
+        assert_eq!(Vec2::new(100.0, 10.0), checkpoints.respawn_position());
+    }
+
+    #[test]
+    fn active_checkpoint_and_seed_survive_save_and_load() {
+        let mut checkpoints = level().with_seed(WorldSeed::new(7));
+        checkpoints.activate("summit");
+
+        let saved = checkpoints.save_str().unwrap();
+        let mut restored = level();
+        restored.load_str(&saved).unwrap();
+
+        assert_eq!(Vec2::new(200.0, 80.0), restored.respawn_position());
+        assert_eq!(Some(WorldSeed::new(7)), restored.seed());
+    }
+
+    #[test]
+    fn fade_signals_respawn_when_fully_covered() {
+        let mut fade = RespawnFade::new(1.0);
+        fade.start();
+
+        let signals: Vec<bool> = (0..10).map(|_| fade.update(0.1)).collect();
+
+        assert_eq!(1, signals.iter().filter(|&&signal| signal).count());
+        assert!(signals[4]);
+        assert!(!fade.is_active());
+    }
+}
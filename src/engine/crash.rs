@@ -0,0 +1,213 @@
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::panic::PanicHookInfo;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, Once};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use flexi_logger::LoggerHandle;
+use image::RgbaImage;
+use log::error;
+
+use crate::engine::key::Key;
+use crate::engine::mouse::MouseButton;
+
+/// Where crash bundles are written and how much input history they contain.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CrashSettings {
+    pub directory: PathBuf,
+    pub frames: usize,
+}
+
+/// The input state of a single frame.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct FrameRecord {
+    pub(crate) frame: u64,
+    pub(crate) keys: Vec<Key>,
+    pub(crate) mouse: (f32, f32),
+    pub(crate) buttons: Vec<MouseButton>,
+}
+
+#[derive(Default)]
+struct CrashState {
+    settings: String,
+    frames: VecDeque<FrameRecord>,
+    /// The bundle written by the last panic, which the screenshot is added to.
+    bundle: Option<PathBuf>,
+}
+
+/// The reporter the panic hook writes bundles for.
+struct ActiveReporter {
+    directory: PathBuf,
+    state: Arc<Mutex<CrashState>>,
+    logger: LoggerHandle,
+}
+
+static ACTIVE_REPORTER: Mutex<Option<ActiveReporter>> = Mutex::new(None);
+static INSTALL_HOOK: Once = Once::new();
+
+/// Keeps a rolling history of recent frames so a panic hook can write a diagnostic bundle: the
+/// panic message and backtrace, engine settings and recent input. The screenshot is added by
+/// the game loop as the panic unwinds, so frames aren't copied while running.
+pub(crate) struct CrashReporter {
+    settings: CrashSettings,
+    state: Arc<Mutex<CrashState>>,
+}
+
+impl CrashReporter {
+    pub(crate) fn new(settings: CrashSettings, engine_settings: String) -> Self {
+        let state = CrashState {
+            settings: engine_settings,
+            ..CrashState::default()
+        };
+
+        Self {
+            settings,
+            state: Arc::new(Mutex::new(state)),
+        }
+    }
+
+    /// Make this the reporter that crash bundles are written for. The panic hook is installed
+    /// the first time and flushes the log before running the previously installed hook.
+    pub(crate) fn install(&self, logger: LoggerHandle) {
+        let reporter = ActiveReporter {
+            directory: self.settings.directory.clone(),
+            state: Arc::clone(&self.state),
+            logger,
+        };
+        *lock(&ACTIVE_REPORTER) = Some(reporter);
+
+        INSTALL_HOOK.call_once(|| {
+            let previous = std::panic::take_hook();
+            std::panic::set_hook(Box::new(move |info| {
+                if let Some(reporter) = &*lock(&ACTIVE_REPORTER) {
+                    let mut state = lock(&reporter.state);
+                    match write_bundle(&reporter.directory, &state, info) {
+                        Ok(path) => {
+                            error!("crash report written to {}", path.display());
+                            state.bundle = Some(path);
+                        }
+                        Err(e) => error!("unable to write crash report: {}", e),
+                    }
+                    drop(state);
+
+                    reporter.logger.flush();
+                }
+                previous(info);
+            }));
+        });
+    }
+
+    /// Remember the input of a completed frame.
+    pub(crate) fn record(&self, record: FrameRecord) {
+        let mut state = lock(&self.state);
+
+        if state.frames.len() >= self.settings.frames {
+            state.frames.pop_front();
+        }
+        if self.settings.frames > 0 {
+            state.frames.push_back(record);
+        }
+    }
+
+    /// Add `screenshot` to the bundle written by the last panic, if any.
+    pub(crate) fn write_screenshot(&self, screenshot: &RgbaImage) {
+        let bundle = match lock(&self.state).bundle.take() {
+            Some(bundle) => bundle,
+            None => return,
+        };
+
+        if let Err(e) = screenshot.save(bundle.join("screenshot.png")) {
+            error!("unable to write crash screenshot: {}", e);
+        }
+    }
+}
+
+/// A panic while holding a lock leaves it poisoned, but the data is still useful.
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn write_bundle(
+    directory: &Path,
+    state: &CrashState,
+    info: &PanicHookInfo,
+) -> std::io::Result<PathBuf> {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|t| t.as_secs())
+        .unwrap_or_default();
+    let path = directory.join(format!("crash-{}", timestamp));
+    std::fs::create_dir_all(&path)?;
+
+    let backtrace = std::backtrace::Backtrace::force_capture();
+    std::fs::write(path.join("panic.txt"), format!("{}\n\n{}", info, backtrace))?;
+    std::fs::write(path.join("settings.txt"), &state.settings)?;
+    std::fs::write(path.join("input.txt"), format_frames(&state.frames))?;
+
+    Ok(path)
+}
+
+fn format_frames(frames: &VecDeque<FrameRecord>) -> String {
+    let mut output = String::new();
+    for record in frames {
+        let _ = writeln!(
+            output,
+            "frame {}: keys {:?}, mouse ({:.1}, {:.1}) {:?}",
+            record.frame, record.keys, record.mouse.0, record.mouse.1, record.buttons
+        );
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reporter_keeps_only_the_most_recent_frames() {
+        let settings = CrashSettings {
+            directory: PathBuf::from("crashes"),
+            frames: 2,
+        };
+        let reporter = CrashReporter::new(settings, String::new());
+
+        for frame in 0..5 {
+            let record = FrameRecord {
+                frame,
+                ..FrameRecord::default()
+            };
+            reporter.record(record);
+        }
+
+        let state = reporter.state.lock().unwrap();
+        let frames: Vec<u64> = state.frames.iter().map(|record| record.frame).collect();
+        assert_eq!(vec![3, 4], frames);
+    }
+
+    #[test]
+    fn screenshot_is_added_to_the_last_bundle() {
+        let bundle =
+            std::env::temp_dir().join(format!("apparatus-crash-test-{}", std::process::id()));
+        std::fs::create_dir_all(&bundle).unwrap();
+        let settings = CrashSettings {
+            directory: bundle.clone(),
+            frames: 0,
+        };
+        let reporter = CrashReporter::new(settings, String::new());
+
+        reporter.write_screenshot(&RgbaImage::new(1, 1));
+        assert!(!bundle.join("screenshot.png").exists());
+
+        reporter.state.lock().unwrap().bundle = Some(bundle.clone());
+        reporter.write_screenshot(&RgbaImage::new(1, 1));
+        let exists = bundle.join("screenshot.png").exists();
+        std::fs::remove_dir_all(&bundle).unwrap();
+
+        assert!(exists);
+        assert!(reporter.state.lock().unwrap().bundle.is_none());
+    }
+}
@@ -0,0 +1,374 @@
+use std::collections::HashMap;
+
+use crate::color::Color;
+use crate::engine::apparatus::Apparatus;
+use crate::engine::sprite::Sprite;
+use crate::maths::Vec2;
+use crate::physics::body::BodyHandle;
+use crate::physics::world::PhysicsWorld;
+
+struct DecalChunk {
+    data: Vec<u8>,
+    last_used: u64,
+}
+
+/// A world-space surface that sprites can be stamped into permanently, e.g. scorch marks,
+/// tyre tracks or splats.
+///
+/// The surface is split into square chunks which are only allocated once something is stamped
+/// into them. When more than `max_chunks` chunks exist, the least recently used chunk is dropped,
+/// bounding memory use for large or endless worlds.
+pub struct DecalLayer {
+    chunk_size: u32,
+    max_chunks: usize,
+    chunks: HashMap<(i32, i32), DecalChunk>,
+    tick: u64,
+}
+
+impl Default for DecalLayer {
+    fn default() -> Self {
+        Self::new(64, 256)
+    }
+}
+
+impl DecalLayer {
+    pub fn new(chunk_size: u32, max_chunks: usize) -> Self {
+        Self {
+            chunk_size: chunk_size.max(1),
+            max_chunks: max_chunks.max(1),
+            chunks: HashMap::new(),
+            tick: 0,
+        }
+    }
+
+    pub fn chunk_count(&self) -> usize {
+        self.chunks.len()
+    }
+
+    pub fn clear(&mut self) {
+        self.chunks.clear();
+    }
+
+    /// Stamp a sprite into the surface with its bottom left corner at world position (x, y).
+    pub fn stamp(&mut self, sprite: &Sprite, x: f32, y: f32) {
+        let (x, y) = (x.floor() as i32, y.floor() as i32);
+        let (width, height) = (sprite.width() as i32, sprite.height() as i32);
+        let data = sprite.data();
+
+        for sprite_y in 0..height {
+            for sprite_x in 0..width {
+                let offset = ((sprite_y * width + sprite_x) * 4) as usize;
+                let src = Color::rgba(
+                    data[offset],
+                    data[offset + 1],
+                    data[offset + 2],
+                    data[offset + 3],
+                );
+                if src.a() == 0 {
+                    continue;
+                }
+
+                self.blend_pixel(x + sprite_x, y + height - sprite_y, src);
+            }
+        }
+
+        self.evict();
+    }
+
+    /// The color of the surface at world position (x, y), if anything has been stamped there.
+    pub fn pixel(&self, x: i32, y: i32) -> Option<Color> {
+        let (key, index) = self.locate(x, y);
+        let chunk = self.chunks.get(&key)?;
+        let color = Color::rgba(
+            chunk.data[index],
+            chunk.data[index + 1],
+            chunk.data[index + 2],
+            chunk.data[index + 3],
+        );
+
+        (color.a() > 0).then_some(color)
+    }
+
+    /// Draw the visible part of the surface, offset by the camera position.
+    pub fn draw(&mut self, app: &mut Apparatus, camera_x: f32, camera_y: f32) {
+        self.tick += 1;
+
+        let size = self.chunk_size as i32;
+        let (screen_width, screen_height) = (app.screen_width() as f32, app.screen_height() as f32);
+        let min_key = (
+            (camera_x.floor() as i32).div_euclid(size),
+            (camera_y.floor() as i32).div_euclid(size),
+        );
+        let max_key = (
+            ((camera_x + screen_width).ceil() as i32).div_euclid(size),
+            ((camera_y + screen_height).ceil() as i32).div_euclid(size),
+        );
+
+        for ((chunk_x, chunk_y), chunk) in self.chunks.iter_mut() {
+            if *chunk_x < min_key.0
+                || *chunk_x > max_key.0
+                || *chunk_y < min_key.1
+                || *chunk_y > max_key.1
+            {
+                continue;
+            }
+
+            chunk.last_used = self.tick;
+            for (i, pixel) in chunk.data.chunks_exact(4).enumerate() {
+                if pixel[3] == 0 {
+                    continue;
+                }
+
+                let world_x = chunk_x * size + i as i32 % size;
+                let world_y = chunk_y * size + i as i32 / size;
+                app.draw(
+                    world_x as f32 - camera_x,
+                    world_y as f32 - camera_y,
+                    Color::rgba(pixel[0], pixel[1], pixel[2], pixel[3]),
+                );
+            }
+        }
+    }
+
+    fn locate(&self, x: i32, y: i32) -> ((i32, i32), usize) {
+        let size = self.chunk_size as i32;
+        let key = (x.div_euclid(size), y.div_euclid(size));
+        let index = (y.rem_euclid(size) * size + x.rem_euclid(size)) as usize * 4;
+
+        (key, index)
+    }
+
+    fn blend_pixel(&mut self, x: i32, y: i32, src: Color) {
+        self.tick += 1;
+
+        let (key, index) = self.locate(x, y);
+        let chunk_len = (self.chunk_size * self.chunk_size * 4) as usize;
+        let chunk = self.chunks.entry(key).or_insert_with(|| DecalChunk {
+            data: vec![0; chunk_len],
+            last_used: 0,
+        });
+        chunk.last_used = self.tick;
+
+        let dst = &mut chunk.data[index..index + 4];
+        let out = Color::blend_over(src, Color::rgba(dst[0], dst[1], dst[2], dst[3]));
+        dst.copy_from_slice(&[out.r(), out.g(), out.b(), out.a()]);
+    }
+
+    fn evict(&mut self) {
+        while self.chunks.len() > self.max_chunks {
+            let oldest = self
+                .chunks
+                .iter()
+                .min_by_key(|(_, chunk)| chunk.last_used)
+                .map(|(key, _)| *key);
+
+            match oldest {
+                Some(key) => self.chunks.remove(&key),
+                None => break,
+            };
+        }
+    }
+}
+
+/// Marks a body leaves in a `DecalLayer` wherever it touches another body, e.g. footprints or
+/// skid marks. Call `update` after each physics step.
+pub struct Trail {
+    body: BodyHandle,
+    sprite: Sprite,
+    spacing: f32,
+    min_speed: f32,
+    stride: f32,
+    last_mark: Option<Vec2>,
+    left: bool,
+}
+
+impl Trail {
+    pub fn new(body: BodyHandle, sprite: Sprite) -> Self {
+        Self {
+            body,
+            sprite,
+            spacing: 8.0,
+            min_speed: 0.0,
+            stride: 0.0,
+            last_mark: None,
+            left: false,
+        }
+    }
+
+    /// The distance travelled between marks. Defaults to 8.
+    pub fn with_spacing(mut self, spacing: f32) -> Self {
+        self.spacing = spacing;
+        self
+    }
+
+    /// Only leave marks while the body moves at least this fast, e.g. so a car only leaves skid
+    /// marks at speed. Defaults to 0.
+    pub fn with_min_speed(mut self, min_speed: f32) -> Self {
+        self.min_speed = min_speed;
+        self
+    }
+
+    /// Alternate marks this far either side of the direction of travel, like left and right
+    /// feet. Defaults to 0.
+    pub fn with_stride(mut self, stride: f32) -> Self {
+        self.stride = stride;
+        self
+    }
+
+    /// Stamp a mark at the body's contact point if it has moved far enough since the last one.
+    /// The trail is broken whenever the body leaves the ground.
+    pub fn update(&mut self, world: &PhysicsWorld, decals: &mut DecalLayer) {
+        let (body, contact) = match (world.get(self.body), self.contact_point(world)) {
+            (Some(body), Some(contact)) => (body, contact),
+            _ => {
+                self.last_mark = None;
+                return;
+            }
+        };
+
+        let speed = body.velocity.length();
+        if speed < self.min_speed {
+            return;
+        }
+        if let Some(last) = self.last_mark {
+            if (contact - last).length() < self.spacing {
+                return;
+            }
+        }
+
+        let side = if self.left { -1.0 } else { 1.0 };
+        let direction = if speed > 0.0 {
+            body.velocity * (1.0 / speed)
+        } else {
+            Vec2::new(0.0, 0.0)
+        };
+        let offset = Vec2::new(-direction.y, direction.x) * (self.stride * side);
+        let center = contact + offset;
+
+        decals.stamp(
+            &self.sprite,
+            center.x - self.sprite.width() as f32 / 2.0,
+            center.y - self.sprite.height() as f32 / 2.0,
+        );
+        self.last_mark = Some(contact);
+        self.left = !self.left;
+    }
+
+    fn contact_point(&self, world: &PhysicsWorld) -> Option<Vec2> {
+        world
+            .contacts()
+            .iter()
+            .find(|contact| contact.a == self.body || contact.b == self.body)
+            .map(|contact| contact.point)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physics::body::{Body, Shape};
+
+    fn solid_sprite(width: u32, height: u32, color: Color) -> Sprite {
+        let pixel = [color.r(), color.g(), color.b(), color.a()];
+        Sprite::from_raw_rgba(width, height, pixel.repeat((width * height) as usize))
+    }
+
+    #[test]
+    fn stamp_is_stored_in_world_space() {
+        let mut decals = DecalLayer::new(8, 4);
+
+        decals.stamp(&solid_sprite(2, 2, Color::rgba(255, 0, 0, 255)), 10.0, 20.0);
+
+        assert_eq!(Some(Color::rgba(255, 0, 0, 255)), decals.pixel(11, 21));
+        assert_eq!(None, decals.pixel(12, 21));
+    }
+
+    #[test]
+    fn stamp_spanning_chunk_boundary_allocates_each_chunk() {
+        let mut decals = DecalLayer::new(8, 16);
+
+        decals.stamp(&solid_sprite(4, 4, Color::rgba(0, 0, 0, 255)), -2.0, 6.0);
+
+        assert_eq!(4, decals.chunk_count());
+    }
+
+    #[test]
+    fn translucent_stamps_accumulate_alpha() {
+        let mut decals = DecalLayer::new(8, 4);
+        let smoke = solid_sprite(1, 1, Color::rgba(0, 0, 0, 128));
+
+        decals.stamp(&smoke, 0.0, 0.0);
+        decals.stamp(&smoke, 0.0, 0.0);
+
+        assert_eq!(192, decals.pixel(0, 1).unwrap().a());
+    }
+
+    fn ground_and_ball(velocity_x: f32) -> (PhysicsWorld, BodyHandle) {
+        let mut world = PhysicsWorld::new(Vec2::new(0.0, -100.0));
+        world.add(Body::fixed(
+            Shape::Rectangle {
+                width: 200.0,
+                height: 10.0,
+            },
+            0.0,
+            0.0,
+        ));
+        let ball = world.add(
+            Body::dynamic(Shape::Circle { radius: 4.0 }, -50.0, 8.0)
+                .with_velocity(velocity_x, 0.0)
+                .with_friction(0.0),
+        );
+
+        (world, ball)
+    }
+
+    #[test]
+    fn trail_is_stamped_where_the_body_touches_the_ground() {
+        let (mut world, ball) = ground_and_ball(60.0);
+        let mut decals = DecalLayer::new(16, 64);
+        let mut trail =
+            Trail::new(ball, solid_sprite(1, 1, Color::rgba(0, 0, 0, 255))).with_spacing(10.0);
+
+        for _ in 0..60 {
+            world.step(1.0 / 60.0);
+            trail.update(&world, &mut decals);
+        }
+
+        let marked: Vec<i32> = (-60..60)
+            .filter(|&x| (0..12).any(|y| decals.pixel(x, y).is_some()))
+            .collect();
+        assert!(marked.len() >= 4, "marks at {:?}", marked);
+        assert!(marked.windows(2).all(|pair| pair[1] - pair[0] >= 9));
+    }
+
+    #[test]
+    fn trail_is_not_stamped_below_the_minimum_speed() {
+        let (mut world, ball) = ground_and_ball(5.0);
+        let mut decals = DecalLayer::new(16, 64);
+        let mut trail =
+            Trail::new(ball, solid_sprite(1, 1, Color::rgba(0, 0, 0, 255))).with_min_speed(20.0);
+
+        for _ in 0..60 {
+            world.step(1.0 / 60.0);
+            trail.update(&world, &mut decals);
+        }
+
+        assert_eq!(0, decals.chunk_count());
+    }
+
+    #[test]
+    fn least_recently_used_chunk_is_evicted() {
+        let mut decals = DecalLayer::new(8, 2);
+        let mark = solid_sprite(1, 1, Color::rgba(0, 0, 0, 255));
+
+        decals.stamp(&mark, 0.0, 0.0);
+        decals.stamp(&mark, 8.0, 0.0);
+        decals.stamp(&mark, 0.0, 0.0);
+        decals.stamp(&mark, 16.0, 0.0);
+
+        assert_eq!(2, decals.chunk_count());
+        assert!(decals.pixel(0, 1).is_some());
+        assert!(decals.pixel(8, 1).is_none());
+        assert!(decals.pixel(16, 1).is_some());
+    }
+}
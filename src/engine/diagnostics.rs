@@ -0,0 +1,151 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+
+/// How many consecutive frames the same string must be drawn before it is reported.
+const REPEATED_STRING_FRAMES: u32 = 60;
+/// The fewest frames between two reports of the same kind, so the log isn't flooded.
+const REPORT_INTERVAL: u64 = 600;
+
+/// A slow drawing pattern spotted during a frame.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Diagnostic {
+    RepeatedString(String),
+    OffScreenSprites(u32),
+    RepeatedClear(u32),
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Diagnostic::RepeatedString(value) => write!(
+                f,
+                "\"{}\" has been rasterized every frame for {} frames; draw text that rarely \
+                 changes with a TextGrid, which caches its glyphs",
+                value, REPEATED_STRING_FRAMES
+            ),
+            Diagnostic::OffScreenSprites(count) => write!(
+                f,
+                "{} sprites were drawn entirely off-screen in one frame; cull them first, e.g. \
+                 with Camera::visible_rect",
+                count
+            ),
+            Diagnostic::RepeatedClear(count) => write!(
+                f,
+                "the screen was cleared {} times in one frame; clear it once at the start of \
+                 the frame",
+                count
+            ),
+        }
+    }
+}
+
+/// Watches what is drawn each frame for patterns with faster alternatives.
+#[derive(Debug, Default)]
+pub(crate) struct Diagnostics {
+    frame: u64,
+    strings: HashMap<String, u32>,
+    strings_this_frame: HashSet<String>,
+    reported_strings: HashSet<String>,
+    off_screen_sprites: u32,
+    clears: u32,
+    last_reported: HashMap<&'static str, u64>,
+}
+
+impl Diagnostics {
+    pub(crate) fn string_drawn(&mut self, value: &str) {
+        if !self.strings_this_frame.contains(value) {
+            self.strings_this_frame.insert(value.to_string());
+        }
+    }
+
+    pub(crate) fn sprite_drawn(&mut self, visible: bool) {
+        if !visible {
+            self.off_screen_sprites += 1;
+        }
+    }
+
+    pub(crate) fn cleared(&mut self) {
+        self.clears += 1;
+    }
+
+    /// Finish the frame, returning anything worth reporting.
+    pub(crate) fn end_frame(&mut self) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+
+        let drawn = std::mem::take(&mut self.strings_this_frame);
+        self.strings.retain(|value, _| drawn.contains(value));
+        for value in drawn {
+            let frames = self.strings.entry(value.clone()).or_insert(0);
+            *frames += 1;
+            if *frames >= REPEATED_STRING_FRAMES && self.reported_strings.insert(value.clone()) {
+                diagnostics.push(Diagnostic::RepeatedString(value));
+            }
+        }
+
+        let off_screen_sprites = std::mem::take(&mut self.off_screen_sprites);
+        if off_screen_sprites > 0 && self.should_report("off-screen sprites") {
+            diagnostics.push(Diagnostic::OffScreenSprites(off_screen_sprites));
+        }
+
+        let clears = std::mem::take(&mut self.clears);
+        if clears > 1 && self.should_report("repeated clear") {
+            diagnostics.push(Diagnostic::RepeatedClear(clears));
+        }
+
+        self.frame += 1;
+        diagnostics
+    }
+
+    fn should_report(&mut self, kind: &'static str) -> bool {
+        match self.last_reported.get(kind) {
+            Some(frame) if self.frame - frame < REPORT_INTERVAL => false,
+            _ => {
+                self.last_reported.insert(kind, self.frame);
+                true
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strings_drawn_every_frame_are_reported_once() {
+        let mut diagnostics = Diagnostics::default();
+        let mut reported = Vec::new();
+
+        for frame in 0..REPEATED_STRING_FRAMES * 2 {
+            diagnostics.string_drawn("Score");
+            diagnostics.string_drawn(&frame.to_string());
+            reported.extend(diagnostics.end_frame());
+        }
+
+        assert_eq!(vec![Diagnostic::RepeatedString("Score".into())], reported);
+    }
+
+    #[test]
+    fn frame_problems_are_rate_limited() {
+        let mut diagnostics = Diagnostics::default();
+        let frame = |diagnostics: &mut Diagnostics| {
+            diagnostics.cleared();
+            diagnostics.cleared();
+            diagnostics.sprite_drawn(true);
+            diagnostics.sprite_drawn(false);
+            diagnostics.end_frame()
+        };
+
+        assert_eq!(
+            vec![
+                Diagnostic::OffScreenSprites(1),
+                Diagnostic::RepeatedClear(2)
+            ],
+            frame(&mut diagnostics)
+        );
+        for _ in 1..REPORT_INTERVAL {
+            assert!(frame(&mut diagnostics).is_empty());
+        }
+        assert_eq!(2, frame(&mut diagnostics).len());
+    }
+}
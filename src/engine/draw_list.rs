@@ -0,0 +1,501 @@
+use std::rc::Rc;
+
+use crate::color::Color;
+use crate::engine::apparatus::Apparatus;
+use crate::engine::camera::Camera2D;
+use crate::engine::sprite::Sprite;
+use crate::maths::{Rect, Vec2};
+use crate::renderer::custom::DrawCommand;
+
+/// What the last `DrawList::draw` did, to check whether sorting and culling help.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct DrawListStats {
+    /// Commands drawn, not counting culled ones.
+    pub commands: usize,
+    /// Commands skipped because they were entirely off screen.
+    pub culled: usize,
+    /// How many times consecutive drawn commands changed material, i.e. color or sprite.
+    pub material_switches: usize,
+}
+
+#[derive(Clone)]
+enum Primitive {
+    Pixel(Vec2),
+    Line(Vec2, Vec2),
+    Rectangle(Rect),
+    FilledRectangle(Rect),
+    Circle(Vec2, f32),
+    FilledCircle(Vec2, f32),
+    FilledTriangle([Vec2; 3]),
+    String(String, Vec2, f32),
+    Sprite(Vec2, Vec2),
+    /// A custom draw command, with its clip in virtual pixels on screen.
+    Custom(Option<Rect>),
+}
+
+#[derive(Clone)]
+enum Material {
+    Color(Color),
+    Sprite(Rc<Sprite>),
+    Custom(Rc<dyn DrawCommand>),
+}
+
+impl Material {
+    /// Orders colors before sprites before custom commands, then colors by value and the rest
+    /// by identity.
+    fn key(&self) -> (u8, usize) {
+        match self {
+            Material::Color(color) => (0, u32::from(*color) as usize),
+            Material::Sprite(sprite) => (1, Rc::as_ptr(sprite) as usize),
+            Material::Custom(command) => (2, Rc::as_ptr(command) as *const () as usize),
+        }
+    }
+
+    fn is(&self, other: &Material) -> bool {
+        match (self, other) {
+            (Material::Color(a), Material::Color(b)) => a == b,
+            (Material::Sprite(a), Material::Sprite(b)) => Rc::ptr_eq(a, b),
+            (Material::Custom(a), Material::Custom(b)) => Rc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+}
+
+#[derive(Clone)]
+struct Command {
+    layer: i32,
+    primitive: Primitive,
+    material: Material,
+}
+
+impl Command {
+    /// The area the command may draw to, in virtual pixels, or `None` if it can't be known
+    /// cheaply, e.g. strings, which are measured in window pixels.
+    fn bounds(&self) -> Option<Rect> {
+        let around = |min: Vec2, max: Vec2| Rect::new(min.x, min.y, max.x - min.x, max.y - min.y);
+        let corners = |points: &[Vec2]| {
+            let min = points.iter().fold(points[0], |min, point| {
+                Vec2::new(min.x.min(point.x), min.y.min(point.y))
+            });
+            let max = points.iter().fold(points[0], |max, point| {
+                Vec2::new(max.x.max(point.x), max.y.max(point.y))
+            });
+            around(min, max)
+        };
+
+        match &self.primitive {
+            Primitive::Pixel(position) => Some(Rect::new(position.x, position.y, 0.0, 0.0)),
+            Primitive::Line(from, to) => Some(corners(&[*from, *to])),
+            Primitive::Rectangle(rect) | Primitive::FilledRectangle(rect) => Some(*rect),
+            Primitive::Circle(center, radius) | Primitive::FilledCircle(center, radius) => {
+                let extent = Vec2::new(*radius, *radius);
+                Some(around(*center - extent, *center + extent))
+            }
+            Primitive::FilledTriangle(points) => Some(corners(points)),
+            Primitive::String(..) => None,
+            // Custom commands may draw anywhere, e.g. a raycaster's whole view.
+            Primitive::Custom(_) => None,
+            Primitive::Sprite(position, scale) => {
+                let sprite = match &self.material {
+                    Material::Sprite(sprite) => sprite,
+                    _ => return None,
+                };
+                // Trimmed sprites are drawn within their untrimmed size.
+                let (width, height) = sprite.source_size();
+                Some(Rect::new(
+                    position.x,
+                    position.y,
+                    width as f32 * scale.x,
+                    height as f32 * scale.y,
+                ))
+            }
+        }
+    }
+
+    fn draw(&self, app: &mut Apparatus) {
+        let color = match (&self.material, &self.primitive) {
+            (Material::Color(color), _) => *color,
+            (Material::Sprite(sprite), Primitive::Sprite(position, scale)) => {
+                if *scale == Vec2::new(1.0, 1.0) {
+                    app.draw_sprite(position.x, position.y, sprite);
+                } else {
+                    app.draw_sprite_scaled(position.x, position.y, sprite, scale.x, scale.y);
+                }
+                return;
+            }
+            (Material::Custom(command), Primitive::Custom(clip)) => {
+                app.draw_custom(command.as_ref(), *clip);
+                return;
+            }
+            (Material::Sprite(_), _) | (Material::Custom(_), _) => return,
+        };
+
+        match &self.primitive {
+            Primitive::Pixel(position) => app.draw(position.x, position.y, color),
+            Primitive::Line(from, to) => app.draw_line(from.x, from.y, to.x, to.y, color),
+            Primitive::Rectangle(rect) => {
+                app.draw_wireframe_rectangle(rect.x, rect.y, rect.width, rect.height, color)
+            }
+            Primitive::FilledRectangle(rect) => {
+                app.draw_filled_rectangle(rect.x, rect.y, rect.width, rect.height, color)
+            }
+            Primitive::Circle(center, radius) => {
+                app.draw_wireframe_circle(center.x, center.y, *radius, color)
+            }
+            Primitive::FilledCircle(center, radius) => {
+                app.draw_filled_circle(center.x, center.y, *radius, color)
+            }
+            Primitive::FilledTriangle([a, b, c]) => {
+                app.draw_filled_triangle(a.x, a.y, b.x, b.y, c.x, c.y, color)
+            }
+            // Strings are placed in window pixels, so they aren't moved with the camera.
+            Primitive::String(value, position, size) => {
+                app.draw_string(value, position.x, position.y, color, *size)
+            }
+            // Sprites and custom commands are drawn with their material above.
+            Primitive::Sprite(..) | Primitive::Custom(_) => {}
+        }
+    }
+}
+
+fn overlaps(a: &Rect, b: &Rect) -> bool {
+    a.x <= b.x + b.width && b.x <= a.x + a.width && a.y <= b.y + b.height && b.y <= a.y + a.height
+}
+
+/// The screen, in virtual pixels, and the camera commands are seen through, to cull against.
+struct View {
+    camera: Option<Camera2D>,
+    screen: Vec2,
+}
+
+impl View {
+    /// Whether any of a command's bounds is on screen, once zoomed and rotated by the camera.
+    /// Commands without bounds are always drawn.
+    fn can_see(&self, command: &Command) -> bool {
+        let bounds = match command.bounds() {
+            Some(bounds) => bounds,
+            None => return true,
+        };
+        let screen = Rect::new(0.0, 0.0, self.screen.x, self.screen.y);
+        let camera = match &self.camera {
+            Some(camera) => camera,
+            None => return overlaps(&bounds, &screen),
+        };
+
+        let corners = [
+            Vec2::new(bounds.x, bounds.y),
+            Vec2::new(bounds.x + bounds.width, bounds.y),
+            Vec2::new(bounds.x, bounds.y + bounds.height),
+            Vec2::new(bounds.x + bounds.width, bounds.y + bounds.height),
+        ]
+        .map(|corner| camera.world_to_screen(corner, self.screen));
+        let min = corners.iter().fold(corners[0], |min, corner| {
+            Vec2::new(min.x.min(corner.x), min.y.min(corner.y))
+        });
+        let max = corners.iter().fold(corners[0], |max, corner| {
+            Vec2::new(max.x.max(corner.x), max.y.max(corner.y))
+        });
+        overlaps(
+            &Rect::new(min.x, min.y, max.x - min.x, max.y - min.y),
+            &screen,
+        )
+    }
+}
+
+/// A recorded list of draw commands that can be replayed every frame, e.g. static scenery that
+/// is built once and kept rather than drawn call by call. Commands are drawn layer by layer and
+/// those entirely off screen are skipped. Sprites are shared with `Rc` so the list can outlive
+/// the frame it was built in.
+#[derive(Clone, Default)]
+pub struct DrawList {
+    commands: Vec<Command>,
+    layer: i32,
+    sort_materials: bool,
+    sorted: bool,
+    stats: DrawListStats,
+}
+
+impl DrawList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Draw commands with the same color or sprite one after another within each layer. Commands
+    /// within a layer are then no longer drawn in the order they were recorded, so only enable
+    /// this for commands that don't overlap or whose overlap order doesn't matter.
+    pub fn with_material_sorting(mut self) -> Self {
+        self.sort_materials = true;
+        self.sorted = false;
+        self
+    }
+
+    /// Put commands recorded from now on in `layer`. Lower layers are drawn first, behind higher
+    /// ones. Defaults to 0.
+    pub fn set_layer(&mut self, layer: i32) {
+        self.layer = layer;
+    }
+
+    pub fn layer(&self) -> i32 {
+        self.layer
+    }
+
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// Remove every command, e.g. to record the list again after the scenery changed.
+    pub fn clear(&mut self) {
+        self.commands.clear();
+        self.sorted = true;
+    }
+
+    /// Statistics from the most recent draw.
+    pub fn stats(&self) -> DrawListStats {
+        self.stats
+    }
+
+    fn push(&mut self, primitive: Primitive, material: Material) {
+        self.commands.push(Command {
+            layer: self.layer,
+            primitive,
+            material,
+        });
+        self.sorted = false;
+    }
+
+    pub fn draw_pixel(&mut self, x: f32, y: f32, color: Color) {
+        self.push(Primitive::Pixel(Vec2::new(x, y)), Material::Color(color));
+    }
+
+    pub fn draw_line(&mut self, x0: f32, y0: f32, x1: f32, y1: f32, color: Color) {
+        let line = Primitive::Line(Vec2::new(x0, y0), Vec2::new(x1, y1));
+        self.push(line, Material::Color(color));
+    }
+
+    pub fn draw_wireframe_rectangle(&mut self, rect: Rect, color: Color) {
+        self.push(Primitive::Rectangle(rect), Material::Color(color));
+    }
+
+    pub fn draw_filled_rectangle(&mut self, rect: Rect, color: Color) {
+        self.push(Primitive::FilledRectangle(rect), Material::Color(color));
+    }
+
+    pub fn draw_wireframe_circle(&mut self, x: f32, y: f32, radius: f32, color: Color) {
+        let circle = Primitive::Circle(Vec2::new(x, y), radius);
+        self.push(circle, Material::Color(color));
+    }
+
+    pub fn draw_filled_circle(&mut self, x: f32, y: f32, radius: f32, color: Color) {
+        let circle = Primitive::FilledCircle(Vec2::new(x, y), radius);
+        self.push(circle, Material::Color(color));
+    }
+
+    pub fn draw_filled_triangle(&mut self, a: Vec2, b: Vec2, c: Vec2, color: Color) {
+        self.push(Primitive::FilledTriangle([a, b, c]), Material::Color(color));
+    }
+
+    /// Record a string at (x, y) in window pixels, as for `Apparatus::draw_string`. Strings are
+    /// never culled or moved by the camera.
+    pub fn draw_string(
+        &mut self,
+        value: impl Into<String>,
+        x: f32,
+        y: f32,
+        color: Color,
+        size: f32,
+    ) {
+        let string = Primitive::String(value.into(), Vec2::new(x, y), size);
+        self.push(string, Material::Color(color));
+    }
+
+    pub fn draw_sprite(&mut self, x: f32, y: f32, sprite: Rc<Sprite>) {
+        self.draw_sprite_scaled(x, y, sprite, 1.0, 1.0);
+    }
+
+    pub fn draw_sprite_scaled(
+        &mut self,
+        x: f32,
+        y: f32,
+        sprite: Rc<Sprite>,
+        scale_x: f32,
+        scale_y: f32,
+    ) {
+        let primitive = Primitive::Sprite(Vec2::new(x, y), Vec2::new(scale_x, scale_y));
+        self.push(primitive, Material::Sprite(sprite));
+    }
+
+    /// Record a custom rasterizer, run as with `Apparatus::draw_custom`. It is never culled, and
+    /// world positions are placed through the camera by the command with `DrawContext`.
+    pub fn draw_custom(&mut self, command: Rc<dyn DrawCommand>, clip: Option<Rect>) {
+        self.push(Primitive::Custom(clip), Material::Custom(command));
+    }
+
+    /// Replay every command on screen, whether or not a camera is set. The list is kept, so it
+    /// can be drawn again next frame.
+    pub fn draw(&mut self, app: &mut Apparatus) {
+        app.in_screen_space(|app| self.draw_visible(app));
+    }
+
+    /// Replay every command at its world position through the engine's camera, culling those
+    /// the camera can't see once zoomed and rotated.
+    pub fn draw_in_world(&mut self, app: &mut Apparatus) {
+        self.draw_visible(app);
+    }
+
+    fn draw_visible(&mut self, app: &mut Apparatus) {
+        let view = View {
+            camera: app.camera(),
+            screen: Vec2::new(app.screen_width() as f32, app.screen_height() as f32),
+        };
+        self.sort();
+        self.stats = self.measure(&view);
+
+        for command in &self.commands {
+            if view.can_see(command) {
+                command.draw(app);
+            }
+        }
+    }
+
+    /// Only sorts when commands were recorded since the last sort. Commands sharing a layer keep
+    /// their recorded order unless materials are sorted.
+    fn sort(&mut self) {
+        if self.sorted {
+            return;
+        }
+
+        let sort_materials = self.sort_materials;
+        self.commands.sort_by(|a, b| {
+            let order = a.layer.cmp(&b.layer);
+            if sort_materials {
+                order.then_with(|| a.material.key().cmp(&b.material.key()))
+            } else {
+                order
+            }
+        });
+        self.sorted = true;
+    }
+
+    fn measure(&self, view: &View) -> DrawListStats {
+        let visible: Vec<&Command> = self
+            .commands
+            .iter()
+            .filter(|command| view.can_see(command))
+            .collect();
+        let material_switches = visible
+            .windows(2)
+            .filter(|pair| !pair[0].material.is(&pair[1].material))
+            .count();
+
+        DrawListStats {
+            commands: visible.len(),
+            culled: self.commands.len() - visible.len(),
+            material_switches,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color;
+
+    fn screen() -> View {
+        View {
+            camera: None,
+            screen: Vec2::new(100.0, 100.0),
+        }
+    }
+
+    fn positions(list: &DrawList) -> Vec<Vec2> {
+        list.commands
+            .iter()
+            .filter_map(|command| match command.primitive {
+                Primitive::Pixel(position) => Some(position),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn commands_are_sorted_by_layer_then_material() {
+        let (red, blue) = (color::css::RED, color::css::BLUE);
+        let mut list = DrawList::new().with_material_sorting();
+        list.set_layer(1);
+        list.draw_pixel(1.0, 1.0, red);
+        list.set_layer(0);
+        list.draw_pixel(2.0, 2.0, blue);
+        list.draw_pixel(3.0, 3.0, red);
+        list.draw_pixel(4.0, 4.0, blue);
+
+        list.sort();
+
+        // Blue packs to a smaller value than red, so its pixels come first, in recorded order.
+        let expected = [(2.0, 2.0), (4.0, 4.0), (3.0, 3.0), (1.0, 1.0)];
+        assert_eq!(
+            expected.map(|(x, y)| Vec2::new(x, y)).to_vec(),
+            positions(&list)
+        );
+        assert_eq!(1, list.measure(&screen()).material_switches);
+    }
+
+    #[test]
+    fn off_screen_commands_are_culled() {
+        let sprite = Rc::new(Sprite::from_raw_rgba(8, 8, vec![255; 8 * 8 * 4]));
+        let mut list = DrawList::new();
+        list.draw_filled_rectangle(Rect::new(10.0, 10.0, 5.0, 5.0), color::css::RED);
+        list.draw_filled_circle(-20.0, 50.0, 10.0, color::css::RED);
+        list.draw_sprite(-7.0, 96.0, sprite.clone());
+        list.draw_sprite_scaled(-20.0, 0.0, sprite, 2.0, 2.0);
+        list.draw_string("always drawn", -500.0, -500.0, color::css::WHITE, 12.0);
+
+        let stats = list.measure(&screen());
+
+        assert_eq!(3, stats.commands);
+        assert_eq!(2, stats.culled);
+    }
+
+    #[test]
+    fn culling_follows_the_camera_zoom_and_rotation() {
+        let mut list = DrawList::new();
+        list.draw_filled_rectangle(Rect::new(5.0, 5.0, 5.0, 5.0), color::css::RED);
+        let view = |camera: Camera2D| View {
+            camera: Some(camera),
+            screen: Vec2::new(100.0, 100.0),
+        };
+        let camera = Camera2D::new(Vec2::new(50.0, 50.0));
+
+        assert_eq!(0, list.measure(&view(camera)).culled);
+        assert_eq!(1, list.measure(&view(camera.with_zoom(2.0))).culled);
+        let turned = camera.with_rotation(std::f32::consts::FRAC_PI_4);
+        assert_eq!(1, list.measure(&view(turned)).culled);
+    }
+
+    #[test]
+    fn custom_commands_are_grouped_and_never_culled() {
+        use crate::renderer::custom::DrawContext;
+
+        struct Nothing;
+        impl DrawCommand for Nothing {
+            fn draw(&self, _: &mut DrawContext) {}
+        }
+
+        let command: Rc<dyn DrawCommand> = Rc::new(Nothing);
+        let mut list = DrawList::new().with_material_sorting();
+        list.draw_custom(command.clone(), Some(Rect::new(-50.0, -50.0, 10.0, 10.0)));
+        list.draw_pixel(1.0, 1.0, color::css::RED);
+        list.draw_custom(command, None);
+        list.sort();
+
+        assert_eq!(vec![Vec2::new(1.0, 1.0)], positions(&list));
+        let stats = list.measure(&screen());
+        assert_eq!(
+            (3, 0, 1),
+            (stats.commands, stats.culled, stats.material_switches)
+        );
+    }
+}
@@ -0,0 +1,120 @@
+use std::ops::{Add, Sub};
+
+use crate::maths::Vec2;
+
+/// A position in a world too large for `f32`, e.g. a save file's record of where the player is.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct WorldPosition {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl WorldPosition {
+    pub const fn new(x: f64, y: f64) -> Self {
+        Self { x, y }
+    }
+}
+
+impl Add<Vec2> for WorldPosition {
+    type Output = WorldPosition;
+
+    fn add(self, offset: Vec2) -> Self::Output {
+        WorldPosition::new(self.x + offset.x as f64, self.y + offset.y as f64)
+    }
+}
+
+/// The offset from one position to another, which must be small enough for `f32`.
+impl Sub for WorldPosition {
+    type Output = Vec2;
+
+    fn sub(self, other: WorldPosition) -> Self::Output {
+        Vec2::new((self.x - other.x) as f32, (self.y - other.y) as f32)
+    }
+}
+
+/// Keeps game positions near (0, 0), where `f32` is precise, in a world that scrolls far from
+/// it. Positions are local to an origin that moves with the player: once the player is more
+/// than a threshold from it, `rebase` moves the origin to them and returns the shift to subtract
+/// from everything positioned in the world, e.g. with `PhysicsWorld::shift_origin` and
+/// `Camera::shift_origin`. Where the origin is in the world is kept as a `WorldPosition`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct FloatingOrigin {
+    origin: WorldPosition,
+    threshold: f32,
+}
+
+impl FloatingOrigin {
+    /// Rebase once the focus is more than `threshold` from the origin on either axis. A few
+    /// thousand keeps positions accurate to well under a pixel.
+    pub fn new(threshold: f32) -> Self {
+        Self {
+            origin: WorldPosition::default(),
+            threshold: threshold.abs(),
+        }
+    }
+
+    /// Where local (0, 0) is in the world.
+    pub fn origin(&self) -> WorldPosition {
+        self.origin
+    }
+
+    pub fn threshold(&self) -> f32 {
+        self.threshold
+    }
+
+    pub fn to_world(&self, local: Vec2) -> WorldPosition {
+        self.origin + local
+    }
+
+    pub fn to_local(&self, world: WorldPosition) -> Vec2 {
+        world - self.origin
+    }
+
+    /// Move the origin to `focus`, in local positions, if it has gone past the threshold. The
+    /// shift is returned to subtract from every local position, and is rounded to whole units so
+    /// subtracting it doesn't lose precision.
+    pub fn rebase(&mut self, focus: Vec2) -> Option<Vec2> {
+        if focus.x.abs() <= self.threshold && focus.y.abs() <= self.threshold {
+            return None;
+        }
+
+        let shift = Vec2::new(focus.x.round(), focus.y.round());
+        self.origin = self.origin + shift;
+        Some(shift)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rebasing_moves_the_origin_to_the_focus() {
+        let mut origin = FloatingOrigin::new(1000.0);
+
+        assert_eq!(None, origin.rebase(Vec2::new(999.0, -20.0)));
+        assert_eq!(
+            Some(Vec2::new(1500.0, -20.0)),
+            origin.rebase(Vec2::new(1500.2, -20.0))
+        );
+        assert_eq!(WorldPosition::new(1500.0, -20.0), origin.origin());
+        assert_eq!(
+            Vec2::new(0.2, 0.0),
+            origin.to_local(WorldPosition::new(1500.2, -20.0))
+        );
+    }
+
+    #[test]
+    fn positions_far_from_zero_stay_precise() {
+        let far = WorldPosition::new(50_000_000.0, 0.0);
+        let mut origin = FloatingOrigin::new(1000.0);
+        let mut local = far - origin.origin();
+        let shift = origin.rebase(local).unwrap();
+        local = local - shift;
+
+        // An f32 can't tell 50,000,000 from 50,000,000.25, but a local position can.
+        assert_eq!(50_000_000.0_f32, 50_000_000.25_f32);
+        let nudged = origin.to_world(local + Vec2::new(0.25, 0.0));
+        assert_eq!(WorldPosition::new(50_000_000.25, 0.0), nudged);
+    }
+}
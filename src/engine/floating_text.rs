@@ -0,0 +1,182 @@
+use std::time::Duration;
+
+use crate::color;
+use crate::color::Color;
+use crate::engine::camera::Camera2D;
+use crate::maths::Vec2;
+use crate::renderer::software_2d::Renderer;
+
+/// Text that floats away from a world position and fades out, e.g. damage numbers.
+/// Spawn with `Apparatus::spawn_floating_text`; the engine updates and draws it each frame,
+/// placed through the camera set with `Apparatus::set_camera`.
+#[derive(Clone, Debug)]
+pub struct FloatingText {
+    text: String,
+    position: Vec2,
+    velocity: Vec2,
+    gravity: f32,
+    lifetime: Duration,
+    age: Duration,
+    color: Color,
+    size: f32,
+}
+
+impl FloatingText {
+    /// Create text at (x, y) in the world, in virtual pixels, which rises for one second.
+    pub fn new(text: impl Into<String>, x: f32, y: f32) -> Self {
+        Self {
+            text: text.into(),
+            position: Vec2::new(x, y),
+            velocity: Vec2::new(0.0, 30.0),
+            gravity: 0.0,
+            lifetime: Duration::from_secs(1),
+            age: Duration::ZERO,
+            color: color::css::WHITE,
+            size: 12.0,
+        }
+    }
+
+    /// Set the initial velocity in virtual pixels per second. Defaults to rising at 30.
+    pub fn with_velocity(mut self, x: f32, y: f32) -> Self {
+        self.velocity = Vec2::new(x, y);
+        self
+    }
+
+    /// Set the downward acceleration in virtual pixels per second squared. Defaults to 0.
+    pub fn with_gravity(mut self, gravity: f32) -> Self {
+        self.gravity = gravity;
+        self
+    }
+
+    /// Set how long the text lives for, fading out over its lifetime. Defaults to 1 second.
+    pub fn with_lifetime(mut self, lifetime: Duration) -> Self {
+        self.lifetime = lifetime;
+        self
+    }
+
+    /// Defaults to white.
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Defaults to 12.
+    pub fn with_size(mut self, size: f32) -> Self {
+        self.size = size;
+        self
+    }
+
+    pub fn position(&self) -> Vec2 {
+        self.position
+    }
+
+    /// Where the text is drawn on a `screen_size` screen viewed through `camera`.
+    pub fn screen_position(&self, camera: &Camera2D, screen_size: Vec2) -> Vec2 {
+        camera.world_to_screen(self.position, screen_size)
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.age >= self.lifetime
+    }
+
+    /// The text color with alpha reduced in proportion to the remaining lifetime.
+    pub fn current_color(&self) -> Color {
+        let remaining = if self.lifetime.is_zero() {
+            0.0
+        } else {
+            1.0 - (self.age.as_secs_f32() / self.lifetime.as_secs_f32()).min(1.0)
+        };
+        let alpha = (self.color.a() as f32 * remaining) as u8;
+
+        Color::rgba(self.color.r(), self.color.g(), self.color.b(), alpha)
+    }
+
+    fn update(&mut self, dt: Duration) {
+        let dt_secs = dt.as_secs_f32();
+        self.velocity.y -= self.gravity * dt_secs;
+        self.position.x += self.velocity.x * dt_secs;
+        self.position.y += self.velocity.y * dt_secs;
+        self.age += dt;
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct FloatingTexts {
+    texts: Vec<FloatingText>,
+}
+
+impl FloatingTexts {
+    pub(crate) fn spawn(&mut self, text: FloatingText) {
+        self.texts.push(text);
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.texts.clear();
+    }
+
+    pub(crate) fn update(&mut self, dt: Duration) {
+        self.texts.iter_mut().for_each(|text| text.update(dt));
+        self.texts.retain(|text| !text.is_expired());
+    }
+
+    pub(crate) fn draw(&self, renderer: &mut Renderer, pixel_width: usize, pixel_height: usize) {
+        for text in &self.texts {
+            let position = renderer.world_to_screen(text.position);
+            renderer.draw_string(
+                &text.text,
+                position.x * pixel_width as f32,
+                position.y * pixel_height as f32,
+                text.current_color(),
+                text.size,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn floating_text_moves_by_velocity_and_gravity() {
+        let mut text = FloatingText::new("10", 0.0, 0.0)
+            .with_velocity(2.0, 10.0)
+            .with_gravity(4.0);
+
+        text.update(Duration::from_secs_f32(0.5));
+
+        assert_eq!(Vec2::new(1.0, 4.0), text.position());
+    }
+
+    #[test]
+    fn floating_text_fades_over_lifetime() {
+        let mut text = FloatingText::new("10", 0.0, 0.0).with_lifetime(Duration::from_secs(2));
+
+        text.update(Duration::from_secs(1));
+
+        assert_eq!(127, text.current_color().a());
+    }
+
+    #[test]
+    fn floating_text_is_drawn_through_the_camera() {
+        let text = FloatingText::new("10", 50.0, 40.0);
+        let camera = Camera2D::new(Vec2::new(40.0, 20.0)).with_zoom(2.0);
+
+        assert_eq!(
+            Vec2::new(30.0, 50.0),
+            text.screen_position(&camera, Vec2::new(20.0, 20.0))
+        );
+    }
+
+    #[test]
+    fn expired_floating_text_is_removed() {
+        let mut texts = FloatingTexts::default();
+        texts.spawn(FloatingText::new("short", 0.0, 0.0).with_lifetime(Duration::from_millis(100)));
+        texts.spawn(FloatingText::new("long", 0.0, 0.0));
+
+        texts.update(Duration::from_millis(500));
+
+        assert_eq!(1, texts.texts.len());
+        assert_eq!("long", texts.texts[0].text);
+    }
+}
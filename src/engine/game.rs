@@ -7,4 +7,19 @@ pub trait Game<Game = Self> {
 
     /// Called once per frame.
     fn on_update(&mut self, app: &mut Apparatus);
+
+    /// Called when the built-in pause menu opens.
+    fn on_pause(&mut self, _app: &mut Apparatus) {}
+
+    /// Called when the built-in pause menu closes and the game resumes.
+    fn on_resume(&mut self, _app: &mut Apparatus) {}
+
+    /// Called when "Options" is chosen from the built-in pause menu.
+    fn on_pause_menu_options(&mut self, _app: &mut Apparatus) {}
+
+    /// Called when quitting is confirmed from the built-in pause menu.
+    /// Return `false` to keep the game running.
+    fn on_quit_requested(&mut self, _app: &mut Apparatus) -> bool {
+        true
+    }
 }
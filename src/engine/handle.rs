@@ -0,0 +1,156 @@
+use std::fmt::{Debug, Formatter};
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+
+/// Refers to something allocated by a `HandleAllocator<T>`, e.g. an entity, sound or timer.
+/// Once freed, the handle stays invalid even after its slot is reused.
+pub struct Handle<T> {
+    index: u32,
+    generation: u32,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Handle<T> {
+    /// The slot the handle occupies, for indexing into the game's own storage.
+    pub fn index(&self) -> usize {
+        self.index as usize
+    }
+
+    /// How many times the slot had been freed before this handle was allocated.
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+}
+
+// Implemented by hand so handles are copyable and comparable whatever `T` is.
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Handle<T> {}
+
+impl<T> PartialEq for Handle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index && self.generation == other.generation
+    }
+}
+
+impl<T> Eq for Handle<T> {}
+
+impl<T> Hash for Handle<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.index.hash(state);
+        self.generation.hash(state);
+    }
+}
+
+/// Formats as the type name, index and generation, e.g. `Enemy#3v2`.
+impl<T> Debug for Handle<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = std::any::type_name::<T>();
+        let name = name.rsplit("::").next().unwrap_or(name);
+        write!(f, "{}#{}v{}", name, self.index, self.generation)
+    }
+}
+
+/// Hands out generational handles, reusing the slots of freed ones.
+pub struct HandleAllocator<T> {
+    generations: Vec<u32>,
+    free: Vec<u32>,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for HandleAllocator<T> {
+    fn default() -> Self {
+        Self {
+            generations: Vec::new(),
+            free: Vec::new(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> HandleAllocator<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn allocate(&mut self) -> Handle<T> {
+        let index = match self.free.pop() {
+            Some(index) => index,
+            None => {
+                self.generations.push(0);
+                (self.generations.len() - 1) as u32
+            }
+        };
+
+        Handle {
+            index,
+            generation: self.generations[index as usize],
+            _marker: PhantomData,
+        }
+    }
+
+    /// Free a handle so its slot can be reused. Returns false if it was already invalid.
+    pub fn free(&mut self, handle: Handle<T>) -> bool {
+        if !self.is_valid(handle) {
+            return false;
+        }
+
+        let index = handle.index as usize;
+        self.generations[index] = self.generations[index].wrapping_add(1);
+        self.free.push(handle.index);
+        true
+    }
+
+    /// Whether the handle was allocated here and hasn't been freed.
+    pub fn is_valid(&self, handle: Handle<T>) -> bool {
+        let index = handle.index as usize;
+        index < self.generations.len() && self.generations[index] == handle.generation
+    }
+
+    /// How many handles are allocated and not freed.
+    pub fn len(&self) -> usize {
+        self.generations.len() - self.free.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Enemy;
+
+    #[test]
+    fn freed_handles_stay_invalid_when_their_slot_is_reused() {
+        let mut handles = HandleAllocator::<Enemy>::new();
+        let first = handles.allocate();
+        let second = handles.allocate();
+
+        assert!(handles.free(first));
+        assert!(!handles.free(first));
+        let reused = handles.allocate();
+
+        assert_eq!(first.index(), reused.index());
+        assert_ne!(first, reused);
+        assert!(!handles.is_valid(first));
+        assert!(handles.is_valid(second));
+        assert!(handles.is_valid(reused));
+        assert_eq!(2, handles.len());
+    }
+
+    #[test]
+    fn handles_are_debug_formatted_with_their_type() {
+        let mut handles = HandleAllocator::<Enemy>::new();
+        let handle = handles.allocate();
+        handles.free(handle);
+
+        assert_eq!("Enemy#0v1", format!("{:?}", handles.allocate()));
+    }
+}
@@ -0,0 +1,288 @@
+//! Run a game from a dynamic library and reload it whenever the library is rebuilt, keeping its
+//! state. Intended for development only: the library must be built by the same compiler, with the
+//! same version of the engine, as the executable loading it.
+//!
+//! Build the game as a `cdylib`/`dylib` crate, implement `HotReload` for it and export it with
+//! `export_hot_reload_game!`, then start it with `Apparatus::run_hot_reload`.
+
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use libloading::Library;
+use log::{error, info};
+
+use crate::engine::apparatus::Apparatus;
+use crate::engine::game::Game;
+use crate::errors::ApparatusError;
+
+/// The name of the function exported by `export_hot_reload_game!`.
+pub const CREATE_SYMBOL: &[u8] = b"apparatus_hot_reload_create";
+
+/// The signature of the function exported by `export_hot_reload_game!`. It uses the C calling
+/// convention so the symbol has a stable ABI, though its arguments are still Rust types.
+#[allow(improper_ctypes_definitions)]
+pub type CreateFn =
+    extern "C" fn(&Apparatus, Option<&[u8]>) -> Result<Box<dyn DynGame>, ApparatusError>;
+
+/// A game whose state survives being reloaded.
+pub trait HotReload: Game + Sized {
+    /// Serialize whatever should survive a reload.
+    fn save_state(&self) -> Vec<u8>;
+
+    /// Rebuild the game from state saved by the previous build of the library.
+    fn restore_state(app: &Apparatus, state: &[u8]) -> Result<Self, ApparatusError>;
+}
+
+/// An object safe view of a `HotReload` game, used across the library boundary.
+pub trait DynGame {
+    fn on_update(&mut self, app: &mut Apparatus);
+    fn on_pause(&mut self, app: &mut Apparatus);
+    fn on_resume(&mut self, app: &mut Apparatus);
+    fn on_pause_menu_options(&mut self, app: &mut Apparatus);
+    fn on_quit_requested(&mut self, app: &mut Apparatus) -> bool;
+    fn save_state(&self) -> Vec<u8>;
+}
+
+impl<G: HotReload> DynGame for G {
+    fn on_update(&mut self, app: &mut Apparatus) {
+        Game::on_update(self, app);
+    }
+
+    fn on_pause(&mut self, app: &mut Apparatus) {
+        Game::on_pause(self, app);
+    }
+
+    fn on_resume(&mut self, app: &mut Apparatus) {
+        Game::on_resume(self, app);
+    }
+
+    fn on_pause_menu_options(&mut self, app: &mut Apparatus) {
+        Game::on_pause_menu_options(self, app);
+    }
+
+    fn on_quit_requested(&mut self, app: &mut Apparatus) -> bool {
+        Game::on_quit_requested(self, app)
+    }
+
+    fn save_state(&self) -> Vec<u8> {
+        HotReload::save_state(self)
+    }
+}
+
+/// Export a `HotReload` game from a dynamic library so `Apparatus::run_hot_reload` can load it.
+#[macro_export]
+macro_rules! export_hot_reload_game {
+    ($game:ty) => {
+        #[no_mangle]
+        #[allow(improper_ctypes_definitions)]
+        pub extern "C" fn apparatus_hot_reload_create(
+            app: &$crate::engine::apparatus::Apparatus,
+            state: Option<&[u8]>,
+        ) -> Result<Box<dyn $crate::engine::hot_reload::DynGame>, $crate::errors::ApparatusError> {
+            let game: $game = match state {
+                Some(state) => {
+                    <$game as $crate::engine::hot_reload::HotReload>::restore_state(app, state)?
+                }
+                None => <$game as $crate::engine::game::Game>::on_create(app)?,
+            };
+
+            Ok(Box::new(game))
+        }
+    };
+}
+
+/// A loaded build of the library. The game is dropped before the library that contains its code.
+struct Loaded {
+    game: Box<dyn DynGame>,
+    _library: Library,
+    path: PathBuf,
+}
+
+/// Hosts a game loaded from a dynamic library, reloading it when the library file changes.
+pub struct HotReloader {
+    source: PathBuf,
+    modified: Option<SystemTime>,
+    generation: u32,
+    loaded: Option<Loaded>,
+}
+
+impl HotReloader {
+    pub(crate) fn new(app: &Apparatus, source: impl Into<PathBuf>) -> Result<Self, ApparatusError> {
+        let mut reloader = Self {
+            source: source.into(),
+            modified: None,
+            generation: 0,
+            loaded: None,
+        };
+        reloader.modified = reloader.source_modified();
+        reloader.loaded = Some(reloader.load(app, None)?);
+
+        Ok(reloader)
+    }
+
+    fn source_modified(&self) -> Option<SystemTime> {
+        std::fs::metadata(&self.source).ok()?.modified().ok()
+    }
+
+    fn load(&mut self, app: &Apparatus, state: Option<&[u8]>) -> Result<Loaded, ApparatusError> {
+        self.generation += 1;
+        let (library, create, path) = load_copy(&self.source, self.generation)?;
+
+        match create(app, state) {
+            Ok(game) => Ok(Loaded {
+                game,
+                _library: library,
+                path,
+            }),
+            Err(e) => {
+                drop(library);
+                let _ = std::fs::remove_file(path);
+                Err(e)
+            }
+        }
+    }
+
+    /// Swap in the new build of the library if it has changed. The old build keeps running when
+    /// the new one fails to load.
+    fn reload_if_changed(&mut self, app: &Apparatus) {
+        let modified = self.source_modified();
+        if modified.is_none() || modified == self.modified {
+            return;
+        }
+        self.modified = modified;
+
+        let state = self.loaded.as_ref().map(|loaded| loaded.game.save_state());
+        match self.load(app, state.as_deref()) {
+            Ok(loaded) => {
+                if let Some(old) = self.loaded.replace(loaded) {
+                    unload(old);
+                }
+                info!("reloaded {}", self.source.display());
+            }
+            Err(e) => error!("unable to reload {}: {}", self.source.display(), e),
+        }
+    }
+
+    fn game(&mut self) -> &mut dyn DynGame {
+        self.loaded
+            .as_mut()
+            .expect("a game is always loaded")
+            .game
+            .as_mut()
+    }
+}
+
+impl Drop for HotReloader {
+    fn drop(&mut self) {
+        if let Some(loaded) = self.loaded.take() {
+            unload(loaded);
+        }
+    }
+}
+
+fn unload(loaded: Loaded) {
+    let Loaded {
+        game,
+        _library,
+        path,
+    } = loaded;
+    drop(game);
+    drop(_library);
+    let _ = std::fs::remove_file(path);
+}
+
+/// Load a copy of the library, so the original can be overwritten by the next build and so the
+/// platform doesn't hand back the previously loaded library from its cache. The copy is deleted
+/// if it can't be loaded.
+fn load_copy(
+    source: &Path,
+    generation: u32,
+) -> Result<(Library, CreateFn, PathBuf), ApparatusError> {
+    let path = loaded_copy_path(source, generation);
+    std::fs::copy(source, &path).map_err(|e| ApparatusError::Game(e.into()))?;
+
+    match open_library(&path) {
+        Ok((library, create)) => Ok((library, create, path)),
+        Err(e) => {
+            let _ = std::fs::remove_file(&path);
+            Err(e)
+        }
+    }
+}
+
+fn open_library(path: &Path) -> Result<(Library, CreateFn), ApparatusError> {
+    // Safety: loading runs the library's initialisers. The library is expected to be a game
+    // built against this engine with `export_hot_reload_game!`.
+    let library = unsafe { Library::new(path) }.map_err(|e| ApparatusError::Game(e.into()))?;
+    let create = unsafe { library.get::<CreateFn>(CREATE_SYMBOL) }
+        .map(|symbol| *symbol)
+        .map_err(|e| ApparatusError::Game(e.into()))?;
+
+    Ok((library, create))
+}
+
+fn loaded_copy_path(source: &Path, generation: u32) -> PathBuf {
+    let name = source
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    std::env::temp_dir().join(format!("{}-{}-{}", std::process::id(), generation, name))
+}
+
+impl Game for HotReloader {
+    fn on_create(_app: &Apparatus) -> Result<Self, ApparatusError> {
+        Err(ApparatusError::Initialisation(
+            "hot reloaded games are started with Apparatus::run_hot_reload".into(),
+        ))
+    }
+
+    fn on_update(&mut self, app: &mut Apparatus) {
+        self.reload_if_changed(app);
+        self.game().on_update(app);
+    }
+
+    fn on_pause(&mut self, app: &mut Apparatus) {
+        self.game().on_pause(app);
+    }
+
+    fn on_resume(&mut self, app: &mut Apparatus) {
+        self.game().on_resume(app);
+    }
+
+    fn on_pause_menu_options(&mut self, app: &mut Apparatus) {
+        self.game().on_pause_menu_options(app);
+    }
+
+    fn on_quit_requested(&mut self, app: &mut Apparatus) -> bool {
+        self.game().on_quit_requested(app)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn loaded_copies_are_unique_per_generation() {
+        let source = Path::new("target/debug/libgame.so");
+
+        let first = loaded_copy_path(source, 1);
+        let second = loaded_copy_path(source, 2);
+
+        assert_ne!(first, second);
+        assert!(first.to_string_lossy().ends_with("libgame.so"));
+    }
+
+    #[test]
+    fn failed_load_deletes_the_copy() {
+        let source = std::env::temp_dir().join("apparatus-hot-reload-test-not-a-library.so");
+        std::fs::write(&source, b"not a library").unwrap();
+
+        let result = load_copy(&source, u32::MAX);
+        std::fs::remove_file(&source).unwrap();
+
+        assert!(result.is_err());
+        assert!(!loaded_copy_path(&source, u32::MAX).exists());
+    }
+}
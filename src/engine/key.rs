@@ -41,4 +41,68 @@ pub enum Key {
     Left,
     Right,
     Space,
+    Escape,
+    Enter,
+    Backspace,
+    Tab,
+    LeftShift,
+    RightShift,
+}
+
+impl Key {
+    /// Every key, in declaration order, e.g. to store held keys as bits.
+    pub(crate) const ALL: [Key; 47] = [
+        Key::Num1,
+        Key::Num2,
+        Key::Num3,
+        Key::Num4,
+        Key::Num5,
+        Key::Num6,
+        Key::Num7,
+        Key::Num8,
+        Key::Num9,
+        Key::Num0,
+        Key::A,
+        Key::B,
+        Key::C,
+        Key::D,
+        Key::E,
+        Key::F,
+        Key::G,
+        Key::H,
+        Key::I,
+        Key::J,
+        Key::K,
+        Key::L,
+        Key::M,
+        Key::N,
+        Key::O,
+        Key::P,
+        Key::Q,
+        Key::R,
+        Key::S,
+        Key::T,
+        Key::U,
+        Key::V,
+        Key::W,
+        Key::X,
+        Key::Y,
+        Key::Z,
+        Key::Up,
+        Key::Down,
+        Key::Left,
+        Key::Right,
+        Key::Space,
+        Key::Escape,
+        Key::Enter,
+        Key::Backspace,
+        Key::Tab,
+        Key::LeftShift,
+        Key::RightShift,
+    ];
+
+    /// Keys held alongside others to change what they do.
+    pub fn is_modifier(&self) -> bool {
+        matches!(self, Key::LeftShift | Key::RightShift)
+    }
 }
@@ -0,0 +1,220 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum LocalizationError {
+    #[error("unable to read string table")]
+    Io(#[from] std::io::Error),
+    #[error("expected `key = value` on line {0}")]
+    Parse(usize),
+}
+
+/// Per-language tables of key → string, used to look up text shown to the player.
+///
+/// String tables are plain text with one `key = value` entry per line. Blank lines and lines
+/// starting with `#` are ignored. Values may contain `{name}` placeholders which are replaced by
+/// `Localization::format`.
+#[derive(Debug, Default)]
+pub struct Localization {
+    tables: HashMap<String, HashMap<String, String>>,
+    language: String,
+    fallback: Option<String>,
+}
+
+impl Localization {
+    pub fn new(language: impl Into<String>) -> Self {
+        Self {
+            language: language.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Set a language to look keys up in when they are missing from the current language.
+    pub fn with_fallback(mut self, language: impl Into<String>) -> Self {
+        self.fallback = Some(language.into());
+        self
+    }
+
+    /// Load a string table for `language`, merging it with any entries already loaded.
+    pub fn load_str(&mut self, language: &str, source: &str) -> Result<(), LocalizationError> {
+        let table = self.tables.entry(language.to_string()).or_default();
+
+        for (i, line) in source.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .ok_or(LocalizationError::Parse(i + 1))?;
+            let key = key.trim();
+            if key.is_empty() {
+                return Err(LocalizationError::Parse(i + 1));
+            }
+
+            table.insert(key.to_string(), value.trim().to_string());
+        }
+
+        Ok(())
+    }
+
+    pub fn load_file(
+        &mut self,
+        language: &str,
+        path: impl AsRef<Path>,
+    ) -> Result<(), LocalizationError> {
+        let source = std::fs::read_to_string(path)?;
+        self.load_str(language, &source)
+    }
+
+    pub fn language(&self) -> &str {
+        &self.language
+    }
+
+    pub fn set_language(&mut self, language: impl Into<String>) {
+        self.language = language.into();
+    }
+
+    pub fn languages(&self) -> impl Iterator<Item = &str> {
+        self.tables.keys().map(String::as_str)
+    }
+
+    /// Look up `key` in the current language, then the fallback language.
+    /// Returns the key itself when no translation exists, so missing strings are easy to spot.
+    pub fn get<'a>(&'a self, key: &'a str) -> &'a str {
+        self.lookup(key).unwrap_or(key)
+    }
+
+    /// Look up `key` like `get`, but return `default` when no translation exists, e.g. for
+    /// engine text that has built-in English.
+    pub fn get_or<'a>(&'a self, key: &str, default: &'a str) -> &'a str {
+        self.lookup(key).unwrap_or(default)
+    }
+
+    /// Look up `key` and replace each `{name}` placeholder with its value from `args`.
+    /// Placeholders are replaced in a single pass, so braces inside argument values are kept
+    /// as they are. Placeholders without an argument are left in place.
+    pub fn format(&self, key: &str, args: &[(&str, &str)]) -> String {
+        let mut rest = self.get(key);
+        let mut value = String::with_capacity(rest.len());
+
+        while let Some(open) = rest.find('{') {
+            value.push_str(&rest[..open]);
+            rest = &rest[open..];
+
+            let arg = rest.find('}').and_then(|close| {
+                let name = &rest[1..close];
+                args.iter()
+                    .find(|(arg_name, _)| *arg_name == name)
+                    .map(|(_, arg)| (*arg, close))
+            });
+            match arg {
+                Some((arg, close)) => {
+                    value.push_str(arg);
+                    rest = &rest[close + 1..];
+                }
+                None => {
+                    value.push('{');
+                    rest = &rest[1..];
+                }
+            }
+        }
+        value.push_str(rest);
+
+        value
+    }
+
+    fn lookup(&self, key: &str) -> Option<&str> {
+        let lookup = |language: &str| {
+            self.tables
+                .get(language)
+                .and_then(|table| table.get(key))
+                .map(String::as_str)
+        };
+
+        lookup(&self.language).or_else(|| self.fallback.as_deref().and_then(lookup))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EN: &str = "# Menu\nmenu.start = Start game\ngreeting = Hello, {name}!\n";
+    const FR: &str = "menu.start = Commencer\n";
+
+    #[test]
+    fn get_returns_value_for_current_language() {
+        let mut localization = Localization::new("fr");
+        localization.load_str("en", EN).unwrap();
+        localization.load_str("fr", FR).unwrap();
+
+        assert_eq!("Commencer", localization.get("menu.start"));
+
+        localization.set_language("en");
+
+        assert_eq!("Start game", localization.get("menu.start"));
+    }
+
+    #[test]
+    fn get_uses_fallback_language_for_missing_keys() {
+        let mut localization = Localization::new("fr").with_fallback("en");
+        localization.load_str("en", EN).unwrap();
+        localization.load_str("fr", FR).unwrap();
+
+        assert_eq!("Hello, {name}!", localization.get("greeting"));
+    }
+
+    #[test]
+    fn get_returns_key_when_no_translation_exists() {
+        let localization = Localization::new("en");
+
+        assert_eq!("menu.quit", localization.get("menu.quit"));
+    }
+
+    #[test]
+    fn format_substitutes_parameters() {
+        let mut localization = Localization::new("en");
+        localization.load_str("en", EN).unwrap();
+
+        assert_eq!(
+            "Hello, Worm!",
+            localization.format("greeting", &[("name", "Worm")])
+        );
+    }
+
+    #[test]
+    fn format_does_not_substitute_inside_arguments() {
+        let mut localization = Localization::new("en");
+        localization
+            .load_str("en", "kill = {killer} defeated {victim}")
+            .unwrap();
+
+        assert_eq!(
+            "{victim} defeated Worm",
+            localization.format("kill", &[("killer", "{victim}"), ("victim", "Worm")])
+        );
+    }
+
+    #[test]
+    fn get_or_uses_default_for_missing_keys() {
+        let mut localization = Localization::new("en");
+        localization.load_str("en", "menu.quit = Leave").unwrap();
+
+        assert_eq!("Leave", localization.get_or("menu.quit", "Quit"));
+        assert_eq!("Resume", localization.get_or("menu.resume", "Resume"));
+    }
+
+    #[test]
+    fn load_str_rejects_lines_without_separator() {
+        let mut localization = Localization::new("en");
+
+        assert!(matches!(
+            localization.load_str("en", "title = Worms\nbroken line"),
+            Err(LocalizationError::Parse(2))
+        ));
+    }
+}
@@ -19,4 +19,8 @@ impl Logger {
 
         Ok(logger)
     }
+
+    pub(crate) fn handle(&self) -> flexi_logger::LoggerHandle {
+        self._handle.clone()
+    }
 }
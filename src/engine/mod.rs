@@ -1,12 +1,41 @@
 use std::fmt::{Display, Formatter};
 
 pub mod apparatus;
+pub mod archetype;
+pub mod camera;
+pub mod checkpoint;
 pub mod clock;
+pub mod crash;
+pub mod decal;
+pub(crate) mod diagnostics;
+pub mod draw_list;
+pub mod floating_origin;
+pub mod floating_text;
 pub mod game;
+pub mod handle;
+#[cfg(feature = "hot-reload")]
+pub mod hot_reload;
 pub mod key;
+pub mod localization;
 pub mod logger;
 pub mod mouse;
+pub mod palette;
+pub(crate) mod pause_menu;
+pub mod plugin;
+pub mod recorder;
+pub mod replay;
+pub mod score;
+pub mod skeleton;
 pub mod sprite;
+pub mod sprite_batch;
+pub(crate) mod strict;
+pub mod text_art;
+pub mod tilemap;
+pub mod timeline;
+pub mod track;
+pub mod transform;
+pub mod vector_model;
+pub mod weather;
 
 #[derive(Default, Debug, Copy, Clone, PartialEq)]
 pub struct Point(f32, f32);
@@ -4,3 +4,9 @@ pub enum MouseButton {
     Middle,
     Right,
 }
+
+impl MouseButton {
+    /// Every button, in declaration order, e.g. to store held buttons as bits.
+    pub(crate) const ALL: [MouseButton; 3] =
+        [MouseButton::Left, MouseButton::Middle, MouseButton::Right];
+}
@@ -0,0 +1,75 @@
+use std::collections::HashMap;
+
+use crate::color::Color;
+
+/// Maps colors in a sprite to replacements, e.g. to draw one unit sprite in each team's colors or
+/// give a character different skins. Colors are matched on red, green and blue, and each pixel
+/// keeps its own alpha, so anti-aliased edges recolor too.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Palette {
+    swaps: HashMap<u32, Color>,
+}
+
+impl Palette {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace `from` with `to`.
+    pub fn with_swap(mut self, from: Color, to: Color) -> Self {
+        self.set(from, to);
+        self
+    }
+
+    pub fn set(&mut self, from: Color, to: Color) {
+        self.swaps.insert(rgb(from), to);
+    }
+
+    pub fn len(&self) -> usize {
+        self.swaps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.swaps.is_empty()
+    }
+
+    /// The replacement for `color`, or `color` itself if it isn't swapped.
+    pub fn apply(&self, color: Color) -> Color {
+        match self.swaps.get(&rgb(color)) {
+            Some(to) => Color::rgba(to.r(), to.g(), to.b(), color.a()),
+            None => color,
+        }
+    }
+}
+
+impl FromIterator<(Color, Color)> for Palette {
+    fn from_iter<I: IntoIterator<Item = (Color, Color)>>(swaps: I) -> Self {
+        let mut palette = Palette::new();
+        for (from, to) in swaps {
+            palette.set(from, to);
+        }
+        palette
+    }
+}
+
+fn rgb(color: Color) -> u32 {
+    u32::from(color) & 0x00FF_FFFF
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color;
+
+    #[test]
+    fn swaps_matching_colors_and_keeps_alpha() {
+        let palette = Palette::new().with_swap(color::css::RED, color::css::BLUE);
+
+        assert_eq!(color::css::BLUE, palette.apply(color::css::RED));
+        assert_eq!(
+            Color::rgba(0, 0, 255, 128),
+            palette.apply(Color::rgba(255, 0, 0, 128))
+        );
+        assert_eq!(color::css::GREEN, palette.apply(color::css::GREEN));
+    }
+}
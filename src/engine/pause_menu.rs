@@ -0,0 +1,175 @@
+use crate::color;
+use crate::color::Color;
+use crate::engine::localization::Localization;
+use crate::renderer::software_2d::Renderer;
+
+/// Each item with its string table key and the English text shown when the key isn't
+/// translated.
+const ITEMS: [(PauseMenuAction, &str, &str); 3] = [
+    (PauseMenuAction::Resume, "apparatus.pause.resume", "Resume"),
+    (
+        PauseMenuAction::Options,
+        "apparatus.pause.options",
+        "Options",
+    ),
+    (PauseMenuAction::Quit, "apparatus.pause.quit", "Quit"),
+];
+const TITLE: (&str, &str) = ("apparatus.pause.title", "Paused");
+const CONFIRM_QUIT: (&str, &str) = ("apparatus.pause.confirm_quit", "Really quit?");
+const CONFIRM_QUIT_HINT: (&str, &str) = (
+    "apparatus.pause.confirm_quit_hint",
+    "Enter to quit, Escape to cancel",
+);
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum PauseMenuCommand {
+    Up,
+    Down,
+    Select,
+    Back,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum PauseMenuAction {
+    Resume,
+    Options,
+    Quit,
+}
+
+/// The built-in pause overlay, enabled with `ApparatusSettings::with_pause_menu`.
+#[derive(Default)]
+pub(crate) struct PauseMenu {
+    selected: usize,
+    confirming_quit: bool,
+}
+
+impl PauseMenu {
+    pub(crate) fn reset(&mut self) {
+        self.selected = 0;
+        self.confirming_quit = false;
+    }
+
+    /// Apply a navigation command, returning an action once the player has made a choice.
+    /// Choosing quit asks for confirmation before `PauseMenuAction::Quit` is returned.
+    pub(crate) fn handle(&mut self, command: PauseMenuCommand) -> Option<PauseMenuAction> {
+        if self.confirming_quit {
+            return match command {
+                PauseMenuCommand::Select => Some(PauseMenuAction::Quit),
+                PauseMenuCommand::Back => {
+                    self.confirming_quit = false;
+                    None
+                }
+                _ => None,
+            };
+        }
+
+        match command {
+            PauseMenuCommand::Up => {
+                self.selected = (self.selected + ITEMS.len() - 1) % ITEMS.len();
+                None
+            }
+            PauseMenuCommand::Down => {
+                self.selected = (self.selected + 1) % ITEMS.len();
+                None
+            }
+            PauseMenuCommand::Select => match ITEMS[self.selected].0 {
+                PauseMenuAction::Quit => {
+                    self.confirming_quit = true;
+                    None
+                }
+                action => Some(action),
+            },
+            PauseMenuCommand::Back => Some(PauseMenuAction::Resume),
+        }
+    }
+
+    /// Draw the menu with its text looked up in `localization`, falling back to English.
+    pub(crate) fn draw(
+        &self,
+        renderer: &mut Renderer,
+        localization: &Localization,
+        width: f32,
+        height: f32,
+    ) {
+        let text = |(key, default)| localization.get_or(key, default);
+        renderer.draw_filled_rectangle_unscaled(0.0, 0.0, width, height, Color::rgba(0, 0, 0, 160));
+
+        let x = width / 2.0 - 60.0;
+        let y = height / 2.0 + 40.0;
+        renderer.draw_string(text(TITLE), x, y, color::css::WHITE, 24.0);
+
+        if self.confirming_quit {
+            renderer.draw_string(text(CONFIRM_QUIT), x, y - 40.0, color::css::WHITE, 16.0);
+            renderer.draw_string(
+                text(CONFIRM_QUIT_HINT),
+                x,
+                y - 60.0,
+                color::css::SILVER,
+                12.0,
+            );
+            return;
+        }
+
+        for (i, &(_, key, default)) in ITEMS.iter().enumerate() {
+            let label = text((key, default));
+            let (label, color) = if i == self.selected {
+                (format!("> {}", label), color::css::YELLOW)
+            } else {
+                (format!("  {}", label), color::css::WHITE)
+            };
+            renderer.draw_string(label, x, y - 40.0 - 25.0 * i as f32, color, 16.0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn selecting_first_item_resumes() {
+        let mut menu = PauseMenu::default();
+
+        assert_eq!(
+            Some(PauseMenuAction::Resume),
+            menu.handle(PauseMenuCommand::Select)
+        );
+    }
+
+    #[test]
+    fn navigation_wraps_around_items() {
+        let mut menu = PauseMenu::default();
+        menu.handle(PauseMenuCommand::Up);
+
+        assert_eq!(2, menu.selected);
+
+        menu.handle(PauseMenuCommand::Down);
+
+        assert_eq!(0, menu.selected);
+    }
+
+    #[test]
+    fn quit_requires_confirmation() {
+        let mut menu = PauseMenu::default();
+        menu.handle(PauseMenuCommand::Up);
+
+        assert_eq!(None, menu.handle(PauseMenuCommand::Select));
+        assert_eq!(
+            Some(PauseMenuAction::Quit),
+            menu.handle(PauseMenuCommand::Select)
+        );
+    }
+
+    #[test]
+    fn back_cancels_quit_confirmation() {
+        let mut menu = PauseMenu::default();
+        menu.handle(PauseMenuCommand::Up);
+        menu.handle(PauseMenuCommand::Select);
+
+        assert_eq!(None, menu.handle(PauseMenuCommand::Back));
+        assert_eq!(
+            Some(PauseMenuAction::Resume),
+            menu.handle(PauseMenuCommand::Back)
+        );
+    }
+}
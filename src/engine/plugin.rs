@@ -0,0 +1,17 @@
+use crate::engine::apparatus::Apparatus;
+
+/// A reusable add-on, e.g. an overlay, recorder or network sync, added with
+/// `Apparatus::add_plugin`. Every hook is optional and plugins run in the order they were added.
+pub trait Plugin {
+    /// Called once, after the game has been created and before the first frame.
+    fn on_engine_start(&mut self, _app: &mut Apparatus) {}
+
+    /// Called each frame after input is read and before the game updates.
+    fn pre_update(&mut self, _app: &mut Apparatus) {}
+
+    /// Called each frame after the game updates, e.g. to draw over the game.
+    fn post_update(&mut self, _app: &mut Apparatus) {}
+
+    /// Called each frame just before it is shown, after everything has been drawn.
+    fn pre_present(&mut self, _app: &mut Apparatus) {}
+}
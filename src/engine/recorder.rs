@@ -0,0 +1,217 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::{Delay, Frame, ImageError, RgbaImage};
+use log::{error, info};
+
+use crate::engine::key::Key;
+
+/// Where GIF recordings are saved, how much is kept and the key that saves it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecorderSettings {
+    pub directory: PathBuf,
+    /// How many seconds of gameplay each recording holds.
+    pub seconds: f32,
+    /// Capture one frame in this many, e.g. 2 for a 30 fps GIF.
+    pub every: usize,
+    pub key: Key,
+}
+
+/// Keeps the last few seconds of frames, one virtual pixel per GIF pixel, so they can be saved
+/// as an animated GIF.
+pub(crate) struct GifRecorder {
+    settings: RecorderSettings,
+    frame_duration: Duration,
+    frames: VecDeque<RgbaImage>,
+    /// Frames left to skip before the next capture.
+    skip: usize,
+}
+
+impl GifRecorder {
+    pub(crate) fn new(settings: RecorderSettings, frame_duration: Duration) -> Self {
+        Self {
+            settings,
+            frame_duration,
+            frames: VecDeque::new(),
+            skip: 0,
+        }
+    }
+
+    pub(crate) fn key(&self) -> Key {
+        self.settings.key
+    }
+
+    fn capacity(&self) -> usize {
+        let captured_per_second =
+            1.0 / (self.frame_duration.as_secs_f32() * self.settings.every.max(1) as f32);
+        (self.settings.seconds * captured_per_second)
+            .ceil()
+            .max(1.0) as usize
+    }
+
+    /// Capture a `width` x `height` ARGB frame, sampling one pixel of each
+    /// `pixel_width` x `pixel_height` block, if it is this frame's turn.
+    pub(crate) fn capture(
+        &mut self,
+        frame: &[u32],
+        width: usize,
+        height: usize,
+        pixel_width: usize,
+        pixel_height: usize,
+    ) {
+        if self.skip > 0 {
+            self.skip -= 1;
+            return;
+        }
+        self.skip = self.settings.every.max(1) - 1;
+
+        let (columns, rows) = (width / pixel_width.max(1), height / pixel_height.max(1));
+        let dimensions = (columns as u32, rows as u32);
+
+        // The screen was resized, so older frames can't go in the same GIF.
+        if self
+            .frames
+            .back()
+            .is_some_and(|last| last.dimensions() != dimensions)
+        {
+            self.frames.clear();
+        }
+        // Reuse the oldest frame's pixels once full, rather than allocating every capture.
+        let mut image = if self.frames.len() >= self.capacity() {
+            self.frames
+                .pop_front()
+                .expect("a full recording has frames")
+        } else {
+            RgbaImage::new(dimensions.0, dimensions.1)
+        };
+
+        for (i, pixel) in image.pixels_mut().enumerate() {
+            let (row, column) = (i / columns, i % columns);
+            let [_, r, g, b] =
+                frame[row * pixel_height * width + column * pixel_width].to_be_bytes();
+            pixel.0 = [r, g, b, 255];
+        }
+        self.frames.push_back(image);
+    }
+
+    /// Write the captured frames to a new GIF in the recording directory on a background
+    /// thread, so the game doesn't stall while it is encoded.
+    pub(crate) fn save(&self) {
+        if self.frames.is_empty() {
+            return;
+        }
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|t| t.as_millis())
+            .unwrap_or_default();
+        let path = self
+            .settings
+            .directory
+            .join(format!("recording-{}.gif", timestamp));
+        let frames: Vec<RgbaImage> = self.frames.iter().cloned().collect();
+        let delay = self.frame_duration * self.settings.every.max(1) as u32;
+
+        std::thread::spawn(move || match write_gif(&path, frames, delay) {
+            Ok(()) => info!("saved recording to {}", path.display()),
+            Err(e) => error!("unable to save recording: {}", e),
+        });
+    }
+}
+
+fn write_gif(path: &Path, frames: Vec<RgbaImage>, delay: Duration) -> Result<(), ImageError> {
+    if let Some(directory) = path.parent() {
+        std::fs::create_dir_all(directory)?;
+    }
+
+    let mut encoder = GifEncoder::new(BufWriter::new(File::create(path)?));
+    encoder.set_repeat(Repeat::Infinite)?;
+    let delay = Delay::from_saturating_duration(delay);
+    encoder.encode_frames(
+        frames
+            .into_iter()
+            .map(|image| Frame::from_parts(image, 0, 0, delay)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recorder(seconds: f32, every: usize) -> GifRecorder {
+        let settings = RecorderSettings {
+            directory: PathBuf::from("recordings"),
+            seconds,
+            every,
+            key: Key::R,
+        };
+        GifRecorder::new(settings, Duration::from_millis(100))
+    }
+
+    #[test]
+    fn only_the_last_few_seconds_are_kept() {
+        let mut recorder = recorder(1.0, 2);
+
+        for frame in 0..30u32 {
+            recorder.capture(&[frame; 4], 2, 2, 2, 2);
+        }
+
+        let kept: Vec<u8> = recorder
+            .frames
+            .iter()
+            .map(|image| image.as_raw()[2])
+            .collect();
+        assert_eq!(vec![20, 22, 24, 26, 28], kept);
+    }
+
+    #[test]
+    fn frames_are_sampled_at_the_pixel_size() {
+        let mut recorder = recorder(1.0, 1);
+        let frame = [
+            0xFF11_2233,
+            0,
+            0xFF44_5566,
+            0, //
+            0,
+            0,
+            0,
+            0,
+        ];
+
+        recorder.capture(&frame, 4, 2, 2, 2);
+
+        assert_eq!((2, 1), recorder.frames[0].dimensions());
+        assert_eq!(
+            &[0x11, 0x22, 0x33, 255, 0x44, 0x55, 0x66, 255][..],
+            recorder.frames[0].as_raw().as_slice()
+        );
+
+        recorder.capture(&[0; 4], 2, 2, 1, 1);
+
+        assert_eq!(1, recorder.frames.len());
+        assert_eq!((2, 2), recorder.frames[0].dimensions());
+    }
+
+    #[test]
+    fn recordings_are_animated_gifs() {
+        use image::codecs::gif::GifDecoder;
+        use image::AnimationDecoder;
+
+        let path = std::env::temp_dir().join(format!(
+            "apparatus-recording-test-{}.gif",
+            std::process::id()
+        ));
+        let frames = vec![RgbaImage::new(2, 2), RgbaImage::new(2, 2)];
+
+        write_gif(&path, frames, Duration::from_millis(50)).unwrap();
+        let file = File::open(&path).unwrap();
+        let decoded = GifDecoder::new(file).unwrap().into_frames().count();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(2, decoded);
+    }
+}
@@ -0,0 +1,386 @@
+use std::path::Path;
+
+use thiserror::Error;
+
+use crate::engine::key::Key;
+use crate::engine::mouse::MouseButton;
+use crate::procgen::seed::{self, WorldSeed};
+
+const MAGIC: &[u8; 4] = b"APRP";
+/// The newest container format this build reads and the one it writes.
+const FORMAT_VERSION: u16 = 1;
+const ENGINE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[derive(Debug, Error)]
+pub enum ReplayError {
+    #[error("unable to access replay")]
+    Io(#[from] std::io::Error),
+    #[error("not a replay")]
+    NotAReplay,
+    #[error("replay format {0} is newer than this build supports")]
+    UnsupportedFormat(u16),
+    #[error("replay is corrupt")]
+    Corrupt,
+    #[error("replay recorded with engine {recorded} can't be played by engine {running}")]
+    IncompatibleEngine { recorded: String, running: String },
+    #[error("replay diverged from the recording at frame {frame}")]
+    Desync { frame: u64 },
+}
+
+/// The input held during one frame. Keys and buttons load in the order they're declared in.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ReplayFrame {
+    pub keys: Vec<Key>,
+    /// In window pixels from the bottom left, as `Apparatus::mouse_window_pos` reports it.
+    pub mouse: (f32, f32),
+    pub buttons: Vec<MouseButton>,
+}
+
+/// A recorded play session: the engine version, world seed and settings it was recorded with,
+/// the input of every frame, and hashes of the game state every so often to catch a replay
+/// drifting from the recording.
+///
+/// Replays are saved in a compact binary format: held keys and buttons are stored as bits and
+/// runs of identical frames once, and a checksum over the whole file detects corruption. Replays
+/// load in any engine with the same major version, or the same minor version before 1.0, and
+/// fail with `ReplayError::IncompatibleEngine` otherwise.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Replay {
+    engine_version: String,
+    seed: Option<WorldSeed>,
+    settings: String,
+    frames: Vec<ReplayFrame>,
+    state_hashes: Vec<(u64, u64)>,
+}
+
+impl Replay {
+    /// Start an empty replay of a game with `seed` and `settings`, e.g. its options saved as TOML.
+    pub fn new(seed: Option<WorldSeed>, settings: impl Into<String>) -> Self {
+        Self {
+            engine_version: String::from(ENGINE_VERSION),
+            seed,
+            settings: settings.into(),
+            frames: Vec::new(),
+            state_hashes: Vec::new(),
+        }
+    }
+
+    /// The version of the engine the replay was recorded with.
+    pub fn engine_version(&self) -> &str {
+        &self.engine_version
+    }
+
+    pub fn seed(&self) -> Option<WorldSeed> {
+        self.seed
+    }
+
+    pub fn settings(&self) -> &str {
+        &self.settings
+    }
+
+    pub fn push_frame(&mut self, frame: ReplayFrame) {
+        self.frames.push(frame);
+    }
+
+    pub fn frames(&self) -> &[ReplayFrame] {
+        &self.frames
+    }
+
+    /// Remember a hash of the game state after `frame`, e.g. every second, to check against on
+    /// playback with `check_state_hash`.
+    pub fn push_state_hash(&mut self, frame: u64, hash: u64) {
+        self.state_hashes.push((frame, hash));
+    }
+
+    pub fn state_hash(&self, frame: u64) -> Option<u64> {
+        self.state_hashes
+            .iter()
+            .find(|&&(hashed, _)| hashed == frame)
+            .map(|&(_, hash)| hash)
+    }
+
+    /// Check the game state after `frame` of playback against the recording. Frames without a
+    /// recorded hash always pass.
+    pub fn check_state_hash(&self, frame: u64, hash: u64) -> Result<(), ReplayError> {
+        match self.state_hash(frame) {
+            Some(recorded) if recorded != hash => Err(ReplayError::Desync { frame }),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+        write_string(&mut bytes, &self.engine_version);
+        match self.seed {
+            Some(seed) => {
+                bytes.push(1);
+                bytes.extend_from_slice(&seed.value().to_le_bytes());
+            }
+            None => bytes.push(0),
+        }
+        write_string(&mut bytes, &self.settings);
+
+        // Frames are stored as runs, so standing still costs a few bytes however long it lasts.
+        let mut runs: Vec<(u64, &ReplayFrame)> = Vec::new();
+        for frame in &self.frames {
+            match runs.last_mut() {
+                Some((count, last)) if *last == frame => *count += 1,
+                _ => runs.push((1, frame)),
+            }
+        }
+        write_varint(&mut bytes, runs.len() as u64);
+        for (count, frame) in runs {
+            write_varint(&mut bytes, count);
+            bytes.extend_from_slice(&to_bits(&Key::ALL, &frame.keys).to_le_bytes());
+            bytes.extend_from_slice(&frame.mouse.0.to_le_bytes());
+            bytes.extend_from_slice(&frame.mouse.1.to_le_bytes());
+            bytes.push(to_bits(&MouseButton::ALL, &frame.buttons) as u8);
+        }
+
+        write_varint(&mut bytes, self.state_hashes.len() as u64);
+        for &(frame, hash) in &self.state_hashes {
+            write_varint(&mut bytes, frame);
+            bytes.extend_from_slice(&hash.to_le_bytes());
+        }
+
+        let checksum = seed::fnv1a(&bytes);
+        bytes.extend_from_slice(&checksum.to_le_bytes());
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ReplayError> {
+        if bytes.len() < MAGIC.len() || &bytes[..MAGIC.len()] != MAGIC {
+            return Err(ReplayError::NotAReplay);
+        }
+        if bytes.len() < MAGIC.len() + 8 {
+            return Err(ReplayError::Corrupt);
+        }
+        let (body, checksum) = bytes.split_at(bytes.len() - 8);
+        if seed::fnv1a(body).to_le_bytes() != checksum {
+            return Err(ReplayError::Corrupt);
+        }
+
+        let mut reader = Reader {
+            bytes: body,
+            position: MAGIC.len(),
+        };
+        let format = u16::from_le_bytes(reader.array()?);
+        if format > FORMAT_VERSION {
+            return Err(ReplayError::UnsupportedFormat(format));
+        }
+
+        let engine_version = reader.string()?;
+        if !is_compatible(&engine_version, ENGINE_VERSION) {
+            return Err(ReplayError::IncompatibleEngine {
+                recorded: engine_version,
+                running: String::from(ENGINE_VERSION),
+            });
+        }
+        let seed = match reader.byte()? {
+            0 => None,
+            _ => Some(WorldSeed::new(u64::from_le_bytes(reader.array()?))),
+        };
+        let settings = reader.string()?;
+
+        let mut frames = Vec::new();
+        for _ in 0..reader.varint()? {
+            let count = reader.varint()?;
+            let keys = from_bits(&Key::ALL, u64::from_le_bytes(reader.array()?));
+            let mouse = (
+                f32::from_le_bytes(reader.array()?),
+                f32::from_le_bytes(reader.array()?),
+            );
+            let buttons = from_bits(&MouseButton::ALL, reader.byte()? as u64);
+            let frame = ReplayFrame {
+                keys,
+                mouse,
+                buttons,
+            };
+            frames.extend(std::iter::repeat_n(frame, count as usize));
+        }
+
+        let mut state_hashes = Vec::new();
+        for _ in 0..reader.varint()? {
+            let frame = reader.varint()?;
+            state_hashes.push((frame, u64::from_le_bytes(reader.array()?)));
+        }
+
+        Ok(Self {
+            engine_version,
+            seed,
+            settings,
+            frames,
+            state_hashes,
+        })
+    }
+
+    pub fn save_file(&self, path: impl AsRef<Path>) -> Result<(), ReplayError> {
+        std::fs::write(path, self.to_bytes())?;
+        Ok(())
+    }
+
+    pub fn load_file(path: impl AsRef<Path>) -> Result<Self, ReplayError> {
+        let bytes = std::fs::read(path)?;
+        Self::from_bytes(&bytes)
+    }
+}
+
+/// Whether a replay recorded with engine `recorded` plays the same in engine `running`: patch
+/// releases keep replays working, so the major version must match, or the minor version before
+/// 1.0.
+fn is_compatible(recorded: &str, running: &str) -> bool {
+    let release = |version: &str| {
+        let mut parts = version.split('.').map(|part| part.parse::<u64>().ok());
+        match (parts.next().flatten(), parts.next().flatten()) {
+            (Some(0), Some(minor)) => Some((0, minor)),
+            (Some(major), Some(_)) => Some((major, 0)),
+            _ => None,
+        }
+    };
+
+    release(recorded).is_some() && release(recorded) == release(running)
+}
+
+fn to_bits<T: PartialEq>(all: &[T], held: &[T]) -> u64 {
+    all.iter()
+        .enumerate()
+        .filter(|(_, item)| held.contains(item))
+        .fold(0, |bits, (i, _)| bits | 1 << i)
+}
+
+fn from_bits<T: Copy>(all: &[T], bits: u64) -> Vec<T> {
+    all.iter()
+        .enumerate()
+        .filter(|&(i, _)| bits & (1 << i) != 0)
+        .map(|(_, &item)| item)
+        .collect()
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    loop {
+        let byte = (value & 0x7F) as u8;
+        value >>= 7;
+        if value == 0 {
+            bytes.push(byte);
+            return;
+        }
+        bytes.push(byte | 0x80);
+    }
+}
+
+fn write_string(bytes: &mut Vec<u8>, value: &str) {
+    write_varint(bytes, value.len() as u64);
+    bytes.extend_from_slice(value.as_bytes());
+}
+
+/// Reads a replay's fields in order. Running out of bytes means the replay is corrupt, as the
+/// checksum has already passed.
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl Reader<'_> {
+    fn take(&mut self, count: usize) -> Result<&[u8], ReplayError> {
+        let end = self
+            .position
+            .checked_add(count)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or(ReplayError::Corrupt)?;
+        let taken = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(taken)
+    }
+
+    fn byte(&mut self) -> Result<u8, ReplayError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], ReplayError> {
+        Ok(self.take(N)?.try_into().expect("took N bytes"))
+    }
+
+    fn varint(&mut self) -> Result<u64, ReplayError> {
+        let mut value = 0;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            value |= ((byte & 0x7F) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(ReplayError::Corrupt)
+    }
+
+    fn string(&mut self) -> Result<String, ReplayError> {
+        let length = self.varint()? as usize;
+        String::from_utf8(self.take(length)?.to_vec()).map_err(|_| ReplayError::Corrupt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn replay() -> Replay {
+        let mut replay = Replay::new(Some(WorldSeed::new(42)), "difficulty = \"hard\"");
+        let idle = ReplayFrame::default();
+        let jumping = ReplayFrame {
+            keys: vec![Key::Right, Key::Space],
+            mouse: (12.5, 40.0),
+            buttons: vec![MouseButton::Left],
+        };
+        for _ in 0..100 {
+            replay.push_frame(idle.clone());
+        }
+        replay.push_frame(jumping);
+        replay.push_state_hash(60, 0xDEAD_BEEF);
+        replay
+    }
+
+    #[test]
+    fn replays_round_trip_compactly() {
+        let replay = replay();
+
+        let bytes = replay.to_bytes();
+        let loaded = Replay::from_bytes(&bytes).unwrap();
+
+        assert!(bytes.len() < 100);
+        assert_eq!(replay, loaded);
+        assert!(loaded.check_state_hash(60, 0xDEAD_BEEF).is_ok());
+        assert!(matches!(
+            loaded.check_state_hash(60, 1),
+            Err(ReplayError::Desync { frame: 60 })
+        ));
+    }
+
+    #[test]
+    fn damaged_replays_fail_to_load() {
+        let mut bytes = replay().to_bytes();
+        bytes[10] ^= 0xFF;
+        assert!(matches!(
+            Replay::from_bytes(&bytes),
+            Err(ReplayError::Corrupt)
+        ));
+
+        assert!(matches!(
+            Replay::from_bytes(b"PNG..."),
+            Err(ReplayError::NotAReplay)
+        ));
+        assert!(matches!(
+            Replay::from_bytes(&replay().to_bytes()[..20]),
+            Err(ReplayError::Corrupt)
+        ));
+    }
+
+    #[test]
+    fn patch_releases_keep_replays_playable() {
+        assert!(is_compatible("0.3.1", "0.3.7"));
+        assert!(!is_compatible("0.3.1", "0.4.0"));
+        assert!(is_compatible("1.2.0", "1.9.3"));
+        assert!(!is_compatible("1.2.0", "2.0.0"));
+        assert!(!is_compatible("nonsense", "nonsense"));
+    }
+}
@@ -0,0 +1,197 @@
+use std::time::Duration;
+
+use crate::color::Color;
+use crate::engine::apparatus::Apparatus;
+use crate::engine::floating_text::FloatingText;
+
+/// Keeps score with a combo multiplier that grows with consecutive hits and resets when no
+/// points are scored within the combo window.
+#[derive(Clone, Debug)]
+pub struct Score {
+    total: u64,
+    high_score: u64,
+    combo: u32,
+    combo_window: Duration,
+    combo_remaining: Duration,
+    hits_per_multiplier: u32,
+    max_multiplier: u32,
+}
+
+impl Default for Score {
+    fn default() -> Self {
+        Self {
+            total: 0,
+            high_score: 0,
+            combo: 0,
+            combo_window: Duration::from_secs(2),
+            combo_remaining: Duration::ZERO,
+            hits_per_multiplier: 5,
+            max_multiplier: 8,
+        }
+    }
+}
+
+impl Score {
+    /// How long after scoring the combo stays alive. Defaults to 2 seconds.
+    pub fn with_combo_window(mut self, combo_window: Duration) -> Self {
+        self.combo_window = combo_window;
+        self
+    }
+
+    /// How many hits in a row raise the multiplier by one. Defaults to 5.
+    pub fn with_hits_per_multiplier(mut self, hits: u32) -> Self {
+        self.hits_per_multiplier = hits.max(1);
+        self
+    }
+
+    /// Defaults to 8.
+    pub fn with_max_multiplier(mut self, max_multiplier: u32) -> Self {
+        self.max_multiplier = max_multiplier.max(1);
+        self
+    }
+
+    pub fn with_high_score(mut self, high_score: u64) -> Self {
+        self.high_score = high_score;
+        self
+    }
+
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    pub fn high_score(&self) -> u64 {
+        self.high_score
+    }
+
+    pub fn combo(&self) -> u32 {
+        self.combo
+    }
+
+    pub fn multiplier(&self) -> u32 {
+        (1 + self.combo / self.hits_per_multiplier).min(self.max_multiplier)
+    }
+
+    /// How much of the combo window is left, from 1 (just scored) to 0 (combo lost).
+    pub fn combo_remaining(&self) -> f32 {
+        if self.combo_window.is_zero() {
+            return 0.0;
+        }
+
+        self.combo_remaining.as_secs_f32() / self.combo_window.as_secs_f32()
+    }
+
+    /// Add `points` scaled by the current multiplier, extend the combo and return the points
+    /// awarded.
+    pub fn add(&mut self, points: u64) -> u64 {
+        let awarded = points * self.multiplier() as u64;
+        self.total += awarded;
+        self.high_score = self.high_score.max(self.total);
+        self.combo += 1;
+        self.combo_remaining = self.combo_window;
+
+        awarded
+    }
+
+    /// Like `add`, also spawning a floating "+points" popup at (x, y) showing the multiplier.
+    pub fn award(&mut self, app: &mut Apparatus, points: u64, x: f32, y: f32, color: Color) -> u64 {
+        let multiplier = self.multiplier();
+        let awarded = self.add(points);
+        let text = if multiplier > 1 {
+            format!(
+                "+{} {}",
+                format_score(awarded),
+                format_multiplier(multiplier)
+            )
+        } else {
+            format!("+{}", format_score(awarded))
+        };
+        app.spawn_floating_text(FloatingText::new(text, x, y).with_color(color));
+
+        awarded
+    }
+
+    /// Break the combo immediately, e.g. when the player takes damage.
+    pub fn break_combo(&mut self) {
+        self.combo = 0;
+        self.combo_remaining = Duration::ZERO;
+    }
+
+    /// Count down the combo window, resetting the combo when it runs out.
+    pub fn update(&mut self, elapsed: Duration) {
+        self.combo_remaining = self.combo_remaining.saturating_sub(elapsed);
+        if self.combo_remaining.is_zero() {
+            self.combo = 0;
+        }
+    }
+
+    /// Reset the score for a new game, keeping the high score.
+    pub fn reset(&mut self) {
+        self.total = 0;
+        self.break_combo();
+    }
+}
+
+/// Format a score with thousands separators, e.g. "1,234,567".
+pub fn format_score(score: u64) -> String {
+    let digits = score.to_string();
+    let mut formatted = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            formatted.push(',');
+        }
+        formatted.push(digit);
+    }
+
+    formatted
+}
+
+/// Format a score padded with leading zeros to `width` digits, arcade style, e.g. "000450".
+pub fn format_score_padded(score: u64, width: usize) -> String {
+    format!("{:0width$}", score, width = width)
+}
+
+/// Format a multiplier, e.g. "x3".
+pub fn format_multiplier(multiplier: u32) -> String {
+    format!("x{}", multiplier)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn consecutive_hits_raise_the_multiplier() {
+        let mut score = Score::default().with_hits_per_multiplier(2);
+
+        let awarded: Vec<u64> = (0..5).map(|_| score.add(10)).collect();
+
+        assert_eq!(vec![10, 10, 20, 20, 30], awarded);
+        assert_eq!(90, score.total());
+        assert_eq!(90, score.high_score());
+    }
+
+    #[test]
+    fn combo_decays_when_window_expires() {
+        let mut score = Score::default()
+            .with_hits_per_multiplier(1)
+            .with_combo_window(Duration::from_secs(1));
+        score.add(10);
+        score.add(10);
+
+        score.update(Duration::from_millis(500));
+        assert_eq!(3, score.multiplier());
+
+        score.update(Duration::from_millis(500));
+        assert_eq!(0, score.combo());
+        assert_eq!(1, score.multiplier());
+    }
+
+    #[test]
+    fn scores_are_formatted_for_display() {
+        assert_eq!("0", format_score(0));
+        assert_eq!("999", format_score(999));
+        assert_eq!("1,234,567", format_score(1_234_567));
+        assert_eq!("000450", format_score_padded(450, 6));
+        assert_eq!("x4", format_multiplier(4));
+    }
+}
@@ -0,0 +1,374 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::engine::apparatus::Apparatus;
+use crate::engine::sprite::Sprite;
+use crate::engine::transform::Transform;
+use crate::engine::Point;
+use crate::maths::Vec2;
+use crate::renderer::software_2d::Origin;
+
+#[derive(Debug, Error)]
+pub enum SkeletonError {
+    #[error("unable to read skeleton")]
+    Io(#[from] std::io::Error),
+    #[error("invalid skeleton")]
+    Parse(#[from] toml::de::Error),
+    #[error("bone '{bone}' has parent '{parent}', which isn't listed before it")]
+    UnknownParent { bone: String, parent: String },
+    #[error("animation '{animation}' has keys for unknown bone '{bone}'")]
+    UnknownBone { animation: String, bone: String },
+}
+
+#[derive(Debug, Deserialize)]
+struct SkeletonDescriptor {
+    #[serde(default)]
+    bones: Vec<BoneDescriptor>,
+    #[serde(default)]
+    animations: HashMap<String, AnimationDescriptor>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BoneDescriptor {
+    name: String,
+    parent: Option<String>,
+    #[serde(default)]
+    x: f32,
+    #[serde(default)]
+    y: f32,
+    #[serde(default)]
+    rotation: f32,
+    attachment: Option<Attachment>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AnimationDescriptor {
+    #[serde(default = "default_looping")]
+    looping: bool,
+    #[serde(default)]
+    bones: HashMap<String, Vec<Keyframe>>,
+}
+
+fn default_looping() -> bool {
+    true
+}
+
+/// A sprite drawn with a bone, turning with it about `pivot_x`, `pivot_y`, given in sprite
+/// pixels from the corner `draw_sprite` anchors.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct Attachment {
+    pub sprite: String,
+    #[serde(default)]
+    pub pivot_x: f32,
+    #[serde(default)]
+    pub pivot_y: f32,
+}
+
+/// How far a bone has moved and turned from its rest position at `time` seconds.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+pub struct Keyframe {
+    pub time: f32,
+    #[serde(default)]
+    pub x: f32,
+    #[serde(default)]
+    pub y: f32,
+    #[serde(default)]
+    pub rotation: f32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Bone {
+    name: String,
+    parent: Option<usize>,
+    rest: Transform,
+    attachment: Option<Attachment>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct BoneAnimation {
+    looping: bool,
+    duration: f32,
+    /// Keyframes sorted by time for each bone, by bone index.
+    keys: Vec<Vec<Keyframe>>,
+}
+
+/// A hierarchy of bones with sprites attached and keyframed animations, loaded from TOML.
+/// Bones are listed parents first and attachments are drawn in the same order. Positions are
+/// in virtual pixels relative to the parent bone and rotations are in radians, clockwise as
+/// for `Transform`. Animation keys are offsets from each bone's rest position and are
+/// interpolated linearly:
+///
+/// ```toml
+/// [[bones]]
+/// name = "body"
+/// attachment = { sprite = "body", pivot_x = 4.0, pivot_y = 0.0 }
+///
+/// [[bones]]
+/// name = "arm"
+/// parent = "body"
+/// x = 3.0
+/// y = 10.0
+/// attachment = { sprite = "arm", pivot_x = 1.0, pivot_y = 6.0 }
+///
+/// [animations.wave]
+/// looping = true
+/// bones.arm = [
+///     { time = 0.0, rotation = 0.0 },
+///     { time = 0.5, rotation = -1.2 },
+///     { time = 1.0, rotation = 0.0 },
+/// ]
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Skeleton {
+    bones: Vec<Bone>,
+    animations: HashMap<String, BoneAnimation>,
+}
+
+impl Skeleton {
+    pub fn load_str(source: &str) -> Result<Self, SkeletonError> {
+        let descriptor: SkeletonDescriptor = toml::from_str(source)?;
+
+        let mut skeleton = Self::default();
+        for bone in descriptor.bones {
+            let parent = bone
+                .parent
+                .map(|parent| {
+                    skeleton
+                        .bone(&parent)
+                        .ok_or_else(|| SkeletonError::UnknownParent {
+                            bone: bone.name.clone(),
+                            parent,
+                        })
+                })
+                .transpose()?;
+            skeleton.bones.push(Bone {
+                name: bone.name,
+                parent,
+                rest: Transform::new(Vec2::new(bone.x, bone.y), bone.rotation, 1.0),
+                attachment: bone.attachment,
+            });
+        }
+
+        for (name, animation) in descriptor.animations {
+            let mut keys = vec![Vec::new(); skeleton.bones.len()];
+            for (bone, mut frames) in animation.bones {
+                let index = skeleton
+                    .bone(&bone)
+                    .ok_or_else(|| SkeletonError::UnknownBone {
+                        animation: name.clone(),
+                        bone,
+                    })?;
+                frames.sort_by(|a, b| a.time.total_cmp(&b.time));
+                keys[index] = frames;
+            }
+            let duration = keys
+                .iter()
+                .filter_map(|frames| frames.last())
+                .map(|key| key.time)
+                .fold(0.0, f32::max);
+
+            skeleton.animations.insert(
+                name,
+                BoneAnimation {
+                    looping: animation.looping,
+                    duration,
+                    keys,
+                },
+            );
+        }
+
+        Ok(skeleton)
+    }
+
+    pub fn load_file(path: impl AsRef<Path>) -> Result<Self, SkeletonError> {
+        let source = std::fs::read_to_string(path)?;
+        Self::load_str(&source)
+    }
+
+    /// The index of the bone called `name`, matching its place in a pose.
+    pub fn bone(&self, name: &str) -> Option<usize> {
+        self.bones.iter().position(|bone| bone.name == name)
+    }
+
+    pub fn bone_names(&self) -> impl Iterator<Item = &str> {
+        self.bones.iter().map(|bone| bone.name.as_str())
+    }
+
+    /// The time of the last key in the animation, in seconds.
+    pub fn duration(&self, animation: &str) -> Option<f32> {
+        self.animations
+            .get(animation)
+            .map(|animation| animation.duration)
+    }
+
+    /// Every bone's transform at rest, relative to the skeleton's root.
+    pub fn rest_pose(&self) -> Vec<Transform> {
+        self.world_pose(|_| Keyframe::default())
+    }
+
+    /// Every bone's transform `time` seconds into `animation`, relative to the skeleton's root,
+    /// or `None` if there's no such animation. Looping animations wrap and others hold their
+    /// last key.
+    pub fn pose(&self, animation: &str, time: f32) -> Option<Vec<Transform>> {
+        let animation = self.animations.get(animation)?;
+        let time = if animation.looping && animation.duration > 0.0 {
+            time.rem_euclid(animation.duration)
+        } else {
+            time
+        };
+
+        Some(self.world_pose(|bone| sample(&animation.keys[bone], time)))
+    }
+
+    fn world_pose(&self, offset: impl Fn(usize) -> Keyframe) -> Vec<Transform> {
+        let mut pose: Vec<Transform> = Vec::with_capacity(self.bones.len());
+        for (index, bone) in self.bones.iter().enumerate() {
+            let offset = offset(index);
+            let local = Transform::new(
+                bone.rest.position + Vec2::new(offset.x, offset.y),
+                bone.rest.rotation + offset.rotation,
+                bone.rest.scale,
+            );
+            // Parents are listed first, so theirs is already in the pose.
+            let world = match bone.parent {
+                Some(parent) => pose[parent].then(&local),
+                None => local,
+            };
+            pose.push(world);
+        }
+
+        pose
+    }
+
+    /// Draw each bone's attachment in `pose` with the skeleton's root placed by `root`. Sprites
+    /// are looked up by name in `sprites` and attachments without one are skipped.
+    pub fn draw(
+        &self,
+        app: &mut Apparatus,
+        pose: &[Transform],
+        root: Transform,
+        sprites: &HashMap<String, Sprite>,
+    ) {
+        let origin = app.origin();
+        for (bone, transform) in self.bones.iter().zip(pose) {
+            let attachment = match &bone.attachment {
+                Some(attachment) => attachment,
+                None => continue,
+            };
+            let sprite = match sprites.get(&attachment.sprite) {
+                Some(sprite) => sprite,
+                None => continue,
+            };
+
+            let world = root.then(transform);
+            let position = world.apply(Point::new(0.0, 0.0));
+            // Transforms turn clockwise with y up, the opposite way to sprites.
+            let rotation = match origin {
+                Origin::BottomLeft => -world.rotation,
+                Origin::TopLeft => world.rotation,
+            };
+            app.draw_sprite_ex(
+                position.x(),
+                position.y(),
+                sprite,
+                rotation,
+                world.scale,
+                world.scale,
+                Vec2::new(attachment.pivot_x, attachment.pivot_y),
+            );
+        }
+    }
+}
+
+/// The offset at `time` between the keys either side of it.
+fn sample(keys: &[Keyframe], time: f32) -> Keyframe {
+    let next = keys.iter().position(|key| key.time > time);
+    match next {
+        None => keys.last().copied().unwrap_or_default(),
+        Some(0) => keys[0],
+        Some(next) => {
+            let (a, b) = (keys[next - 1], keys[next]);
+            let t = (time - a.time) / (b.time - a.time);
+            let lerp = |a: f32, b: f32| a + (b - a) * t;
+            Keyframe {
+                time,
+                x: lerp(a.x, b.x),
+                y: lerp(a.y, b.y),
+                rotation: lerp(a.rotation, b.rotation),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ARM: &str = r#"
+        [[bones]]
+        name = "body"
+        x = 10.0
+        attachment = { sprite = "body", pivot_x = 4.0 }
+
+        [[bones]]
+        name = "arm"
+        parent = "body"
+        y = 10.0
+
+        [[bones]]
+        name = "hand"
+        parent = "arm"
+        x = 5.0
+
+        [animations.wave]
+        bones.arm = [
+            { time = 1.0, rotation = 1.0 },
+            { time = 0.0, rotation = 0.0 },
+        ]
+        bones.body = [{ time = 0.0 }, { time = 2.0, y = 4.0 }]
+    "#;
+
+    fn assert_near(expected: (f32, f32), actual: Vec2) {
+        assert!(
+            (expected.0 - actual.x).abs() < 1e-4 && (expected.1 - actual.y).abs() < 1e-4,
+            "expected {:?}, got {:?}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn poses_chain_through_parents() {
+        let skeleton = Skeleton::load_str(ARM).unwrap();
+
+        assert_eq!(Some(2), skeleton.bone("hand"));
+        assert_eq!(Some(2.0), skeleton.duration("wave"));
+        assert_near((15.0, 10.0), skeleton.rest_pose()[2].position);
+        assert_eq!(0.5, skeleton.pose("wave", 0.5).unwrap()[1].rotation);
+        assert_eq!(None, skeleton.pose("run", 0.0));
+
+        // Wraps to 1s: the body has risen by 2 and the arm turned clockwise by 1 radian.
+        let pose = skeleton.pose("wave", 3.0).unwrap();
+        let (sin, cos) = 1.0f32.sin_cos();
+        assert_near((10.0, 2.0), pose[0].position);
+        assert_near((10.0 + 5.0 * cos, 12.0 - 5.0 * sin), pose[2].position);
+    }
+
+    #[test]
+    fn bones_must_refer_to_known_bones() {
+        let orphan = "[[bones]]\nname = \"arm\"\nparent = \"body\"\n";
+        assert!(matches!(
+            Skeleton::load_str(orphan),
+            Err(SkeletonError::UnknownParent { bone, parent }) if bone == "arm" && parent == "body"
+        ));
+
+        let stray = "[[bones]]\nname = \"body\"\n[animations.wave]\nbones.arm = [{ time = 0.0 }]\n";
+        assert!(matches!(
+            Skeleton::load_str(stray),
+            Err(SkeletonError::UnknownBone { bone, .. }) if bone == "arm"
+        ));
+    }
+}
@@ -1,29 +1,95 @@
+use std::collections::HashMap;
 use std::io::Cursor;
+use std::path::Path;
+use std::time::Duration;
 
+use image::codecs::gif::GifDecoder;
+use image::codecs::png::PngDecoder;
 use image::io::Reader;
+use image::{AnimationDecoder, Frames, ImageFormat};
+use serde::Deserialize;
+use thiserror::Error;
 
+use crate::color::Color;
+use crate::engine::palette::Palette;
+use crate::errors::ApparatusError;
+use crate::maths::{Rect, Vec2};
+use crate::renderer::software_2d::{Flip, Origin};
+
+/// Where a trimmed sprite's pixels sit in the image it was trimmed from, so it can be drawn in
+/// the same place. Positions are in pixels from the top left of the source image.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Trim {
+    pub x: u32,
+    pub y: u32,
+    pub source_width: u32,
+    pub source_height: u32,
+}
+
+#[derive(Clone)]
 pub struct Sprite {
     width: u32,
     height: u32,
     data: Vec<u8>,
+    opaque: bool,
+    /// Each successive half-size level, if generated.
+    mipmaps: Vec<Sprite>,
+    trim: Option<Trim>,
 }
 
 impl Sprite {
+    /// Decode an image, e.g. one embedded with `include_bytes!`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the image can't be decoded. Use `try_from_bytes` to handle bad images.
     pub fn from_bytes(bytes: &[u8]) -> Self {
+        Self::try_from_bytes(bytes).expect("sprite image should be valid")
+    }
+
+    pub fn try_from_bytes(bytes: &[u8]) -> Result<Self, ApparatusError> {
         let cursor = Cursor::new(bytes);
         let reader = Reader::new(cursor)
             .with_guessed_format()
             .expect("Cursor io never fails");
-        let image = reader.decode().unwrap(); // TODO: remove unwraps.
+        let image = reader
+            .decode()
+            .map_err(|e| ApparatusError::Asset(Box::new(e)))?;
         let image = image.to_rgba8();
 
         let (width, height) = image.dimensions();
         let data = image.to_vec();
 
+        Ok(Self::from_raw_rgba(width, height, data))
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, ApparatusError> {
+        let bytes = std::fs::read(path).map_err(|e| ApparatusError::Asset(Box::new(e)))?;
+        Self::try_from_bytes(&bytes)
+    }
+
+    /// Build a sprite from RGBA pixels in rows from the top left, e.g. a generated texture.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `data` isn't exactly `width * height * 4` bytes.
+    pub fn from_raw_rgba(width: u32, height: u32, data: Vec<u8>) -> Self {
+        assert_eq!(
+            width as usize * height as usize * 4,
+            data.len(),
+            "sprite data should hold {} x {} RGBA pixels",
+            width,
+            height
+        );
+        let opaque = data.chunks_exact(4).all(|pixel| pixel[3] == 255);
+
         Self {
             width,
             height,
             data,
+            opaque,
+            mipmaps: Vec::new(),
+            trim: None,
         }
     }
 
@@ -38,4 +104,840 @@ impl Sprite {
     pub fn data(&self) -> &Vec<u8> {
         &self.data
     }
+
+    /// The alpha of the pixel at (x, y), counting rows from the top of the image, or `None`
+    /// outside the sprite.
+    pub fn alpha(&self, x: u32, y: u32) -> Option<u8> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+
+        Some(self.data[((y * self.width + x) * 4 + 3) as usize])
+    }
+
+    /// The color of the pixel at (x, y), counting rows from the top of the image, or `None`
+    /// outside the sprite.
+    pub fn pixel(&self, x: u32, y: u32) -> Option<Color> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+
+        let offset = ((y * self.width + x) * 4) as usize;
+        let pixel = &self.data[offset..offset + 4];
+        Some(Color::rgba(pixel[0], pixel[1], pixel[2], pixel[3]))
+    }
+
+    /// Replace the pixel at (x, y), counting rows from the top of the image. Pixels outside the
+    /// sprite are ignored.
+    pub fn set_pixel(&mut self, x: u32, y: u32, color: Color) {
+        if x >= self.width || y >= self.height {
+            return;
+        }
+
+        let offset = ((y * self.width + x) * 4) as usize;
+        self.data[offset..offset + 4].copy_from_slice(&[
+            color.r(),
+            color.g(),
+            color.b(),
+            color.a(),
+        ]);
+        self.opaque &= color.a() == 255;
+        self.mipmaps.clear();
+    }
+
+    /// Replace every pixel with `color`.
+    pub fn fill(&mut self, color: Color) {
+        for pixel in self.data.chunks_exact_mut(4) {
+            pixel.copy_from_slice(&[color.r(), color.g(), color.b(), color.a()]);
+        }
+        self.opaque = color.a() == 255;
+        self.mipmaps.clear();
+    }
+
+    /// Composite `sprite` over this one with its top left corner at (x, y), e.g. to add damage
+    /// to a sprite before drawing it. Pixels falling outside this sprite are discarded.
+    pub fn blit(&mut self, sprite: &Sprite, x: i32, y: i32) {
+        for source_y in 0..sprite.height {
+            for source_x in 0..sprite.width {
+                let (target_x, target_y) = (x + source_x as i32, y + source_y as i32);
+                if target_x < 0 || target_y < 0 {
+                    continue;
+                }
+
+                let (target_x, target_y) = (target_x as u32, target_y as u32);
+                if let (Some(src), Some(dst)) = (
+                    sprite.pixel(source_x, source_y),
+                    self.pixel(target_x, target_y),
+                ) {
+                    if src.a() > 0 {
+                        self.set_pixel(target_x, target_y, Color::blend_over(src, dst));
+                    }
+                }
+            }
+        }
+    }
+
+    /// A copy of the sprite with `palette` applied, for variants drawn often enough that
+    /// recoloring on every draw isn't worth it.
+    pub fn recolored(&self, palette: &Palette) -> Sprite {
+        let data = self
+            .data
+            .chunks_exact(4)
+            .flat_map(|pixel| {
+                let color = Color::rgba(pixel[0], pixel[1], pixel[2], pixel[3]);
+                let color = palette.apply(color);
+                [color.r(), color.g(), color.b(), color.a()]
+            })
+            .collect();
+
+        Sprite::from_raw_rgba(self.width, self.height, data)
+    }
+
+    /// A copy of the sprite with each `(from, to)` color swapped, e.g. to bake one copy of a
+    /// worm sprite per team.
+    pub fn remap_colors(&self, swaps: &[(Color, Color)]) -> Sprite {
+        self.recolored(&swaps.iter().copied().collect())
+    }
+
+    /// Replace each color with its luminance, e.g. for a disabled button icon. Clone the sprite
+    /// first to keep the original, as for the other color operations.
+    pub fn grayscale(&mut self) {
+        self.map_rgb(|[r, g, b]| {
+            let luma = 0.299 * r + 0.587 * g + 0.114 * b;
+            [luma, luma, luma]
+        });
+    }
+
+    pub fn invert(&mut self) {
+        self.map_rgb(|[r, g, b]| [255.0 - r, 255.0 - g, 255.0 - b]);
+    }
+
+    /// Lighten by `amount` from 0 to 1, or darken from 0 to -1, e.g. for a hit flash.
+    pub fn adjust_brightness(&mut self, amount: f32) {
+        let offset = amount * 255.0;
+        self.map_rgb(|rgb| rgb.map(|channel| channel + offset));
+    }
+
+    /// Scale how far each channel is from mid-grey by `factor`; below 1 flattens the sprite and
+    /// above 1 adds contrast.
+    pub fn adjust_contrast(&mut self, factor: f32) {
+        let factor = factor.max(0.0);
+        self.map_rgb(|rgb| rgb.map(|channel| (channel - 128.0) * factor + 128.0));
+    }
+
+    /// Rotate every color's hue by `degrees`, keeping its saturation and value, e.g. to make
+    /// enemy variants.
+    pub fn shift_hue(&mut self, degrees: f32) {
+        self.map_rgb(|[r, g, b]| {
+            let max = r.max(g).max(b);
+            let chroma = max - r.min(g).min(b);
+            if chroma == 0.0 {
+                return [r, g, b];
+            }
+
+            let sector = if max == r {
+                ((g - b) / chroma).rem_euclid(6.0)
+            } else if max == g {
+                (b - r) / chroma + 2.0
+            } else {
+                (r - g) / chroma + 4.0
+            };
+            let sector = (sector + degrees / 60.0).rem_euclid(6.0);
+            let x = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+            let m = max - chroma;
+            let [r, g, b] = match sector as u32 {
+                0 => [chroma, x, 0.0],
+                1 => [x, chroma, 0.0],
+                2 => [0.0, chroma, x],
+                3 => [0.0, x, chroma],
+                4 => [x, 0.0, chroma],
+                _ => [chroma, 0.0, x],
+            };
+            [r + m, g + m, b + m]
+        });
+    }
+
+    /// Replace the red, green and blue of every pixel, leaving alpha untouched.
+    fn map_rgb(&mut self, f: impl Fn([f32; 3]) -> [f32; 3]) {
+        for pixel in self.data.chunks_exact_mut(4) {
+            let rgb = f([pixel[0] as f32, pixel[1] as f32, pixel[2] as f32]);
+            for (channel, value) in pixel.iter_mut().zip(rgb) {
+                *channel = value.round().clamp(0.0, 255.0) as u8;
+            }
+        }
+        self.mipmaps.clear();
+    }
+
+    /// Precompute half-size copies down to 1 x 1, so `draw_sprite_scaled` can sample the
+    /// nearest level when shrinking the sprite, e.g. for far-away cars. Changing the sprite's
+    /// pixels discards them.
+    pub fn generate_mipmaps(&mut self) {
+        self.mipmaps.clear();
+        let mut level = self.half_size();
+        while let Some(next) = level {
+            level = next.half_size();
+            self.mipmaps.push(next);
+        }
+    }
+
+    /// How many levels there are, counting the sprite itself as level 0.
+    pub fn mipmap_count(&self) -> usize {
+        self.mipmaps.len() + 1
+    }
+
+    /// The sprite at `level`, each half the size of the one before, with level 0 being the
+    /// sprite itself.
+    pub fn mipmap(&self, level: usize) -> Option<&Sprite> {
+        match level {
+            0 => Some(self),
+            _ => self.mipmaps.get(level - 1),
+        }
+    }
+
+    /// The smallest level that is still no smaller than the sprite drawn at `scale`.
+    pub(crate) fn mipmap_for_scale(&self, scale: f32) -> &Sprite {
+        if scale >= 1.0 || self.mipmaps.is_empty() {
+            return self;
+        }
+
+        let level = (1.0 / scale).log2().floor() as usize;
+        self.mipmap(level.min(self.mipmaps.len())).unwrap_or(self)
+    }
+
+    /// Average each 2 x 2 block of pixels, weighting colors by alpha so transparent pixels don't
+    /// darken edges. `None` once the sprite is 1 x 1.
+    fn half_size(&self) -> Option<Sprite> {
+        if self.width <= 1 && self.height <= 1 {
+            return None;
+        }
+
+        let (width, height) = ((self.width / 2).max(1), (self.height / 2).max(1));
+        let mut data = Vec::with_capacity((width * height * 4) as usize);
+        for y in 0..height {
+            for x in 0..width {
+                let mut sum = [0.0; 4];
+                for (sample_x, sample_y) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                    let sample_x = (x * 2 + sample_x).min(self.width - 1);
+                    let sample_y = (y * 2 + sample_y).min(self.height - 1);
+                    let color = self
+                        .pixel(sample_x, sample_y)
+                        .unwrap_or(Color::rgba(0, 0, 0, 0));
+                    let alpha = color.a() as f32;
+                    sum[0] += color.r() as f32 * alpha;
+                    sum[1] += color.g() as f32 * alpha;
+                    sum[2] += color.b() as f32 * alpha;
+                    sum[3] += alpha;
+                }
+
+                let alpha = sum[3].max(1.0);
+                data.extend([
+                    (sum[0] / alpha).round() as u8,
+                    (sum[1] / alpha).round() as u8,
+                    (sum[2] / alpha).round() as u8,
+                    (sum[3] / 4.0).round() as u8,
+                ]);
+            }
+        }
+
+        Some(Sprite::from_raw_rgba(width, height, data))
+    }
+
+    /// True when every pixel in the sprite has an alpha of 255.
+    pub fn is_opaque(&self) -> bool {
+        self.opaque
+    }
+
+    /// A copy of the `width` x `height` area whose top left corner is at (x, y), clipped to the
+    /// sprite.
+    pub fn region(&self, x: u32, y: u32, width: u32, height: u32) -> Sprite {
+        let (x, y) = (x.min(self.width), y.min(self.height));
+        let width = width.min(self.width - x);
+        let height = height.min(self.height - y);
+
+        let mut data = Vec::with_capacity((width * height * 4) as usize);
+        for row in y..y + height {
+            let start = ((row * self.width + x) * 4) as usize;
+            data.extend_from_slice(&self.data[start..start + (width * 4) as usize]);
+        }
+
+        Sprite::from_raw_rgba(width, height, data)
+    }
+
+    /// A copy with its fully transparent borders cut off, so drawing touches fewer pixels. The
+    /// copy remembers where it was cut from and is drawn in the same place as the original,
+    /// except with `draw_sprite_region` and `draw_sprite_tiled`, which use its pixels as they
+    /// are. A fully transparent sprite trims to nothing.
+    pub fn trimmed(&self) -> Sprite {
+        let visible = |x: u32, y: u32| self.alpha(x, y).is_some_and(|alpha| alpha > 0);
+        let columns: Vec<u32> = (0..self.width)
+            .filter(|&x| (0..self.height).any(|y| visible(x, y)))
+            .collect();
+        let rows: Vec<u32> = (0..self.height)
+            .filter(|&y| (0..self.width).any(|x| visible(x, y)))
+            .collect();
+        let (left, top, width, height) =
+            match (columns.first(), columns.last(), rows.first(), rows.last()) {
+                (Some(&left), Some(&right), Some(&top), Some(&bottom)) => {
+                    (left, top, right - left + 1, bottom - top + 1)
+                }
+                _ => (0, 0, 0, 0),
+            };
+
+        // Trimming a trimmed sprite keeps its place in the first source.
+        let source = self.trim.unwrap_or(Trim {
+            x: 0,
+            y: 0,
+            source_width: self.width,
+            source_height: self.height,
+        });
+        let mut sprite = self.region(left, top, width, height);
+        sprite.trim = Some(Trim {
+            x: source.x + left,
+            y: source.y + top,
+            ..source
+        });
+        sprite
+    }
+
+    /// Where the sprite was trimmed from, if it was.
+    pub fn trim(&self) -> Option<Trim> {
+        self.trim
+    }
+
+    /// The size of the image before trimming, or of the sprite itself if it wasn't trimmed.
+    pub fn source_size(&self) -> (u32, u32) {
+        match self.trim {
+            Some(trim) => (trim.source_width, trim.source_height),
+            None => (self.width, self.height),
+        }
+    }
+
+    /// The area of the source image the sprite's pixels cover, in pixels from its top left, e.g.
+    /// for a collision box that fits the visible part of the sprite.
+    pub fn trimmed_rect(&self) -> Rect {
+        let trim = self.trim.unwrap_or_default();
+        Rect::new(
+            trim.x as f32,
+            trim.y as f32,
+            self.width as f32,
+            self.height as f32,
+        )
+    }
+
+    /// How far the sprite's pixels are from the corner `draw_sprite` anchors for `origin`, in
+    /// sprite pixels with y up for a bottom left origin, when drawn mirrored by `flip`. Zero
+    /// unless the sprite was trimmed.
+    pub fn trim_offset(&self, origin: Origin, flip: Flip) -> Vec2 {
+        let trim = match self.trim {
+            Some(trim) => trim,
+            None => return Vec2::default(),
+        };
+
+        let left = trim.x;
+        let right = trim.source_width - trim.x - self.width;
+        let top = trim.y;
+        let bottom = trim.source_height - trim.y - self.height;
+        let x = if flip.contains(Flip::HORIZONTAL) {
+            right
+        } else {
+            left
+        };
+        let (top, bottom) = if flip.contains(Flip::VERTICAL) {
+            (bottom, top)
+        } else {
+            (top, bottom)
+        };
+        let y = match origin {
+            Origin::BottomLeft => bottom,
+            Origin::TopLeft => top,
+        };
+
+        Vec2::new(x as f32, y as f32)
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum AnimatedSpriteError {
+    #[error("unable to read animated sprite")]
+    Io(#[from] std::io::Error),
+    #[error("invalid animated sprite")]
+    Decode(#[from] image::ImageError),
+}
+
+/// The frames of an animated GIF or APNG and how long each is shown. Images with a single frame
+/// load as an animation of one frame.
+pub struct AnimatedSprite {
+    frames: Vec<(Sprite, Duration)>,
+}
+
+impl AnimatedSprite {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, AnimatedSpriteError> {
+        let frames = match image::guess_format(bytes)? {
+            ImageFormat::Gif => Some(GifDecoder::new(Cursor::new(bytes))?.into_frames()),
+            ImageFormat::Png => {
+                let decoder = PngDecoder::new(Cursor::new(bytes))?;
+                decoder.is_apng().then(|| decoder.apng().into_frames())
+            }
+            _ => None,
+        };
+
+        match frames {
+            Some(frames) => Self::from_frames(frames),
+            None => {
+                let image = Reader::new(Cursor::new(bytes))
+                    .with_guessed_format()?
+                    .decode()?
+                    .to_rgba8();
+                let (width, height) = image.dimensions();
+                let sprite = Sprite::from_raw_rgba(width, height, image.into_raw());
+
+                Ok(Self {
+                    frames: vec![(sprite, Duration::ZERO)],
+                })
+            }
+        }
+    }
+
+    pub fn load_file(path: impl AsRef<Path>) -> Result<Self, AnimatedSpriteError> {
+        let bytes = std::fs::read(path)?;
+        Self::from_bytes(&bytes)
+    }
+
+    fn from_frames(frames: Frames) -> Result<Self, AnimatedSpriteError> {
+        let frames = frames
+            .map(|frame| {
+                let frame = frame?;
+                let delay = Duration::from(frame.delay());
+                let image = frame.into_buffer();
+                let (width, height) = image.dimensions();
+
+                Ok((
+                    Sprite::from_raw_rgba(width, height, image.into_raw()),
+                    delay,
+                ))
+            })
+            .collect::<Result<_, image::ImageError>>()?;
+
+        Ok(Self { frames })
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn frame(&self, index: usize) -> Option<&Sprite> {
+        self.frames.get(index).map(|(sprite, _)| sprite)
+    }
+
+    /// How long the frame at `index` is shown for.
+    pub fn delay(&self, index: usize) -> Option<Duration> {
+        self.frames.get(index).map(|(_, delay)| *delay)
+    }
+
+    /// How long it takes to play every frame once.
+    pub fn duration(&self) -> Duration {
+        self.frames.iter().map(|(_, delay)| *delay).sum()
+    }
+
+    /// The frame to show `elapsed` after the animation started, either looping or holding the
+    /// last frame.
+    pub fn frame_at(&self, elapsed: Duration, looping: bool) -> &Sprite {
+        let duration = self.duration();
+        let mut remaining = if duration.is_zero() {
+            Duration::ZERO
+        } else if looping {
+            Duration::from_nanos((elapsed.as_nanos() % duration.as_nanos()) as u64)
+        } else {
+            elapsed
+        };
+
+        for (sprite, delay) in &self.frames {
+            if remaining < *delay {
+                return sprite;
+            }
+            remaining -= *delay;
+        }
+
+        &self.frames[self.frames.len() - 1].0
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum SpriteAtlasError {
+    #[error("unable to read sprite atlas")]
+    Io(#[from] std::io::Error),
+    #[error("invalid sprite atlas")]
+    Parse(#[from] toml::de::Error),
+    #[error("frame '{0}' is outside the sprite")]
+    OutOfBounds(String),
+}
+
+/// The region of an atlas's sprite holding one frame, in sprite pixels from the top left.
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct AtlasFrame {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl AtlasFrame {
+    pub fn new(x: u32, y: u32, width: u32, height: u32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct AtlasDescriptor {
+    frames: HashMap<String, AtlasFrame>,
+}
+
+/// One sprite sheet and the names of the frames on it, drawn with
+/// `Apparatus::draw_atlas_frame`. Frames can be described in TOML:
+///
+/// ```toml
+/// [frames]
+/// car = { x = 0, y = 0, width = 32, height = 32 }
+/// car_left = { x = 32, y = 0, width = 32, height = 32 }
+/// ```
+pub struct SpriteAtlas {
+    sprite: Sprite,
+    frames: HashMap<String, AtlasFrame>,
+}
+
+impl SpriteAtlas {
+    pub fn new(sprite: Sprite) -> Self {
+        Self {
+            sprite,
+            frames: HashMap::new(),
+        }
+    }
+
+    /// Load frames for `sprite` from a TOML descriptor.
+    pub fn load_str(sprite: Sprite, source: &str) -> Result<Self, SpriteAtlasError> {
+        let descriptor: AtlasDescriptor = toml::from_str(source)?;
+        let mut atlas = Self::new(sprite);
+        for (name, frame) in descriptor.frames {
+            atlas.add_frame(name, frame)?;
+        }
+
+        Ok(atlas)
+    }
+
+    pub fn load_file(sprite: Sprite, path: impl AsRef<Path>) -> Result<Self, SpriteAtlasError> {
+        let source = std::fs::read_to_string(path)?;
+        Self::load_str(sprite, &source)
+    }
+
+    pub fn with_frame(
+        mut self,
+        name: impl Into<String>,
+        frame: AtlasFrame,
+    ) -> Result<Self, SpriteAtlasError> {
+        self.add_frame(name, frame)?;
+        Ok(self)
+    }
+
+    /// Name a region of the sprite, replacing any frame with the same name.
+    pub fn add_frame(
+        &mut self,
+        name: impl Into<String>,
+        frame: AtlasFrame,
+    ) -> Result<(), SpriteAtlasError> {
+        let name = name.into();
+        let inside =
+            |start: u32, len: u32, size: u32| start.checked_add(len).is_some_and(|end| end <= size);
+        if !inside(frame.x, frame.width, self.sprite.width)
+            || !inside(frame.y, frame.height, self.sprite.height)
+        {
+            return Err(SpriteAtlasError::OutOfBounds(name));
+        }
+
+        self.frames.insert(name, frame);
+        Ok(())
+    }
+
+    pub fn sprite(&self) -> &Sprite {
+        &self.sprite
+    }
+
+    pub fn frame(&self, name: &str) -> Option<AtlasFrame> {
+        self.frames.get(name).copied()
+    }
+
+    pub fn frame_names(&self) -> impl Iterator<Item = &str> {
+        self.frames.keys().map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sheet() -> Sprite {
+        Sprite::from_raw_rgba(64, 32, vec![0; 64 * 32 * 4])
+    }
+
+    #[test]
+    fn atlas_frames_are_loaded_by_name() {
+        let atlas = SpriteAtlas::load_str(
+            sheet(),
+            "[frames]\ncar = { x = 0, y = 0, width = 32, height = 32 }\n\
+             car_left = { x = 32, y = 0, width = 32, height = 32 }",
+        )
+        .unwrap();
+
+        assert_eq!(
+            Some(AtlasFrame::new(32, 0, 32, 32)),
+            atlas.frame("car_left")
+        );
+        assert_eq!(None, atlas.frame("car_right"));
+    }
+
+    fn frame_colors() -> [[u8; 4]; 3] {
+        [[255, 0, 0, 255], [0, 255, 0, 255], [0, 0, 255, 255]]
+    }
+
+    fn assert_frames(sprite: &AnimatedSprite) {
+        assert_eq!(3, sprite.frame_count());
+        for (i, color) in frame_colors().iter().enumerate() {
+            assert_eq!(&color.repeat(4), sprite.frame(i).unwrap().data());
+            assert_eq!(Some(Duration::from_millis(100)), sprite.delay(i));
+        }
+    }
+
+    #[test]
+    fn pixels_can_be_read_and_written() {
+        let mut sprite = Sprite::from_raw_rgba(2, 2, vec![255; 16]);
+        assert!(sprite.is_opaque());
+
+        sprite.set_pixel(1, 0, Color::rgba(255, 0, 0, 128));
+        sprite.set_pixel(2, 0, Color::rgba(0, 0, 0, 0));
+
+        assert_eq!(Some(Color::rgba(255, 0, 0, 128)), sprite.pixel(1, 0));
+        assert_eq!(None, sprite.pixel(2, 0));
+        assert!(!sprite.is_opaque());
+
+        sprite.fill(Color::rgba(0, 0, 255, 255));
+        assert_eq!(Some(Color::rgba(0, 0, 255, 255)), sprite.pixel(1, 0));
+        assert!(sprite.is_opaque());
+    }
+
+    #[test]
+    fn blitting_composites_and_clips() {
+        let mut sprite = Sprite::from_raw_rgba(2, 2, [0, 0, 255, 255].repeat(4));
+        let mut damage = Sprite::from_raw_rgba(2, 2, vec![0; 16]);
+        damage.set_pixel(0, 0, Color::rgba(0, 0, 0, 255));
+        damage.set_pixel(1, 0, Color::rgba(255, 0, 0, 128));
+        damage.set_pixel(1, 1, Color::rgba(255, 0, 0, 255));
+
+        sprite.blit(&damage, -1, 0);
+
+        assert_eq!(Some(Color::rgba(128, 0, 127, 255)), sprite.pixel(0, 0));
+        assert_eq!(Some(Color::rgba(255, 0, 0, 255)), sprite.pixel(0, 1));
+        assert_eq!(Some(Color::rgba(0, 0, 255, 255)), sprite.pixel(1, 0));
+        assert_eq!(Some(Color::rgba(0, 0, 255, 255)), sprite.pixel(1, 1));
+        assert!(sprite.is_opaque());
+    }
+
+    #[test]
+    fn remapped_colors_keep_their_alpha() {
+        let sprite = Sprite::from_raw_rgba(2, 1, vec![255, 0, 0, 255, 255, 0, 0, 64]);
+
+        let team =
+            sprite.remap_colors(&[(Color::rgba(255, 0, 0, 255), Color::rgba(0, 0, 255, 255))]);
+
+        assert_eq!(&vec![0, 0, 255, 255, 0, 0, 255, 64], team.data());
+        assert!(!team.is_opaque());
+    }
+
+    #[test]
+    fn color_operations_keep_alpha() {
+        let pixels = |sprite: &Sprite| (sprite.pixel(0, 0).unwrap(), sprite.pixel(1, 0).unwrap());
+        let original = Sprite::from_raw_rgba(2, 1, vec![255, 0, 0, 255, 100, 150, 200, 128]);
+
+        let mut sprite = original.clone();
+        sprite.grayscale();
+        assert_eq!(
+            (
+                Color::rgba(76, 76, 76, 255),
+                Color::rgba(141, 141, 141, 128)
+            ),
+            pixels(&sprite)
+        );
+
+        let mut sprite = original.clone();
+        sprite.invert();
+        assert_eq!(
+            (
+                Color::rgba(0, 255, 255, 255),
+                Color::rgba(155, 105, 55, 128)
+            ),
+            pixels(&sprite)
+        );
+
+        let mut sprite = original.clone();
+        sprite.adjust_brightness(0.2);
+        sprite.adjust_contrast(2.0);
+        assert_eq!(
+            (Color::rgba(255, 0, 0, 255), Color::rgba(174, 255, 255, 128)),
+            pixels(&sprite)
+        );
+
+        let mut sprite = original;
+        sprite.shift_hue(120.0);
+        assert_eq!(
+            (Color::rgba(0, 255, 0, 255), Color::rgba(200, 100, 150, 128)),
+            pixels(&sprite)
+        );
+    }
+
+    #[test]
+    fn mipmaps_halve_down_to_one_pixel() {
+        let mut sprite = Sprite::from_raw_rgba(4, 2, vec![0; 32]);
+        sprite.set_pixel(0, 0, Color::rgba(255, 0, 0, 255));
+        sprite.set_pixel(1, 1, Color::rgba(0, 0, 255, 255));
+
+        sprite.generate_mipmaps();
+
+        assert_eq!(3, sprite.mipmap_count());
+        let half = sprite.mipmap(1).unwrap();
+        assert_eq!((2, 1), (half.width(), half.height()));
+        assert_eq!(Some(Color::rgba(128, 0, 128, 128)), half.pixel(0, 0));
+        assert_eq!((1, 1), (sprite.mipmap(2).unwrap().width(), 1));
+        assert_eq!(2, sprite.mipmap_for_scale(0.3).width());
+        assert_eq!(4, sprite.mipmap_for_scale(1.5).width());
+
+        sprite.fill(Color::rgba(0, 0, 0, 255));
+        assert_eq!(1, sprite.mipmap_count());
+    }
+
+    #[test]
+    fn raw_pixels_are_a_sprite() {
+        let sprite = Sprite::from_raw_rgba(2, 1, vec![255, 0, 0, 255, 0, 0, 255, 128]);
+
+        assert_eq!((2, 1), (sprite.width(), sprite.height()));
+        assert_eq!(Some(128), sprite.alpha(1, 0));
+        assert!(!sprite.is_opaque());
+    }
+
+    #[test]
+    #[should_panic(expected = "sprite data should hold 2 x 2 RGBA pixels")]
+    fn raw_pixels_must_fill_the_sprite() {
+        Sprite::from_raw_rgba(2, 2, vec![0; 4]);
+    }
+
+    #[test]
+    fn bad_sprites_are_an_asset_error() {
+        assert!(matches!(
+            Sprite::try_from_bytes(b"not an image"),
+            Err(ApparatusError::Asset(_))
+        ));
+        assert!(matches!(
+            Sprite::from_file("does/not/exist.png"),
+            Err(ApparatusError::Asset(_))
+        ));
+    }
+
+    #[test]
+    fn gif_frames_are_decoded_with_delays() {
+        use image::codecs::gif::GifEncoder;
+        use image::{Delay, Frame, RgbaImage};
+
+        let mut bytes = Vec::new();
+        let frames = frame_colors().map(|color| {
+            let image = RgbaImage::from_raw(2, 2, color.repeat(4)).unwrap();
+            Frame::from_parts(image, 0, 0, Delay::from_numer_denom_ms(100, 1))
+        });
+        GifEncoder::new(&mut bytes).encode_frames(frames).unwrap();
+
+        assert_frames(&AnimatedSprite::from_bytes(&bytes).unwrap());
+    }
+
+    #[test]
+    fn apng_frames_are_decoded_with_delays() {
+        let mut bytes = Vec::new();
+        let mut encoder = png::Encoder::new(&mut bytes, 2, 2);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_animated(3, 0).unwrap();
+        encoder.set_frame_delay(1, 10).unwrap();
+        let mut writer = encoder.write_header().unwrap();
+        for color in frame_colors() {
+            writer.write_image_data(&color.repeat(4)).unwrap();
+        }
+        writer.finish().unwrap();
+
+        assert_frames(&AnimatedSprite::from_bytes(&bytes).unwrap());
+    }
+
+    #[test]
+    fn frames_are_chosen_by_elapsed_time() {
+        let frames = frame_colors()
+            .into_iter()
+            .map(|color| {
+                (
+                    Sprite::from_raw_rgba(1, 1, color.to_vec()),
+                    Duration::from_millis(100),
+                )
+            })
+            .collect();
+        let sprite = AnimatedSprite { frames };
+
+        let color = |elapsed, looping| {
+            sprite
+                .frame_at(Duration::from_millis(elapsed), looping)
+                .data()[..3]
+                .to_vec()
+        };
+        assert_eq!(vec![0, 255, 0], color(150, true));
+        assert_eq!(vec![255, 0, 0], color(350, true));
+        assert_eq!(vec![0, 0, 255], color(350, false));
+    }
+
+    #[test]
+    fn frames_outside_the_sprite_are_an_error() {
+        let result = SpriteAtlas::new(sheet()).with_frame("car", AtlasFrame::new(40, 0, 32, 32));
+
+        assert!(matches!(result, Err(SpriteAtlasError::OutOfBounds(name)) if name == "car"));
+    }
+
+    #[test]
+    fn trimming_keeps_the_visible_pixels_and_where_they_were() {
+        let mut sprite = Sprite::from_raw_rgba(4, 3, vec![0; 4 * 3 * 4]);
+        sprite.set_pixel(1, 1, Color::rgba(255, 0, 0, 255));
+        sprite.set_pixel(2, 1, Color::rgba(0, 255, 0, 255));
+
+        let trimmed = sprite.trimmed();
+
+        assert_eq!((2, 1), (trimmed.width(), trimmed.height()));
+        assert_eq!(
+            Some(Trim {
+                x: 1,
+                y: 1,
+                source_width: 4,
+                source_height: 3
+            }),
+            trimmed.trim()
+        );
+        assert_eq!((4, 3), trimmed.source_size());
+        assert_eq!(Rect::new(1.0, 1.0, 2.0, 1.0), trimmed.trimmed_rect());
+        assert_eq!(
+            Vec2::new(1.0, 1.0),
+            trimmed.trim_offset(Origin::BottomLeft, Flip::NONE)
+        );
+        assert_eq!(
+            Vec2::new(1.0, 1.0),
+            trimmed.trim_offset(Origin::TopLeft, Flip::HORIZONTAL)
+        );
+        assert_eq!(trimmed.trim(), trimmed.trimmed().trim());
+        assert_eq!(
+            0,
+            Sprite::from_raw_rgba(2, 2, vec![0; 16]).trimmed().width()
+        );
+    }
 }
@@ -0,0 +1,391 @@
+use std::time::Duration;
+
+use crate::engine::apparatus::Apparatus;
+use crate::engine::sprite::{AnimatedSprite, Sprite};
+use crate::engine::vector_model::VectorModel;
+use crate::engine::Point;
+use crate::renderer::software_2d::Origin;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SortMode {
+    /// Draw in the order things were pushed.
+    Submission,
+    /// Draw sprites furthest up the screen first, so lower sprites appear in front, e.g. a
+    /// character walking in front of or behind a tree in a top-down game.
+    YSorted,
+}
+
+/// What the last `SpriteBatch::draw` did, to check whether grouping sprites helps.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct BatchStats {
+    /// Sprites, animation frames and models drawn.
+    pub sprites: usize,
+    /// How many times consecutive draws changed source sprite or model. Fewer switches means
+    /// better cache locality when blitting.
+    pub sprite_switches: usize,
+}
+
+#[derive(Copy, Clone)]
+enum Drawable<'a> {
+    Sprite {
+        sprite: &'a Sprite,
+        scale: (f32, f32),
+    },
+    Model {
+        model: &'a VectorModel,
+        rotation: f32,
+        scale: f32,
+    },
+}
+
+impl Drawable<'_> {
+    /// Identifies the sprite or model drawn, to group and count switches by.
+    fn key(&self) -> usize {
+        match self {
+            Drawable::Sprite { sprite, .. } => *sprite as *const Sprite as usize,
+            Drawable::Model { model, .. } => *model as *const VectorModel as usize,
+        }
+    }
+}
+
+#[derive(Copy, Clone)]
+struct Entry<'a> {
+    layer: i32,
+    x: f32,
+    y: f32,
+    baseline: Option<f32>,
+    drawable: Drawable<'a>,
+}
+
+impl Entry<'_> {
+    /// How far down the screen the entry is; greater values are drawn later, in front. Models
+    /// are placed by their origin, so that is their baseline.
+    fn depth(&self, origin: Origin) -> f32 {
+        let bottom = match (origin, self.drawable) {
+            (Origin::TopLeft, Drawable::Sprite { sprite, scale }) => {
+                self.y + sprite.height() as f32 * scale.1
+            }
+            _ => self.y,
+        };
+        match origin {
+            Origin::BottomLeft => -self.baseline.unwrap_or(bottom),
+            Origin::TopLeft => self.baseline.unwrap_or(bottom),
+        }
+    }
+}
+
+/// Collects sprites, animations and vector models for a frame so they can be drawn together in a
+/// chosen order, e.g. every entity in a top-down scene sorted by how far down the screen it is.
+pub struct SpriteBatch<'a> {
+    mode: SortMode,
+    group_sprites: bool,
+    layer: i32,
+    entries: Vec<Entry<'a>>,
+    stats: BatchStats,
+}
+
+impl<'a> SpriteBatch<'a> {
+    pub fn new(mode: SortMode) -> Self {
+        Self {
+            mode,
+            group_sprites: false,
+            layer: 0,
+            entries: Vec::new(),
+            stats: BatchStats::default(),
+        }
+    }
+
+    /// Draw uses of the same sprite one after another within each layer, so its pixels stay in
+    /// cache. A layer is the whole batch when drawing in submission order, or sprites sharing a
+    /// baseline when y-sorted. Sprites within a layer are then no longer drawn in submission
+    /// order, so only enable this for sprites that don't overlap or whose overlap order doesn't
+    /// matter, such as tiles and particles.
+    pub fn with_sprite_grouping(mut self) -> Self {
+        self.group_sprites = true;
+        self
+    }
+
+    /// Put sprites pushed from now on in `layer`. Lower layers are drawn first, behind higher
+    /// ones, e.g. the ground on layer 0 and characters on layer 1, and each layer is sorted by
+    /// the batch's mode. Defaults to 0.
+    pub fn set_layer(&mut self, layer: i32) {
+        self.layer = layer;
+    }
+
+    /// Queue a sprite drawn at (x, y), as for `Apparatus::draw_sprite`. Its baseline is its
+    /// bottom edge: `y` with a bottom left origin, or `y` plus its height with a top left origin.
+    pub fn push(&mut self, x: f32, y: f32, sprite: &'a Sprite) {
+        self.push_scaled(x, y, sprite, 1.0, 1.0);
+    }
+
+    /// Queue a sprite sorted by `baseline` rather than its bottom edge, e.g. for a sprite with
+    /// a drop shadow below its feet.
+    pub fn push_with_baseline(&mut self, x: f32, y: f32, baseline: f32, sprite: &'a Sprite) {
+        let drawable = Drawable::Sprite {
+            sprite,
+            scale: (1.0, 1.0),
+        };
+        self.push_entry(x, y, Some(baseline), drawable);
+    }
+
+    pub fn push_scaled(&mut self, x: f32, y: f32, sprite: &'a Sprite, scale_x: f32, scale_y: f32) {
+        let drawable = Drawable::Sprite {
+            sprite,
+            scale: (scale_x, scale_y),
+        };
+        self.push_entry(x, y, None, drawable);
+    }
+
+    /// Queue the frame of `animation` showing `elapsed` after it started, as for
+    /// `AnimatedSprite::frame_at`, drawn and sorted like a sprite.
+    pub fn push_animation(
+        &mut self,
+        x: f32,
+        y: f32,
+        animation: &'a AnimatedSprite,
+        elapsed: Duration,
+        looping: bool,
+    ) {
+        self.push(x, y, animation.frame_at(elapsed, looping));
+    }
+
+    /// Queue a vector model drawn as for `Apparatus::draw_vector_model`. Its baseline is its
+    /// position.
+    pub fn push_model(
+        &mut self,
+        position: Point,
+        rotation: f32,
+        scale: f32,
+        model: &'a VectorModel,
+    ) {
+        let drawable = Drawable::Model {
+            model,
+            rotation,
+            scale,
+        };
+        self.push_entry(position.x(), position.y(), None, drawable);
+    }
+
+    fn push_entry(&mut self, x: f32, y: f32, baseline: Option<f32>, drawable: Drawable<'a>) {
+        self.entries.push(Entry {
+            layer: self.layer,
+            x,
+            y,
+            baseline,
+            drawable,
+        });
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Statistics from the most recent draw.
+    pub fn stats(&self) -> BatchStats {
+        self.stats
+    }
+
+    /// Draw every queued sprite on screen, whether or not a camera is set, and empty the batch,
+    /// ready for the next frame.
+    pub fn draw(&mut self, app: &mut Apparatus) {
+        app.in_screen_space(|app| self.draw_entries(app));
+    }
+
+    /// Draw every queued sprite at its world position through the engine's camera, zoom and
+    /// rotation included, and empty the batch.
+    pub fn draw_in_world(&mut self, app: &mut Apparatus) {
+        self.draw_entries(app);
+    }
+
+    fn draw_entries(&mut self, app: &mut Apparatus) {
+        self.sort(app.origin());
+        self.stats = self.measure();
+        for entry in self.entries.drain(..) {
+            let (x, y) = (entry.x, entry.y);
+            match entry.drawable {
+                Drawable::Sprite {
+                    sprite,
+                    scale: (1.0, 1.0),
+                } => app.draw_sprite(x, y, sprite),
+                Drawable::Sprite { sprite, scale } => {
+                    app.draw_sprite_scaled(x, y, sprite, scale.0, scale.1)
+                }
+                Drawable::Model {
+                    model,
+                    rotation,
+                    scale,
+                } => app.draw_vector_model(Point::new(x, y), rotation, scale, model),
+            }
+        }
+    }
+
+    /// Sprites sharing a layer and baseline keep their submission order unless grouped.
+    fn sort(&mut self, origin: Origin) {
+        let key = |entry: &Entry| entry.drawable.key();
+        let (mode, group_sprites) = (self.mode, self.group_sprites);
+
+        self.entries.sort_by(|a, b| {
+            let order = a.layer.cmp(&b.layer);
+            let order = match mode {
+                SortMode::Submission => order,
+                SortMode::YSorted => {
+                    order.then_with(|| a.depth(origin).total_cmp(&b.depth(origin)))
+                }
+            };
+            if group_sprites {
+                order.then_with(|| key(a).cmp(&key(b)))
+            } else {
+                order
+            }
+        });
+    }
+
+    fn measure(&self) -> BatchStats {
+        let sprite_switches = self
+            .entries
+            .windows(2)
+            .filter(|pair| pair[0].drawable.key() != pair[1].drawable.key())
+            .count();
+
+        BatchStats {
+            sprites: self.entries.len(),
+            sprite_switches,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn positions(batch: &SpriteBatch) -> Vec<(f32, f32)> {
+        batch
+            .entries
+            .iter()
+            .map(|entry| (entry.x, entry.y))
+            .collect()
+    }
+
+    #[test]
+    fn y_sorted_batch_draws_higher_sprites_first_with_a_bottom_left_origin() {
+        let sprite = Sprite::from_raw_rgba(1, 1, vec![255; 4]);
+        let mut batch = SpriteBatch::new(SortMode::YSorted);
+        batch.push(0.0, 10.0, &sprite);
+        batch.push(1.0, 30.0, &sprite);
+        batch.push(2.0, 10.0, &sprite);
+        batch.push_with_baseline(3.0, 5.0, 20.0, &sprite);
+
+        batch.sort(Origin::BottomLeft);
+
+        assert_eq!(
+            vec![(1.0, 30.0), (3.0, 5.0), (0.0, 10.0), (2.0, 10.0)],
+            positions(&batch)
+        );
+    }
+
+    #[test]
+    fn y_sorted_batch_draws_higher_sprites_first_with_a_top_left_origin() {
+        let short = Sprite::from_raw_rgba(1, 1, vec![255; 4]);
+        let tall = Sprite::from_raw_rgba(1, 20, vec![255; 80]);
+        let mut batch = SpriteBatch::new(SortMode::YSorted);
+        batch.push(0.0, 10.0, &short);
+        batch.push(1.0, 5.0, &tall);
+        batch.push(2.0, 30.0, &short);
+        batch.push_with_baseline(3.0, 5.0, 20.0, &short);
+
+        batch.sort(Origin::TopLeft);
+
+        // Bottom edges are at 11, 25, 31 and the explicit baseline 20.
+        assert_eq!(
+            vec![(0.0, 10.0), (3.0, 5.0), (1.0, 5.0), (2.0, 30.0)],
+            positions(&batch)
+        );
+    }
+
+    #[test]
+    fn submission_batch_keeps_order() {
+        let sprite = Sprite::from_raw_rgba(1, 1, vec![255; 4]);
+        let mut batch = SpriteBatch::new(SortMode::Submission);
+        batch.push(0.0, 10.0, &sprite);
+        batch.push(1.0, 30.0, &sprite);
+
+        batch.sort(Origin::BottomLeft);
+
+        assert_eq!(vec![(0.0, 10.0), (1.0, 30.0)], positions(&batch));
+    }
+
+    #[test]
+    fn layers_are_drawn_in_order_then_sorted() {
+        let sprite = Sprite::from_raw_rgba(1, 1, vec![255; 4]);
+        let mut batch = SpriteBatch::new(SortMode::YSorted);
+        batch.set_layer(1);
+        batch.push(0.0, 30.0, &sprite);
+        batch.push(1.0, 10.0, &sprite);
+        batch.set_layer(0);
+        batch.push(2.0, 5.0, &sprite);
+        batch.push(3.0, 20.0, &sprite);
+
+        batch.sort(Origin::BottomLeft);
+
+        assert_eq!(
+            vec![(3.0, 20.0), (2.0, 5.0), (0.0, 30.0), (1.0, 10.0)],
+            positions(&batch)
+        );
+    }
+
+    #[test]
+    fn grouping_draws_same_sprite_together_within_a_row() {
+        let grass = Sprite::from_raw_rgba(1, 1, vec![0, 255, 0, 255]);
+        let water = Sprite::from_raw_rgba(1, 1, vec![0, 0, 255, 255]);
+        let mut batch = SpriteBatch::new(SortMode::YSorted).with_sprite_grouping();
+        for x in 0..4 {
+            let sprite = if x % 2 == 0 { &grass } else { &water };
+            batch.push(x as f32, 0.0, sprite);
+            batch.push(x as f32, 1.0, sprite);
+        }
+
+        batch.sort(Origin::BottomLeft);
+        let stats = batch.measure();
+
+        // Two rows, each with one switch between sprites and one between rows.
+        assert_eq!(8, stats.sprites);
+        assert!(stats.sprite_switches <= 3);
+        assert!(batch.entries[..4].iter().all(|entry| entry.y == 1.0));
+    }
+
+    #[test]
+    fn animations_and_models_are_sorted_with_sprites() {
+        let sprite = Sprite::from_raw_rgba(1, 1, vec![255; 4]);
+        let animation = AnimatedSprite::from_bytes(&sprite_png()).unwrap();
+        let model = VectorModel::default();
+        let mut batch = SpriteBatch::new(SortMode::YSorted).with_sprite_grouping();
+        batch.push(0.0, 10.0, &sprite);
+        batch.push_model(Point::new(1.0, 30.0), 0.0, 1.0, &model);
+        batch.push_animation(2.0, 20.0, &animation, Duration::ZERO, true);
+        batch.push_model(Point::new(3.0, 30.0), 0.5, 2.0, &model);
+
+        batch.sort(Origin::BottomLeft);
+
+        assert_eq!(
+            vec![(1.0, 30.0), (3.0, 30.0), (2.0, 20.0), (0.0, 10.0)],
+            positions(&batch)
+        );
+        assert_eq!(2, batch.measure().sprite_switches);
+    }
+
+    fn sprite_png() -> Vec<u8> {
+        let mut bytes = Vec::new();
+        image::RgbaImage::from_raw(1, 1, vec![255; 4])
+            .unwrap()
+            .write_to(
+                &mut std::io::Cursor::new(&mut bytes),
+                image::ImageFormat::Png,
+            )
+            .unwrap();
+        bytes
+    }
+}
@@ -0,0 +1,80 @@
+//! Draw call argument checks for strict rendering. Each check panics at the game's call site,
+//! so every function here and the draw calls using them are `#[track_caller]`.
+
+/// Panic unless every value is finite.
+#[track_caller]
+pub(crate) fn finite(call: &str, values: &[(&str, f32)]) {
+    for &(name, value) in values {
+        if !value.is_finite() {
+            panic!("{}: {} must be finite, got {}", call, name, value);
+        }
+    }
+}
+
+/// Panic unless every value is finite and zero or more, e.g. a radius.
+#[track_caller]
+pub(crate) fn non_negative(call: &str, values: &[(&str, f32)]) {
+    finite(call, values);
+    for &(name, value) in values {
+        if value < 0.0 {
+            panic!("{}: {} must not be negative, got {}", call, name, value);
+        }
+    }
+}
+
+/// Panic unless every value is finite and more than zero, e.g. a text size.
+#[track_caller]
+pub(crate) fn positive(call: &str, values: &[(&str, f32)]) {
+    finite(call, values);
+    for &(name, value) in values {
+        if value <= 0.0 {
+            panic!("{}: {} must be positive, got {}", call, name, value);
+        }
+    }
+}
+
+/// Panic unless `min` is no more than `max`, e.g. an arc's inner and outer radius.
+#[track_caller]
+pub(crate) fn ordered(call: &str, min: (&str, f32), max: (&str, f32)) {
+    if min.1 > max.1 {
+        panic!(
+            "{}: {} must not be more than {}, got {} > {}",
+            call, min.0, max.0, min.1, max.1
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid_arguments_pass() {
+        finite("draw", &[("x", -1.0), ("y", 0.0)]);
+        non_negative("draw_filled_circle", &[("radius", 0.0)]);
+        positive("draw_string", &[("size", 12.0)]);
+        ordered(
+            "draw_filled_arc",
+            ("inner_radius", 1.0),
+            ("outer_radius", 1.0),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "draw: y must be finite, got NaN")]
+    fn nan_coordinates_panic() {
+        finite("draw", &[("x", 0.0), ("y", f32::NAN)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "draw_filled_circle: radius must not be negative, got -1")]
+    fn negative_radius_panics() {
+        non_negative("draw_filled_circle", &[("radius", -1.0)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "draw_string: size must be positive, got 0")]
+    fn zero_text_size_panics() {
+        positive("draw_string", &[("size", 0.0)]);
+    }
+}
@@ -0,0 +1,143 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use thiserror::Error;
+
+use crate::color::Color;
+use crate::engine::sprite::Sprite;
+
+#[derive(Debug, Error)]
+pub enum TextArtError {
+    #[error("unable to read text art")]
+    Io(#[from] std::io::Error),
+    #[error("no color for '{character}' on line {line}, column {column}")]
+    UnknownCharacter {
+        character: char,
+        line: usize,
+        column: usize,
+    },
+    #[error("text art has no pixels")]
+    Empty,
+}
+
+/// Builds sprites from text art, one character per pixel, so small examples can define their
+/// graphics inline. With `r` mapped to red, this is a heart:
+///
+/// ```text
+/// .r.r.
+/// rrrrr
+/// .rrr.
+/// ..r..
+/// ```
+///
+/// Lines are trimmed and blank lines skipped, so art can be indented to match the code around
+/// it. Shorter lines are padded with transparent pixels. `.` is transparent unless mapped to a
+/// color.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextArt {
+    colors: HashMap<char, Color>,
+}
+
+impl Default for TextArt {
+    fn default() -> Self {
+        let mut colors = HashMap::new();
+        colors.insert('.', Color::rgba(0, 0, 0, 0));
+
+        Self { colors }
+    }
+}
+
+impl TextArt {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Draw `character` as `color`.
+    pub fn with_color(mut self, character: char, color: Color) -> Self {
+        self.set_color(character, color);
+        self
+    }
+
+    pub fn set_color(&mut self, character: char, color: Color) {
+        self.colors.insert(character, color);
+    }
+
+    pub fn load_str(&self, source: &str) -> Result<Sprite, TextArtError> {
+        let mut rows = Vec::new();
+        for (i, line) in source.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let row = line
+                .chars()
+                .enumerate()
+                .map(|(j, character)| {
+                    self.colors
+                        .get(&character)
+                        .copied()
+                        .ok_or(TextArtError::UnknownCharacter {
+                            character,
+                            line: i + 1,
+                            column: j + 1,
+                        })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            rows.push(row);
+        }
+
+        let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+        if width == 0 {
+            return Err(TextArtError::Empty);
+        }
+
+        let mut data = Vec::with_capacity(width * rows.len() * 4);
+        for row in &rows {
+            for x in 0..width {
+                let color = row.get(x).copied().unwrap_or(Color::rgba(0, 0, 0, 0));
+                data.extend_from_slice(&[color.r(), color.g(), color.b(), color.a()]);
+            }
+        }
+
+        Ok(Sprite::from_raw_rgba(width as u32, rows.len() as u32, data))
+    }
+
+    pub fn load_file(&self, path: impl AsRef<Path>) -> Result<Sprite, TextArtError> {
+        let source = std::fs::read_to_string(path)?;
+        self.load_str(&source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color;
+
+    #[test]
+    fn pads_short_rows_with_transparent_pixels() {
+        let sprite = TextArt::new()
+            .with_color('#', color::css::RED)
+            .load_str("\n  ##\n  #\n")
+            .unwrap();
+
+        assert_eq!((2, 2), (sprite.width(), sprite.height()));
+        assert_eq!(Some(255), sprite.alpha(0, 1));
+        assert_eq!(Some(0), sprite.alpha(1, 1));
+        assert_eq!(&[255, 0, 0, 255], &sprite.data()[0..4]);
+    }
+
+    #[test]
+    fn unmapped_characters_are_an_error() {
+        let result = TextArt::new().load_str("..\n.x");
+
+        assert!(matches!(
+            result,
+            Err(TextArtError::UnknownCharacter {
+                character: 'x',
+                line: 2,
+                column: 2
+            })
+        ));
+    }
+}
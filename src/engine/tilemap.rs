@@ -0,0 +1,259 @@
+/// A grid of tiles addressed by (x, y), where y increases upwards like the rest of the engine.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tilemap<T> {
+    width: u32,
+    height: u32,
+    tiles: Vec<T>,
+}
+
+impl<T: Clone> Tilemap<T> {
+    pub fn new(width: u32, height: u32, fill: T) -> Self {
+        Self {
+            width,
+            height,
+            tiles: vec![fill; width as usize * height as usize],
+        }
+    }
+}
+
+impl<T> Tilemap<T> {
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn in_bounds(&self, x: i32, y: i32) -> bool {
+        x >= 0 && y >= 0 && (x as u32) < self.width && (y as u32) < self.height
+    }
+
+    pub fn get(&self, x: i32, y: i32) -> Option<&T> {
+        self.index(x, y).map(|index| &self.tiles[index])
+    }
+
+    pub fn get_mut(&mut self, x: i32, y: i32) -> Option<&mut T> {
+        self.index(x, y).map(move |index| &mut self.tiles[index])
+    }
+
+    /// Set the tile at (x, y). Positions outside the map are ignored.
+    pub fn set(&mut self, x: i32, y: i32, tile: T) {
+        if let Some(index) = self.index(x, y) {
+            self.tiles[index] = tile;
+        }
+    }
+
+    /// Iterate over every tile as (x, y, tile), row by row from the bottom.
+    pub fn iter(&self) -> impl Iterator<Item = (i32, i32, &T)> {
+        let width = self.width as usize;
+        self.tiles
+            .iter()
+            .enumerate()
+            .map(move |(i, tile)| ((i % width) as i32, (i / width) as i32, tile))
+    }
+
+    /// Build a new map of the same size by applying `f` to every tile.
+    pub fn map<U>(&self, mut f: impl FnMut(i32, i32, &T) -> U) -> Tilemap<U> {
+        Tilemap {
+            width: self.width,
+            height: self.height,
+            tiles: self.iter().map(|(x, y, tile)| f(x, y, tile)).collect(),
+        }
+    }
+
+    /// Which neighbours of (x, y) satisfy `matches`, as a bitmask of the `neighbour` constants.
+    /// Positions outside the map count as matching when `edges_match` is true, so terrain
+    /// touching the edge of the map doesn't grow a border.
+    ///
+    /// In `AutotileMode::EightBit`, a corner only counts when both of its adjacent edges match,
+    /// which reduces the 256 possible masks to the 47 distinct tiles of a "blob" tileset.
+    pub fn neighbour_mask(
+        &self,
+        x: i32,
+        y: i32,
+        mode: AutotileMode,
+        edges_match: bool,
+        matches: impl Fn(&T) -> bool,
+    ) -> u8 {
+        let is_match = |dx: i32, dy: i32| match self.get(x + dx, y + dy) {
+            Some(tile) => matches(tile),
+            None => edges_match,
+        };
+
+        let mut mask = 0;
+        for (bit, dx, dy) in [
+            (neighbour::NORTH, 0, 1),
+            (neighbour::EAST, 1, 0),
+            (neighbour::SOUTH, 0, -1),
+            (neighbour::WEST, -1, 0),
+        ] {
+            if is_match(dx, dy) {
+                mask |= bit;
+            }
+        }
+
+        if mode == AutotileMode::EightBit {
+            for (bit, dx, dy) in [
+                (neighbour::NORTH_EAST, 1, 1),
+                (neighbour::SOUTH_EAST, 1, -1),
+                (neighbour::SOUTH_WEST, -1, -1),
+                (neighbour::NORTH_WEST, -1, 1),
+            ] {
+                if is_match(dx, dy) {
+                    mask |= bit;
+                }
+            }
+            mask = reduce_corners(mask);
+        }
+
+        mask
+    }
+
+    /// Choose a tile from a tileset template for every tile that satisfies `is_terrain`.
+    ///
+    /// The result holds the index into the template: in `AutotileMode::FourBit` this is the
+    /// neighbour mask itself (16 tiles); in `AutotileMode::EightBit` it is `blob_index` of the
+    /// mask (47 tiles). Tiles that aren't terrain are `None`.
+    pub fn autotile(
+        &self,
+        mode: AutotileMode,
+        edges_match: bool,
+        is_terrain: impl Fn(&T) -> bool,
+    ) -> Tilemap<Option<u8>> {
+        self.map(|x, y, tile| {
+            if !is_terrain(tile) {
+                return None;
+            }
+
+            let mask = self.neighbour_mask(x, y, mode, edges_match, &is_terrain);
+            Some(match mode {
+                AutotileMode::FourBit => mask,
+                AutotileMode::EightBit => blob_index(mask),
+            })
+        })
+    }
+
+    fn index(&self, x: i32, y: i32) -> Option<usize> {
+        self.in_bounds(x, y)
+            .then(|| y as usize * self.width as usize + x as usize)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AutotileMode {
+    /// Consider the four edge neighbours, giving 16 tile variations.
+    FourBit,
+    /// Consider all eight neighbours, giving 47 tile variations.
+    EightBit,
+}
+
+pub mod neighbour {
+    pub const NORTH: u8 = 1;
+    pub const EAST: u8 = 2;
+    pub const SOUTH: u8 = 4;
+    pub const WEST: u8 = 8;
+    pub const NORTH_EAST: u8 = 16;
+    pub const SOUTH_EAST: u8 = 32;
+    pub const SOUTH_WEST: u8 = 64;
+    pub const NORTH_WEST: u8 = 128;
+}
+
+/// Clear corner bits whose adjacent edges are not both set.
+pub fn reduce_corners(mask: u8) -> u8 {
+    use neighbour::*;
+
+    let mut reduced = mask & (NORTH | EAST | SOUTH | WEST);
+    for (corner, a, b) in [
+        (NORTH_EAST, NORTH, EAST),
+        (SOUTH_EAST, SOUTH, EAST),
+        (SOUTH_WEST, SOUTH, WEST),
+        (NORTH_WEST, NORTH, WEST),
+    ] {
+        if mask & corner != 0 && mask & a != 0 && mask & b != 0 {
+            reduced |= corner;
+        }
+    }
+
+    reduced
+}
+
+/// The position (0..47) of an eight bit neighbour mask amongst all reduced masks in ascending
+/// order, i.e. the tile to use from a 47 tile blob template laid out in that order.
+pub fn blob_index(mask: u8) -> u8 {
+    let mask = reduce_corners(mask);
+    (0..mask).filter(|&m| reduce_corners(m) == m).count() as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    fn plus_shape() -> Tilemap<bool> {
+        let mut map = Tilemap::new(3, 3, false);
+        for (x, y) in [(1, 0), (0, 1), (1, 1), (2, 1), (1, 2)] {
+            map.set(x, y, true);
+        }
+        map
+    }
+
+    #[test]
+    fn positions_outside_map_are_ignored() {
+        let mut map = Tilemap::new(2, 2, 0);
+        map.set(5, 5, 1);
+
+        assert_eq!(None, map.get(5, 5));
+        assert!(map.iter().all(|(_, _, tile)| *tile == 0));
+    }
+
+    #[test]
+    fn four_bit_mask_records_matching_edges() {
+        let map = plus_shape();
+
+        let center = map.neighbour_mask(1, 1, AutotileMode::FourBit, false, |t| *t);
+        let top = map.neighbour_mask(1, 2, AutotileMode::FourBit, false, |t| *t);
+
+        assert_eq!(15, center);
+        assert_eq!(neighbour::SOUTH, top);
+    }
+
+    #[test]
+    fn edges_can_count_as_matching() {
+        let map = Tilemap::new(1, 1, true);
+
+        assert_eq!(
+            255,
+            map.neighbour_mask(0, 0, AutotileMode::EightBit, true, |t| *t)
+        );
+    }
+
+    #[test]
+    fn corners_without_both_edges_are_ignored() {
+        use neighbour::*;
+
+        assert_eq!(NORTH, reduce_corners(NORTH | NORTH_EAST));
+        assert_eq!(
+            NORTH | EAST | NORTH_EAST,
+            reduce_corners(NORTH | EAST | NORTH_EAST)
+        );
+    }
+
+    #[test]
+    fn eight_bit_masks_reduce_to_47_blob_tiles() {
+        let indices: HashSet<u8> = (0..=255).map(blob_index).collect();
+
+        assert_eq!(47, indices.len());
+        assert_eq!(Some(&46), indices.iter().max());
+    }
+
+    #[test]
+    fn autotile_only_assigns_terrain_tiles() {
+        let tiles = plus_shape().autotile(AutotileMode::FourBit, false, |t| *t);
+
+        assert_eq!(Some(&None), tiles.get(0, 0));
+        assert_eq!(Some(&Some(15)), tiles.get(1, 1));
+        assert_eq!(Some(&Some(neighbour::EAST)), tiles.get(0, 1));
+    }
+}
@@ -0,0 +1,263 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+#[derive(Clone, Debug, PartialEq)]
+enum Step<A> {
+    Wait(f32),
+    Action { action: A, cosmetic: bool },
+    Over { action: A, duration: f32 },
+}
+
+/// Steps played one after another on a `Timeline`, e.g. walking a character to a door while
+/// another track pans the camera.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Track<A> {
+    steps: VecDeque<Step<A>>,
+}
+
+impl<A> Default for Track<A> {
+    fn default() -> Self {
+        Self {
+            steps: VecDeque::new(),
+        }
+    }
+}
+
+impl<A> Track<A> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Do nothing for `duration`.
+    pub fn wait(mut self, duration: Duration) -> Self {
+        self.steps.push_back(Step::Wait(duration.as_secs_f32()));
+        self
+    }
+
+    /// Fire `action` once, e.g. to change game state. It is still fired if the timeline is
+    /// skipped.
+    pub fn action(mut self, action: A) -> Self {
+        self.steps.push_back(Step::Action {
+            action,
+            cosmetic: false,
+        });
+        self
+    }
+
+    /// Fire `action` once, e.g. to play a sound or show text. It is dropped if the timeline is
+    /// skipped.
+    pub fn cosmetic(mut self, action: A) -> Self {
+        self.steps.push_back(Step::Action {
+            action,
+            cosmetic: true,
+        });
+        self
+    }
+
+    /// Report `action` every frame for `duration` with its progress, e.g. to move an entity.
+    pub fn over(mut self, action: A, duration: Duration) -> Self {
+        self.steps.push_back(Step::Over {
+            action,
+            duration: duration.as_secs_f32(),
+        });
+        self
+    }
+}
+
+/// An action that is due, for the game to carry out.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cue<A> {
+    /// The index of the track it belongs to.
+    pub track: usize,
+    pub action: A,
+    /// How far through the action is, from 0 to 1. Actions fired once are always 1.
+    pub progress: f32,
+}
+
+#[derive(Clone, Debug)]
+struct TrackState<A> {
+    steps: VecDeque<Step<A>>,
+    elapsed: f32,
+}
+
+/// Plays tracks of scripted actions in parallel, driven by the frame time, so a cutscene can be
+/// written as data rather than timers. Actions are the game's own type, e.g. an enum with
+/// variants to move an entity, show text, play a sound or run a `CameraSequence`; `update`
+/// hands back the cues that are due each frame.
+#[derive(Clone, Debug)]
+pub struct Timeline<A> {
+    tracks: Vec<TrackState<A>>,
+}
+
+impl<A> Default for Timeline<A> {
+    fn default() -> Self {
+        Self { tracks: Vec::new() }
+    }
+}
+
+impl<A: Clone> Timeline<A> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a track, which starts playing with the others.
+    pub fn with_track(mut self, track: Track<A>) -> Self {
+        self.tracks.push(TrackState {
+            steps: track.steps,
+            elapsed: 0.0,
+        });
+        self
+    }
+
+    /// Whether every track has played all of its steps.
+    pub fn is_finished(&self) -> bool {
+        self.tracks.iter().all(|track| track.steps.is_empty())
+    }
+
+    /// Advance every track by `dt`, e.g. `Apparatus::elapsed_time`, returning the cues that are
+    /// due in track order.
+    pub fn update(&mut self, dt: Duration) -> Vec<Cue<A>> {
+        let mut cues = Vec::new();
+
+        for (index, track) in self.tracks.iter_mut().enumerate() {
+            let mut remaining = dt.as_secs_f32();
+
+            while let Some(step) = track.steps.front() {
+                match step {
+                    Step::Wait(duration) => {
+                        if track.elapsed + remaining < *duration {
+                            track.elapsed += remaining;
+                            break;
+                        }
+                        remaining -= duration - track.elapsed;
+                    }
+                    Step::Action { action, .. } => cues.push(Cue {
+                        track: index,
+                        action: action.clone(),
+                        progress: 1.0,
+                    }),
+                    Step::Over { action, duration } => {
+                        if track.elapsed + remaining < *duration {
+                            track.elapsed += remaining;
+                            cues.push(Cue {
+                                track: index,
+                                action: action.clone(),
+                                progress: track.elapsed / duration,
+                            });
+                            break;
+                        }
+                        remaining -= duration - track.elapsed;
+                        cues.push(Cue {
+                            track: index,
+                            action: action.clone(),
+                            progress: 1.0,
+                        });
+                    }
+                }
+
+                track.steps.pop_front();
+                track.elapsed = 0.0;
+            }
+        }
+
+        cues
+    }
+
+    /// Finish every track straight away, e.g. when the player skips a cutscene. Actions are
+    /// returned completed so the game ends up as if the timeline had played; cosmetic actions
+    /// are dropped.
+    pub fn skip(&mut self) -> Vec<Cue<A>> {
+        let mut cues = Vec::new();
+
+        for (index, track) in self.tracks.iter_mut().enumerate() {
+            for step in track.steps.drain(..) {
+                match step {
+                    Step::Action {
+                        action,
+                        cosmetic: false,
+                    }
+                    | Step::Over { action, .. } => cues.push(Cue {
+                        track: index,
+                        action,
+                        progress: 1.0,
+                    }),
+                    Step::Wait(_) | Step::Action { .. } => {}
+                }
+            }
+            track.elapsed = 0.0;
+        }
+
+        cues
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Debug, PartialEq)]
+    enum Action {
+        Walk,
+        Say(&'static str),
+        OpenDoor,
+    }
+
+    fn cutscene() -> Timeline<Action> {
+        Timeline::new()
+            .with_track(
+                Track::new()
+                    .over(Action::Walk, Duration::from_secs(2))
+                    .action(Action::OpenDoor),
+            )
+            .with_track(
+                Track::new()
+                    .wait(Duration::from_secs(1))
+                    .cosmetic(Action::Say("hello")),
+            )
+    }
+
+    fn cue(track: usize, action: Action, progress: f32) -> Cue<Action> {
+        Cue {
+            track,
+            action,
+            progress,
+        }
+    }
+
+    #[test]
+    fn tracks_play_in_parallel() {
+        let mut timeline = cutscene();
+
+        assert_eq!(
+            vec![cue(0, Action::Walk, 0.25)],
+            timeline.update(Duration::from_millis(500))
+        );
+        assert_eq!(
+            vec![
+                cue(0, Action::Walk, 0.75),
+                cue(1, Action::Say("hello"), 1.0)
+            ],
+            timeline.update(Duration::from_secs(1))
+        );
+        assert!(!timeline.is_finished());
+
+        assert_eq!(
+            vec![cue(0, Action::Walk, 1.0), cue(0, Action::OpenDoor, 1.0)],
+            timeline.update(Duration::from_secs(1))
+        );
+        assert!(timeline.is_finished());
+    }
+
+    #[test]
+    fn skipping_completes_actions_and_drops_cosmetics() {
+        let mut timeline = cutscene();
+        timeline.update(Duration::from_millis(500));
+
+        assert_eq!(
+            vec![cue(0, Action::Walk, 1.0), cue(0, Action::OpenDoor, 1.0)],
+            timeline.skip()
+        );
+        assert!(timeline.is_finished());
+        assert!(timeline.update(Duration::from_secs(1)).is_empty());
+    }
+}
@@ -0,0 +1,330 @@
+use std::path::Path;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum TrackError {
+    #[error("unable to read track")]
+    Io(#[from] std::io::Error),
+    #[error("invalid track definition")]
+    Deserialize(#[from] toml::de::Error),
+    #[error("track has no length")]
+    Empty,
+}
+
+/// A sprite placed beside the road.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct Scenery {
+    /// Name of the sprite, looked up by the game.
+    pub sprite: String,
+    /// Distance from the start of the segment.
+    #[serde(default)]
+    pub offset: f32,
+    /// Sideways position; negative is left of the road and positive right, with ±1 at its edges.
+    pub side: f32,
+}
+
+/// A stretch of road with constant curvature.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct TrackSegment {
+    /// Negative bends left, positive bends right.
+    #[serde(default)]
+    pub curvature: f32,
+    pub length: f32,
+    /// Change in elevation over the segment; positive climbs.
+    #[serde(default)]
+    pub hill: f32,
+    /// Whether the end of this segment is a checkpoint that must be passed to complete a lap.
+    #[serde(default)]
+    pub checkpoint: bool,
+    #[serde(default)]
+    pub scenery: Vec<Scenery>,
+}
+
+/// A looping race track, loaded from TOML:
+///
+/// ```toml
+/// name = "Oval"
+///
+/// [[segments]]
+/// length = 200.0
+///
+/// [[segments]]
+/// curvature = 1.0
+/// length = 200.0
+/// checkpoint = true
+/// scenery = [{ sprite = "tree", offset = 50.0, side = -1.5 }]
+/// ```
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct Track {
+    #[serde(default)]
+    pub name: String,
+    pub segments: Vec<TrackSegment>,
+}
+
+impl Track {
+    pub fn from_segments(name: impl Into<String>, segments: Vec<TrackSegment>) -> Self {
+        Self {
+            name: name.into(),
+            segments,
+        }
+    }
+
+    pub fn load_str(source: &str) -> Result<Self, TrackError> {
+        let track: Track = toml::from_str(source)?;
+        if track.length() <= 0.0 {
+            return Err(TrackError::Empty);
+        }
+
+        Ok(track)
+    }
+
+    pub fn load_file(path: impl AsRef<Path>) -> Result<Self, TrackError> {
+        let source = std::fs::read_to_string(path)?;
+        Self::load_str(&source)
+    }
+
+    /// Length of one lap.
+    pub fn length(&self) -> f32 {
+        self.segments.iter().map(|segment| segment.length).sum()
+    }
+
+    /// The segment `distance` along the track, its index and the distance into it. Distances
+    /// wrap around the lap.
+    pub fn segment_at(&self, distance: f32) -> Option<(usize, &TrackSegment, f32)> {
+        let length = self.length();
+        if length <= 0.0 {
+            return None;
+        }
+
+        let mut remaining = distance.rem_euclid(length);
+        for (index, segment) in self.segments.iter().enumerate() {
+            if remaining < segment.length {
+                return Some((index, segment, remaining));
+            }
+            remaining -= segment.length;
+        }
+
+        self.segments
+            .iter()
+            .enumerate()
+            .rfind(|(_, segment)| segment.length > 0.0)
+            .map(|(index, segment)| (index, segment, segment.length))
+    }
+
+    /// Distances of the checkpoints along the lap, in order.
+    pub fn checkpoints(&self) -> Vec<f32> {
+        let mut end = 0.0;
+        let mut checkpoints = Vec::new();
+        for segment in &self.segments {
+            end += segment.length;
+            if segment.checkpoint && end < self.length() {
+                checkpoints.push(end);
+            }
+        }
+
+        checkpoints
+    }
+
+    /// Elevation `distance` along the track relative to the start line, interpolated smoothly
+    /// through each segment's hill.
+    pub fn elevation(&self, distance: f32) -> f32 {
+        let (index, segment, into) = match self.segment_at(distance) {
+            Some(found) => found,
+            None => return 0.0,
+        };
+
+        let before: f32 = self.segments[..index].iter().map(|s| s.hill).sum();
+        let t = if segment.length > 0.0 {
+            into / segment.length
+        } else {
+            0.0
+        };
+
+        // Ease in and out so the road doesn't kink between segments.
+        before + segment.hill * t * t * (3.0 - 2.0 * t)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LapEvent {
+    /// Checkpoint `index` was passed `split` into the lap.
+    Checkpoint { index: usize, split: Duration },
+    /// A lap was completed.
+    Lap { time: Duration, best: bool },
+}
+
+/// Times laps of a track. A lap only counts once every checkpoint has been passed in order, so
+/// reversing over the start line doesn't complete one.
+#[derive(Clone, Debug)]
+pub struct LapTimer {
+    length: f32,
+    checkpoints: Vec<f32>,
+    next_checkpoint: usize,
+    last_distance: f32,
+    current: Duration,
+    splits: Vec<Duration>,
+    laps: Vec<Duration>,
+    best: Option<Duration>,
+}
+
+impl LapTimer {
+    pub fn new(track: &Track) -> Self {
+        Self {
+            length: track.length(),
+            checkpoints: track.checkpoints(),
+            next_checkpoint: 0,
+            last_distance: 0.0,
+            current: Duration::ZERO,
+            splits: Vec::new(),
+            laps: Vec::new(),
+            best: None,
+        }
+    }
+
+    /// Advance the clock and check progress. `distance` is the position along the lap, wrapping
+    /// back to 0 at the start line.
+    pub fn update(&mut self, distance: f32, dt: Duration) -> Option<LapEvent> {
+        self.current += dt;
+        let last = std::mem::replace(&mut self.last_distance, distance);
+
+        let moved_forward = distance >= last && distance - last < self.length / 2.0;
+        if let Some(&checkpoint) = self.checkpoints.get(self.next_checkpoint) {
+            if moved_forward && last < checkpoint && distance >= checkpoint {
+                let index = self.next_checkpoint;
+                self.next_checkpoint += 1;
+                self.splits.push(self.current);
+                return Some(LapEvent::Checkpoint {
+                    index,
+                    split: self.current,
+                });
+            }
+        }
+
+        // Crossing the start line forwards jumps from the end of the lap back to the start.
+        let crossed_line = last - distance > self.length / 2.0;
+        if crossed_line && self.next_checkpoint == self.checkpoints.len() {
+            let time = std::mem::take(&mut self.current);
+            let best = self.best.is_none_or(|best| time < best);
+            if best {
+                self.best = Some(time);
+            }
+            self.laps.push(time);
+            self.splits.clear();
+            self.next_checkpoint = 0;
+            return Some(LapEvent::Lap { time, best });
+        }
+
+        None
+    }
+
+    pub fn current_lap_time(&self) -> Duration {
+        self.current
+    }
+
+    /// Times of the checkpoints passed so far this lap.
+    pub fn splits(&self) -> &[Duration] {
+        &self.splits
+    }
+
+    /// Completed lap times, oldest first.
+    pub fn lap_times(&self) -> &[Duration] {
+        &self.laps
+    }
+
+    pub fn best_lap(&self) -> Option<Duration> {
+        self.best
+    }
+
+    /// Forget all lap times and restart the current lap from the start line.
+    pub fn reset(&mut self) {
+        self.next_checkpoint = 0;
+        self.last_distance = 0.0;
+        self.current = Duration::ZERO;
+        self.splits.clear();
+        self.laps.clear();
+        self.best = None;
+    }
+}
+
+/// Format a lap time as `minutes:seconds.millis`, e.g. `01:05.250`.
+pub fn format_lap_time(time: Duration) -> String {
+    let minutes = time.as_secs() / 60;
+    let seconds = time.as_secs() % 60;
+    format!("{:02}:{:02}.{:03}", minutes, seconds, time.subsec_millis())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRACK: &str = r#"
+        name = "Test"
+
+        [[segments]]
+        length = 100.0
+        checkpoint = true
+
+        [[segments]]
+        curvature = 1.0
+        length = 100.0
+        hill = 10.0
+        scenery = [{ sprite = "tree", side = -1.5 }]
+    "#;
+
+    fn step(timer: &mut LapTimer, distance: f32) -> Option<LapEvent> {
+        timer.update(distance, Duration::from_secs(1))
+    }
+
+    #[test]
+    fn load_track_from_toml() {
+        let track = Track::load_str(TRACK).unwrap();
+
+        assert_eq!(200.0, track.length());
+        assert_eq!(vec![100.0], track.checkpoints());
+        assert_eq!("tree", track.segments[1].scenery[0].sprite);
+        assert_eq!(Some(1), track.segment_at(150.0).map(|(index, ..)| index));
+        assert_eq!(Some(50.0), track.segment_at(250.0).map(|(_, _, into)| into));
+        assert_eq!(10.0, track.elevation(199.999).round());
+        assert!(matches!(
+            Track::load_str("segments = []"),
+            Err(TrackError::Empty)
+        ));
+    }
+
+    #[test]
+    fn lap_requires_passing_checkpoints() {
+        let track = Track::load_str(TRACK).unwrap();
+        let mut timer = LapTimer::new(&track);
+
+        // Reversing back and forth over the start line doesn't count.
+        assert_eq!(None, step(&mut timer, 5.0));
+        assert_eq!(None, step(&mut timer, 195.0));
+        assert_eq!(None, step(&mut timer, 5.0));
+        assert_eq!(None, step(&mut timer, 60.0));
+
+        assert_eq!(
+            Some(LapEvent::Checkpoint {
+                index: 0,
+                split: Duration::from_secs(5)
+            }),
+            step(&mut timer, 120.0)
+        );
+        assert_eq!(None, step(&mut timer, 190.0));
+        assert_eq!(
+            Some(LapEvent::Lap {
+                time: Duration::from_secs(7),
+                best: true
+            }),
+            step(&mut timer, 5.0)
+        );
+        assert_eq!(Some(Duration::from_secs(7)), timer.best_lap());
+    }
+
+    #[test]
+    fn lap_time_is_formatted_with_millis() {
+        assert_eq!("01:05.250", format_lap_time(Duration::from_millis(65_250)));
+    }
+}
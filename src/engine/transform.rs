@@ -0,0 +1,305 @@
+use crate::engine::Point;
+use crate::maths::Vec2;
+
+/// A position, rotation (radians, clockwise as with `draw_wireframe_model`) and uniform scale.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transform {
+    pub position: Vec2,
+    pub rotation: f32,
+    pub scale: f32,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::new(Vec2::new(0.0, 0.0), 0.0, 1.0)
+    }
+}
+
+impl Transform {
+    pub fn new(position: Vec2, rotation: f32, scale: f32) -> Self {
+        Self {
+            position,
+            rotation,
+            scale,
+        }
+    }
+
+    pub fn from_position(x: f32, y: f32) -> Self {
+        Self::new(Vec2::new(x, y), 0.0, 1.0)
+    }
+
+    /// Transform a point from this transform's local space into its parent's space.
+    pub fn apply(&self, point: Point) -> Point {
+        let (x, y) = (point.x() * self.scale, point.y() * self.scale);
+        let (sin, cos) = self.rotation.sin_cos();
+        let (x, y) = (x * cos + y * sin, y * cos - x * sin);
+
+        Point::new(x + self.position.x, y + self.position.y)
+    }
+
+    /// Combine with a transform that is local to this one, giving the child's transform in this
+    /// transform's parent space.
+    pub fn then(&self, child: &Transform) -> Transform {
+        let position = self.apply(Point::new(child.position.x, child.position.y));
+
+        Transform::new(
+            Vec2::new(position.x(), position.y()),
+            self.rotation + child.rotation,
+            self.scale * child.scale,
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct NodeId(usize);
+
+struct Node {
+    local: Transform,
+    parent: Option<NodeId>,
+    children: Vec<NodeId>,
+}
+
+/// Entities with local transforms attached to parents, e.g. a turret attached to a vehicle.
+/// World transforms are resolved on demand, typically when drawing.
+#[derive(Default)]
+pub struct TransformTree {
+    nodes: Vec<Option<Node>>,
+    free: Vec<usize>,
+}
+
+impl TransformTree {
+    /// Add a node with a transform relative to `parent`, or to the world when there is no parent.
+    pub fn add(&mut self, local: Transform, parent: Option<NodeId>) -> NodeId {
+        let node = Node {
+            local,
+            parent: None,
+            children: Vec::new(),
+        };
+
+        let id = match self.free.pop() {
+            Some(index) => {
+                self.nodes[index] = Some(node);
+                NodeId(index)
+            }
+            None => {
+                self.nodes.push(Some(node));
+                NodeId(self.nodes.len() - 1)
+            }
+        };
+
+        self.set_parent(id, parent);
+
+        id
+    }
+
+    /// Remove a node along with all of its descendants.
+    pub fn remove(&mut self, id: NodeId) {
+        self.set_parent(id, None);
+
+        let mut pending = vec![id];
+        while let Some(id) = pending.pop() {
+            if let Some(node) = self.nodes.get_mut(id.0).and_then(Option::take) {
+                pending.extend(node.children);
+                self.free.push(id.0);
+            }
+        }
+    }
+
+    pub fn contains(&self, id: NodeId) -> bool {
+        self.node(id).is_some()
+    }
+
+    pub fn local(&self, id: NodeId) -> Option<Transform> {
+        self.node(id).map(|node| node.local)
+    }
+
+    pub fn set_local(&mut self, id: NodeId, local: Transform) {
+        if let Some(node) = self.node_mut(id) {
+            node.local = local;
+        }
+    }
+
+    pub fn parent(&self, id: NodeId) -> Option<NodeId> {
+        self.node(id).and_then(|node| node.parent)
+    }
+
+    pub fn children(&self, id: NodeId) -> &[NodeId] {
+        self.node(id).map_or(&[], |node| &node.children)
+    }
+
+    /// Attach a node to a new parent, keeping its local transform.
+    /// Attaching a node to itself or one of its descendants is ignored.
+    pub fn set_parent(&mut self, id: NodeId, parent: Option<NodeId>) {
+        if !self.contains(id) {
+            return;
+        }
+
+        if let Some(parent) = parent {
+            if !self.contains(parent) || self.is_ancestor_or_self(id, parent) {
+                return;
+            }
+        }
+
+        if let Some(old_parent) = self.parent(id) {
+            if let Some(node) = self.node_mut(old_parent) {
+                node.children.retain(|&child| child != id);
+            }
+        }
+
+        if let Some(parent) = parent {
+            if let Some(node) = self.node_mut(parent) {
+                node.children.push(id);
+            }
+        }
+
+        if let Some(node) = self.node_mut(id) {
+            node.parent = parent;
+        }
+    }
+
+    /// Resolve a node's transform in world space by combining it with all of its ancestors.
+    pub fn world(&self, id: NodeId) -> Option<Transform> {
+        let node = self.node(id)?;
+        let world = match node.parent {
+            Some(parent) => self.world(parent)?.then(&node.local),
+            None => node.local,
+        };
+
+        Some(world)
+    }
+
+    /// Spawn each part of a prefab under `root`, returning the new node ids in prefab order.
+    pub fn spawn(&mut self, prefab: &Prefab, root: Transform) -> Vec<NodeId> {
+        let root_id = self.add(root, None);
+        let mut ids: Vec<NodeId> = Vec::with_capacity(prefab.parts.len());
+
+        for part in &prefab.parts {
+            let parent = part.parent.map_or(root_id, |parent| ids[parent]);
+            ids.push(self.add(part.local, Some(parent)));
+        }
+
+        ids.insert(0, root_id);
+        ids
+    }
+
+    fn is_ancestor_or_self(&self, ancestor: NodeId, mut id: NodeId) -> bool {
+        loop {
+            if id == ancestor {
+                return true;
+            }
+
+            match self.parent(id) {
+                Some(parent) => id = parent,
+                None => return false,
+            }
+        }
+    }
+
+    fn node(&self, id: NodeId) -> Option<&Node> {
+        self.nodes.get(id.0).and_then(Option::as_ref)
+    }
+
+    fn node_mut(&mut self, id: NodeId) -> Option<&mut Node> {
+        self.nodes.get_mut(id.0).and_then(Option::as_mut)
+    }
+}
+
+pub struct PrefabPart {
+    pub local: Transform,
+    /// Index of an earlier part to attach to, or `None` to attach to the prefab's root.
+    pub parent: Option<usize>,
+}
+
+/// A reusable arrangement of parts, e.g. a worm with a hat and an aiming cursor.
+#[derive(Default)]
+pub struct Prefab {
+    parts: Vec<PrefabPart>,
+}
+
+impl Prefab {
+    /// Add a part. Parts are indexed in the order they are added, for use as parents of later parts.
+    /// Panics if `parent` does not refer to an earlier part.
+    pub fn with_part(mut self, local: Transform, parent: Option<usize>) -> Self {
+        if let Some(parent) = parent {
+            assert!(
+                parent < self.parts.len(),
+                "prefab parent must be an earlier part"
+            );
+        }
+
+        self.parts.push(PrefabPart { local, parent });
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use super::*;
+
+    fn assert_close(expected: Vec2, actual: Vec2) {
+        assert!((expected.x - actual.x).abs() < 1e-4, "{:?}", actual);
+        assert!((expected.y - actual.y).abs() < 1e-4, "{:?}", actual);
+    }
+
+    #[test]
+    fn child_position_is_relative_to_parent() {
+        let mut tree = TransformTree::default();
+        let parent = tree.add(Transform::from_position(10.0, 20.0), None);
+        let child = tree.add(Transform::from_position(1.0, 2.0), Some(parent));
+
+        assert_close(Vec2::new(11.0, 22.0), tree.world(child).unwrap().position);
+    }
+
+    #[test]
+    fn child_inherits_parent_rotation_and_scale() {
+        let mut tree = TransformTree::default();
+        let parent = tree.add(Transform::new(Vec2::new(0.0, 0.0), FRAC_PI_2, 2.0), None);
+        let child = tree.add(Transform::from_position(1.0, 0.0), Some(parent));
+
+        let world = tree.world(child).unwrap();
+
+        assert_close(Vec2::new(0.0, -2.0), world.position);
+        assert_eq!(FRAC_PI_2, world.rotation);
+        assert_eq!(2.0, world.scale);
+    }
+
+    #[test]
+    fn removing_parent_removes_descendants() {
+        let mut tree = TransformTree::default();
+        let parent = tree.add(Transform::default(), None);
+        let child = tree.add(Transform::default(), Some(parent));
+        let grandchild = tree.add(Transform::default(), Some(child));
+
+        tree.remove(parent);
+
+        assert!(!tree.contains(child));
+        assert!(!tree.contains(grandchild));
+    }
+
+    #[test]
+    fn cannot_parent_node_to_its_descendant() {
+        let mut tree = TransformTree::default();
+        let parent = tree.add(Transform::default(), None);
+        let child = tree.add(Transform::default(), Some(parent));
+
+        tree.set_parent(parent, Some(child));
+
+        assert_eq!(None, tree.parent(parent));
+    }
+
+    #[test]
+    fn prefab_parts_are_spawned_under_root() {
+        let prefab = Prefab::default()
+            .with_part(Transform::from_position(0.0, 5.0), None)
+            .with_part(Transform::from_position(0.0, 1.0), Some(0));
+        let mut tree = TransformTree::default();
+
+        let ids = tree.spawn(&prefab, Transform::from_position(100.0, 0.0));
+
+        assert_eq!(3, ids.len());
+        assert_eq!(Some(ids[1]), tree.parent(ids[2]));
+        assert_close(Vec2::new(100.0, 6.0), tree.world(ids[2]).unwrap().position);
+    }
+}
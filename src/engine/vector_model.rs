@@ -0,0 +1,661 @@
+use std::path::Path;
+
+use thiserror::Error;
+
+use crate::color::Color;
+use crate::engine::Point;
+use crate::maths::spline::CubicBezier;
+use crate::maths::Vec2;
+
+/// Straight lines each curve in a path is flattened into.
+const CURVE_SEGMENTS: usize = 8;
+
+#[derive(Debug, Error)]
+pub enum VectorModelError {
+    #[error("unable to read vector model")]
+    Io(#[from] std::io::Error),
+    #[error("invalid number '{0}'")]
+    Number(String),
+    #[error("invalid color '{0}'")]
+    Color(String),
+    #[error("unsupported path command '{0}'")]
+    UnsupportedCommand(char),
+    #[error("path data must start with a move")]
+    MissingMove,
+    #[error("unsupported element '<{0}>'")]
+    UnsupportedElement(String),
+    #[error("invalid transform '{0}'")]
+    Transform(String),
+}
+
+/// One outline of a `VectorModel`, drawn as a wireframe in its stroke color and filled in its
+/// fill color.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VectorShape {
+    pub points: Vec<Point>,
+    /// Whether the outline joins its last point back to its first.
+    pub closed: bool,
+    pub stroke: Option<Color>,
+    pub fill: Option<Color>,
+}
+
+/// Shapes loaded from a small subset of SVG, so models can be drawn in a vector editor such as
+/// Inkscape rather than written out as point arrays.
+///
+/// `<polygon>`, `<polyline>`, `<rect>` and `<path>` elements are read, with colors from their
+/// `stroke` and `fill` attributes or `style`, inherited through `<g>` groups, and `transform`s
+/// applied. Paths support the move, line, cubic and quadratic curve and close commands; curves
+/// are flattened into straight lines. Other drawable elements such as `<circle>` are an error
+/// rather than being left out. As in SVG, shapes are filled black and not stroked unless told
+/// otherwise.
+///
+/// SVG y increases downwards, so y is flipped on loading to keep the model the right way up
+/// with y increasing upwards.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct VectorModel {
+    pub shapes: Vec<VectorShape>,
+}
+
+impl VectorModel {
+    pub fn load_str(source: &str) -> Result<Self, VectorModelError> {
+        let mut shapes = Vec::new();
+        // Transforms and paint inherited from the enclosing groups.
+        let mut groups = vec![Group::default()];
+
+        for element in elements(source) {
+            let (name, attributes) = match element {
+                Element::Close(name) => {
+                    if matches!(name, "g" | "svg") && groups.len() > 1 {
+                        groups.pop();
+                    }
+                    continue;
+                }
+                Element::Open(name, attributes) | Element::Empty(name, attributes) => {
+                    (name, attributes)
+                }
+            };
+
+            let parent = groups.last().cloned().unwrap_or_default();
+            let transform = match attribute(attributes, "transform") {
+                Some(value) => parent.transform.compose(Transform::parse(value)?),
+                None => parent.transform,
+            };
+            let stroke = paint(attributes, "stroke")?.unwrap_or(parent.stroke);
+            let fill = paint(attributes, "fill")?.unwrap_or(parent.fill);
+
+            let outlines = match name {
+                "g" | "svg" => {
+                    if matches!(element, Element::Open(..)) {
+                        groups.push(Group {
+                            transform,
+                            stroke,
+                            fill,
+                        });
+                    }
+                    continue;
+                }
+                "polygon" | "polyline" => {
+                    let numbers = numbers(attribute(attributes, "points").unwrap_or(""))?;
+                    let points = numbers
+                        .chunks_exact(2)
+                        .map(|pair| Vec2::new(pair[0], pair[1]))
+                        .collect();
+                    vec![(points, name == "polygon")]
+                }
+                "path" => path(attribute(attributes, "d").unwrap_or(""))?,
+                "rect" => {
+                    let number = |name| -> Result<f32, VectorModelError> {
+                        numbers(attribute(attributes, name).unwrap_or("0"))
+                            .map(|numbers| numbers.first().copied().unwrap_or(0.0))
+                    };
+                    let (x, y) = (number("x")?, number("y")?);
+                    let (width, height) = (number("width")?, number("height")?);
+                    let points = vec![
+                        Vec2::new(x, y),
+                        Vec2::new(x + width, y),
+                        Vec2::new(x + width, y + height),
+                        Vec2::new(x, y + height),
+                    ];
+                    vec![(points, true)]
+                }
+                "line" | "circle" | "ellipse" | "use" | "text" | "image" => {
+                    return Err(VectorModelError::UnsupportedElement(name.to_string()))
+                }
+                _ => continue,
+            };
+
+            for (points, closed) in outlines {
+                if points.len() < 2 {
+                    continue;
+                }
+
+                shapes.push(VectorShape {
+                    points: points
+                        .iter()
+                        .map(|&p| transform.apply(p))
+                        .map(|p| Point::new(p.x, -p.y))
+                        .collect(),
+                    closed,
+                    stroke,
+                    fill,
+                });
+            }
+        }
+
+        Ok(Self { shapes })
+    }
+
+    pub fn load_file(path: impl AsRef<Path>) -> Result<Self, VectorModelError> {
+        let source = std::fs::read_to_string(path)?;
+        Self::load_str(&source)
+    }
+
+    /// The model moved so the center of its bounds is at (0, 0), which is the point models are
+    /// rotated and scaled about when drawn.
+    pub fn centered(mut self) -> Self {
+        let points = self.shapes.iter().flat_map(|shape| &shape.points);
+        let (mut min_x, mut min_y) = (f32::MAX, f32::MAX);
+        let (mut max_x, mut max_y) = (f32::MIN, f32::MIN);
+        for point in points {
+            min_x = min_x.min(point.x());
+            min_y = min_y.min(point.y());
+            max_x = max_x.max(point.x());
+            max_y = max_y.max(point.y());
+        }
+
+        if min_x > max_x {
+            return self;
+        }
+
+        let (cx, cy) = ((min_x + max_x) / 2.0, (min_y + max_y) / 2.0);
+        for shape in &mut self.shapes {
+            for point in &mut shape.points {
+                *point = Point::new(point.x() - cx, point.y() - cy);
+            }
+        }
+
+        self
+    }
+}
+
+/// What an element inherits from the groups around it.
+#[derive(Clone, Debug, PartialEq)]
+struct Group {
+    transform: Transform,
+    stroke: Option<Color>,
+    fill: Option<Color>,
+}
+
+impl Default for Group {
+    fn default() -> Self {
+        Self {
+            transform: Transform::IDENTITY,
+            stroke: None,
+            fill: Some(Color::rgba(0, 0, 0, 255)),
+        }
+    }
+}
+
+/// An SVG affine transform `[a c e; b d f]`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Transform([f32; 6]);
+
+impl Transform {
+    const IDENTITY: Transform = Transform([1.0, 0.0, 0.0, 1.0, 0.0, 0.0]);
+
+    /// Parse a transform list such as `translate(10 5) rotate(45)`.
+    fn parse(value: &str) -> Result<Transform, VectorModelError> {
+        let invalid = || VectorModelError::Transform(value.to_string());
+        let mut transform = Transform::IDENTITY;
+        let mut rest = value;
+
+        loop {
+            rest = rest.trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+            if rest.is_empty() {
+                return Ok(transform);
+            }
+
+            let open = rest.find('(').ok_or_else(invalid)?;
+            let close = rest.find(')').ok_or_else(invalid)?;
+            let name = rest[..open].trim();
+            let args = numbers(&rest[open + 1..close])?;
+            rest = &rest[close + 1..];
+
+            let next = match (name, args.as_slice()) {
+                ("matrix", &[a, b, c, d, e, f]) => Transform([a, b, c, d, e, f]),
+                ("translate", &[x]) => Transform([1.0, 0.0, 0.0, 1.0, x, 0.0]),
+                ("translate", &[x, y]) => Transform([1.0, 0.0, 0.0, 1.0, x, y]),
+                ("scale", &[s]) => Transform([s, 0.0, 0.0, s, 0.0, 0.0]),
+                ("scale", &[x, y]) => Transform([x, 0.0, 0.0, y, 0.0, 0.0]),
+                ("rotate", &[angle]) => Transform::rotation(angle),
+                ("rotate", &[angle, x, y]) => Transform([1.0, 0.0, 0.0, 1.0, x, y])
+                    .compose(Transform::rotation(angle))
+                    .compose(Transform([1.0, 0.0, 0.0, 1.0, -x, -y])),
+                ("skewX", &[angle]) => {
+                    Transform([1.0, 0.0, angle.to_radians().tan(), 1.0, 0.0, 0.0])
+                }
+                ("skewY", &[angle]) => {
+                    Transform([1.0, angle.to_radians().tan(), 0.0, 1.0, 0.0, 0.0])
+                }
+                _ => return Err(invalid()),
+            };
+            transform = transform.compose(next);
+        }
+    }
+
+    fn rotation(degrees: f32) -> Transform {
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        Transform([cos, sin, -sin, cos, 0.0, 0.0])
+    }
+
+    /// `inner` followed by this transform, as for a child element inside a transformed group.
+    fn compose(self, inner: Transform) -> Transform {
+        let [a, b, c, d, e, f] = self.0;
+        let [ia, ib, ic, id, ie, if_] = inner.0;
+        Transform([
+            a * ia + c * ib,
+            b * ia + d * ib,
+            a * ic + c * id,
+            b * ic + d * id,
+            a * ie + c * if_ + e,
+            b * ie + d * if_ + f,
+        ])
+    }
+
+    fn apply(&self, point: Vec2) -> Vec2 {
+        let [a, b, c, d, e, f] = self.0;
+        Vec2::new(a * point.x + c * point.y + e, b * point.x + d * point.y + f)
+    }
+}
+
+/// A tag in the source, with its name and attribute text.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Element<'a> {
+    Open(&'a str, &'a str),
+    Empty(&'a str, &'a str),
+    Close(&'a str),
+}
+
+/// The tags in `source`, in order.
+fn elements(source: &str) -> Vec<Element<'_>> {
+    let mut elements = Vec::new();
+    let mut rest = source;
+
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start + 1..];
+        let end = match rest.find('>') {
+            Some(end) => end,
+            None => break,
+        };
+        let tag = &rest[..end];
+        rest = &rest[end + 1..];
+
+        if let Some(name) = tag.strip_prefix('/') {
+            elements.push(Element::Close(name.trim()));
+            continue;
+        }
+
+        let (tag, empty) = match tag.strip_suffix('/') {
+            Some(tag) => (tag, true),
+            None => (tag, false),
+        };
+        let name_end = tag.find(|c: char| c.is_whitespace()).unwrap_or(tag.len());
+        let (name, attributes) = tag.split_at(name_end);
+        elements.push(if empty {
+            Element::Empty(name, attributes)
+        } else {
+            Element::Open(name, attributes)
+        });
+    }
+
+    elements
+}
+
+/// The value of attribute `name`, quoted with either `"` or `'`.
+fn attribute<'a>(attributes: &'a str, name: &str) -> Option<&'a str> {
+    let mut rest = attributes;
+
+    loop {
+        let equals = rest.find('=')?;
+        let key = rest[..equals].trim();
+        let key = key.rsplit(char::is_whitespace).next().unwrap_or(key);
+        let value = rest[equals + 1..].trim_start();
+        let quote = value.chars().next()?;
+        let value = &value[quote.len_utf8()..];
+        let close = value.find(quote)?;
+
+        if key == name {
+            return Some(&value[..close]);
+        }
+        rest = &value[close + 1..];
+    }
+}
+
+/// A stroke or fill color from the `style` attribute, or failing that the attribute itself.
+/// `Some(None)` means explicitly unpainted, and `None` that the paint isn't given.
+fn paint(attributes: &str, name: &str) -> Result<Option<Option<Color>>, VectorModelError> {
+    let styled = attribute(attributes, "style").and_then(|style| {
+        style.split(';').find_map(|declaration| {
+            let (key, value) = declaration.split_once(':')?;
+            (key.trim() == name).then(|| value.trim())
+        })
+    });
+
+    match styled.or_else(|| attribute(attributes, name)) {
+        Some(value) => color(value).map(Some),
+        None => Ok(None),
+    }
+}
+
+/// An SVG color: `none`, `#rgb` or `#rrggbb`.
+fn color(value: &str) -> Result<Option<Color>, VectorModelError> {
+    let invalid = || VectorModelError::Color(value.to_string());
+    if value == "none" {
+        return Ok(None);
+    }
+
+    let hex = value.strip_prefix('#').ok_or_else(invalid)?;
+    let digits = hex
+        .chars()
+        .map(|c| c.to_digit(16).map(|d| d as u8))
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(invalid)?;
+
+    match digits[..] {
+        [r, g, b] => Ok(Some(Color::rgba(r * 17, g * 17, b * 17, 255))),
+        [r1, r2, g1, g2, b1, b2] => Ok(Some(Color::rgba(
+            r1 * 16 + r2,
+            g1 * 16 + g2,
+            b1 * 16 + b2,
+            255,
+        ))),
+        _ => Err(invalid()),
+    }
+}
+
+/// The numbers in a list separated by whitespace, commas or signs.
+fn numbers(value: &str) -> Result<Vec<f32>, VectorModelError> {
+    let mut tokens = Tokens::new(value);
+    let mut numbers = Vec::new();
+    while let Some(number) = tokens.number()? {
+        numbers.push(number);
+    }
+
+    Ok(numbers)
+}
+
+/// The outlines traced by path data `d`, with whether each is closed.
+fn path(d: &str) -> Result<Vec<(Vec<Vec2>, bool)>, VectorModelError> {
+    let mut tokens = Tokens::new(d);
+    let mut outlines = Vec::new();
+    let mut points: Vec<Vec2> = Vec::new();
+    let mut current = Vec2::default();
+    let mut start = Vec2::default();
+    let mut previous_control: Option<Vec2> = None;
+    let mut command = None;
+
+    loop {
+        command = match tokens.command() {
+            Some(next) => Some(next),
+            None if tokens.is_empty() => break,
+            // Repeated arguments repeat the command, and a move repeats as a line.
+            None => match command {
+                Some('M') => Some('L'),
+                Some('m') => Some('l'),
+                Some('Z' | 'z') | None => return Err(VectorModelError::MissingMove),
+                repeated => repeated,
+            },
+        };
+
+        let command = command.unwrap_or_default();
+        let relative = command.is_ascii_lowercase();
+        let offset = if relative { current } else { Vec2::default() };
+        let point = |tokens: &mut Tokens| -> Result<Vec2, VectorModelError> {
+            Ok(Vec2::new(tokens.expect()?, tokens.expect()?) + offset)
+        };
+
+        if points.is_empty() && !matches!(command, 'M' | 'm') {
+            return Err(VectorModelError::MissingMove);
+        }
+
+        let mut control = None;
+        match command.to_ascii_uppercase() {
+            'M' => {
+                if points.len() > 1 {
+                    outlines.push((std::mem::take(&mut points), false));
+                }
+                current = point(&mut tokens)?;
+                start = current;
+                points = vec![current];
+            }
+            'L' => {
+                current = point(&mut tokens)?;
+                points.push(current);
+            }
+            'H' => {
+                current.x = tokens.expect()? + offset.x;
+                points.push(current);
+            }
+            'V' => {
+                current.y = tokens.expect()? + offset.y;
+                points.push(current);
+            }
+            'C' | 'S' => {
+                let p1 = if command.eq_ignore_ascii_case(&'C') {
+                    point(&mut tokens)?
+                } else {
+                    reflect(previous_control, current)
+                };
+                let p2 = point(&mut tokens)?;
+                let p3 = point(&mut tokens)?;
+                flatten(&mut points, CubicBezier::new(current, p1, p2, p3));
+                current = p3;
+                control = Some(p2);
+            }
+            'Q' => {
+                let q = point(&mut tokens)?;
+                let p3 = point(&mut tokens)?;
+                let p1 = current + (q - current) * (2.0 / 3.0);
+                let p2 = p3 + (q - p3) * (2.0 / 3.0);
+                flatten(&mut points, CubicBezier::new(current, p1, p2, p3));
+                current = p3;
+            }
+            'Z' => {
+                if points.len() > 1 {
+                    outlines.push((std::mem::take(&mut points), true));
+                }
+                current = start;
+                points = vec![current];
+            }
+            other => return Err(VectorModelError::UnsupportedCommand(other)),
+        }
+        previous_control = control;
+    }
+
+    if points.len() > 1 {
+        outlines.push((points, false));
+    }
+
+    Ok(outlines)
+}
+
+/// The first control point of a smooth curve: the previous curve's last control point mirrored
+/// through the current point.
+fn reflect(control: Option<Vec2>, current: Vec2) -> Vec2 {
+    match control {
+        Some(control) => current + (current - control),
+        None => current,
+    }
+}
+
+fn flatten(points: &mut Vec<Vec2>, curve: CubicBezier) {
+    for i in 1..=CURVE_SEGMENTS {
+        points.push(curve.point(i as f32 / CURVE_SEGMENTS as f32));
+    }
+}
+
+/// Splits SVG number lists and path data into numbers and command letters.
+struct Tokens<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Tokens<'a> {
+    fn new(value: &'a str) -> Self {
+        Self { rest: value }
+    }
+
+    fn skip_separators(&mut self) {
+        self.rest = self
+            .rest
+            .trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+    }
+
+    fn is_empty(&mut self) -> bool {
+        self.skip_separators();
+        self.rest.is_empty()
+    }
+
+    fn command(&mut self) -> Option<char> {
+        self.skip_separators();
+        let c = self.rest.chars().next()?;
+        if c.is_ascii_alphabetic() {
+            self.rest = &self.rest[1..];
+            Some(c)
+        } else {
+            None
+        }
+    }
+
+    fn number(&mut self) -> Result<Option<f32>, VectorModelError> {
+        self.skip_separators();
+        if self.rest.is_empty() {
+            return Ok(None);
+        }
+
+        let bytes = self.rest.as_bytes();
+        let mut end = 0;
+        let mut seen_point = false;
+        let mut seen_exponent = false;
+        while end < bytes.len() {
+            match bytes[end] {
+                b'+' | b'-' if end == 0 || matches!(bytes[end - 1], b'e' | b'E') => {}
+                b'0'..=b'9' => {}
+                b'.' if !seen_point && !seen_exponent => seen_point = true,
+                b'e' | b'E' if !seen_exponent && end > 0 => seen_exponent = true,
+                _ => break,
+            }
+            end += 1;
+        }
+
+        let (number, rest) = self.rest.split_at(end);
+        let value = number
+            .parse()
+            .map_err(|_| VectorModelError::Number(number.to_string()))?;
+        self.rest = rest;
+
+        Ok(Some(value))
+    }
+
+    fn expect(&mut self) -> Result<f32, VectorModelError> {
+        self.number()?
+            .ok_or_else(|| VectorModelError::Number(String::new()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color;
+
+    #[test]
+    fn loads_polygons_with_styled_colors_and_flips_y() {
+        let model = VectorModel::load_str(
+            r#"<svg><polygon points="0,0 10,0 10,5" style="fill:none;stroke:#f00"/></svg>"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            vec![VectorShape {
+                points: vec![
+                    Point::new(0.0, 0.0),
+                    Point::new(10.0, 0.0),
+                    Point::new(10.0, -5.0)
+                ],
+                closed: true,
+                stroke: Some(color::css::RED),
+                fill: None,
+            }],
+            model.shapes
+        );
+    }
+
+    #[test]
+    fn path_commands_trace_relative_and_closed_outlines() {
+        let model = VectorModel::load_str(r##"<path d="m1-1h2v-2.5.5zM0 0L4 4" fill="#0000ff"/>"##)
+            .unwrap();
+
+        assert_eq!(2, model.shapes.len());
+        let first = &model.shapes[0];
+        assert!(first.closed);
+        assert_eq!(Some(color::css::BLUE), first.fill);
+        assert_eq!(
+            vec![
+                Point::new(1.0, 1.0),
+                Point::new(3.0, 1.0),
+                Point::new(3.0, 3.5),
+                Point::new(3.0, 3.0),
+            ],
+            first.points
+        );
+        assert!(!model.shapes[1].closed);
+    }
+
+    #[test]
+    fn curves_are_flattened_to_their_end_point() {
+        let model = VectorModel::load_str(r#"<path d="M0,0 C0,10 10,10 10,0"/>"#).unwrap();
+
+        let points = &model.shapes[0].points;
+        assert_eq!(1 + CURVE_SEGMENTS, points.len());
+        assert_eq!(Point::new(10.0, 0.0), points[CURVE_SEGMENTS]);
+    }
+
+    #[test]
+    fn unsupported_commands_are_an_error() {
+        let result = VectorModel::load_str(r#"<path d="M0,0 A1,1 0 0 1 2,2"/>"#);
+
+        assert!(matches!(
+            result,
+            Err(VectorModelError::UnsupportedCommand('A'))
+        ));
+    }
+
+    #[test]
+    fn groups_pass_transforms_and_paint_to_their_children() {
+        let model = VectorModel::load_str(
+            r##"<g transform="translate(10,0)" style="stroke:#00f">
+                  <polyline points="0,0 1,0" transform="scale(2)" fill="none"/>
+                </g>
+                <polyline points="0,0 1,0" fill="none"/>"##,
+        )
+        .unwrap();
+
+        let grouped = &model.shapes[0];
+        assert_eq!(
+            vec![Point::new(10.0, 0.0), Point::new(12.0, 0.0)],
+            grouped.points
+        );
+        assert_eq!(Some(color::css::BLUE), grouped.stroke);
+        assert_eq!(Point::new(1.0, 0.0), model.shapes[1].points[1]);
+        assert_eq!(None, model.shapes[1].stroke);
+    }
+
+    #[test]
+    fn unsupported_elements_are_an_error() {
+        let result = VectorModel::load_str(r#"<circle cx="0" cy="0" r="1"/>"#);
+
+        assert!(matches!(
+            result,
+            Err(VectorModelError::UnsupportedElement(name)) if name == "circle"
+        ));
+    }
+}
@@ -0,0 +1,358 @@
+use crate::color::Color;
+use crate::engine::apparatus::Apparatus;
+use crate::maths::Vec2;
+use crate::procgen::rng::Rng;
+use crate::renderer::software_2d::Origin;
+
+/// The most raindrops or snowflakes on screen at full intensity.
+const MAX_PARTICLES: usize = 400;
+const GRAVITY: f32 = 200.0;
+
+/// Rain falling in streaks that splash when they reach the bottom of the screen.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Rain {
+    intensity: f32,
+    wind: f32,
+    color: Color,
+}
+
+impl Rain {
+    /// `intensity` from 0 (none) to 1 (a downpour).
+    pub fn new(intensity: f32) -> Self {
+        Self {
+            intensity: intensity.clamp(0.0, 1.0),
+            wind: 0.0,
+            color: Color::rgba(180, 200, 255, 160),
+        }
+    }
+
+    /// Sideways speed in virtual pixels per second. Defaults to 0.
+    pub fn with_wind(mut self, wind: f32) -> Self {
+        self.wind = wind;
+        self
+    }
+
+    /// Defaults to translucent pale blue.
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+}
+
+/// Snow drifting down and swaying from side to side.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Snow {
+    intensity: f32,
+    wind: f32,
+    color: Color,
+}
+
+impl Snow {
+    /// `intensity` from 0 (none) to 1 (a blizzard).
+    pub fn new(intensity: f32) -> Self {
+        Self {
+            intensity: intensity.clamp(0.0, 1.0),
+            wind: 0.0,
+            color: Color::rgba(255, 255, 255, 220),
+        }
+    }
+
+    /// Sideways speed in virtual pixels per second. Defaults to 0.
+    pub fn with_wind(mut self, wind: f32) -> Self {
+        self.wind = wind;
+        self
+    }
+
+    /// Defaults to white.
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+}
+
+/// Bands of fog drifting across the screen, thickest at the bottom.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Fog {
+    density: f32,
+    layers: u32,
+    color: Color,
+}
+
+impl Fog {
+    /// `density` from 0 (clear) to 1 (thick).
+    pub fn new(density: f32) -> Self {
+        Self {
+            density: density.clamp(0.0, 1.0),
+            layers: 3,
+            color: Color::rgba(200, 200, 210, 255),
+        }
+    }
+
+    /// How many bands of fog are drawn. Defaults to 3.
+    pub fn with_layers(mut self, layers: u32) -> Self {
+        self.layers = layers;
+        self
+    }
+
+    /// The color of the fog; its alpha is set by the density. Defaults to grey.
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct Particle {
+    /// In virtual pixels from the top left of the screen.
+    position: Vec2,
+    velocity: Vec2,
+    /// Seconds left for splashes; the sway phase for snowflakes.
+    life: f32,
+}
+
+/// Weather drawn over a scene in screen space. Enable any mix of rain, snow and fog, e.g. when
+/// a scene starts, and call `update` and `draw` every frame.
+#[derive(Clone, Debug)]
+pub struct Weather {
+    width: f32,
+    height: f32,
+    rng: Rng,
+    time: f32,
+    rain: Option<Rain>,
+    snow: Option<Snow>,
+    fog: Option<Fog>,
+    drops: Vec<Particle>,
+    splashes: Vec<Particle>,
+    flakes: Vec<Particle>,
+}
+
+impl Weather {
+    /// Weather for a `width` x `height` screen in virtual pixels.
+    pub fn new(width: f32, height: f32, seed: u64) -> Self {
+        Self {
+            width,
+            height,
+            rng: Rng::new(seed),
+            time: 0.0,
+            rain: None,
+            snow: None,
+            fog: None,
+            drops: Vec::new(),
+            splashes: Vec::new(),
+            flakes: Vec::new(),
+        }
+    }
+
+    pub fn set_rain(&mut self, rain: Option<Rain>) {
+        if rain.is_none() {
+            self.drops.clear();
+            self.splashes.clear();
+        }
+        self.rain = rain;
+    }
+
+    pub fn set_snow(&mut self, snow: Option<Snow>) {
+        if snow.is_none() {
+            self.flakes.clear();
+        }
+        self.snow = snow;
+    }
+
+    pub fn set_fog(&mut self, fog: Option<Fog>) {
+        self.fog = fog;
+    }
+
+    /// Turn off all weather.
+    pub fn clear(&mut self) {
+        self.set_rain(None);
+        self.set_snow(None);
+        self.set_fog(None);
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        self.time += dt;
+
+        if let Some(rain) = self.rain {
+            self.update_rain(rain, dt);
+        }
+        if let Some(snow) = self.snow {
+            self.update_snow(snow, dt);
+        }
+    }
+
+    fn update_rain(&mut self, rain: Rain, dt: f32) {
+        let target = (MAX_PARTICLES as f32 * rain.intensity) as usize;
+        self.drops.truncate(target);
+        while self.drops.len() < target {
+            let speed = self.rng.range_f32(300.0, 400.0);
+            let drop = self.spawn(rain.wind, speed);
+            self.drops.push(drop);
+        }
+
+        let height = self.height;
+        let mut landed = Vec::new();
+        for drop in &mut self.drops {
+            drop.position = drop.position + drop.velocity * dt;
+            if drop.position.y >= height {
+                landed.push(drop.position.x);
+            }
+        }
+        for x in landed {
+            for _ in 0..2 {
+                let velocity = Vec2::new(self.rng.range_f32(-30.0, 30.0), -60.0);
+                self.splashes.push(Particle {
+                    position: Vec2::new(x, height - 1.0),
+                    velocity,
+                    life: 0.25,
+                });
+            }
+        }
+        self.drops.retain(|drop| drop.position.y < height);
+
+        for splash in &mut self.splashes {
+            splash.velocity.y += GRAVITY * dt;
+            splash.position = splash.position + splash.velocity * dt;
+            splash.life -= dt;
+        }
+        self.splashes.retain(|splash| splash.life > 0.0);
+    }
+
+    fn update_snow(&mut self, snow: Snow, dt: f32) {
+        let target = (MAX_PARTICLES as f32 * snow.intensity) as usize;
+        self.flakes.truncate(target);
+        while self.flakes.len() < target {
+            let speed = self.rng.range_f32(20.0, 40.0);
+            let mut flake = self.spawn(snow.wind, speed);
+            flake.life = self.rng.range_f32(0.0, std::f32::consts::TAU);
+            self.flakes.push(flake);
+        }
+
+        let (width, height, time) = (self.width, self.height, self.time);
+        for flake in &mut self.flakes {
+            let sway = (time * 2.0 + flake.life).sin() * 10.0;
+            flake.position =
+                flake.position + Vec2::new(flake.velocity.x + sway, flake.velocity.y) * dt;
+            if flake.position.y >= height {
+                flake.position.y -= height;
+            }
+            flake.position.x = flake.position.x.rem_euclid(width.max(1.0));
+        }
+    }
+
+    /// A particle somewhere above the screen, so they arrive spread out.
+    fn spawn(&mut self, wind: f32, speed: f32) -> Particle {
+        // Start far enough upwind that the wind carries particles across the whole screen.
+        let drift = wind * self.height / speed;
+        let x = self
+            .rng
+            .range_f32(-drift.max(0.0), self.width - drift.min(0.0));
+
+        Particle {
+            position: Vec2::new(x, self.rng.range_f32(-self.height, 0.0)),
+            velocity: Vec2::new(wind, speed),
+            life: 0.0,
+        }
+    }
+
+    pub fn draw(&self, app: &mut Apparatus) {
+        let origin = app.origin();
+        let height = self.height;
+        let to_screen = |position: Vec2| match origin {
+            Origin::TopLeft => position,
+            Origin::BottomLeft => Vec2::new(position.x, height - position.y),
+        };
+
+        if let Some(fog) = self.fog {
+            for (y, band_height, color) in self.fog_bands(fog) {
+                let corner = match origin {
+                    Origin::TopLeft => y,
+                    Origin::BottomLeft => height - y - band_height,
+                };
+                app.draw_filled_rectangle(0.0, corner, self.width, band_height, color);
+            }
+        }
+
+        if let Some(rain) = self.rain {
+            for drop in &self.drops {
+                let head = to_screen(drop.position);
+                let tail = to_screen(drop.position - drop.velocity * 0.02);
+                app.draw_line(tail.x, tail.y, head.x, head.y, rain.color);
+            }
+            for splash in &self.splashes {
+                let position = to_screen(splash.position);
+                app.draw(position.x, position.y, rain.color);
+            }
+        }
+
+        if let Some(snow) = self.snow {
+            for flake in &self.flakes {
+                let position = to_screen(flake.position);
+                app.draw(position.x, position.y, snow.color);
+            }
+        }
+    }
+
+    /// Each band of fog from the top of the screen down, as its top, height and color. Lower
+    /// bands are thicker and each drifts in and out over time.
+    fn fog_bands(&self, fog: Fog) -> Vec<(f32, f32, Color)> {
+        let layers = fog.layers.max(1);
+        let band_height = self.height / (2 * layers) as f32;
+
+        (0..layers)
+            .map(|layer| {
+                let depth = (layer + 1) as f32 / layers as f32;
+                let drift = 0.75 + 0.25 * (self.time * 0.5 + layer as f32).sin();
+                let alpha = (fog.density * depth * drift * 160.0) as u8;
+                let y = self.height - band_height * (layers - layer) as f32;
+                let color = Color::rgba(fog.color.r(), fog.color.g(), fog.color.b(), alpha);
+
+                (y, band_height, color)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn intensity_sets_how_much_falls() {
+        let mut weather = Weather::new(100.0, 100.0, 1);
+        weather.set_rain(Some(Rain::new(0.5)));
+        weather.set_snow(Some(Snow::new(0.25)));
+
+        weather.update(0.0);
+
+        assert_eq!(MAX_PARTICLES / 2, weather.drops.len());
+        assert_eq!(MAX_PARTICLES / 4, weather.flakes.len());
+    }
+
+    #[test]
+    fn raindrops_splash_at_the_bottom_of_the_screen() {
+        let mut weather = Weather::new(100.0, 100.0, 1);
+        weather.set_rain(Some(Rain::new(0.1)));
+
+        weather.update(0.0);
+        for _ in 0..30 {
+            weather.update(1.0 / 60.0);
+        }
+
+        assert!(!weather.splashes.is_empty());
+        assert!(weather.drops.iter().all(|drop| drop.position.y < 100.0));
+
+        weather.set_rain(None);
+        assert!(weather.drops.is_empty() && weather.splashes.is_empty());
+    }
+
+    #[test]
+    fn fog_is_thickest_at_the_bottom() {
+        let weather = Weather::new(100.0, 120.0, 1);
+
+        let bands = weather.fog_bands(Fog::new(1.0).with_layers(3));
+
+        assert_eq!(3, bands.len());
+        assert_eq!(120.0, bands[2].0 + bands[2].1);
+        assert!(bands[0].2.a() < bands[2].2.a());
+    }
+}
@@ -4,10 +4,14 @@ use thiserror::Error;
 
 #[derive(Error, Debug)]
 pub enum ApparatusError {
+    #[error("error loading asset")]
+    Asset(#[source] Box<dyn std::error::Error + Send + Sync>),
     #[error("error running game")]
     Game(#[from] Box<dyn std::error::Error + Send + Sync>),
     #[error("error initialising engine")]
     Initialisation(#[source] Box<dyn std::error::Error + Send + Sync>),
+    #[error("error saving screenshot")]
+    Screenshot(#[source] Box<dyn std::error::Error + Send + Sync>),
     #[error("logger error")]
     Logger(#[source] Box<dyn std::error::Error + Send + Sync>),
     #[error("window error")]
@@ -1,4 +1,9 @@
+use std::collections::HashMap;
+use std::path::Path;
+
 use fontdue::{Font as NativeFont, FontSettings};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
 
 pub struct Font(NativeFont);
 
@@ -9,12 +14,10 @@ pub(crate) fn load_default_font() -> Font {
         ..FontSettings::default()
     };
     let default_font_bytes = include_bytes!("../assets/fonts/Orbitron Medium.otf") as &[u8];
-    let default_font =
-        Font(NativeFont::from_bytes(default_font_bytes, default_font_settings).unwrap());
-
-    default_font
+    Font(NativeFont::from_bytes(default_font_bytes, default_font_settings).unwrap())
 }
 
+#[derive(Clone, Debug, PartialEq)]
 pub struct RasterizedFont {
     pub width: usize,
     pub height: usize,
@@ -36,3 +39,232 @@ pub(crate) fn rasterize(character: char, font: &Font, size: f32) -> RasterizedFo
         data,
     }
 }
+
+/// How far the pen moves after drawing `character`.
+pub(crate) fn advance_width(character: char, font: &Font, size: f32) -> f32 {
+    font.0.metrics(character, size).advance_width
+}
+
+#[derive(Debug, Error)]
+pub enum GlyphAtlasError {
+    #[error("unable to access glyph atlas")]
+    Io(#[from] std::io::Error),
+    #[error("unable to write glyph atlas")]
+    Serialize(#[from] toml::ser::Error),
+    #[error("invalid glyph atlas")]
+    Deserialize(#[from] toml::de::Error),
+    #[error("glyph '{0}' does not hold width x height pixels")]
+    Malformed(char),
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct SavedGlyphAtlas {
+    glyphs: Vec<SavedGlyph>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct SavedGlyph {
+    size: f32,
+    character: char,
+    width: usize,
+    height: usize,
+    xmin: i32,
+    ymin: i32,
+    advance_width: f32,
+    data: Vec<u8>,
+}
+
+/// Glyphs rasterized ahead of time for given text sizes, e.g. baked once and shipped with a
+/// game so text is drawn without rasterizing on first use and looks the same on every platform.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GlyphAtlas {
+    glyphs: HashMap<(u32, char), RasterizedFont>,
+}
+
+impl GlyphAtlas {
+    pub(crate) fn bake(font: &Font, sizes: &[f32], characters: &str) -> Self {
+        let mut atlas = Self::default();
+        for &size in sizes {
+            for character in characters.chars() {
+                atlas.glyphs.insert(
+                    (size.to_bits(), character),
+                    rasterize(character, font, size),
+                );
+            }
+        }
+
+        atlas
+    }
+
+    pub(crate) fn glyph(&self, character: char, size: f32) -> Option<&RasterizedFont> {
+        self.glyphs.get(&(size.to_bits(), character))
+    }
+
+    /// Add the glyphs from `other`, replacing any baked for the same size.
+    pub(crate) fn extend(&mut self, other: GlyphAtlas) {
+        self.glyphs.extend(other.glyphs);
+    }
+
+    /// How many glyphs are baked, counting each size separately.
+    pub fn len(&self) -> usize {
+        self.glyphs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.glyphs.is_empty()
+    }
+
+    pub fn save_str(&self) -> Result<String, GlyphAtlasError> {
+        let mut glyphs: Vec<_> = self
+            .glyphs
+            .iter()
+            .map(|(&(size, character), glyph)| SavedGlyph {
+                size: f32::from_bits(size),
+                character,
+                width: glyph.width,
+                height: glyph.height,
+                xmin: glyph.xmin,
+                ymin: glyph.ymin,
+                advance_width: glyph.advance_width,
+                data: glyph.data.clone(),
+            })
+            .collect();
+        // Sorted so the same atlas always saves to the same file.
+        glyphs.sort_by(|a, b| {
+            a.size
+                .total_cmp(&b.size)
+                .then(a.character.cmp(&b.character))
+        });
+
+        Ok(toml::to_string(&SavedGlyphAtlas { glyphs })?)
+    }
+
+    pub fn load_str(source: &str) -> Result<Self, GlyphAtlasError> {
+        let saved: SavedGlyphAtlas = toml::from_str(source)?;
+        let mut atlas = Self::default();
+        for glyph in saved.glyphs {
+            if glyph.data.len() != glyph.width * glyph.height {
+                return Err(GlyphAtlasError::Malformed(glyph.character));
+            }
+
+            atlas.glyphs.insert(
+                (glyph.size.to_bits(), glyph.character),
+                RasterizedFont {
+                    width: glyph.width,
+                    height: glyph.height,
+                    xmin: glyph.xmin,
+                    ymin: glyph.ymin,
+                    advance_width: glyph.advance_width,
+                    data: glyph.data,
+                },
+            );
+        }
+
+        Ok(atlas)
+    }
+
+    pub fn save_file(&self, path: impl AsRef<Path>) -> Result<(), GlyphAtlasError> {
+        std::fs::write(path, self.save_str()?)?;
+
+        Ok(())
+    }
+
+    pub fn load_file(path: impl AsRef<Path>) -> Result<Self, GlyphAtlasError> {
+        let source = std::fs::read_to_string(path)?;
+        Self::load_str(&source)
+    }
+}
+
+/// Glyphs rendered once into fixed size cells, so grids of text can be drawn without
+/// rasterizing every frame.
+pub(crate) struct BitmapFont {
+    cell_width: usize,
+    cell_height: usize,
+    size: f32,
+    baseline: i32,
+    glyphs: HashMap<char, Vec<u8>>,
+}
+
+impl BitmapFont {
+    /// Size the font so its ascent and descent fill `cell_height` pixels.
+    pub(crate) fn new(font: &Font, cell_width: usize, cell_height: usize) -> Self {
+        let height = cell_height as f32;
+        let (size, baseline) = match font.0.horizontal_line_metrics(height) {
+            Some(metrics) if metrics.ascent > metrics.descent => {
+                let scale = height / (metrics.ascent - metrics.descent);
+                (height * scale, (metrics.ascent * scale).round() as i32)
+            }
+            _ => (height, cell_height as i32),
+        };
+
+        Self {
+            cell_width,
+            cell_height,
+            size,
+            baseline,
+            glyphs: HashMap::new(),
+        }
+    }
+
+    /// The coverage of each pixel of the cell for `character`, top row first, centered
+    /// horizontally.
+    pub(crate) fn glyph(&mut self, font: &Font, character: char) -> &[u8] {
+        let (cell_width, cell_height) = (self.cell_width, self.cell_height);
+        let (size, baseline) = (self.size, self.baseline);
+
+        self.glyphs.entry(character).or_insert_with(|| {
+            let mut cell = vec![0; cell_width * cell_height];
+            let rasterized = rasterize(character, font, size);
+            let left = ((cell_width as f32 - rasterized.advance_width) / 2.0).round() as i32
+                + rasterized.xmin;
+            let top = baseline - rasterized.ymin - rasterized.height as i32;
+
+            for y in 0..rasterized.height {
+                for x in 0..rasterized.width {
+                    let (cell_x, cell_y) = (left + x as i32, top + y as i32);
+                    if cell_x >= 0
+                        && cell_y >= 0
+                        && (cell_x as usize) < cell_width
+                        && (cell_y as usize) < cell_height
+                    {
+                        cell[cell_y as usize * cell_width + cell_x as usize] =
+                            rasterized.data[y * rasterized.width + x];
+                    }
+                }
+            }
+
+            cell
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn baked_glyphs_survive_saving_and_loading() {
+        let atlas = GlyphAtlas::bake(&load_default_font(), &[12.0, 18.5], "Ab ");
+
+        let loaded = GlyphAtlas::load_str(&atlas.save_str().unwrap()).unwrap();
+
+        assert_eq!(6, loaded.len());
+        assert_eq!(atlas, loaded);
+        assert_eq!(
+            Some(&rasterize('b', &load_default_font(), 18.5)),
+            loaded.glyph('b', 18.5)
+        );
+        assert_eq!(None, loaded.glyph('b', 18.0));
+    }
+
+    #[test]
+    fn glyphs_must_hold_their_pixels() {
+        let source = "[[glyphs]]\nsize = 12.0\ncharacter = \"A\"\nwidth = 2\nheight = 2\n\
+                      xmin = 0\nymin = 0\nadvance_width = 3.0\ndata = [0, 255, 0]\n";
+
+        assert!(matches!(
+            GlyphAtlas::load_str(source),
+            Err(GlyphAtlasError::Malformed('A'))
+        ));
+    }
+}
@@ -0,0 +1,174 @@
+use std::path::PathBuf;
+
+use thiserror::Error;
+
+use crate::engine::apparatus::ApparatusSettings;
+use crate::engine::key::Key;
+
+/// Where `--record` saves recordings.
+pub const RECORD_DIRECTORY: &str = "recordings";
+/// The key that saves a recording with `--record`.
+pub const RECORD_KEY: Key = Key::Num0;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum LauncherError {
+    #[error("{0} requires a value")]
+    MissingValue(String),
+    #[error("invalid scale '{0}', expected a whole number of at least 1")]
+    InvalidScale(String),
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum DisplayMode {
+    #[default]
+    Windowed,
+    /// The same size window without a border, kept above all others.
+    Borderless,
+    /// A borderless window scaled up to cover the screen, see
+    /// `ApparatusSettings::with_fullscreen_window`.
+    Fullscreen,
+}
+
+/// The common command line switches shared by every game:
+///
+/// - `--windowed` / `--borderless` / `--fullscreen`
+/// - `--scale N` sets the size of each virtual pixel
+/// - `--record` keeps the last ten seconds as a GIF, saved to `RECORD_DIRECTORY` with
+///   `RECORD_KEY`
+/// - `--replay FILE` plays back a replay instead of reading input
+/// - `--headless` runs without a window, see `ApparatusSettings::with_headless`
+///
+/// Anything else is left in `remaining` for the game's own flags.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LaunchOptions {
+    pub display: DisplayMode,
+    pub scale: Option<usize>,
+    pub record: bool,
+    pub replay: Option<PathBuf>,
+    pub headless: bool,
+    pub remaining: Vec<String>,
+}
+
+impl LaunchOptions {
+    /// Parse the current process's arguments, skipping the program name.
+    pub fn from_env() -> Result<Self, LauncherError> {
+        Self::parse(std::env::args().skip(1))
+    }
+
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, LauncherError> {
+        let mut options = LaunchOptions::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--windowed" => options.display = DisplayMode::Windowed,
+                "--borderless" => options.display = DisplayMode::Borderless,
+                "--fullscreen" => options.display = DisplayMode::Fullscreen,
+                "--scale" => {
+                    let value = args.next().ok_or(LauncherError::MissingValue(arg))?;
+                    let scale = value
+                        .parse::<usize>()
+                        .ok()
+                        .filter(|&scale| scale >= 1)
+                        .ok_or(LauncherError::InvalidScale(value))?;
+                    options.scale = Some(scale);
+                }
+                "--record" => options.record = true,
+                "--replay" => {
+                    let path = args.next().ok_or(LauncherError::MissingValue(arg))?;
+                    options.replay = Some(PathBuf::from(path));
+                }
+                "--headless" => options.headless = true,
+                _ => options.remaining.push(arg),
+            }
+        }
+
+        Ok(options)
+    }
+
+    /// Apply the switches on top of the game's own settings.
+    pub fn apply(&self, mut settings: ApparatusSettings) -> ApparatusSettings {
+        if let Some(scale) = self.scale {
+            settings = settings.with_pixel_size(scale, scale);
+        }
+        match self.display {
+            DisplayMode::Windowed => {}
+            DisplayMode::Borderless => {
+                settings = settings
+                    .with_borderless_window(true)
+                    .with_topmost_window(true);
+            }
+            DisplayMode::Fullscreen => settings = settings.with_fullscreen_window(true),
+        }
+        if self.record {
+            settings = settings.with_gif_recorder(RECORD_DIRECTORY, 10.0, 2, RECORD_KEY);
+        }
+        if let Some(replay) = &self.replay {
+            settings = settings.with_replay(replay);
+        }
+        if self.headless {
+            settings = settings.with_headless(true);
+        }
+
+        settings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<LaunchOptions, LauncherError> {
+        LaunchOptions::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn common_flags_are_parsed() {
+        let options = parse(&["--borderless", "--scale", "3"]).unwrap();
+
+        assert_eq!(DisplayMode::Borderless, options.display);
+        assert_eq!(Some(3), options.scale);
+        assert!(options.remaining.is_empty());
+    }
+
+    #[test]
+    fn run_modes_are_parsed() {
+        let options = parse(&[
+            "--fullscreen",
+            "--record",
+            "--replay",
+            "run.aprp",
+            "--headless",
+        ])
+        .unwrap();
+
+        assert_eq!(DisplayMode::Fullscreen, options.display);
+        assert!(options.record);
+        assert_eq!(Some(PathBuf::from("run.aprp")), options.replay);
+        assert!(options.headless);
+        assert!(options.remaining.is_empty());
+    }
+
+    #[test]
+    fn unknown_arguments_are_left_for_the_game() {
+        let options = parse(&["--level", "2", "--god-mode"]).unwrap();
+
+        assert_eq!(vec!["--level", "2", "--god-mode"], options.remaining);
+    }
+
+    #[test]
+    fn invalid_values_are_rejected() {
+        assert_eq!(
+            Err(LauncherError::InvalidScale("0".to_string())),
+            parse(&["--scale", "0"])
+        );
+        assert_eq!(
+            Err(LauncherError::MissingValue("--scale".to_string())),
+            parse(&["--scale"])
+        );
+        assert_eq!(
+            Err(LauncherError::MissingValue("--replay".to_string())),
+            parse(&["--replay"])
+        );
+    }
+}
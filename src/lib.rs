@@ -1,8 +1,16 @@
+pub mod capabilities;
 pub mod color;
 pub mod engine;
 pub mod errors;
 pub mod font;
+pub mod launcher;
 pub mod maths;
+pub mod net;
+pub mod physics;
 pub mod platform;
+pub mod procgen;
 pub mod renderer;
+pub mod ui;
 pub mod util;
+
+pub use capabilities::version;
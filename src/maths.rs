@@ -1,7 +1,11 @@
-use std::ops::Add;
+use std::ops::{Add, Mul, Neg, Sub};
+
+pub mod polygon;
+pub mod sampling;
+pub mod spline;
 
 // TODO: Use a maths library and re-export it; or, these are probably good candidates for macros.
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub struct Vec2 {
     pub x: f32,
     pub y: f32,
@@ -11,6 +15,152 @@ impl Vec2 {
     pub fn new(x: f32, y: f32) -> Self {
         Self { x, y }
     }
+
+    pub fn dot(&self, rhs: Vec2) -> f32 {
+        self.x * rhs.x + self.y * rhs.y
+    }
+
+    pub fn length(&self) -> f32 {
+        self.dot(*self).sqrt()
+    }
+
+    /// A vector of length 1 in the same direction, or zero if the vector has no length.
+    pub fn normalized(&self) -> Vec2 {
+        let length = self.length();
+        if length > 0.0 {
+            Vec2::new(self.x / length, self.y / length)
+        } else {
+            Vec2::new(0.0, 0.0)
+        }
+    }
+}
+
+impl Add for Vec2 {
+    type Output = Vec2;
+
+    fn add(self, rhs: Vec2) -> Self::Output {
+        Self::Output::new(self.x + rhs.x, self.y + rhs.y)
+    }
+}
+
+impl Sub for Vec2 {
+    type Output = Vec2;
+
+    fn sub(self, rhs: Vec2) -> Self::Output {
+        Self::Output::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl Mul<f32> for Vec2 {
+    type Output = Vec2;
+
+    fn mul(self, rhs: f32) -> Self::Output {
+        Self::Output::new(self.x * rhs, self.y * rhs)
+    }
+}
+
+impl Neg for Vec2 {
+    type Output = Vec2;
+
+    fn neg(self) -> Self::Output {
+        Self::Output::new(-self.x, -self.y)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Rect {
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+}
+
+/// An oriented bounding box: a rectangle rotated about its center by `rotation` radians,
+/// counter-clockwise.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Obb {
+    pub center: Vec2,
+    pub half_extents: Vec2,
+    pub rotation: f32,
+}
+
+impl Obb {
+    pub fn new(center: Vec2, width: f32, height: f32, rotation: f32) -> Self {
+        Self {
+            center,
+            half_extents: Vec2::new(width / 2.0, height / 2.0),
+            rotation,
+        }
+    }
+
+    /// The box's local x and y axes as unit vectors.
+    pub fn axes(&self) -> [Vec2; 2] {
+        let (sin, cos) = self.rotation.sin_cos();
+        [Vec2::new(cos, sin), Vec2::new(-sin, cos)]
+    }
+
+    /// The corners in counter-clockwise order, starting bottom left of the unrotated box.
+    pub fn corners(&self) -> [Vec2; 4] {
+        let [x_axis, y_axis] = self.axes();
+        let x = x_axis * self.half_extents.x;
+        let y = y_axis * self.half_extents.y;
+
+        [
+            self.center - x - y,
+            self.center + x - y,
+            self.center + x + y,
+            self.center - x + y,
+        ]
+    }
+
+    pub fn contains(&self, point: Vec2) -> bool {
+        let offset = point - self.center;
+        let [x_axis, y_axis] = self.axes();
+
+        offset.dot(x_axis).abs() <= self.half_extents.x
+            && offset.dot(y_axis).abs() <= self.half_extents.y
+    }
+
+    /// Whether the boxes overlap, using the separating axis theorem.
+    pub fn intersects(&self, other: &Obb) -> bool {
+        let (a, b) = (self.corners(), other.corners());
+        let project = |corners: &[Vec2; 4], axis: Vec2| {
+            corners
+                .iter()
+                .map(|corner| corner.dot(axis))
+                .fold((f32::MAX, f32::MIN), |(min, max), d| {
+                    (min.min(d), max.max(d))
+                })
+        };
+
+        self.axes().into_iter().chain(other.axes()).all(|axis| {
+            let (a_min, a_max) = project(&a, axis);
+            let (b_min, b_max) = project(&b, axis);
+            a_min <= b_max && b_min <= a_max
+        })
+    }
+}
+
+impl From<Rect> for Obb {
+    fn from(rect: Rect) -> Self {
+        Obb::new(
+            Vec2::new(rect.x + rect.width / 2.0, rect.y + rect.height / 2.0),
+            rect.width,
+            rect.height,
+            0.0,
+        )
+    }
 }
 
 impl Add<f32> for Vec2 {
@@ -36,6 +186,84 @@ pub fn lerp(src: f32, dst: f32, t: f32) -> f32 {
     // Or: `dst + (src - dst) * t`.
 }
 
+/// Move `current` towards `target`, closing the same fraction of the gap each second regardless
+/// of frame rate. Use instead of `current += (target - current) * rate * dt`, which overshoots
+/// and behaves differently as `dt` varies.
+pub fn exp_decay(current: f32, target: f32, rate: f32, dt: f32) -> f32 {
+    target + (current - target) * (-rate * dt).exp()
+}
+
+pub fn exp_decay_vec2(current: Vec2, target: Vec2, rate: f32, dt: f32) -> Vec2 {
+    Vec2::new(
+        exp_decay(current.x, target.x, rate, dt),
+        exp_decay(current.y, target.y, rate, dt),
+    )
+}
+
+/// Like `exp_decay`, but expressed as the time taken to close half the gap.
+pub fn damp_half_life(current: f32, target: f32, half_life: f32, dt: f32) -> f32 {
+    if half_life <= 0.0 {
+        return target;
+    }
+
+    exp_decay(current, target, std::f32::consts::LN_2 / half_life, dt)
+}
+
+/// Move `current` towards `target` like a critically damped spring, easing in and out without
+/// overshooting. `velocity` carries the spring's speed between frames and `smooth_time` is roughly
+/// how long it takes to arrive.
+pub fn smooth_damp(
+    current: f32,
+    target: f32,
+    velocity: &mut f32,
+    smooth_time: f32,
+    dt: f32,
+) -> f32 {
+    let smooth_time = smooth_time.max(0.0001);
+    let omega = 2.0 / smooth_time;
+    let x = omega * dt;
+    let decay = 1.0 / (1.0 + x + 0.48 * x * x + 0.235 * x * x * x);
+
+    let change = current - target;
+    let temp = (*velocity + omega * change) * dt;
+    *velocity = (*velocity - omega * temp) * decay;
+    let mut result = target + (change + temp) * decay;
+
+    // Don't overshoot the target.
+    if (target - current > 0.0) == (result > target) {
+        result = target;
+        *velocity = 0.0;
+    }
+
+    result
+}
+
+/// How a transition progresses over its duration.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Easing {
+    #[default]
+    Linear,
+    /// Start slowly and speed up.
+    EaseIn,
+    /// Start quickly and slow down.
+    EaseOut,
+    /// Start and finish slowly.
+    EaseInOut,
+}
+
+impl Easing {
+    /// Map progress `t` from 0 to 1 onto the eased progress, also from 0 to 1.
+    pub fn apply(self, t: f32) -> f32 {
+        let t = clamp(0.0, t, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t,
+            Easing::EaseOut => t * (2.0 - t),
+            Easing::EaseInOut => t * t * (3.0 - 2.0 * t),
+        }
+    }
+}
+
 #[cfg(test)]
 mod maths_tests {
     use super::*;
@@ -70,4 +298,86 @@ mod maths_tests {
 
         assert_eq!(Vec2::new(7.0, 9.0), vec + 4.0);
     }
+
+    #[test]
+    fn vec2_addition_and_subtraction_are_component_wise() {
+        let a = Vec2::new(3.0, 5.0);
+        let b = Vec2::new(1.0, 2.0);
+
+        assert_eq!(Vec2::new(4.0, 7.0), a + b);
+        assert_eq!(Vec2::new(2.0, 3.0), a - b);
+    }
+
+    #[test]
+    fn rotated_obb_contains_points_along_its_axes() {
+        let obb = Obb::new(Vec2::new(0.0, 0.0), 10.0, 2.0, std::f32::consts::FRAC_PI_2);
+
+        assert!(obb.contains(Vec2::new(0.0, 4.0)));
+        assert!(!obb.contains(Vec2::new(4.0, 0.0)));
+    }
+
+    #[test]
+    fn obb_intersection_uses_separating_axes() {
+        let diamond = Obb::new(Vec2::new(0.0, 0.0), 2.0, 2.0, std::f32::consts::FRAC_PI_4);
+        // Overlaps the diamond's bounding box but not the diamond itself.
+        let corner = Obb::from(Rect::new(0.8, 0.8, 1.0, 1.0));
+        let touching = Obb::from(Rect::new(0.5, -0.5, 1.0, 1.0));
+
+        assert!(!diamond.intersects(&corner));
+        assert!(diamond.intersects(&touching));
+        assert!(touching.intersects(&diamond));
+    }
+
+    #[test]
+    fn exp_decay_is_frame_rate_independent() {
+        let one_step = exp_decay(0.0, 10.0, 5.0, 1.0 / 30.0);
+        let two_steps = exp_decay(exp_decay(0.0, 10.0, 5.0, 1.0 / 60.0), 10.0, 5.0, 1.0 / 60.0);
+
+        assert!((one_step - two_steps).abs() < 1e-5);
+        assert!(one_step < 10.0);
+    }
+
+    #[test]
+    fn damp_half_life_halves_the_gap() {
+        assert!((damp_half_life(0.0, 10.0, 0.5, 0.5) - 5.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn smooth_damp_arrives_without_overshooting() {
+        let mut value = 0.0;
+        let mut velocity = 0.0;
+
+        for _ in 0..120 {
+            value = smooth_damp(value, 10.0, &mut velocity, 0.3, 1.0 / 60.0);
+            assert!(value <= 10.0);
+        }
+
+        assert!((value - 10.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn vec2_dot_product_and_length() {
+        let vec = Vec2::new(3.0, 4.0);
+
+        assert_eq!(11.0, vec.dot(Vec2::new(1.0, 2.0)));
+        assert_eq!(5.0, vec.length());
+        assert_eq!(Vec2::new(0.6, 0.8), vec.normalized());
+    }
+
+    #[test]
+    fn easing_starts_at_zero_and_finishes_at_one() {
+        for easing in [
+            Easing::Linear,
+            Easing::EaseIn,
+            Easing::EaseOut,
+            Easing::EaseInOut,
+        ] {
+            assert_eq!(0.0, easing.apply(-1.0));
+            assert_eq!(1.0, easing.apply(2.0));
+        }
+
+        assert!(Easing::EaseIn.apply(0.5) < 0.5);
+        assert!(Easing::EaseOut.apply(0.5) > 0.5);
+        assert_eq!(0.5, Easing::EaseInOut.apply(0.5));
+    }
 }
@@ -0,0 +1,411 @@
+use crate::maths::Vec2;
+
+/// Nudge applied to the clip polygon when vertices land exactly on the other polygon's edges,
+/// which the clipping algorithm can't handle directly.
+const PERTURBATION: f32 = 1e-4;
+
+/// A simple (non self-intersecting) polygon. The last point connects back to the first.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Polygon {
+    pub points: Vec<Vec2>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Operation {
+    Intersection,
+    Union,
+    Difference,
+}
+
+/// Where an edge of the subject crosses an edge of the clip polygon, with how far along each
+/// edge the crossing is.
+#[derive(Copy, Clone, Debug)]
+struct Crossing {
+    subject_edge: usize,
+    subject_alpha: f32,
+    clip_edge: usize,
+    clip_alpha: f32,
+    point: Vec2,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Node {
+    point: Vec2,
+    intersection: bool,
+    entry: bool,
+    neighbour: usize,
+    visited: bool,
+}
+
+impl Polygon {
+    pub fn new(points: Vec<Vec2>) -> Self {
+        Self { points }
+    }
+
+    /// An axis aligned rectangle with its bottom left corner at (x, y).
+    pub fn rectangle(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Self::new(vec![
+            Vec2::new(x, y),
+            Vec2::new(x + width, y),
+            Vec2::new(x + width, y + height),
+            Vec2::new(x, y + height),
+        ])
+    }
+
+    /// The area, positive when the points wind counter-clockwise.
+    pub fn signed_area(&self) -> f32 {
+        self.edges()
+            .map(|(a, b)| a.x * b.y - b.x * a.y)
+            .sum::<f32>()
+            / 2.0
+    }
+
+    pub fn area(&self) -> f32 {
+        self.signed_area().abs()
+    }
+
+    pub fn contains(&self, point: Vec2) -> bool {
+        let mut inside = false;
+        for (a, b) in self.edges() {
+            if (a.y > point.y) != (b.y > point.y)
+                && point.x < a.x + (point.y - a.y) / (b.y - a.y) * (b.x - a.x)
+            {
+                inside = !inside;
+            }
+        }
+
+        inside
+    }
+
+    pub fn translated(&self, offset: Vec2) -> Polygon {
+        Polygon::new(self.points.iter().map(|&point| point + offset).collect())
+    }
+
+    fn edges(&self) -> impl Iterator<Item = (Vec2, Vec2)> + '_ {
+        let count = self.points.len();
+        (0..count).map(move |i| (self.points[i], self.points[(i + 1) % count]))
+    }
+
+    /// The areas covered by both polygons.
+    pub fn intersection(&self, other: &Polygon) -> Vec<Polygon> {
+        clip(self, other, Operation::Intersection)
+    }
+
+    /// The areas covered by either polygon. Holes enclosed by the result are returned as extra
+    /// polygons winding the opposite way to the outline.
+    pub fn union(&self, other: &Polygon) -> Vec<Polygon> {
+        clip(self, other, Operation::Union)
+    }
+
+    /// The areas of this polygon not covered by `other`, e.g. terrain with a crater cut out.
+    /// When `other` is entirely inside this polygon, the hole is returned as a second polygon
+    /// winding the opposite way to the first.
+    pub fn difference(&self, other: &Polygon) -> Vec<Polygon> {
+        clip(self, other, Operation::Difference)
+    }
+}
+
+/// Greiner-Hormann polygon clipping.
+fn clip(subject: &Polygon, clip: &Polygon, operation: Operation) -> Vec<Polygon> {
+    if subject.points.len() < 3 || clip.points.len() < 3 {
+        return match operation {
+            Operation::Intersection => Vec::new(),
+            Operation::Union => [subject, clip]
+                .into_iter()
+                .filter(|polygon| polygon.points.len() >= 3)
+                .cloned()
+                .collect(),
+            Operation::Difference if subject.points.len() >= 3 => vec![subject.clone()],
+            Operation::Difference => Vec::new(),
+        };
+    }
+
+    let mut clip = clip.clone();
+    let (mut subject_nodes, mut clip_nodes) = loop {
+        match build_nodes(subject, &clip) {
+            Some(nodes) => break nodes,
+            None => clip = clip.translated(Vec2::new(PERTURBATION, PERTURBATION * 0.7)),
+        }
+    };
+
+    if !subject_nodes.iter().any(|node| node.intersection) {
+        return without_crossings(subject, &clip, operation);
+    }
+
+    let (subject_entry, clip_entry) = {
+        let subject_inside = clip.contains(subject.points[0]);
+        let clip_inside = subject.contains(clip.points[0]);
+        match operation {
+            Operation::Intersection => (!subject_inside, !clip_inside),
+            Operation::Union => (subject_inside, clip_inside),
+            Operation::Difference => (subject_inside, !clip_inside),
+        }
+    };
+    mark_entries(&mut subject_nodes, subject_entry);
+    mark_entries(&mut clip_nodes, clip_entry);
+
+    let mut polygons = Vec::new();
+    while let Some(start) = subject_nodes
+        .iter()
+        .position(|node| node.intersection && !node.visited)
+    {
+        let mut points = vec![subject_nodes[start].point];
+        let mut on_subject = true;
+        let mut current = start;
+
+        loop {
+            let nodes = if on_subject {
+                &mut subject_nodes
+            } else {
+                &mut clip_nodes
+            };
+            nodes[current].visited = true;
+            let forward = nodes[current].entry;
+            let count = nodes.len();
+
+            loop {
+                current = if forward {
+                    (current + 1) % count
+                } else {
+                    (current + count - 1) % count
+                };
+                points.push(nodes[current].point);
+                if nodes[current].intersection {
+                    break;
+                }
+            }
+            nodes[current].visited = true;
+
+            current = nodes[current].neighbour;
+            on_subject = !on_subject;
+            let nodes = if on_subject {
+                &subject_nodes
+            } else {
+                &clip_nodes
+            };
+            if nodes[current].visited {
+                break;
+            }
+        }
+
+        if points.len() > 1 && points.first() == points.last() {
+            points.pop();
+        }
+        if points.len() >= 3 {
+            polygons.push(Polygon::new(points));
+        }
+    }
+
+    polygons
+}
+
+/// Interleave each polygon's vertices with its edge crossings, linking matching crossings.
+/// Returns `None` when a crossing lands on a vertex, which needs the polygons nudging apart.
+fn build_nodes(subject: &Polygon, clip: &Polygon) -> Option<(Vec<Node>, Vec<Node>)> {
+    let mut crossings = Vec::new();
+    for (i, (a, b)) in subject.edges().enumerate() {
+        for (j, (c, d)) in clip.edges().enumerate() {
+            let r = b - a;
+            let s = d - c;
+            let denominator = r.x * s.y - r.y * s.x;
+            if denominator == 0.0 {
+                let collinear = (c - a).x * r.y - (c - a).y * r.x == 0.0;
+                if collinear && overlaps_on_line(a, b, c, d) {
+                    return None;
+                }
+                continue;
+            }
+
+            let t = ((c - a).x * s.y - (c - a).y * s.x) / denominator;
+            let u = ((c - a).x * r.y - (c - a).y * r.x) / denominator;
+            let touches = |value: f32| value.abs() < 1e-6 || (value - 1.0).abs() < 1e-6;
+            if (0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u) {
+                if touches(t) || touches(u) {
+                    return None;
+                }
+                crossings.push(Crossing {
+                    subject_edge: i,
+                    subject_alpha: t,
+                    clip_edge: j,
+                    clip_alpha: u,
+                    point: a + r * t,
+                });
+            }
+        }
+    }
+
+    let subject_order = interleave(subject, &crossings, |c| (c.subject_edge, c.subject_alpha));
+    let clip_order = interleave(clip, &crossings, |c| (c.clip_edge, c.clip_alpha));
+
+    let nodes = |order: &[(Vec2, Option<usize>)]| -> Vec<Node> {
+        order
+            .iter()
+            .map(|&(point, crossing)| Node {
+                point,
+                intersection: crossing.is_some(),
+                entry: false,
+                neighbour: 0,
+                visited: false,
+            })
+            .collect()
+    };
+    let mut subject_nodes = nodes(&subject_order);
+    let mut clip_nodes = nodes(&clip_order);
+
+    let position_of = |order: &[(Vec2, Option<usize>)], crossing: usize| {
+        order
+            .iter()
+            .position(|&(_, c)| c == Some(crossing))
+            .expect("every crossing is on both polygons")
+    };
+    for crossing in 0..crossings.len() {
+        let s = position_of(&subject_order, crossing);
+        let c = position_of(&clip_order, crossing);
+        subject_nodes[s].neighbour = c;
+        clip_nodes[c].neighbour = s;
+    }
+
+    Some((subject_nodes, clip_nodes))
+}
+
+/// The polygon's points with crossings inserted along each edge in order, tagged with the index
+/// of the crossing.
+fn interleave(
+    polygon: &Polygon,
+    crossings: &[Crossing],
+    edge_and_alpha: impl Fn(&Crossing) -> (usize, f32),
+) -> Vec<(Vec2, Option<usize>)> {
+    let mut order = Vec::new();
+    for (edge, &point) in polygon.points.iter().enumerate() {
+        order.push((point, None));
+
+        let mut on_edge: Vec<(f32, usize)> = crossings
+            .iter()
+            .enumerate()
+            .filter_map(|(index, crossing)| {
+                let (crossing_edge, alpha) = edge_and_alpha(crossing);
+                (crossing_edge == edge).then_some((alpha, index))
+            })
+            .collect();
+        on_edge.sort_by(|a, b| a.0.total_cmp(&b.0));
+        order.extend(
+            on_edge
+                .into_iter()
+                .map(|(_, index)| (crossings[index].point, Some(index))),
+        );
+    }
+
+    order
+}
+
+fn overlaps_on_line(a: Vec2, b: Vec2, c: Vec2, d: Vec2) -> bool {
+    let axis = b - a;
+    let project = |point: Vec2| (point - a).dot(axis);
+    let (c, d) = (project(c), project(d));
+    let length = axis.dot(axis);
+
+    c.max(d) >= 0.0 && c.min(d) <= length
+}
+
+fn mark_entries(nodes: &mut [Node], mut entry: bool) {
+    for node in nodes.iter_mut().filter(|node| node.intersection) {
+        node.entry = entry;
+        entry = !entry;
+    }
+}
+
+/// Results when the outlines never cross: one polygon is inside the other or they're apart.
+fn without_crossings(subject: &Polygon, clip: &Polygon, operation: Operation) -> Vec<Polygon> {
+    let subject_in_clip = clip.contains(subject.points[0]);
+    let clip_in_subject = subject.contains(clip.points[0]);
+
+    match operation {
+        Operation::Intersection if subject_in_clip => vec![subject.clone()],
+        Operation::Intersection if clip_in_subject => vec![clip.clone()],
+        Operation::Intersection => Vec::new(),
+        Operation::Union if subject_in_clip => vec![clip.clone()],
+        Operation::Union if clip_in_subject => vec![subject.clone()],
+        Operation::Union => vec![subject.clone(), clip.clone()],
+        Operation::Difference if subject_in_clip => Vec::new(),
+        Operation::Difference if clip_in_subject => {
+            let mut hole = clip.clone();
+            if hole.signed_area().signum() == subject.signed_area().signum() {
+                hole.points.reverse();
+            }
+            vec![subject.clone(), hole]
+        }
+        Operation::Difference => vec![subject.clone()],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn total_area(polygons: &[Polygon]) -> f32 {
+        polygons.iter().map(Polygon::signed_area).sum::<f32>().abs()
+    }
+
+    #[test]
+    fn area_and_containment() {
+        let square = Polygon::rectangle(0.0, 0.0, 4.0, 2.0);
+
+        assert_eq!(8.0, square.signed_area());
+        assert!(square.contains(Vec2::new(1.0, 1.0)));
+        assert!(!square.contains(Vec2::new(5.0, 1.0)));
+    }
+
+    #[test]
+    fn overlapping_squares() {
+        let a = Polygon::rectangle(0.0, 0.0, 4.0, 4.0);
+        let b = Polygon::rectangle(2.0, 1.0, 4.0, 2.0);
+
+        let intersection = a.intersection(&b);
+        assert_eq!(1, intersection.len());
+        assert!((total_area(&intersection) - 4.0).abs() < 1e-3);
+
+        let union = a.union(&b);
+        assert_eq!(1, union.len());
+        assert!((total_area(&union) - 20.0).abs() < 1e-3);
+
+        let difference = a.difference(&b);
+        assert_eq!(1, difference.len());
+        assert!((total_area(&difference) - 12.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn cutting_through_splits_terrain_in_two() {
+        let terrain = Polygon::rectangle(0.0, 0.0, 10.0, 2.0);
+        let cut = Polygon::rectangle(4.0, -1.0, 2.0, 4.0);
+
+        let pieces = terrain.difference(&cut);
+
+        assert_eq!(2, pieces.len());
+        for piece in &pieces {
+            assert!((piece.area() - 8.0).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn shared_vertices_are_handled() {
+        let a = Polygon::rectangle(0.0, 0.0, 2.0, 2.0);
+        let b = Polygon::rectangle(1.0, 1.0, 2.0, 2.0);
+        let c = Polygon::rectangle(0.0, 0.0, 1.0, 2.0);
+
+        assert!((total_area(&a.intersection(&b)) - 1.0).abs() < 1e-2);
+        assert!((total_area(&a.difference(&c)) - 2.0).abs() < 1e-2);
+    }
+
+    #[test]
+    fn contained_and_separate_polygons() {
+        let outer = Polygon::rectangle(0.0, 0.0, 10.0, 10.0);
+        let inner = Polygon::rectangle(2.0, 2.0, 2.0, 2.0);
+        let apart = Polygon::rectangle(20.0, 0.0, 2.0, 2.0);
+
+        assert_eq!(vec![inner.clone()], outer.intersection(&inner));
+        assert_eq!(vec![outer.clone()], outer.union(&inner));
+        assert!((total_area(&outer.difference(&inner)) - 96.0).abs() < 1e-3);
+        assert!(outer.intersection(&apart).is_empty());
+        assert_eq!(2, outer.union(&apart).len());
+    }
+}
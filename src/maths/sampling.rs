@@ -0,0 +1,160 @@
+use crate::maths::{Rect, Vec2};
+use crate::procgen::rng::Rng;
+
+/// Points scattered over `area` so that no two are closer than `min_distance`, but with no large
+/// gaps either (Bridson's Poisson-disk sampling). `attempts` is how many candidates are tried
+/// around each point before giving up on it; 30 is a good default.
+pub fn poisson_disk(area: Rect, min_distance: f32, attempts: u32, rng: &mut Rng) -> Vec<Vec2> {
+    if min_distance <= 0.0 || area.width <= 0.0 || area.height <= 0.0 {
+        return Vec::new();
+    }
+
+    // Each grid cell is small enough to hold at most one point.
+    let cell_size = min_distance / std::f32::consts::SQRT_2;
+    let columns = (area.width / cell_size).ceil() as usize;
+    let rows = (area.height / cell_size).ceil() as usize;
+    let mut grid: Vec<Option<usize>> = vec![None; columns * rows];
+    let cell_of = |point: Vec2| {
+        let column = (((point.x - area.x) / cell_size) as usize).min(columns - 1);
+        let row = (((point.y - area.y) / cell_size) as usize).min(rows - 1);
+        (column, row)
+    };
+
+    let mut points = Vec::new();
+    let mut active = Vec::new();
+
+    let first = Vec2::new(
+        rng.range_f32(area.x, area.x + area.width),
+        rng.range_f32(area.y, area.y + area.height),
+    );
+    let (column, row) = cell_of(first);
+    grid[row * columns + column] = Some(0);
+    points.push(first);
+    active.push(0);
+
+    while !active.is_empty() {
+        let slot = rng.index(active.len());
+        let origin = points[active[slot]];
+        let mut found = false;
+
+        for _ in 0..attempts {
+            let angle = rng.range_f32(0.0, std::f32::consts::TAU);
+            let distance = rng.range_f32(min_distance, 2.0 * min_distance);
+            let candidate = origin + Vec2::new(angle.cos(), angle.sin()) * distance;
+
+            if candidate.x < area.x
+                || candidate.y < area.y
+                || candidate.x >= area.x + area.width
+                || candidate.y >= area.y + area.height
+            {
+                continue;
+            }
+
+            let (column, row) = cell_of(candidate);
+            let too_close = (row.saturating_sub(2)..(row + 3).min(rows))
+                .flat_map(|r| {
+                    (column.saturating_sub(2)..(column + 3).min(columns)).map(move |c| (r, c))
+                })
+                .filter_map(|(r, c)| grid[r * columns + c])
+                .any(|i| (points[i] - candidate).length() < min_distance);
+
+            if !too_close {
+                grid[row * columns + column] = Some(points.len());
+                active.push(points.len());
+                points.push(candidate);
+                found = true;
+                break;
+            }
+        }
+
+        if !found {
+            active.swap_remove(slot);
+        }
+    }
+
+    points
+}
+
+/// The `index`th element of the Halton sequence in `base`, in [0, 1). Consecutive indices spread
+/// evenly over the range instead of clumping like random numbers.
+pub fn halton(mut index: u32, base: u32) -> f32 {
+    let mut fraction = 1.0;
+    let mut result = 0.0;
+
+    while index > 0 {
+        fraction /= base as f32;
+        result += fraction * (index % base) as f32;
+        index /= base;
+    }
+
+    result
+}
+
+/// The `index`th point of the 2D Halton sequence (bases 2 and 3), in the unit square.
+pub fn halton_2d(index: u32) -> Vec2 {
+    Vec2::new(halton(index + 1, 2), halton(index + 1, 3))
+}
+
+/// The `index`th point of the R2 sequence, in the unit square. Cheaper than Halton and evenly
+/// spread for any number of points, which makes it a good fit for emission patterns.
+pub fn r2(index: u32) -> Vec2 {
+    // The plastic number, the 2D generalisation of the golden ratio.
+    const G: f64 = 1.324_717_957_244_746;
+    let n = index as f64 + 1.0;
+
+    Vec2::new(
+        (0.5 + n / G).fract() as f32,
+        (0.5 + n / (G * G)).fract() as f32,
+    )
+}
+
+/// `count` evenly spread points over `area` from the R2 sequence, starting at `offset` so
+/// successive calls can continue the pattern.
+pub fn scatter(area: Rect, count: u32, offset: u32) -> Vec<Vec2> {
+    (offset..offset + count)
+        .map(|i| {
+            let point = r2(i);
+            Vec2::new(
+                area.x + point.x * area.width,
+                area.y + point.y * area.height,
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn poisson_disk_points_are_spaced_and_inside_area() {
+        let area = Rect::new(10.0, 20.0, 100.0, 50.0);
+        let points = poisson_disk(area, 8.0, 30, &mut Rng::new(4));
+
+        assert!(points.len() > 40);
+        for (i, a) in points.iter().enumerate() {
+            assert!(a.x >= 10.0 && a.x < 110.0 && a.y >= 20.0 && a.y < 70.0);
+            for b in &points[i + 1..] {
+                assert!((*a - *b).length() >= 8.0);
+            }
+        }
+    }
+
+    #[test]
+    fn halton_base_2_subdivides_evenly() {
+        let values: Vec<f32> = (1..5).map(|i| halton(i, 2)).collect();
+
+        assert_eq!(vec![0.5, 0.25, 0.75, 0.125], values);
+    }
+
+    #[test]
+    fn scattered_points_cover_every_quadrant() {
+        let points = scatter(Rect::new(0.0, 0.0, 2.0, 2.0), 8, 0);
+
+        for (qx, qy) in [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0), (1.0, 1.0)] {
+            assert!(points
+                .iter()
+                .any(|p| p.x >= qx && p.x < qx + 1.0 && p.y >= qy && p.y < qy + 1.0));
+        }
+    }
+}
@@ -0,0 +1,250 @@
+use crate::maths::Vec2;
+
+/// Samples taken along each segment when measuring arc length.
+const SAMPLES_PER_SEGMENT: usize = 16;
+
+/// A cubic bezier curve from `p0` to `p3`, pulled towards the control points `p1` and `p2`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CubicBezier {
+    pub p0: Vec2,
+    pub p1: Vec2,
+    pub p2: Vec2,
+    pub p3: Vec2,
+}
+
+impl CubicBezier {
+    pub fn new(p0: Vec2, p1: Vec2, p2: Vec2, p3: Vec2) -> Self {
+        Self { p0, p1, p2, p3 }
+    }
+
+    /// The point at parameter `u` in [0, 1]. Note that equal steps in `u` are not equal distances
+    /// along the curve; use `Spline` for that.
+    pub fn point(&self, u: f32) -> Vec2 {
+        let v = 1.0 - u;
+        self.p0 * (v * v * v)
+            + self.p1 * (3.0 * v * v * u)
+            + self.p2 * (3.0 * v * u * u)
+            + self.p3 * (u * u * u)
+    }
+
+    /// The derivative at parameter `u`.
+    pub fn derivative(&self, u: f32) -> Vec2 {
+        let v = 1.0 - u;
+        (self.p1 - self.p0) * (3.0 * v * v)
+            + (self.p2 - self.p1) * (6.0 * v * u)
+            + (self.p3 - self.p2) * (3.0 * u * u)
+    }
+}
+
+/// A smooth path made of cubic bezier segments, parameterized by arc length so that equal steps
+/// in `t` move equal distances along it. Useful for patrol routes and track centerlines.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Spline {
+    segments: Vec<CubicBezier>,
+    /// Cumulative length at each sample, `SAMPLES_PER_SEGMENT + 1` per segment.
+    lengths: Vec<f32>,
+    closed: bool,
+}
+
+impl Spline {
+    /// A path through consecutive segments, which should join end to start.
+    pub fn bezier_path(segments: Vec<CubicBezier>) -> Self {
+        let closed = match (segments.first(), segments.last()) {
+            (Some(first), Some(last)) => first.p0 == last.p3,
+            _ => false,
+        };
+
+        let mut lengths = Vec::with_capacity(segments.len() * (SAMPLES_PER_SEGMENT + 1));
+        let mut total = 0.0;
+        for segment in &segments {
+            let mut previous = segment.p0;
+            lengths.push(total);
+            for i in 1..=SAMPLES_PER_SEGMENT {
+                let point = segment.point(i as f32 / SAMPLES_PER_SEGMENT as f32);
+                total += (point - previous).length();
+                lengths.push(total);
+                previous = point;
+            }
+        }
+
+        Self {
+            segments,
+            lengths,
+            closed,
+        }
+    }
+
+    /// A Catmull-Rom spline passing through every point. A closed spline loops back from the last
+    /// point to the first.
+    pub fn catmull_rom(points: &[Vec2], closed: bool) -> Self {
+        let n = points.len();
+        if n < 2 {
+            return Self::bezier_path(Vec::new());
+        }
+
+        let at = |i: isize| {
+            if closed {
+                points[i.rem_euclid(n as isize) as usize]
+            } else {
+                points[i.clamp(0, n as isize - 1) as usize]
+            }
+        };
+
+        let count = if closed { n } else { n - 1 };
+        let segments = (0..count as isize)
+            .map(|i| {
+                let (p0, p1, p2, p3) = (at(i - 1), at(i), at(i + 1), at(i + 2));
+                CubicBezier::new(
+                    p1,
+                    p1 + (p2 - p0) * (1.0 / 6.0),
+                    p2 - (p3 - p1) * (1.0 / 6.0),
+                    p2,
+                )
+            })
+            .collect();
+
+        Self::bezier_path(segments)
+    }
+
+    pub fn segments(&self) -> &[CubicBezier] {
+        &self.segments
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    pub fn length(&self) -> f32 {
+        self.lengths.last().copied().unwrap_or(0.0)
+    }
+
+    /// The point a fraction `t` of the way along the path. Closed paths wrap, open paths clamp.
+    pub fn sample(&self, t: f32) -> Vec2 {
+        self.sample_distance(t * self.length())
+    }
+
+    /// The unit direction of travel a fraction `t` of the way along the path.
+    pub fn tangent(&self, t: f32) -> Vec2 {
+        self.tangent_distance(t * self.length())
+    }
+
+    /// The point `distance` along the path.
+    pub fn sample_distance(&self, distance: f32) -> Vec2 {
+        match self.locate(distance) {
+            Some((segment, u)) => self.segments[segment].point(u),
+            None => Vec2::default(),
+        }
+    }
+
+    pub fn tangent_distance(&self, distance: f32) -> Vec2 {
+        match self.locate(distance) {
+            Some((segment, u)) => self.segments[segment].derivative(u).normalized(),
+            None => Vec2::default(),
+        }
+    }
+
+    /// Points at most `spacing` apart along the whole path, including both ends, for drawing.
+    pub fn points(&self, spacing: f32) -> Vec<Vec2> {
+        let length = self.length();
+        if self.segments.is_empty() || spacing <= 0.0 {
+            return Vec::new();
+        }
+
+        let steps = (length / spacing).ceil().max(1.0) as usize;
+        (0..=steps)
+            .map(|i| self.sample_distance(length * i as f32 / steps as f32))
+            .collect()
+    }
+
+    /// The segment and bezier parameter `distance` along the path.
+    fn locate(&self, distance: f32) -> Option<(usize, f32)> {
+        let length = self.length();
+        if self.segments.is_empty() {
+            return None;
+        }
+
+        let distance = if self.closed && length > 0.0 {
+            distance.rem_euclid(length)
+        } else {
+            distance.clamp(0.0, length)
+        };
+
+        let stride = SAMPLES_PER_SEGMENT + 1;
+        let index = self
+            .lengths
+            .partition_point(|&l| l <= distance)
+            .clamp(1, self.lengths.len() - 1);
+        // Index 0 of each segment's samples repeats the previous segment's end.
+        let index = if index % stride == 0 {
+            index + 1
+        } else {
+            index
+        };
+        let index = index.min(self.lengths.len() - 1);
+
+        let (before, after) = (self.lengths[index - 1], self.lengths[index]);
+        let fraction = if after > before {
+            (distance - before) / (after - before)
+        } else {
+            0.0
+        };
+
+        let segment = index / stride;
+        let sample = (index % stride - 1) as f32 + fraction;
+        Some((segment, sample / SAMPLES_PER_SEGMENT as f32))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: Vec2, b: Vec2) -> bool {
+        (a - b).length() < 0.05
+    }
+
+    #[test]
+    fn catmull_rom_passes_through_its_points() {
+        let points = [
+            Vec2::new(0.0, 0.0),
+            Vec2::new(10.0, 5.0),
+            Vec2::new(20.0, 0.0),
+        ];
+        let spline = Spline::catmull_rom(&points, false);
+
+        assert!(close(points[0], spline.sample(0.0)));
+        assert!(close(points[2], spline.sample(1.0)));
+        assert!(close(points[1], spline.segments()[0].point(1.0)));
+    }
+
+    #[test]
+    fn straight_line_is_parameterized_by_arc_length() {
+        // Control points bunched at one end, so the raw parameter moves unevenly.
+        let spline = Spline::bezier_path(vec![CubicBezier::new(
+            Vec2::new(0.0, 0.0),
+            Vec2::new(1.0, 0.0),
+            Vec2::new(2.0, 0.0),
+            Vec2::new(10.0, 0.0),
+        )]);
+
+        assert!((spline.length() - 10.0).abs() < 0.01);
+        assert!(close(Vec2::new(2.5, 0.0), spline.sample(0.25)));
+        assert!(close(Vec2::new(5.0, 0.0), spline.sample_distance(5.0)));
+        assert!(close(Vec2::new(1.0, 0.0), spline.tangent(0.5)));
+    }
+
+    #[test]
+    fn closed_spline_wraps() {
+        let square = [
+            Vec2::new(0.0, 0.0),
+            Vec2::new(10.0, 0.0),
+            Vec2::new(10.0, 10.0),
+            Vec2::new(0.0, 10.0),
+        ];
+        let spline = Spline::catmull_rom(&square, true);
+
+        assert!(spline.is_closed());
+        assert!(close(spline.sample(0.25), spline.sample(1.25)));
+        assert!(close(square[0], spline.sample(1.0)));
+    }
+}
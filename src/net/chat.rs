@@ -0,0 +1,48 @@
+use serde::{Deserialize, Serialize};
+
+/// Marks a datagram as a chat message.
+const MAGIC: &str = "apparatus-chat-v1\n";
+
+/// A chat message as sent between peers.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct ChatMessage {
+    pub sender: String,
+    pub text: String,
+}
+
+impl ChatMessage {
+    pub fn new(sender: impl Into<String>, text: impl Into<String>) -> Self {
+        Self {
+            sender: sender.into(),
+            text: text.into(),
+        }
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        // Two strings always serialize.
+        let body = toml::to_string(self).unwrap_or_default();
+        format!("{}{}", MAGIC, body).into_bytes()
+    }
+
+    /// Decode a message, or `None` if the bytes aren't a chat message.
+    pub fn decode(bytes: &[u8]) -> Option<Self> {
+        let text = std::str::from_utf8(bytes).ok()?;
+        toml::from_str(text.strip_prefix(MAGIC)?).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn message_round_trips() {
+        let message = ChatMessage::new("Ada", "gg \"wp\"\nrematch?");
+
+        assert_eq!(
+            Some(message.clone()),
+            ChatMessage::decode(&message.encode())
+        );
+        assert_eq!(None, ChatMessage::decode(b"sender = \"Ada\""));
+    }
+}
@@ -0,0 +1,303 @@
+use std::io::ErrorKind;
+use std::net::{Ipv4Addr, SocketAddr, UdpSocket};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use socket2::{Domain, Protocol, Socket, Type};
+use thiserror::Error;
+
+/// Port announcements are broadcast on unless configured otherwise.
+pub const DEFAULT_DISCOVERY_PORT: u16 = 47_777;
+
+/// Marks a datagram as ours, so stray traffic on the port is ignored.
+const MAGIC: &str = "apparatus-discovery-v1\n";
+
+/// The longest announcement sent, in bytes, which listeners size their receive buffer to.
+pub const MAX_ANNOUNCEMENT_LEN: usize = 512;
+
+/// The longest session name that can be announced, in bytes.
+pub const MAX_NAME_LEN: usize = 64;
+
+#[derive(Debug, Error)]
+pub enum DiscoveryError {
+    #[error("discovery socket error")]
+    Io(#[from] std::io::Error),
+    #[error("unable to encode announcement")]
+    Encode(#[from] toml::ser::Error),
+    #[error("session name is {len} bytes, longer than the maximum of {MAX_NAME_LEN}")]
+    NameTooLong { len: usize },
+    #[error("announcement is {len} bytes, longer than the maximum of {MAX_ANNOUNCEMENT_LEN}")]
+    TooLong { len: usize },
+}
+
+/// What a hosted game tells the network about itself.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct Announcement {
+    /// Identifies the game, so different games on the same network don't see each other.
+    pub game: String,
+    /// Shown to players choosing a session, e.g. the host's name.
+    pub name: String,
+    pub players: u32,
+    pub max_players: u32,
+    /// Port the game itself accepts connections on.
+    pub port: u16,
+}
+
+impl Announcement {
+    fn encode(&self) -> Result<Vec<u8>, DiscoveryError> {
+        if self.name.len() > MAX_NAME_LEN {
+            return Err(DiscoveryError::NameTooLong {
+                len: self.name.len(),
+            });
+        }
+
+        let bytes = format!("{}{}", MAGIC, toml::to_string(self)?).into_bytes();
+        if bytes.len() > MAX_ANNOUNCEMENT_LEN {
+            return Err(DiscoveryError::TooLong { len: bytes.len() });
+        }
+
+        Ok(bytes)
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Self> {
+        let text = std::str::from_utf8(bytes).ok()?;
+        toml::from_str(text.strip_prefix(MAGIC)?).ok()
+    }
+}
+
+/// Periodically broadcasts an announcement while a game is being hosted.
+pub struct Announcer {
+    socket: UdpSocket,
+    target: SocketAddr,
+    announcement: Announcement,
+    interval: Duration,
+    last_sent: Option<Instant>,
+}
+
+impl Announcer {
+    /// Broadcast to the local network on `port`.
+    pub fn new(port: u16, announcement: Announcement) -> Result<Self, DiscoveryError> {
+        let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))?;
+        socket.set_broadcast(true)?;
+
+        Ok(Self {
+            socket,
+            target: SocketAddr::from((Ipv4Addr::BROADCAST, port)),
+            announcement,
+            interval: Duration::from_secs(1),
+            last_sent: None,
+        })
+    }
+
+    /// Send announcements to a specific address instead of broadcasting.
+    pub fn with_target(mut self, target: SocketAddr) -> Self {
+        self.target = target;
+        self
+    }
+
+    /// How often `update` announces. Defaults to once a second.
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    pub fn announcement(&self) -> &Announcement {
+        &self.announcement
+    }
+
+    /// Change the player count; it is sent straight away.
+    pub fn set_players(&mut self, players: u32) -> Result<(), DiscoveryError> {
+        self.announcement.players = players;
+        self.announce()
+    }
+
+    /// Send the announcement now.
+    pub fn announce(&mut self) -> Result<(), DiscoveryError> {
+        self.socket
+            .send_to(&self.announcement.encode()?, self.target)?;
+        self.last_sent = Some(Instant::now());
+        Ok(())
+    }
+
+    /// Call every frame; announces whenever the interval has passed.
+    pub fn update(&mut self) -> Result<(), DiscoveryError> {
+        if self
+            .last_sent
+            .is_none_or(|sent| sent.elapsed() >= self.interval)
+        {
+            self.announce()?;
+        }
+        Ok(())
+    }
+}
+
+/// A game found on the network.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiscoveredGame {
+    /// Where to connect to the game.
+    pub address: SocketAddr,
+    pub announcement: Announcement,
+    pub last_seen: Instant,
+}
+
+/// Listens for announcements of one game and keeps a list of the sessions currently being hosted.
+pub struct Listener {
+    socket: UdpSocket,
+    game: String,
+    timeout: Duration,
+    games: Vec<DiscoveredGame>,
+}
+
+impl Listener {
+    /// Listen on `port` for announcements whose game matches `game`. The port can be shared,
+    /// so several copies of a game on one machine can each find sessions.
+    pub fn bind(port: u16, game: impl Into<String>) -> Result<Self, DiscoveryError> {
+        let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))?;
+        socket.set_reuse_address(true)?;
+        socket.bind(&SocketAddr::from((Ipv4Addr::UNSPECIFIED, port)).into())?;
+        socket.set_nonblocking(true)?;
+        let socket = UdpSocket::from(socket);
+
+        Ok(Self {
+            socket,
+            game: game.into(),
+            timeout: Duration::from_secs(3),
+            games: Vec::new(),
+        })
+    }
+
+    /// Forget games not heard from for this long. Defaults to 3 seconds.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr, DiscoveryError> {
+        Ok(self.socket.local_addr()?)
+    }
+
+    /// Read any announcements that have arrived without blocking. Call every frame.
+    pub fn poll(&mut self) -> Result<(), DiscoveryError> {
+        // One byte spare, so longer datagrams, which can't be announcements, are noticed.
+        let mut buffer = [0; MAX_ANNOUNCEMENT_LEN + 1];
+
+        loop {
+            let (len, from) = match self.socket.recv_from(&mut buffer) {
+                Ok(received) => received,
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) => return Err(e.into()),
+            };
+
+            if len > MAX_ANNOUNCEMENT_LEN {
+                continue;
+            }
+
+            let announcement = match Announcement::decode(&buffer[..len]) {
+                Some(announcement) if announcement.game == self.game => announcement,
+                _ => continue,
+            };
+
+            let address = SocketAddr::new(from.ip(), announcement.port);
+            let found = DiscoveredGame {
+                address,
+                announcement,
+                last_seen: Instant::now(),
+            };
+            match self.games.iter_mut().find(|game| game.address == address) {
+                Some(game) => *game = found,
+                None => self.games.push(found),
+            }
+        }
+
+        let timeout = self.timeout;
+        self.games.retain(|game| game.last_seen.elapsed() < timeout);
+
+        Ok(())
+    }
+
+    /// Games currently being announced, in the order they were first seen.
+    pub fn games(&self) -> &[DiscoveredGame] {
+        &self.games
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn announcement(game: &str) -> Announcement {
+        Announcement {
+            game: game.to_string(),
+            name: "Host".to_string(),
+            players: 1,
+            max_players: 4,
+            port: 5000,
+        }
+    }
+
+    #[test]
+    fn announcement_round_trips_and_rejects_foreign_data() {
+        let encoded = announcement("worms").encode().unwrap();
+
+        assert_eq!(Some(announcement("worms")), Announcement::decode(&encoded));
+        assert_eq!(None, Announcement::decode(b"game = \"worms\""));
+    }
+
+    #[test]
+    fn announcing_a_long_name_is_an_error() {
+        let mut announcement = announcement("worms");
+        announcement.name = "x".repeat(MAX_NAME_LEN);
+        assert!(announcement.encode().is_ok());
+
+        announcement.name.push('x');
+        assert!(matches!(
+            announcement.encode(),
+            Err(DiscoveryError::NameTooLong { len }) if len == MAX_NAME_LEN + 1
+        ));
+    }
+
+    #[test]
+    fn listeners_can_share_a_port() {
+        let listener = Listener::bind(0, "worms").unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        assert!(Listener::bind(port, "worms").is_ok());
+    }
+
+    #[test]
+    fn listener_finds_announced_games() {
+        let mut listener = Listener::bind(0, "worms").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let target = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
+
+        let mut other = Announcer::new(port, announcement("racer"))
+            .unwrap()
+            .with_target(target);
+        let mut host = Announcer::new(port, announcement("worms"))
+            .unwrap()
+            .with_target(target);
+        other.announce().unwrap();
+        host.announce().unwrap();
+        host.set_players(2).unwrap();
+
+        for _ in 0..100 {
+            listener.poll().unwrap();
+            if listener
+                .games()
+                .first()
+                .is_some_and(|g| g.announcement.players == 2)
+            {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+
+        let games = listener.games();
+        assert_eq!(1, games.len());
+        assert_eq!(2, games[0].announcement.players);
+        assert_eq!(
+            SocketAddr::from((Ipv4Addr::LOCALHOST, 5000)),
+            games[0].address
+        );
+    }
+}
@@ -0,0 +1,267 @@
+use std::collections::VecDeque;
+
+use crate::maths::{exp_decay, Vec2};
+
+/// State received from a remote peer that can be blended between snapshots and predicted past
+/// the latest one.
+pub trait NetState: Clone {
+    /// The state a fraction `t` of the way from `self` to `to`.
+    fn interpolate(&self, to: &Self, t: f32) -> Self;
+
+    /// The state `dt` seconds after `self`, assuming it keeps doing what it was doing. Defaults
+    /// to holding still.
+    fn extrapolate(&self, _dt: f32) -> Self {
+        self.clone()
+    }
+}
+
+impl NetState for f32 {
+    fn interpolate(&self, to: &Self, t: f32) -> Self {
+        self + (to - self) * t
+    }
+}
+
+impl NetState for Vec2 {
+    fn interpolate(&self, to: &Self, t: f32) -> Self {
+        *self + (*to - *self) * t
+    }
+}
+
+/// A moving entity, predicted by dead reckoning along its velocity.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct EntityState {
+    pub position: Vec2,
+    pub velocity: Vec2,
+    /// Radians.
+    pub rotation: f32,
+}
+
+impl NetState for EntityState {
+    fn interpolate(&self, to: &Self, t: f32) -> Self {
+        // Turn the short way round.
+        let turn = (to.rotation - self.rotation + std::f32::consts::PI)
+            .rem_euclid(std::f32::consts::TAU)
+            - std::f32::consts::PI;
+
+        Self {
+            position: self.position.interpolate(&to.position, t),
+            velocity: self.velocity.interpolate(&to.velocity, t),
+            rotation: self.rotation + turn * t,
+        }
+    }
+
+    fn extrapolate(&self, dt: f32) -> Self {
+        Self {
+            position: self.position + self.velocity * dt,
+            ..*self
+        }
+    }
+}
+
+/// Buffers snapshots of a remote entity and plays them back slightly in the past, so there is
+/// nearly always a pair to interpolate between even when packets arrive late or unevenly. When
+/// the buffer runs dry the last state is extrapolated for a short while.
+///
+/// Timestamps are seconds on the local clock, e.g. the sender's time adjusted by a clock offset,
+/// and must be consistent with the `now` passed to `sample` and `update`.
+#[derive(Clone, Debug)]
+pub struct SnapshotBuffer<T: NetState> {
+    snapshots: VecDeque<(f64, T)>,
+    capacity: usize,
+    delay: f64,
+    max_extrapolation: f64,
+    smoothing: f32,
+    displayed: Option<T>,
+}
+
+impl<T: NetState> Default for SnapshotBuffer<T> {
+    fn default() -> Self {
+        Self {
+            snapshots: VecDeque::new(),
+            capacity: 32,
+            delay: 0.1,
+            max_extrapolation: 0.25,
+            smoothing: 0.0,
+            displayed: None,
+        }
+    }
+}
+
+impl<T: NetState> SnapshotBuffer<T> {
+    /// How far behind the latest snapshots to play back, in seconds. Should cover a couple of
+    /// send intervals plus jitter. Defaults to 0.1.
+    pub fn with_delay(mut self, delay: f64) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// How long to keep predicting once snapshots stop arriving, in seconds. Defaults to 0.25.
+    pub fn with_max_extrapolation(mut self, seconds: f64) -> Self {
+        self.max_extrapolation = seconds;
+        self
+    }
+
+    /// Rate at which `update` eases towards corrected states instead of snapping, as in
+    /// `maths::exp_decay`. Defaults to 0, which snaps.
+    pub fn with_smoothing(mut self, rate: f32) -> Self {
+        self.smoothing = rate;
+        self
+    }
+
+    /// Most snapshots kept. Defaults to 32.
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(2);
+        self
+    }
+
+    /// Add a snapshot. Out of order snapshots are slotted into place; duplicates and ones too old
+    /// to matter are dropped.
+    pub fn push(&mut self, time: f64, state: T) {
+        if self
+            .snapshots
+            .front()
+            .is_some_and(|&(oldest, _)| time < oldest)
+            && self.snapshots.len() >= self.capacity
+        {
+            return;
+        }
+
+        let index = self.snapshots.partition_point(|&(t, _)| t < time);
+        if self.snapshots.get(index).is_some_and(|&(t, _)| t == time) {
+            return;
+        }
+
+        self.snapshots.insert(index, (time, state));
+        while self.snapshots.len() > self.capacity {
+            self.snapshots.pop_front();
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    pub fn latest(&self) -> Option<&T> {
+        self.snapshots.back().map(|(_, state)| state)
+    }
+
+    /// The state to show at `now`, without smoothing.
+    pub fn sample(&mut self, now: f64) -> Option<T> {
+        let render_time = now - self.delay;
+
+        // Keep one snapshot at or before the render time to interpolate from.
+        while self.snapshots.len() > 2 && self.snapshots[1].0 <= render_time {
+            self.snapshots.pop_front();
+        }
+
+        let (first_time, first) = self.snapshots.front()?;
+        if render_time <= *first_time {
+            return Some(first.clone());
+        }
+
+        match self.snapshots.get(1) {
+            Some((next_time, next)) if render_time <= *next_time => {
+                let t = (render_time - first_time) / (next_time - first_time);
+                Some(first.interpolate(next, t as f32))
+            }
+            _ => {
+                let (latest_time, latest) = self.snapshots.back()?;
+                let ahead = (render_time - latest_time).clamp(0.0, self.max_extrapolation);
+                Some(latest.extrapolate(ahead as f32))
+            }
+        }
+    }
+
+    /// The state to show at `now`, eased towards the sampled state if smoothing is enabled.
+    /// `dt` is the frame time in seconds.
+    pub fn update(&mut self, now: f64, dt: f32) -> Option<&T> {
+        let target = self.sample(now)?;
+
+        let displayed = match self.displayed.take() {
+            Some(displayed) if self.smoothing > 0.0 => {
+                let t = 1.0 - exp_decay(1.0, 0.0, self.smoothing, dt);
+                displayed.interpolate(&target, t)
+            }
+            _ => target,
+        };
+
+        self.displayed = Some(displayed);
+        self.displayed.as_ref()
+    }
+
+    pub fn clear(&mut self) {
+        self.snapshots.clear();
+        self.displayed = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn moving(x: f32) -> EntityState {
+        EntityState {
+            position: Vec2::new(x, 0.0),
+            velocity: Vec2::new(10.0, 0.0),
+            rotation: 0.0,
+        }
+    }
+
+    #[test]
+    fn interpolates_between_snapshots_behind_now() {
+        let mut buffer = SnapshotBuffer::default().with_delay(0.1);
+        buffer.push(1.0, moving(0.0));
+        buffer.push(1.2, moving(2.0));
+        // Arrives late, out of order.
+        buffer.push(1.1, moving(1.0));
+
+        let state = buffer.sample(1.25).unwrap();
+
+        assert!((state.position.x - 1.5).abs() < 1e-4);
+        // The oldest snapshot is no longer needed.
+        assert_eq!(2, buffer.len());
+    }
+
+    #[test]
+    fn extrapolates_for_a_limited_time() {
+        let mut buffer = SnapshotBuffer::default()
+            .with_delay(0.0)
+            .with_max_extrapolation(0.2);
+        buffer.push(0.0, moving(0.0));
+
+        assert!((buffer.sample(0.1).unwrap().position.x - 1.0).abs() < 1e-4);
+        assert!((buffer.sample(5.0).unwrap().position.x - 2.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn smoothing_eases_towards_corrections() {
+        let mut buffer = SnapshotBuffer::default()
+            .with_delay(0.0)
+            .with_smoothing(10.0);
+        buffer.push(0.0, 0.0);
+        assert_eq!(Some(&0.0), buffer.update(0.0, 0.1));
+
+        buffer.push(0.1, 10.0);
+        let eased = *buffer.update(0.1, 0.1).unwrap();
+
+        assert!(eased > 0.0 && eased < 10.0);
+    }
+
+    #[test]
+    fn rotation_interpolates_the_short_way() {
+        let a = EntityState {
+            rotation: 0.1,
+            ..Default::default()
+        };
+        let b = EntityState {
+            rotation: std::f32::consts::TAU - 0.1,
+            ..Default::default()
+        };
+
+        assert!(a.interpolate(&b, 0.5).rotation.abs() < 1e-4);
+    }
+}
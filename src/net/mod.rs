@@ -0,0 +1,3 @@
+pub mod chat;
+pub mod discovery;
+pub mod interpolation;
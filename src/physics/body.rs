@@ -0,0 +1,144 @@
+use crate::engine::archetype::{Archetype, CollisionShape};
+use crate::maths::Vec2;
+use crate::physics::filter::CollisionFilter;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BodyHandle(pub(crate) usize);
+
+/// A collision shape centered on its body's position.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Shape {
+    Circle {
+        radius: f32,
+    },
+    /// An axis aligned rectangle.
+    Rectangle {
+        width: f32,
+        height: f32,
+    },
+}
+
+impl Shape {
+    /// Half the width and height of the shape's bounding box.
+    pub fn half_extents(&self) -> Vec2 {
+        match *self {
+            Shape::Circle { radius } => Vec2::new(radius, radius),
+            Shape::Rectangle { width, height } => Vec2::new(width / 2.0, height / 2.0),
+        }
+    }
+}
+
+impl From<&CollisionShape> for Shape {
+    fn from(shape: &CollisionShape) -> Self {
+        match *shape {
+            CollisionShape::Circle { radius } => Shape::Circle { radius },
+            CollisionShape::Rectangle { width, height } => Shape::Rectangle { width, height },
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BodyKind {
+    /// Moved by gravity, velocity and collisions.
+    Dynamic,
+    /// Never moves, e.g. terrain and walls.
+    Static,
+}
+
+#[derive(Clone, Debug)]
+pub struct Body {
+    pub position: Vec2,
+    pub velocity: Vec2,
+    pub shape: Shape,
+    pub kind: BodyKind,
+    pub mass: f32,
+    /// Bounciness from 0 (none) to 1 (perfectly elastic).
+    pub elasticity: f32,
+    pub friction: f32,
+    pub gravity_scale: f32,
+    pub filter: CollisionFilter,
+    /// Sensors detect overlaps without pushing other bodies, e.g. pickups and kill zones.
+    pub sensor: bool,
+}
+
+impl Body {
+    pub fn dynamic(shape: Shape, x: f32, y: f32) -> Self {
+        Self {
+            position: Vec2::new(x, y),
+            velocity: Vec2::new(0.0, 0.0),
+            shape,
+            kind: BodyKind::Dynamic,
+            mass: 1.0,
+            elasticity: 0.0,
+            friction: 0.0,
+            gravity_scale: 1.0,
+            filter: CollisionFilter::default(),
+            sensor: false,
+        }
+    }
+
+    pub fn fixed(shape: Shape, x: f32, y: f32) -> Self {
+        Self {
+            kind: BodyKind::Static,
+            gravity_scale: 0.0,
+            ..Self::dynamic(shape, x, y)
+        }
+    }
+
+    /// Build a dynamic body from an archetype's shape and physics properties.
+    /// Returns `None` when the archetype has no collision shape.
+    pub fn from_archetype(archetype: &Archetype, x: f32, y: f32) -> Option<Self> {
+        let mut body = Self::dynamic(archetype.shape.as_ref()?.into(), x, y);
+        if let Some(physics) = &archetype.physics {
+            body.mass = physics.mass;
+            body.elasticity = physics.elasticity;
+            body.friction = physics.friction;
+            body.gravity_scale = physics.gravity_scale;
+        }
+
+        Some(body)
+    }
+
+    pub fn with_velocity(mut self, x: f32, y: f32) -> Self {
+        self.velocity = Vec2::new(x, y);
+        self
+    }
+
+    pub fn with_mass(mut self, mass: f32) -> Self {
+        self.mass = mass;
+        self
+    }
+
+    pub fn with_elasticity(mut self, elasticity: f32) -> Self {
+        self.elasticity = elasticity;
+        self
+    }
+
+    pub fn with_friction(mut self, friction: f32) -> Self {
+        self.friction = friction;
+        self
+    }
+
+    pub fn with_gravity_scale(mut self, gravity_scale: f32) -> Self {
+        self.gravity_scale = gravity_scale;
+        self
+    }
+
+    pub fn with_filter(mut self, filter: CollisionFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    pub fn with_sensor(mut self, sensor: bool) -> Self {
+        self.sensor = sensor;
+        self
+    }
+
+    pub fn inverse_mass(&self) -> f32 {
+        match self.kind {
+            BodyKind::Static => 0.0,
+            BodyKind::Dynamic if self.mass > 0.0 => 1.0 / self.mass,
+            BodyKind::Dynamic => 0.0,
+        }
+    }
+}
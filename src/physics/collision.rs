@@ -0,0 +1,187 @@
+use crate::maths::{clamp, Vec2};
+use crate::physics::body::Shape;
+
+/// How two overlapping shapes touch. The normal points from the first shape towards the second.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Manifold {
+    pub normal: Vec2,
+    pub depth: f32,
+    pub point: Vec2,
+}
+
+/// Test two shapes at the given positions for overlap.
+pub fn collide(a: &Shape, a_position: Vec2, b: &Shape, b_position: Vec2) -> Option<Manifold> {
+    match (*a, *b) {
+        (Shape::Circle { radius: ra }, Shape::Circle { radius: rb }) => {
+            circle_circle(a_position, ra, b_position, rb)
+        }
+        (Shape::Circle { radius }, Shape::Rectangle { width, height }) => {
+            rectangle_circle(b_position, width, height, a_position, radius).map(flip)
+        }
+        (Shape::Rectangle { width, height }, Shape::Circle { radius }) => {
+            rectangle_circle(a_position, width, height, b_position, radius)
+        }
+        (Shape::Rectangle { .. }, Shape::Rectangle { .. }) => {
+            rectangle_rectangle(a_position, a.half_extents(), b_position, b.half_extents())
+        }
+    }
+}
+
+fn flip(manifold: Manifold) -> Manifold {
+    Manifold {
+        normal: Vec2::new(-manifold.normal.x, -manifold.normal.y),
+        ..manifold
+    }
+}
+
+fn circle_circle(a: Vec2, ra: f32, b: Vec2, rb: f32) -> Option<Manifold> {
+    let (dx, dy) = (b.x - a.x, b.y - a.y);
+    let distance = (dx * dx + dy * dy).sqrt();
+    if distance >= ra + rb {
+        return None;
+    }
+
+    let normal = if distance > 0.0 {
+        Vec2::new(dx / distance, dy / distance)
+    } else {
+        Vec2::new(0.0, 1.0)
+    };
+
+    Some(Manifold {
+        normal,
+        depth: ra + rb - distance,
+        point: Vec2::new(a.x + normal.x * ra, a.y + normal.y * ra),
+    })
+}
+
+fn rectangle_circle(
+    rect: Vec2,
+    width: f32,
+    height: f32,
+    circle: Vec2,
+    radius: f32,
+) -> Option<Manifold> {
+    let (half_width, half_height) = (width / 2.0, height / 2.0);
+    let closest = Vec2::new(
+        clamp(rect.x - half_width, circle.x, rect.x + half_width),
+        clamp(rect.y - half_height, circle.y, rect.y + half_height),
+    );
+    let (dx, dy) = (circle.x - closest.x, circle.y - closest.y);
+    let distance_squared = dx * dx + dy * dy;
+
+    if distance_squared > 0.0 {
+        if distance_squared >= radius * radius {
+            return None;
+        }
+
+        let distance = distance_squared.sqrt();
+        return Some(Manifold {
+            normal: Vec2::new(dx / distance, dy / distance),
+            depth: radius - distance,
+            point: closest,
+        });
+    }
+
+    // The circle's center is inside the rectangle, push out along the nearest face.
+    let (left, right) = (
+        circle.x - (rect.x - half_width),
+        rect.x + half_width - circle.x,
+    );
+    let (bottom, top) = (
+        circle.y - (rect.y - half_height),
+        rect.y + half_height - circle.y,
+    );
+    let nearest = left.min(right).min(bottom).min(top);
+    let normal = if nearest == top {
+        Vec2::new(0.0, 1.0)
+    } else if nearest == bottom {
+        Vec2::new(0.0, -1.0)
+    } else if nearest == right {
+        Vec2::new(1.0, 0.0)
+    } else {
+        Vec2::new(-1.0, 0.0)
+    };
+
+    Some(Manifold {
+        normal,
+        depth: nearest + radius,
+        point: circle,
+    })
+}
+
+fn rectangle_rectangle(a: Vec2, a_half: Vec2, b: Vec2, b_half: Vec2) -> Option<Manifold> {
+    let (dx, dy) = (b.x - a.x, b.y - a.y);
+    let overlap_x = a_half.x + b_half.x - dx.abs();
+    let overlap_y = a_half.y + b_half.y - dy.abs();
+    if overlap_x <= 0.0 || overlap_y <= 0.0 {
+        return None;
+    }
+
+    let (normal, depth) = if overlap_x < overlap_y {
+        (Vec2::new(dx.signum(), 0.0), overlap_x)
+    } else {
+        (Vec2::new(0.0, if dy < 0.0 { -1.0 } else { 1.0 }), overlap_y)
+    };
+
+    Some(Manifold {
+        normal,
+        depth,
+        point: Vec2::new(
+            a.x + normal.x * (a_half.x - depth / 2.0),
+            a.y + normal.y * (a_half.y - depth / 2.0),
+        ),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn separated_circles_do_not_collide() {
+        let circle = Shape::Circle { radius: 1.0 };
+
+        assert_eq!(
+            None,
+            collide(&circle, Vec2::new(0.0, 0.0), &circle, Vec2::new(3.0, 0.0))
+        );
+    }
+
+    #[test]
+    fn overlapping_circles_report_depth_along_normal() {
+        let circle = Shape::Circle { radius: 1.0 };
+
+        let manifold = collide(&circle, Vec2::new(0.0, 0.0), &circle, Vec2::new(1.5, 0.0)).unwrap();
+
+        assert_eq!(Vec2::new(1.0, 0.0), manifold.normal);
+        assert_eq!(0.5, manifold.depth);
+    }
+
+    #[test]
+    fn circle_resting_on_rectangle_is_pushed_up() {
+        let ground = Shape::Rectangle {
+            width: 100.0,
+            height: 10.0,
+        };
+        let ball = Shape::Circle { radius: 2.0 };
+
+        let manifold = collide(&ground, Vec2::new(0.0, 0.0), &ball, Vec2::new(0.0, 6.0)).unwrap();
+
+        assert_eq!(Vec2::new(0.0, 1.0), manifold.normal);
+        assert_eq!(1.0, manifold.depth);
+    }
+
+    #[test]
+    fn rectangles_separate_along_axis_of_least_overlap() {
+        let square = Shape::Rectangle {
+            width: 2.0,
+            height: 2.0,
+        };
+
+        let manifold =
+            collide(&square, Vec2::new(0.0, 0.0), &square, Vec2::new(-1.5, 0.2)).unwrap();
+
+        assert_eq!(Vec2::new(-1.0, 0.0), manifold.normal);
+        assert_eq!(0.5, manifold.depth);
+    }
+}
@@ -0,0 +1,85 @@
+use std::ops::BitOr;
+
+/// A set of collision layers, combined with `|`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Layers(pub u32);
+
+impl Layers {
+    pub const NONE: Layers = Layers(0);
+    pub const ALL: Layers = Layers(u32::MAX);
+
+    /// The single layer `index`, from 0 to 31.
+    pub const fn layer(index: u32) -> Layers {
+        Layers(1 << index)
+    }
+
+    pub const fn intersects(&self, other: Layers) -> bool {
+        self.0 & other.0 != 0
+    }
+}
+
+impl BitOr for Layers {
+    type Output = Layers;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Layers(self.0 | rhs.0)
+    }
+}
+
+/// Which layers a body belongs to and which layers it collides with.
+/// Two bodies only collide when each is in a layer the other's mask accepts.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CollisionFilter {
+    pub layers: Layers,
+    pub mask: Layers,
+}
+
+impl Default for CollisionFilter {
+    fn default() -> Self {
+        Self {
+            layers: Layers::layer(0),
+            mask: Layers::ALL,
+        }
+    }
+}
+
+impl CollisionFilter {
+    pub const fn new(layers: Layers, mask: Layers) -> Self {
+        Self { layers, mask }
+    }
+
+    pub const fn can_collide(&self, other: &CollisionFilter) -> bool {
+        self.mask.intersects(other.layers) && other.mask.intersects(self.layers)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TERRAIN: Layers = Layers::layer(0);
+    const DEBRIS: Layers = Layers::layer(1);
+
+    #[test]
+    fn default_filters_collide() {
+        assert!(CollisionFilter::default().can_collide(&CollisionFilter::default()));
+    }
+
+    #[test]
+    fn debris_ignores_debris_but_hits_terrain() {
+        let debris = CollisionFilter::new(DEBRIS, TERRAIN);
+        let terrain = CollisionFilter::new(TERRAIN, Layers::ALL);
+
+        assert!(!debris.can_collide(&debris));
+        assert!(debris.can_collide(&terrain));
+    }
+
+    #[test]
+    fn filtering_is_symmetric() {
+        let a = CollisionFilter::new(TERRAIN, TERRAIN | DEBRIS);
+        let b = CollisionFilter::new(DEBRIS, DEBRIS);
+
+        assert!(!a.can_collide(&b));
+        assert!(!b.can_collide(&a));
+    }
+}
@@ -0,0 +1,118 @@
+use crate::maths::Vec2;
+use crate::physics::body::{Body, BodyHandle};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct JointHandle(pub(crate) usize);
+
+/// A constraint between two bodies. Attach to a static body to anchor to the world, e.g. for a
+/// pendulum or grappling hook.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Joint {
+    /// Holds `b` at a fixed `offset` from `a`, as if the two were welded together.
+    Pin {
+        a: BodyHandle,
+        b: BodyHandle,
+        offset: Vec2,
+    },
+    /// Keeps the bodies exactly `length` apart, like a rigid rod.
+    Distance {
+        a: BodyHandle,
+        b: BodyHandle,
+        length: f32,
+    },
+    /// Pulls the bodies towards `rest_length` apart with a force proportional to the stretch.
+    Spring {
+        a: BodyHandle,
+        b: BodyHandle,
+        rest_length: f32,
+        stiffness: f32,
+        damping: f32,
+    },
+}
+
+impl Joint {
+    pub fn pin(a: BodyHandle, b: BodyHandle, offset: Vec2) -> Self {
+        Joint::Pin { a, b, offset }
+    }
+
+    pub fn distance(a: BodyHandle, b: BodyHandle, length: f32) -> Self {
+        Joint::Distance { a, b, length }
+    }
+
+    pub fn spring(
+        a: BodyHandle,
+        b: BodyHandle,
+        rest_length: f32,
+        stiffness: f32,
+        damping: f32,
+    ) -> Self {
+        Joint::Spring {
+            a,
+            b,
+            rest_length,
+            stiffness,
+            damping,
+        }
+    }
+
+    pub fn bodies(&self) -> (BodyHandle, BodyHandle) {
+        match *self {
+            Joint::Pin { a, b, .. } | Joint::Distance { a, b, .. } | Joint::Spring { a, b, .. } => {
+                (a, b)
+            }
+        }
+    }
+
+    /// Accelerate both bodies by the spring force over `dt`. Rigid joints apply no force.
+    pub(crate) fn apply_force(&self, a: &mut Body, b: &mut Body, dt: f32) {
+        if let Joint::Spring {
+            rest_length,
+            stiffness,
+            damping,
+            ..
+        } = *self
+        {
+            let delta = b.position - a.position;
+            let direction = delta.normalized();
+            let stretch = delta.length() - rest_length;
+            let closing_speed = (b.velocity - a.velocity).dot(direction);
+            let force = direction * (stiffness * stretch + damping * closing_speed);
+
+            a.velocity = a.velocity + force * (a.inverse_mass() * dt);
+            b.velocity = b.velocity - force * (b.inverse_mass() * dt);
+        }
+    }
+
+    /// Move both bodies, weighted by inverse mass, towards satisfying a rigid joint and remove
+    /// any relative velocity that would pull them apart again.
+    pub(crate) fn solve(&self, a: &mut Body, b: &mut Body) {
+        let (inverse_mass_a, inverse_mass_b) = (a.inverse_mass(), b.inverse_mass());
+        let inverse_mass_sum = inverse_mass_a + inverse_mass_b;
+        if inverse_mass_sum == 0.0 {
+            return;
+        }
+
+        let error = match *self {
+            Joint::Pin { offset, .. } => b.position - (a.position + offset),
+            Joint::Distance { length, .. } => {
+                let delta = b.position - a.position;
+                delta.normalized() * (delta.length() - length)
+            }
+            Joint::Spring { .. } => return,
+        };
+
+        a.position = a.position + error * (inverse_mass_a / inverse_mass_sum);
+        b.position = b.position - error * (inverse_mass_b / inverse_mass_sum);
+
+        let relative_velocity = b.velocity - a.velocity;
+        let correction = match *self {
+            Joint::Pin { .. } => relative_velocity,
+            _ => {
+                let axis = (b.position - a.position).normalized();
+                axis * relative_velocity.dot(axis)
+            }
+        };
+        a.velocity = a.velocity + correction * (inverse_mass_a / inverse_mass_sum);
+        b.velocity = b.velocity - correction * (inverse_mass_b / inverse_mass_sum);
+    }
+}
@@ -0,0 +1,7 @@
+pub mod body;
+pub mod collision;
+pub mod filter;
+pub mod joint;
+pub mod platformer;
+pub mod water;
+pub mod world;
@@ -0,0 +1,405 @@
+use crate::maths::{Rect, Vec2};
+
+const EPSILON: f32 = 0.001;
+
+/// Level geometry the platformer controller moves through.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Surface {
+    /// Blocks from every side.
+    Solid(Rect),
+    /// A walkable line, only solid from above. `from` is the left end.
+    Slope { from: Vec2, to: Vec2 },
+    /// A platform the caller moves each frame by `velocity`. One-way platforms can be jumped
+    /// through from below and only hold the controller up.
+    Platform {
+        rect: Rect,
+        velocity: Vec2,
+        one_way: bool,
+    },
+}
+
+impl Surface {
+    /// The rectangle that blocks movement and whether it only blocks from above.
+    fn blocker(&self) -> Option<(Rect, bool)> {
+        match *self {
+            Surface::Solid(rect) => Some((rect, false)),
+            Surface::Platform { rect, one_way, .. } => Some((rect, one_way)),
+            Surface::Slope { .. } => None,
+        }
+    }
+
+    /// The height of a slope at `x`, if `x` is over it.
+    fn height_at(&self, x: f32) -> Option<f32> {
+        match *self {
+            Surface::Slope { from, to } if x >= from.x && x <= to.x && to.x > from.x => {
+                Some(from.y + (to.y - from.y) * (x - from.x) / (to.x - from.x))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// A kinematic box that walks, jumps, climbs slopes and rides platforms.
+///
+/// Surfaces are identified by their index in the slice passed to `update`, so keep the order of
+/// the level geometry stable between frames.
+#[derive(Clone, Debug)]
+pub struct PlatformerController {
+    /// The center of the controller's box.
+    pub position: Vec2,
+    pub velocity: Vec2,
+    pub half_extents: Vec2,
+    pub walk_speed: f32,
+    pub jump_speed: f32,
+    pub gravity: f32,
+    /// How far the controller will drop to stay stuck to the ground, e.g. when walking downhill.
+    pub snap_distance: f32,
+    ground: Option<usize>,
+    inherited: Vec2,
+    crushed: bool,
+}
+
+impl PlatformerController {
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Self {
+            position: Vec2::new(x, y),
+            velocity: Vec2::default(),
+            half_extents: Vec2::new(width / 2.0, height / 2.0),
+            walk_speed: 60.0,
+            jump_speed: 120.0,
+            gravity: 300.0,
+            snap_distance: 2.0,
+            ground: None,
+            inherited: Vec2::default(),
+            crushed: false,
+        }
+    }
+
+    pub fn with_walk_speed(mut self, walk_speed: f32) -> Self {
+        self.walk_speed = walk_speed;
+        self
+    }
+
+    pub fn with_jump_speed(mut self, jump_speed: f32) -> Self {
+        self.jump_speed = jump_speed;
+        self
+    }
+
+    pub fn with_gravity(mut self, gravity: f32) -> Self {
+        self.gravity = gravity;
+        self
+    }
+
+    pub fn is_grounded(&self) -> bool {
+        self.ground.is_some()
+    }
+
+    /// The index of the surface the controller is standing on.
+    pub fn ground(&self) -> Option<usize> {
+        self.ground
+    }
+
+    /// Whether the controller was squeezed between surfaces during the last update.
+    pub fn is_crushed(&self) -> bool {
+        self.crushed
+    }
+
+    /// Move the controller for one frame. `move_x` is from -1 (left) to 1 (right).
+    pub fn update(&mut self, move_x: f32, jump: bool, surfaces: &[Surface], dt: f32) {
+        let carried = match self.ground().and_then(|index| surfaces.get(index)) {
+            Some(Surface::Platform { velocity, .. }) => *velocity,
+            _ => Vec2::default(),
+        };
+        self.position = self.position + carried * dt;
+        self.push_out(surfaces);
+
+        let was_grounded = self.is_grounded();
+        let jumping = was_grounded && jump;
+        if was_grounded {
+            // Leaving a platform keeps its momentum until the next landing.
+            self.inherited = Vec2::new(carried.x, 0.0);
+        }
+
+        self.velocity.x = move_x * self.walk_speed;
+        self.velocity.y -= self.gravity * dt;
+        if jumping {
+            self.velocity.y = self.jump_speed + carried.y.max(0.0);
+        }
+
+        let dx = (self.velocity.x + if was_grounded { 0.0 } else { self.inherited.x }) * dt;
+        let motion = match self.ground().and_then(|index| surfaces.get(index)) {
+            Some(&Surface::Slope { from, to }) if !jumping => (to - from).normalized() * dx,
+            _ => Vec2::new(dx, self.velocity.y * dt),
+        };
+
+        self.move_x(motion.x, surfaces);
+        self.move_y(motion.y, surfaces);
+        if !jumping {
+            self.land_on_slopes(motion, was_grounded, surfaces);
+        }
+
+        if self.is_grounded() {
+            self.velocity.y = 0.0;
+            self.inherited = Vec2::default();
+        }
+        self.crushed = self.overlapping(surfaces).next().is_some();
+    }
+
+    fn bounds(&self) -> Rect {
+        Rect::new(
+            self.position.x - self.half_extents.x,
+            self.position.y - self.half_extents.y,
+            self.half_extents.x * 2.0,
+            self.half_extents.y * 2.0,
+        )
+    }
+
+    /// Solid rectangles the controller is inside of.
+    fn overlapping<'a>(&self, surfaces: &'a [Surface]) -> impl Iterator<Item = Rect> + 'a {
+        let bounds = self.bounds();
+        surfaces
+            .iter()
+            .filter_map(Surface::blocker)
+            .filter(|(_, one_way)| !one_way)
+            .map(|(rect, _)| rect)
+            .filter(move |rect| overlaps(&bounds, rect))
+    }
+
+    /// Resolve along the shallowest axis when a platform has moved into the controller.
+    fn push_out(&mut self, surfaces: &[Surface]) {
+        let intruders: Vec<Rect> = self.overlapping(surfaces).collect();
+        for rect in intruders {
+            let bounds = self.bounds();
+            if !overlaps(&bounds, &rect) {
+                continue;
+            }
+
+            let left = bounds.x + bounds.width - rect.x;
+            let right = rect.x + rect.width - bounds.x;
+            let down = bounds.y + bounds.height - rect.y;
+            let up = rect.y + rect.height - bounds.y;
+            let shallowest = left.min(right).min(down).min(up);
+
+            if shallowest == left {
+                self.position.x -= left;
+            } else if shallowest == right {
+                self.position.x += right;
+            } else if shallowest == down {
+                self.position.y -= down;
+            } else {
+                self.position.y += up;
+            }
+        }
+    }
+
+    fn move_x(&mut self, dx: f32, surfaces: &[Surface]) {
+        self.position.x += dx;
+        if dx == 0.0 {
+            return;
+        }
+
+        let blockers: Vec<Rect> = self.overlapping(surfaces).collect();
+        for rect in blockers {
+            if dx > 0.0 {
+                self.position.x = self.position.x.min(rect.x - self.half_extents.x);
+            } else {
+                self.position.x = self
+                    .position
+                    .x
+                    .max(rect.x + rect.width + self.half_extents.x);
+            }
+            self.velocity.x = 0.0;
+        }
+    }
+
+    fn move_y(&mut self, dy: f32, surfaces: &[Surface]) {
+        let previous_bottom = self.position.y - self.half_extents.y;
+        self.position.y += dy;
+        self.ground = None;
+
+        for (index, surface) in surfaces.iter().enumerate() {
+            let (rect, one_way) = match surface.blocker() {
+                Some(blocker) => blocker,
+                None => continue,
+            };
+            if !overlaps(&self.bounds(), &rect) {
+                continue;
+            }
+
+            let top = rect.y + rect.height;
+            if dy <= 0.0 {
+                if one_way && previous_bottom < top - EPSILON {
+                    continue;
+                }
+                self.position.y = top + self.half_extents.y;
+                self.ground = Some(index);
+            } else if !one_way {
+                self.position.y = rect.y - self.half_extents.y;
+                self.velocity.y = 0.0;
+            }
+        }
+    }
+
+    /// Stand on the highest slope the controller has sunk into this frame, or one just below
+    /// its feet when it was already on the ground.
+    fn land_on_slopes(&mut self, motion: Vec2, was_grounded: bool, surfaces: &[Surface]) {
+        if self.velocity.y > 0.0 && !was_grounded {
+            return;
+        }
+
+        let bottom = self.position.y - self.half_extents.y;
+        let below = motion.x.abs() + motion.y.abs() + self.snap_distance;
+        let above = if was_grounded {
+            self.snap_distance
+        } else {
+            0.0
+        };
+
+        let best = surfaces
+            .iter()
+            .enumerate()
+            .filter_map(|(index, surface)| Some((index, surface.height_at(self.position.x)?)))
+            .filter(|&(_, height)| bottom >= height - below && bottom <= height + above)
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+
+        if let Some((index, height)) = best {
+            let standing_higher = self.is_grounded() && bottom > height + EPSILON;
+            if !standing_higher {
+                self.position.y = height + self.half_extents.y;
+                self.ground = Some(index);
+            }
+        }
+    }
+}
+
+fn overlaps(a: &Rect, b: &Rect) -> bool {
+    a.x + a.width - b.x > EPSILON
+        && b.x + b.width - a.x > EPSILON
+        && a.y + a.height - b.y > EPSILON
+        && b.y + b.height - a.y > EPSILON
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DT: f32 = 1.0 / 60.0;
+
+    fn floor() -> Surface {
+        Surface::Solid(Rect::new(-100.0, -10.0, 400.0, 10.0))
+    }
+
+    fn settle(controller: &mut PlatformerController, surfaces: &[Surface]) {
+        for _ in 0..60 {
+            controller.update(0.0, false, surfaces, DT);
+        }
+    }
+
+    #[test]
+    fn falls_onto_and_stands_on_solid_ground() {
+        let surfaces = [floor()];
+        let mut controller = PlatformerController::new(0.0, 20.0, 8.0, 16.0);
+
+        settle(&mut controller, &surfaces);
+
+        assert!(controller.is_grounded());
+        assert!((controller.position.y - 8.0).abs() < EPSILON);
+    }
+
+    #[test]
+    fn walks_up_a_slope_along_its_surface() {
+        let surfaces = [
+            floor(),
+            Surface::Slope {
+                from: Vec2::new(10.0, 0.0),
+                to: Vec2::new(110.0, 100.0),
+            },
+        ];
+        let mut controller = PlatformerController::new(0.0, 8.0, 8.0, 16.0);
+        settle(&mut controller, &surfaces);
+
+        for _ in 0..60 {
+            controller.update(1.0, false, &surfaces, DT);
+        }
+
+        let feet = controller.position.y - controller.half_extents.y;
+        assert_eq!(Some(1), controller.ground());
+        assert!((feet - (controller.position.x - 10.0)).abs() < 0.01);
+        // Moving along the surface covers the walk speed diagonally rather than horizontally.
+        assert!(controller.position.x < 60.0);
+    }
+
+    #[test]
+    fn jumps_through_one_way_platform_and_lands_on_it() {
+        let surfaces = [
+            floor(),
+            Surface::Platform {
+                rect: Rect::new(-20.0, 20.0, 40.0, 4.0),
+                velocity: Vec2::default(),
+                one_way: true,
+            },
+        ];
+        let mut controller = PlatformerController::new(0.0, 8.0, 8.0, 16.0);
+        settle(&mut controller, &surfaces);
+
+        controller.update(0.0, true, &surfaces, DT);
+        settle(&mut controller, &surfaces);
+
+        assert_eq!(Some(1), controller.ground());
+        assert!((controller.position.y - 32.0).abs() < EPSILON);
+    }
+
+    #[test]
+    fn rides_moving_platform_and_keeps_its_momentum_when_jumping_off() {
+        let mut platform = Rect::new(-20.0, 0.0, 40.0, 4.0);
+        let velocity = Vec2::new(30.0, 0.0);
+        let mut controller = PlatformerController::new(0.0, 12.0, 8.0, 16.0);
+
+        for _ in 0..60 {
+            platform.x += velocity.x * DT;
+            let surfaces = [Surface::Platform {
+                rect: platform,
+                velocity,
+                one_way: false,
+            }];
+            controller.update(0.0, false, &surfaces, DT);
+        }
+        assert!((controller.position.x - 29.5).abs() < 1.0);
+
+        let surfaces = [Surface::Platform {
+            rect: platform,
+            velocity,
+            one_way: false,
+        }];
+        let x = controller.position.x;
+        controller.update(0.0, true, &surfaces, DT);
+        controller.update(0.0, false, &surfaces, DT);
+
+        assert!((controller.position.x - (x + 2.0 * velocity.x * DT)).abs() < EPSILON);
+    }
+
+    #[test]
+    fn detects_being_crushed_between_platform_and_ceiling() {
+        let ceiling = Surface::Solid(Rect::new(-100.0, 20.0, 200.0, 10.0));
+        let mut controller = PlatformerController::new(0.0, 8.0, 8.0, 16.0);
+        let mut platform = Rect::new(-20.0, -4.0, 40.0, 4.0);
+        let velocity = Vec2::new(0.0, 60.0);
+
+        let mut crushed = false;
+        for _ in 0..30 {
+            platform.y += velocity.y * DT;
+            let surfaces = [
+                ceiling,
+                Surface::Platform {
+                    rect: platform,
+                    velocity,
+                    one_way: false,
+                },
+            ];
+            controller.update(0.0, false, &surfaces, DT);
+            crushed |= controller.is_crushed();
+        }
+
+        assert!(crushed);
+    }
+}
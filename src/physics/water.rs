@@ -0,0 +1,153 @@
+use std::f32::consts::PI;
+
+use crate::color::Color;
+use crate::engine::apparatus::Apparatus;
+use crate::maths::{Rect, Vec2};
+use crate::physics::body::{Body, Shape};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct WaterHandle(pub(crate) usize);
+
+/// A rectangular body of water that pushes overlapping bodies up and slows them down.
+///
+/// A body floats when its mass is less than `density` times its area.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct WaterVolume {
+    pub area: Rect,
+    pub density: f32,
+    /// The fraction of velocity lost per second when fully submerged.
+    pub drag: f32,
+}
+
+impl WaterVolume {
+    pub fn new(area: Rect) -> Self {
+        Self {
+            area,
+            density: 1.0,
+            drag: 2.0,
+        }
+    }
+
+    pub fn with_density(mut self, density: f32) -> Self {
+        self.density = density;
+        self
+    }
+
+    pub fn with_drag(mut self, drag: f32) -> Self {
+        self.drag = drag;
+        self
+    }
+
+    /// How much of the body's bounding box is inside the water, from 0 to 1.
+    pub fn submerged_fraction(&self, body: &Body) -> f32 {
+        let half = body.shape.half_extents();
+        if half.x <= 0.0 || half.y <= 0.0 {
+            return 0.0;
+        }
+
+        let overlap = |min: f32, max: f32, area_min: f32, area_max: f32| {
+            (max.min(area_max) - min.max(area_min)).max(0.0) / (max - min)
+        };
+        let Rect {
+            x,
+            y,
+            width,
+            height,
+        } = self.area;
+
+        overlap(
+            body.position.x - half.x,
+            body.position.x + half.x,
+            x,
+            x + width,
+        ) * overlap(
+            body.position.y - half.y,
+            body.position.y + half.y,
+            y,
+            y + height,
+        )
+    }
+
+    /// Accelerate a body by buoyancy against `gravity` and slow it by drag over `dt`.
+    pub(crate) fn apply(&self, body: &mut Body, gravity: Vec2, dt: f32) {
+        let fraction = self.submerged_fraction(body);
+        if fraction <= 0.0 {
+            return;
+        }
+
+        let area = match body.shape {
+            Shape::Circle { radius } => PI * radius * radius,
+            Shape::Rectangle { width, height } => width * height,
+        };
+        let displaced = self.density * area * fraction;
+        body.velocity =
+            body.velocity - gravity * (displaced * body.inverse_mass() * body.gravity_scale * dt);
+        body.velocity = body.velocity * (1.0 - self.drag * fraction * dt).max(0.0);
+    }
+
+    /// The height of the animated surface at `x`, `time` seconds into the animation.
+    pub fn surface_height(&self, x: f32, time: f32, amplitude: f32, wavelength: f32) -> f32 {
+        let top = self.area.y + self.area.height;
+        let phase = 2.0 * PI * (x - self.area.x) / wavelength;
+
+        top + amplitude * (phase + time * 2.0).sin() + amplitude * 0.5 * (phase * 0.5 - time).sin()
+    }
+
+    /// Draw the water's surface as a line of gently rolling waves.
+    pub fn draw_surface(
+        &self,
+        app: &mut Apparatus,
+        time: f32,
+        amplitude: f32,
+        wavelength: f32,
+        color: Color,
+    ) {
+        let Rect { x, width, .. } = self.area;
+        let segments = (width / 4.0).ceil().max(1.0) as usize;
+        let segment_width = width / segments as f32;
+
+        let mut previous = (x, self.surface_height(x, time, amplitude, wavelength));
+        for segment in 1..=segments {
+            let next_x = x + segment as f32 * segment_width;
+            let next = (
+                next_x,
+                self.surface_height(next_x, time, amplitude, wavelength),
+            );
+            app.draw_line(previous.0, previous.1, next.0, next.1, color);
+            previous = next;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn submerged_fraction_measures_bounding_box_overlap() {
+        let water = WaterVolume::new(Rect::new(0.0, 0.0, 100.0, 10.0));
+
+        let floating = Body::dynamic(
+            Shape::Rectangle {
+                width: 2.0,
+                height: 4.0,
+            },
+            50.0,
+            10.0,
+        );
+        let dry = Body::dynamic(Shape::Circle { radius: 1.0 }, 50.0, 20.0);
+
+        assert_eq!(0.5, water.submerged_fraction(&floating));
+        assert_eq!(0.0, water.submerged_fraction(&dry));
+    }
+
+    #[test]
+    fn surface_height_stays_within_amplitude() {
+        let water = WaterVolume::new(Rect::new(0.0, 0.0, 100.0, 10.0));
+
+        for x in 0..100 {
+            let height = water.surface_height(x as f32, 1.3, 2.0, 16.0);
+            assert!((height - 10.0).abs() <= 3.0);
+        }
+    }
+}
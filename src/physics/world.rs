@@ -0,0 +1,852 @@
+use std::collections::{BTreeSet, VecDeque};
+
+use crate::maths::Vec2;
+use crate::physics::body::{Body, BodyHandle, BodyKind};
+use crate::physics::collision;
+use crate::physics::joint::{Joint, JointHandle};
+use crate::physics::water::{WaterHandle, WaterVolume};
+
+const DEFAULT_SOLVER_ITERATIONS: usize = 8;
+
+/// Two bodies touching after a step. The normal points from `a` towards `b`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Contact {
+    pub a: BodyHandle,
+    pub b: BodyHandle,
+    pub normal: Vec2,
+    pub depth: f32,
+    pub point: Vec2,
+}
+
+/// A body entering or leaving a sensor during the most recent step. When a body is removed while
+/// overlapping a sensor, an `Exit` is reported on the next step.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SensorEvent {
+    Enter {
+        sensor: BodyHandle,
+        other: BodyHandle,
+    },
+    Exit {
+        sensor: BodyHandle,
+        other: BodyHandle,
+    },
+}
+
+/// Everything needed to put a world back the way it was after a particular step.
+#[derive(Clone, Debug)]
+pub struct WorldState {
+    tick: u64,
+    bodies: Vec<Option<Body>>,
+    free: Vec<usize>,
+    joints: Vec<Option<Joint>>,
+    free_joints: Vec<usize>,
+    water: Vec<Option<WaterVolume>>,
+    gravity: Vec2,
+    sensor_overlaps: BTreeSet<(BodyHandle, BodyHandle)>,
+}
+
+impl WorldState {
+    /// The number of steps the world had taken when this state was captured.
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+}
+
+/// Simulates a set of bodies under gravity, resolving collisions between them.
+///
+/// Only pairs whose bounding boxes overlap on the x-axis and whose collision filters accept each
+/// other are tested in detail, so putting bodies that never interact on separate layers keeps
+/// large worlds cheap.
+pub struct PhysicsWorld {
+    bodies: Vec<Option<Body>>,
+    free: Vec<usize>,
+    joints: Vec<Option<Joint>>,
+    free_joints: Vec<usize>,
+    solver_iterations: usize,
+    water: Vec<Option<WaterVolume>>,
+    gravity: Vec2,
+    contacts: Vec<Contact>,
+    sensor_overlaps: BTreeSet<(BodyHandle, BodyHandle)>,
+    sensor_events: Vec<SensorEvent>,
+    /// Exits for overlaps ended by removing a body, reported on the next step.
+    removed_overlaps: Vec<SensorEvent>,
+    tick: u64,
+    history: VecDeque<WorldState>,
+    history_capacity: usize,
+}
+
+impl Default for PhysicsWorld {
+    fn default() -> Self {
+        Self {
+            bodies: Vec::new(),
+            free: Vec::new(),
+            joints: Vec::new(),
+            free_joints: Vec::new(),
+            solver_iterations: DEFAULT_SOLVER_ITERATIONS,
+            water: Vec::new(),
+            gravity: Vec2::default(),
+            contacts: Vec::new(),
+            sensor_overlaps: BTreeSet::new(),
+            sensor_events: Vec::new(),
+            removed_overlaps: Vec::new(),
+            tick: 0,
+            history: VecDeque::new(),
+            history_capacity: 0,
+        }
+    }
+}
+
+impl PhysicsWorld {
+    /// Create a world with gravity in virtual pixels per second squared, e.g. `(0.0, -100.0)`.
+    pub fn new(gravity: Vec2) -> Self {
+        Self {
+            gravity,
+            ..Self::default()
+        }
+    }
+
+    pub fn gravity(&self) -> Vec2 {
+        self.gravity
+    }
+
+    pub fn set_gravity(&mut self, gravity: Vec2) {
+        self.gravity = gravity;
+    }
+
+    /// How many times rigid joints are relaxed each step. More iterations make long chains stiffer.
+    pub fn set_solver_iterations(&mut self, iterations: usize) {
+        self.solver_iterations = iterations;
+    }
+
+    pub fn add(&mut self, body: Body) -> BodyHandle {
+        match self.free.pop() {
+            Some(index) => {
+                self.bodies[index] = Some(body);
+                BodyHandle(index)
+            }
+            None => {
+                self.bodies.push(Some(body));
+                BodyHandle(self.bodies.len() - 1)
+            }
+        }
+    }
+
+    /// Remove a body along with any joints attached to it. Its handle is reused by later bodies,
+    /// so an `Exit` is queued for every sensor overlap it was part of rather than waiting for
+    /// the next step to notice it has gone.
+    pub fn remove(&mut self, handle: BodyHandle) -> Option<Body> {
+        let body = self.bodies.get_mut(handle.0)?.take()?;
+        self.free.push(handle.0);
+
+        for i in 0..self.joints.len() {
+            if let Some(joint) = self.joints[i] {
+                let (a, b) = joint.bodies();
+                if a == handle || b == handle {
+                    self.remove_joint(JointHandle(i));
+                }
+            }
+        }
+
+        let (removed, kept) = std::mem::take(&mut self.sensor_overlaps)
+            .into_iter()
+            .partition(|&(sensor, other)| sensor == handle || other == handle);
+        self.sensor_overlaps = kept;
+        self.removed_overlaps.extend(
+            removed
+                .into_iter()
+                .map(|(sensor, other)| SensorEvent::Exit { sensor, other }),
+        );
+
+        Some(body)
+    }
+
+    pub fn get(&self, handle: BodyHandle) -> Option<&Body> {
+        self.bodies.get(handle.0)?.as_ref()
+    }
+
+    pub fn get_mut(&mut self, handle: BodyHandle) -> Option<&mut Body> {
+        self.bodies.get_mut(handle.0)?.as_mut()
+    }
+
+    pub fn bodies(&self) -> impl Iterator<Item = (BodyHandle, &Body)> {
+        self.bodies
+            .iter()
+            .enumerate()
+            .filter_map(|(i, body)| body.as_ref().map(|body| (BodyHandle(i), body)))
+    }
+
+    pub fn add_joint(&mut self, joint: Joint) -> JointHandle {
+        match self.free_joints.pop() {
+            Some(index) => {
+                self.joints[index] = Some(joint);
+                JointHandle(index)
+            }
+            None => {
+                self.joints.push(Some(joint));
+                JointHandle(self.joints.len() - 1)
+            }
+        }
+    }
+
+    pub fn remove_joint(&mut self, handle: JointHandle) -> Option<Joint> {
+        let joint = self.joints.get_mut(handle.0)?.take()?;
+        self.free_joints.push(handle.0);
+
+        Some(joint)
+    }
+
+    pub fn joint(&self, handle: JointHandle) -> Option<&Joint> {
+        self.joints.get(handle.0)?.as_ref()
+    }
+
+    pub fn joints(&self) -> impl Iterator<Item = (JointHandle, &Joint)> {
+        self.joints
+            .iter()
+            .enumerate()
+            .filter_map(|(i, joint)| joint.as_ref().map(|joint| (JointHandle(i), joint)))
+    }
+
+    pub fn add_water(&mut self, water: WaterVolume) -> WaterHandle {
+        self.water.push(Some(water));
+        WaterHandle(self.water.len() - 1)
+    }
+
+    pub fn remove_water(&mut self, handle: WaterHandle) -> Option<WaterVolume> {
+        self.water.get_mut(handle.0)?.take()
+    }
+
+    pub fn water(&self) -> impl Iterator<Item = (WaterHandle, &WaterVolume)> {
+        self.water
+            .iter()
+            .enumerate()
+            .filter_map(|(i, water)| water.as_ref().map(|water| (WaterHandle(i), water)))
+    }
+
+    /// Contacts found during the most recent step.
+    pub fn contacts(&self) -> &[Contact] {
+        &self.contacts
+    }
+
+    /// Sensor enter and exit events from the most recent step.
+    pub fn sensor_events(&self) -> &[SensorEvent] {
+        &self.sensor_events
+    }
+
+    /// Keep the state before each of the last `capacity` steps so they can be rewound. Defaults
+    /// to 0, keeping no history.
+    pub fn set_history_capacity(&mut self, capacity: usize) {
+        self.history_capacity = capacity;
+        while self.history.len() > capacity {
+            self.history.pop_front();
+        }
+    }
+
+    /// The number of steps taken since the world was created.
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    /// How many steps can currently be rewound.
+    pub fn history_len(&self) -> usize {
+        self.history.len()
+    }
+
+    /// Capture the world as it is now.
+    pub fn snapshot(&self) -> WorldState {
+        WorldState {
+            tick: self.tick,
+            bodies: self.bodies.clone(),
+            free: self.free.clone(),
+            joints: self.joints.clone(),
+            free_joints: self.free_joints.clone(),
+            water: self.water.clone(),
+            gravity: self.gravity,
+            sensor_overlaps: self.sensor_overlaps.clone(),
+        }
+    }
+
+    /// Put the world back to a captured state. History newer than the state is discarded, and
+    /// contacts and sensor events are cleared until the next step.
+    pub fn restore(&mut self, state: &WorldState) {
+        let state = state.clone();
+        self.tick = state.tick;
+        self.bodies = state.bodies;
+        self.free = state.free;
+        self.joints = state.joints;
+        self.free_joints = state.free_joints;
+        self.water = state.water;
+        self.gravity = state.gravity;
+        self.sensor_overlaps = state.sensor_overlaps;
+        self.removed_overlaps.clear();
+        self.contacts.clear();
+        self.sensor_events.clear();
+
+        let tick = self.tick;
+        self.history.retain(|past| past.tick < tick);
+    }
+
+    /// Undo the last `steps` steps. Returns false, leaving the world alone, if there isn't that
+    /// much history.
+    pub fn rewind(&mut self, steps: usize) -> bool {
+        if steps == 0 {
+            return true;
+        }
+        if steps > self.history.len() {
+            return false;
+        }
+
+        let state = self.history[self.history.len() - steps].clone();
+        self.restore(&state);
+        true
+    }
+
+    /// Rewind `steps` steps and simulate them again, calling `apply` before each step with the
+    /// tick about to be simulated, e.g. to replay corrected inputs from a server. Returns false
+    /// if there isn't that much history.
+    pub fn resimulate(
+        &mut self,
+        steps: usize,
+        dt: f32,
+        mut apply: impl FnMut(&mut PhysicsWorld, u64),
+    ) -> bool {
+        if !self.rewind(steps) {
+            return false;
+        }
+
+        for _ in 0..steps {
+            apply(self, self.tick);
+            self.step(dt);
+        }
+        true
+    }
+
+    /// Move everything by `-shift`, including the history, e.g. when a `FloatingOrigin` rebases.
+    pub fn shift_origin(&mut self, shift: Vec2) {
+        let shift_bodies = |bodies: &mut Vec<Option<Body>>| {
+            for body in bodies.iter_mut().flatten() {
+                body.position = body.position - shift;
+            }
+        };
+        let shift_water = |water: &mut Vec<Option<WaterVolume>>| {
+            for volume in water.iter_mut().flatten() {
+                volume.area.x -= shift.x;
+                volume.area.y -= shift.y;
+            }
+        };
+
+        shift_bodies(&mut self.bodies);
+        shift_water(&mut self.water);
+        for state in &mut self.history {
+            shift_bodies(&mut state.bodies);
+            shift_water(&mut state.water);
+        }
+        for contact in &mut self.contacts {
+            contact.point = contact.point - shift;
+        }
+    }
+
+    /// Advance the simulation by `dt` seconds.
+    pub fn step(&mut self, dt: f32) {
+        if self.history_capacity > 0 {
+            if self.history.len() == self.history_capacity {
+                self.history.pop_front();
+            }
+            self.history.push_back(self.snapshot());
+        }
+        self.tick += 1;
+
+        for i in 0..self.joints.len() {
+            if let Some(joint) = self.joints[i] {
+                let (a, b) = joint.bodies();
+                if let Some((a, b)) = self.pair_mut(a, b) {
+                    joint.apply_force(a, b, dt);
+                }
+            }
+        }
+
+        let gravity = self.gravity;
+        for body in self.bodies.iter_mut().flatten() {
+            if body.kind == BodyKind::Dynamic {
+                body.velocity = body.velocity + gravity * (body.gravity_scale * dt);
+                for water in self.water.iter().flatten() {
+                    water.apply(body, gravity, dt);
+                }
+                body.position = body.position + body.velocity * dt;
+            }
+        }
+
+        let (contacts, overlaps): (Vec<Contact>, Vec<Contact>) =
+            self.find_contacts().into_iter().partition(|contact| {
+                let is_sensor = |handle: BodyHandle| self.get(handle).is_some_and(|b| b.sensor);
+                !is_sensor(contact.a) && !is_sensor(contact.b)
+            });
+        self.contacts = contacts;
+        self.update_sensors(&overlaps);
+
+        for i in 0..self.contacts.len() {
+            let contact = self.contacts[i];
+            self.resolve(&contact);
+        }
+
+        for _ in 0..self.solver_iterations {
+            for i in 0..self.joints.len() {
+                if let Some(joint) = self.joints[i] {
+                    let (a, b) = joint.bodies();
+                    if let Some((a, b)) = self.pair_mut(a, b) {
+                        joint.solve(a, b);
+                    }
+                }
+            }
+        }
+    }
+
+    fn update_sensors(&mut self, overlaps: &[Contact]) {
+        let overlaps: BTreeSet<(BodyHandle, BodyHandle)> = overlaps
+            .iter()
+            .map(|contact| match self.get(contact.a) {
+                Some(a) if a.sensor => (contact.a, contact.b),
+                _ => (contact.b, contact.a),
+            })
+            .collect();
+
+        self.sensor_events.clear();
+        self.sensor_events.append(&mut self.removed_overlaps);
+        for &(sensor, other) in self.sensor_overlaps.difference(&overlaps) {
+            self.sensor_events.push(SensorEvent::Exit { sensor, other });
+        }
+        for &(sensor, other) in overlaps.difference(&self.sensor_overlaps) {
+            self.sensor_events
+                .push(SensorEvent::Enter { sensor, other });
+        }
+        self.sensor_overlaps = overlaps;
+    }
+
+    /// Sweep and prune along the x-axis, then test surviving pairs that pass their filters.
+    fn find_contacts(&self) -> Vec<Contact> {
+        let mut extents: Vec<(usize, f32, f32)> = self
+            .bodies
+            .iter()
+            .enumerate()
+            .filter_map(|(i, body)| {
+                let body = body.as_ref()?;
+                let half_width = body.shape.half_extents().x;
+                Some((
+                    i,
+                    body.position.x - half_width,
+                    body.position.x + half_width,
+                ))
+            })
+            .collect();
+        extents.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+        let mut contacts = Vec::new();
+        for (i, &(a_index, _, a_max_x)) in extents.iter().enumerate() {
+            for &(b_index, b_min_x, _) in &extents[i + 1..] {
+                if b_min_x > a_max_x {
+                    break;
+                }
+
+                let (a, b) = match (&self.bodies[a_index], &self.bodies[b_index]) {
+                    (Some(a), Some(b)) => (a, b),
+                    _ => continue,
+                };
+                if a.kind == BodyKind::Static && b.kind == BodyKind::Static {
+                    continue;
+                }
+                if a.sensor && b.sensor {
+                    continue;
+                }
+                if !a.filter.can_collide(&b.filter) {
+                    continue;
+                }
+
+                if let Some(manifold) =
+                    collision::collide(&a.shape, a.position, &b.shape, b.position)
+                {
+                    contacts.push(Contact {
+                        a: BodyHandle(a_index),
+                        b: BodyHandle(b_index),
+                        normal: manifold.normal,
+                        depth: manifold.depth,
+                        point: manifold.point,
+                    });
+                }
+            }
+        }
+
+        contacts
+    }
+
+    fn resolve(&mut self, contact: &Contact) {
+        let (a, b) = match self.pair_mut(contact.a, contact.b) {
+            Some(pair) => pair,
+            None => return,
+        };
+
+        let (inverse_mass_a, inverse_mass_b) = (a.inverse_mass(), b.inverse_mass());
+        let inverse_mass_sum = inverse_mass_a + inverse_mass_b;
+        if inverse_mass_sum == 0.0 {
+            return;
+        }
+
+        let normal = contact.normal;
+        let correction = contact.depth / inverse_mass_sum;
+        a.position = a.position - normal * (correction * inverse_mass_a);
+        b.position = b.position + normal * (correction * inverse_mass_b);
+
+        let relative_velocity = b.velocity - a.velocity;
+        let normal_velocity = relative_velocity.dot(normal);
+        if normal_velocity >= 0.0 {
+            return;
+        }
+
+        let elasticity = a.elasticity.min(b.elasticity);
+        let impulse = -(1.0 + elasticity) * normal_velocity / inverse_mass_sum;
+        a.velocity = a.velocity - normal * (impulse * inverse_mass_a);
+        b.velocity = b.velocity + normal * (impulse * inverse_mass_b);
+
+        let tangent = (relative_velocity - normal * normal_velocity).normalized();
+        let friction = (a.friction + b.friction) / 2.0;
+        let friction_impulse = (-relative_velocity.dot(tangent) / inverse_mass_sum)
+            .max(-friction * impulse)
+            .min(friction * impulse);
+        a.velocity = a.velocity - tangent * (friction_impulse * inverse_mass_a);
+        b.velocity = b.velocity + tangent * (friction_impulse * inverse_mass_b);
+    }
+
+    pub(crate) fn pair_mut(
+        &mut self,
+        a: BodyHandle,
+        b: BodyHandle,
+    ) -> Option<(&mut Body, &mut Body)> {
+        if a.0 == b.0 || a.0 >= self.bodies.len() || b.0 >= self.bodies.len() {
+            return None;
+        }
+
+        let (low, high) = (a.0.min(b.0), a.0.max(b.0));
+        let (left, right) = self.bodies.split_at_mut(high);
+        let (low, high) = (left[low].as_mut()?, right[0].as_mut()?);
+
+        if a.0 < b.0 {
+            Some((low, high))
+        } else {
+            Some((high, low))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maths::Rect;
+    use crate::physics::body::Shape;
+    use crate::physics::filter::{CollisionFilter, Layers};
+    use crate::physics::joint::Joint;
+
+    const TERRAIN: Layers = Layers::layer(0);
+    const DEBRIS: Layers = Layers::layer(1);
+
+    fn ground() -> Body {
+        Body::fixed(
+            Shape::Rectangle {
+                width: 100.0,
+                height: 10.0,
+            },
+            0.0,
+            -5.0,
+        )
+    }
+
+    #[test]
+    fn shifting_the_origin_moves_bodies_water_and_history() {
+        let mut world = PhysicsWorld::new(Vec2::default());
+        world.set_history_capacity(4);
+        let ball = world.add(Body::dynamic(Shape::Circle { radius: 1.0 }, 5000.0, 10.0));
+        world.add_water(WaterVolume::new(Rect::new(4990.0, 0.0, 20.0, 5.0)));
+        world.step(0.1);
+
+        world.shift_origin(Vec2::new(5000.0, 0.0));
+
+        assert_eq!(Vec2::new(0.0, 10.0), world.get(ball).unwrap().position);
+        let (_, water) = world.water().next().unwrap();
+        assert_eq!(Rect::new(-10.0, 0.0, 20.0, 5.0), water.area);
+        assert!(world.rewind(1));
+        assert_eq!(Vec2::new(0.0, 10.0), world.get(ball).unwrap().position);
+    }
+
+    #[test]
+    fn gravity_accelerates_dynamic_bodies() {
+        let mut world = PhysicsWorld::new(Vec2::new(0.0, -10.0));
+        let ball = world.add(Body::dynamic(Shape::Circle { radius: 1.0 }, 0.0, 100.0));
+        let wall = world.add(ground());
+
+        world.step(1.0);
+
+        assert_eq!(Vec2::new(0.0, -10.0), world.get(ball).unwrap().velocity);
+        assert_eq!(Vec2::new(0.0, -5.0), world.get(wall).unwrap().position);
+    }
+
+    #[test]
+    fn falling_body_comes_to_rest_on_static_body() {
+        let mut world = PhysicsWorld::new(Vec2::new(0.0, -10.0));
+        world.add(ground());
+        let ball = world.add(Body::dynamic(Shape::Circle { radius: 1.0 }, 0.0, 5.0));
+
+        for _ in 0..200 {
+            world.step(1.0 / 60.0);
+        }
+
+        let ball = world.get(ball).unwrap();
+        assert!((ball.position.y - 1.0).abs() < 0.1, "{:?}", ball.position);
+    }
+
+    #[test]
+    fn bodies_on_layers_that_ignore_each_other_do_not_collide() {
+        let mut world = PhysicsWorld::new(Vec2::new(0.0, 0.0));
+        let debris = CollisionFilter::new(DEBRIS, TERRAIN);
+        world.add(Body::dynamic(Shape::Circle { radius: 1.0 }, 0.0, 0.0).with_filter(debris));
+        world.add(Body::dynamic(Shape::Circle { radius: 1.0 }, 0.5, 0.0).with_filter(debris));
+
+        world.step(1.0 / 60.0);
+
+        assert!(world.contacts().is_empty());
+    }
+
+    #[test]
+    fn debris_collides_with_terrain() {
+        let mut world = PhysicsWorld::new(Vec2::new(0.0, 0.0));
+        let debris = CollisionFilter::new(DEBRIS, TERRAIN);
+        world.add(ground().with_filter(CollisionFilter::new(TERRAIN, Layers::ALL)));
+        world.add(Body::dynamic(Shape::Circle { radius: 1.0 }, 0.0, 0.5).with_filter(debris));
+
+        world.step(1.0 / 60.0);
+
+        assert_eq!(1, world.contacts().len());
+    }
+
+    #[test]
+    fn distance_joint_keeps_pendulum_at_length() {
+        let mut world = PhysicsWorld::new(Vec2::new(0.0, -100.0));
+        let pivot = world.add(Body::fixed(Shape::Circle { radius: 1.0 }, 0.0, 50.0));
+        let bob = world.add(Body::dynamic(Shape::Circle { radius: 1.0 }, 20.0, 50.0));
+        world.add_joint(Joint::distance(pivot, bob, 20.0));
+
+        for _ in 0..60 {
+            world.step(1.0 / 60.0);
+        }
+
+        let delta = world.get(bob).unwrap().position - world.get(pivot).unwrap().position;
+        assert!((delta.length() - 20.0).abs() < 0.1, "{:?}", delta);
+        assert!(delta.y < 0.0);
+    }
+
+    #[test]
+    fn pin_joint_carries_body_with_its_partner() {
+        let mut world = PhysicsWorld::new(Vec2::new(0.0, 0.0));
+        let chassis = world.add(Body::dynamic(Shape::Circle { radius: 1.0 }, 0.0, 0.0));
+        let wheel = world.add(Body::dynamic(Shape::Circle { radius: 1.0 }, 5.0, 0.0));
+        world.add_joint(Joint::pin(chassis, wheel, Vec2::new(5.0, 0.0)));
+        world.get_mut(chassis).unwrap().velocity = Vec2::new(10.0, 0.0);
+
+        for _ in 0..10 {
+            world.step(1.0 / 60.0);
+        }
+
+        let chassis = world.get(chassis).unwrap();
+        let wheel = world.get(wheel).unwrap();
+        assert!((wheel.position.x - chassis.position.x - 5.0).abs() < 0.01);
+        assert!(chassis.position.x > 0.0);
+    }
+
+    #[test]
+    fn spring_pulls_stretched_bodies_together() {
+        let mut world = PhysicsWorld::new(Vec2::new(0.0, 0.0));
+        let a = world.add(Body::dynamic(Shape::Circle { radius: 1.0 }, 0.0, 0.0));
+        let b = world.add(Body::dynamic(Shape::Circle { radius: 1.0 }, 30.0, 0.0));
+        world.add_joint(Joint::spring(a, b, 10.0, 5.0, 0.5));
+
+        world.step(1.0 / 60.0);
+
+        assert!(world.get(a).unwrap().velocity.x > 0.0);
+        assert!(world.get(b).unwrap().velocity.x < 0.0);
+    }
+
+    #[test]
+    fn sensor_reports_enter_and_exit_without_blocking() {
+        let mut world = PhysicsWorld::new(Vec2::new(0.0, 0.0));
+        let pickup =
+            world.add(Body::fixed(Shape::Circle { radius: 2.0 }, 10.0, 0.0).with_sensor(true));
+        let player = world
+            .add(Body::dynamic(Shape::Circle { radius: 1.0 }, 0.0, 0.0).with_velocity(60.0, 0.0));
+
+        let mut events = Vec::new();
+        for _ in 0..30 {
+            world.step(1.0 / 60.0);
+            events.extend_from_slice(world.sensor_events());
+            assert!(world.contacts().is_empty());
+        }
+
+        assert_eq!(
+            vec![
+                SensorEvent::Enter {
+                    sensor: pickup,
+                    other: player
+                },
+                SensorEvent::Exit {
+                    sensor: pickup,
+                    other: player
+                },
+            ],
+            events
+        );
+        assert_eq!(Vec2::new(60.0, 0.0), world.get(player).unwrap().velocity);
+    }
+
+    #[test]
+    fn removing_a_body_inside_a_sensor_reports_exit() {
+        let mut world = PhysicsWorld::new(Vec2::new(0.0, 0.0));
+        let zone =
+            world.add(Body::fixed(Shape::Circle { radius: 5.0 }, 0.0, 0.0).with_sensor(true));
+        let player = world.add(Body::dynamic(Shape::Circle { radius: 1.0 }, 0.0, 0.0));
+        world.step(1.0 / 60.0);
+
+        world.remove(player);
+        world.step(1.0 / 60.0);
+
+        assert_eq!(
+            &[SensorEvent::Exit {
+                sensor: zone,
+                other: player
+            }],
+            world.sensor_events()
+        );
+    }
+
+    #[test]
+    fn reusing_a_removed_sensor_body_handle_still_reports_exit() {
+        let mut world = PhysicsWorld::new(Vec2::new(0.0, 0.0));
+        let zone =
+            world.add(Body::fixed(Shape::Circle { radius: 5.0 }, 0.0, 0.0).with_sensor(true));
+        let player = world.add(Body::dynamic(Shape::Circle { radius: 1.0 }, 0.0, 0.0));
+        world.step(1.0 / 60.0);
+
+        world.remove(player);
+        let replacement = world.add(Body::dynamic(Shape::Circle { radius: 1.0 }, 0.0, 0.0));
+        world.step(1.0 / 60.0);
+
+        assert_eq!(player, replacement);
+        assert_eq!(
+            &[
+                SensorEvent::Exit {
+                    sensor: zone,
+                    other: player
+                },
+                SensorEvent::Enter {
+                    sensor: zone,
+                    other: replacement
+                },
+            ],
+            world.sensor_events()
+        );
+    }
+
+    #[test]
+    fn removing_a_body_removes_its_joints() {
+        let mut world = PhysicsWorld::new(Vec2::new(0.0, -10.0));
+        let anchor = world.add(ground());
+        let bob = world.add(Body::dynamic(Shape::Circle { radius: 1.0 }, 0.0, -10.0));
+        let joint = world.add_joint(Joint::distance(anchor, bob, 10.0));
+
+        world.remove(bob);
+        let replacement = world.add(Body::dynamic(Shape::Circle { radius: 1.0 }, 50.0, -10.0));
+        world.step(1.0 / 60.0);
+
+        assert_eq!(bob, replacement);
+        assert!(world.joint(joint).is_none());
+        assert_eq!(0, world.joints().count());
+        assert!((world.get(replacement).unwrap().position.x - 50.0).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn light_bodies_float_and_heavy_bodies_sink() {
+        let mut world = PhysicsWorld::new(Vec2::new(0.0, -100.0));
+        world.add_water(WaterVolume::new(Rect::new(-50.0, 0.0, 100.0, 50.0)));
+        let square = Shape::Rectangle {
+            width: 2.0,
+            height: 2.0,
+        };
+        let cork = world.add(Body::dynamic(square, -10.0, 25.0).with_mass(2.0));
+        let stone = world.add(Body::dynamic(square, 10.0, 25.0).with_mass(8.0));
+
+        for _ in 0..300 {
+            world.step(1.0 / 60.0);
+        }
+
+        let cork = world.get(cork).unwrap().position.y;
+        let stone = world.get(stone).unwrap().position.y;
+        assert!((cork - 50.0).abs() < 1.0, "{}", cork);
+        assert!(stone < 25.0, "{}", stone);
+    }
+
+    #[test]
+    fn removed_body_handles_are_reused() {
+        let mut world = PhysicsWorld::default();
+        let first = world.add(ground());
+
+        world.remove(first);
+
+        assert!(world.get(first).is_none());
+        assert_eq!(first, world.add(ground()));
+    }
+
+    #[test]
+    fn rewind_restores_earlier_steps() {
+        let mut world = PhysicsWorld::new(Vec2::new(0.0, -10.0));
+        world.set_history_capacity(3);
+        let ball = world.add(Body::dynamic(Shape::Circle { radius: 1.0 }, 0.0, 100.0));
+
+        let start = world.get(ball).unwrap().position;
+        for _ in 0..5 {
+            world.step(0.1);
+        }
+        let after_two = {
+            let mut copy = PhysicsWorld::new(Vec2::new(0.0, -10.0));
+            let ball = copy.add(Body::dynamic(Shape::Circle { radius: 1.0 }, 0.0, 100.0));
+            copy.step(0.1);
+            copy.step(0.1);
+            copy.get(ball).unwrap().position
+        };
+
+        assert_eq!(3, world.history_len());
+        assert!(!world.rewind(4));
+        assert!(world.rewind(3));
+        assert_eq!(2, world.tick());
+        assert_eq!(after_two, world.get(ball).unwrap().position);
+        assert_ne!(start, after_two);
+        assert_eq!(0, world.history_len());
+    }
+
+    #[test]
+    fn resimulate_replays_steps_with_corrections() {
+        let mut world = PhysicsWorld::new(Vec2::new(0.0, 0.0));
+        world.set_history_capacity(10);
+        let ball = world.add(Body::dynamic(Shape::Circle { radius: 1.0 }, 0.0, 0.0));
+        for _ in 0..4 {
+            world.step(1.0);
+        }
+
+        // The server says the ball was pushed at tick 2.
+        let mut ticks = Vec::new();
+        assert!(world.resimulate(3, 1.0, |world, tick| {
+            ticks.push(tick);
+            if tick == 2 {
+                world.get_mut(ball).unwrap().velocity = Vec2::new(1.0, 0.0);
+            }
+        }));
+
+        assert_eq!(vec![1, 2, 3], ticks);
+        assert_eq!(4, world.tick());
+        assert_eq!(Vec2::new(2.0, 0.0), world.get(ball).unwrap().position);
+        assert_eq!(4, world.history_len());
+    }
+}
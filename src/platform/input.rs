@@ -1,8 +1,8 @@
-use minifb::MouseMode;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use crate::engine::key::Key;
 use crate::engine::mouse::MouseButton;
+use crate::engine::replay::ReplayFrame;
 use crate::platform::window::Window;
 
 #[derive(Default, Debug)]
@@ -12,6 +12,37 @@ struct MouseState {
     buttons: HashMap<MouseButton, ButtonState>,
 }
 
+/// Input options for players who find holding keys or pressing combinations difficult. They are
+/// applied before games see the keys, so every game benefits without handling them itself.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct InputAccessibility {
+    toggle_keys: HashSet<Key>,
+    key_repeat: Option<(f32, f32)>,
+    sticky_modifiers: bool,
+}
+
+impl InputAccessibility {
+    /// Press `key` once to hold it down and again to let go, e.g. for sprint or crouch.
+    pub fn with_toggle(mut self, key: Key) -> Self {
+        self.toggle_keys.insert(key);
+        self
+    }
+
+    /// Report a held key as pressed again after `delay` seconds and every `interval` seconds
+    /// after that, so menus can be scrolled without tapping. Defaults to no repeat.
+    pub fn with_key_repeat(mut self, delay: f32, interval: f32) -> Self {
+        self.key_repeat = Some((delay.max(0.0), interval.max(f32::EPSILON)));
+        self
+    }
+
+    /// Tapping a modifier on its own keeps it held until another key has been pressed and
+    /// released, so combinations can be typed one key at a time. Defaults to false.
+    pub fn with_sticky_modifiers(mut self, sticky: bool) -> Self {
+        self.sticky_modifiers = sticky;
+        self
+    }
+}
+
 #[derive(Default, Debug)]
 struct ButtonState {
     is_down: bool,
@@ -26,27 +57,202 @@ impl ButtonState {
 
 pub struct Input {
     keys: HashMap<Key, ButtonState>,
+    physical_keys: HashMap<Key, ButtonState>,
     mouse: MouseState,
+    mouse_moved: bool,
+    typed_text: String,
+    accessibility: InputAccessibility,
+    toggled: HashSet<Key>,
+    sticky: HashSet<Key>,
+    modifier_combined: bool,
+    held_for: HashMap<Key, f32>,
+    repeated: HashSet<Key>,
+}
+
+impl Default for Input {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Input {
     pub fn new() -> Self {
         let keys = HashMap::new();
         let mouse = MouseState::default();
-
-        Self { mouse, keys }
+        let typed_text = String::new();
+
+        Self {
+            mouse,
+            mouse_moved: false,
+            keys,
+            physical_keys: HashMap::new(),
+            typed_text,
+            accessibility: InputAccessibility::default(),
+            toggled: HashSet::new(),
+            sticky: HashSet::new(),
+            modifier_combined: false,
+            held_for: HashMap::new(),
+            repeated: HashSet::new(),
+        }
     }
 
-    pub fn process_input(&mut self, window: &Window) {
-        self.keys = process_keys(window, &self.keys);
+    /// Read this frame's input. `dt` is the time since the last frame in seconds.
+    pub fn process_input(&mut self, window: &Window, dt: f32) {
+        let physical_keys = process_keys(window, &self.physical_keys);
+        self.update_keys(physical_keys, dt);
+        let previous = (self.mouse.x, self.mouse.y);
         self.mouse = process_mouse(window, &self.mouse.buttons);
+        self.mouse_moved = previous != (self.mouse.x, self.mouse.y);
+        self.typed_text = window.take_typed_text();
+    }
+
+    /// Play back a frame of a replay instead of reading the window, as if its keys and buttons
+    /// were held and the mouse was where it was recorded. `dt` is the time since the last frame
+    /// in seconds.
+    pub(crate) fn process_replay_frame(&mut self, frame: &ReplayFrame, dt: f32) {
+        let physical_keys = Key::ALL
+            .into_iter()
+            .map(|key| {
+                let was_down = self
+                    .physical_keys
+                    .get(&key)
+                    .is_some_and(|state| state.is_down);
+                (key, ButtonState::new(frame.keys.contains(&key), was_down))
+            })
+            .collect();
+        self.update_keys(physical_keys, dt);
+
+        let buttons = MouseButton::ALL
+            .into_iter()
+            .map(|button| {
+                let was_down = self
+                    .mouse
+                    .buttons
+                    .get(&button)
+                    .is_some_and(|state| state.is_down);
+                (
+                    button,
+                    ButtonState::new(frame.buttons.contains(&button), was_down),
+                )
+            })
+            .collect();
+        let previous = (self.mouse.x, self.mouse.y);
+        self.mouse = MouseState {
+            x: frame.mouse.0,
+            y: frame.mouse.1,
+            buttons,
+        };
+        self.mouse_moved = previous != frame.mouse;
+        self.typed_text.clear();
+    }
+
+    pub fn accessibility(&self) -> &InputAccessibility {
+        &self.accessibility
+    }
+
+    pub fn set_accessibility(&mut self, accessibility: InputAccessibility) {
+        self.accessibility = accessibility;
+        self.toggled.clear();
+        self.sticky.clear();
+        self.held_for.clear();
     }
 
+    /// Derive the keys games see from the physical key states, applying the accessibility
+    /// options.
+    fn update_keys(&mut self, physical_keys: HashMap<Key, ButtonState>, dt: f32) {
+        let is_pressed = |state: &ButtonState| state.is_down && !state.was_down;
+        let is_released = |state: &ButtonState| !state.is_down && state.was_down;
+
+        let modifier_down = physical_keys
+            .iter()
+            .any(|(key, state)| key.is_modifier() && state.is_down);
+        let other_pressed = physical_keys
+            .iter()
+            .any(|(key, state)| !key.is_modifier() && is_pressed(state));
+        let other_released = physical_keys
+            .iter()
+            .any(|(key, state)| !key.is_modifier() && is_released(state));
+        if other_pressed && (modifier_down || !self.sticky.is_empty()) {
+            self.modifier_combined = true;
+        }
+
+        let mut keys = HashMap::new();
+        self.repeated.clear();
+        for (&key, state) in &physical_keys {
+            let mut is_down = state.is_down;
+
+            if self.accessibility.toggle_keys.contains(&key) {
+                if is_pressed(state) && !self.toggled.remove(&key) {
+                    self.toggled.insert(key);
+                }
+                is_down = self.toggled.contains(&key);
+            } else if self.accessibility.sticky_modifiers && key.is_modifier() {
+                if is_released(state) && !self.modifier_combined {
+                    self.sticky.insert(key);
+                }
+                is_down |= self.sticky.contains(&key);
+            }
+
+            let was_down = self.keys.get(&key).is_some_and(|key| key.is_down);
+            if let Some((delay, interval)) = self.accessibility.key_repeat {
+                let held_for = self.held_for.entry(key).or_default();
+                if is_down && was_down {
+                    let repeats = |time: f32| {
+                        if time < delay {
+                            0
+                        } else {
+                            1 + ((time - delay) / interval) as u32
+                        }
+                    };
+                    if repeats(*held_for + dt) > repeats(*held_for) {
+                        self.repeated.insert(key);
+                    }
+                    *held_for += dt;
+                } else {
+                    *held_for = 0.0;
+                }
+            }
+
+            keys.insert(key, ButtonState::new(is_down, was_down));
+        }
+
+        if other_released && !modifier_down {
+            self.sticky.clear();
+            self.modifier_combined = false;
+        }
+        if !modifier_down && self.sticky.is_empty() {
+            self.modifier_combined = false;
+        }
+
+        self.keys = keys;
+        self.physical_keys = physical_keys;
+    }
+
+    /// Whether the player did nothing this frame: no keys or mouse buttons are down or were just
+    /// released, nothing was typed and the mouse didn't move.
+    pub(crate) fn is_idle(&self) -> bool {
+        let active = |state: &ButtonState| state.is_down || state.was_down;
+
+        !self.keys.values().any(active)
+            && !self.mouse.buttons.values().any(active)
+            && self.typed_text.is_empty()
+            && !self.mouse_moved
+    }
+
+    /// Printable characters typed since the last frame, with keyboard layout and shift applied.
+    pub fn typed_text(&self) -> &str {
+        &self.typed_text
+    }
+
+    /// True on the frame `key` goes down, and again each time it repeats when key repeat is
+    /// enabled.
     pub fn is_key_pressed(&self, key: Key) -> bool {
-        match self.keys.get(&key) {
+        let pressed = match self.keys.get(&key) {
             Some(key) => key.is_down && !key.was_down,
             None => false,
-        }
+        };
+
+        pressed || self.repeated.contains(&key)
     }
 
     pub fn is_key_held(&self, key: Key) -> bool {
@@ -63,6 +269,23 @@ impl Input {
         }
     }
 
+    pub(crate) fn held_keys(&self) -> Vec<Key> {
+        self.keys
+            .iter()
+            .filter(|(_, state)| state.is_down)
+            .map(|(key, _)| *key)
+            .collect()
+    }
+
+    pub(crate) fn held_mouse_buttons(&self) -> Vec<MouseButton> {
+        self.mouse
+            .buttons
+            .iter()
+            .filter(|(_, state)| state.is_down)
+            .map(|(button, _)| *button)
+            .collect()
+    }
+
     pub fn mouse_pos_x(&self) -> f32 {
         self.mouse.x
     }
@@ -98,7 +321,7 @@ fn process_keys(
         previous_keys: &HashMap<Key, ButtonState>,
     ) -> ButtonState {
         let native_key = Into::<NativeKey>::into(key).0;
-        let is_down = window.native_window().is_key_down(native_key);
+        let is_down = window.is_key_down(native_key);
         let was_down = match previous_keys.get(&key) {
             Some(key) => key.is_down,
             None => false,
@@ -230,24 +453,41 @@ fn process_keys(
     let key_state = get_key_state(Key::Space, window, previous_keys);
     keys.insert(Key::Space, key_state);
 
+    let key_state = get_key_state(Key::Escape, window, previous_keys);
+    keys.insert(Key::Escape, key_state);
+
+    let key_state = get_key_state(Key::Enter, window, previous_keys);
+    keys.insert(Key::Enter, key_state);
+
+    let key_state = get_key_state(Key::Backspace, window, previous_keys);
+    keys.insert(Key::Backspace, key_state);
+
+    let key_state = get_key_state(Key::Tab, window, previous_keys);
+    keys.insert(Key::Tab, key_state);
+
+    let key_state = get_key_state(Key::LeftShift, window, previous_keys);
+    keys.insert(Key::LeftShift, key_state);
+
+    let key_state = get_key_state(Key::RightShift, window, previous_keys);
+    keys.insert(Key::RightShift, key_state);
+
     keys
 }
 
+/// minifb gives the mouse position from the top left of the window; (0, 0) is bottom left.
+fn from_native_mouse_pos(x: f32, y: f32, window_height: f32) -> (f32, f32) {
+    (x, window_height - y)
+}
+
 fn process_mouse(
     window: &Window,
     previous_buttons: &HashMap<MouseButton, ButtonState>,
 ) -> MouseState {
     let mut mouse = MouseState::default();
 
-    let (mouse_pos_x, mouse_pos_y) = window
-        .native_window()
-        .get_mouse_pos(MouseMode::Pass)
-        .expect("MouseMode::Pass always returns a position");
-
-    // (0, 0) is bottom left.
-    let (_, window_height) = window.native_window().get_size();
-    mouse.x = mouse_pos_x;
-    mouse.y = window_height as f32 - mouse_pos_y;
+    let (mouse_pos_x, mouse_pos_y) = window.mouse_pos();
+    let (_, window_height) = window.size();
+    (mouse.x, mouse.y) = from_native_mouse_pos(mouse_pos_x, mouse_pos_y, window_height);
 
     fn get_mouse_button_state(
         button: MouseButton,
@@ -255,7 +495,7 @@ fn process_mouse(
         previous_buttons: &HashMap<MouseButton, ButtonState>,
     ) -> ButtonState {
         let native_button = Into::<NativeMouseButton>::into(button).0;
-        let is_down = window.native_window().get_mouse_down(native_button);
+        let is_down = window.is_mouse_down(native_button);
         let was_down = match previous_buttons.get(&button) {
             Some(button) => button.is_down,
             None => false,
@@ -322,6 +562,12 @@ impl From<Key> for NativeKey {
             Key::Left => NativeKey(minifb::Key::Left),
             Key::Right => NativeKey(minifb::Key::Right),
             Key::Space => NativeKey(minifb::Key::Space),
+            Key::Escape => NativeKey(minifb::Key::Escape),
+            Key::Enter => NativeKey(minifb::Key::Enter),
+            Key::Backspace => NativeKey(minifb::Key::Backspace),
+            Key::Tab => NativeKey(minifb::Key::Tab),
+            Key::LeftShift => NativeKey(minifb::Key::LeftShift),
+            Key::RightShift => NativeKey(minifb::Key::RightShift),
         }
     }
 }
@@ -342,6 +588,21 @@ impl From<MouseButton> for NativeMouseButton {
 mod test {
     use super::*;
 
+    #[test]
+    fn input_is_idle_until_something_happens() {
+        let mut input = Input::new();
+        assert!(input.is_idle());
+
+        input.keys.insert(Key::Up, ButtonState::new(false, true));
+        assert!(!input.is_idle());
+
+        input.keys.insert(Key::Up, ButtonState::new(false, false));
+        assert!(input.is_idle());
+
+        input.mouse_moved = true;
+        assert!(!input.is_idle());
+    }
+
     #[test]
     fn key_not_pressed_is_not_pressed() {
         let input = Input::new();
@@ -484,4 +745,98 @@ mod test {
 
         assert!(input.was_mouse_button_released(MouseButton::Left));
     }
+
+    #[test]
+    fn mouse_position_is_from_the_bottom_left_of_the_window() {
+        assert_eq!((10.0, 70.0), from_native_mouse_pos(10.0, 30.0, 100.0));
+        assert_eq!((0.0, 100.0), from_native_mouse_pos(0.0, 0.0, 100.0));
+    }
+
+    #[test]
+    fn replay_frames_press_hold_and_release() {
+        let mut input = Input::new();
+        let held = ReplayFrame {
+            keys: vec![Key::Space],
+            mouse: (12.0, 34.0),
+            buttons: vec![MouseButton::Left],
+        };
+
+        input.process_replay_frame(&held, 1.0 / 60.0);
+        assert!(input.is_key_pressed(Key::Space));
+        assert_eq!((12.0, 34.0), (input.mouse_pos_x(), input.mouse_pos_y()));
+
+        input.process_replay_frame(&held, 1.0 / 60.0);
+        assert!(input.is_key_held(Key::Space));
+        assert!(input.is_mouse_button_held(MouseButton::Left));
+
+        input.process_replay_frame(&ReplayFrame::default(), 1.0 / 60.0);
+        assert!(input.was_key_released(Key::Space));
+        assert!(input.was_mouse_button_released(MouseButton::Left));
+    }
+
+    /// Run a frame with `down` physically held.
+    fn frame(input: &mut Input, down: &[Key], dt: f32) {
+        let keys = [Key::A, Key::Space, Key::LeftShift]
+            .into_iter()
+            .map(|key| {
+                let was_down = input
+                    .physical_keys
+                    .get(&key)
+                    .is_some_and(|state| state.is_down);
+                (key, ButtonState::new(down.contains(&key), was_down))
+            })
+            .collect();
+        input.update_keys(keys, dt);
+    }
+
+    #[test]
+    fn toggle_keys_stay_held_until_pressed_again() {
+        let mut input = Input::new();
+        input.set_accessibility(InputAccessibility::default().with_toggle(Key::Space));
+
+        frame(&mut input, &[Key::Space], 0.1);
+        assert!(input.is_key_pressed(Key::Space));
+        frame(&mut input, &[], 0.1);
+        frame(&mut input, &[], 0.1);
+        assert!(input.is_key_held(Key::Space));
+
+        frame(&mut input, &[Key::Space], 0.1);
+        assert!(input.was_key_released(Key::Space));
+    }
+
+    #[test]
+    fn held_keys_repeat_after_delay() {
+        let mut input = Input::new();
+        input.set_accessibility(InputAccessibility::default().with_key_repeat(0.5, 0.25));
+
+        let pressed: Vec<bool> = (0..9)
+            .map(|_| {
+                frame(&mut input, &[Key::A], 0.125);
+                input.is_key_pressed(Key::A)
+            })
+            .collect();
+
+        assert_eq!(
+            vec![true, false, false, false, true, false, true, false, true],
+            pressed
+        );
+    }
+
+    #[test]
+    fn tapped_modifier_sticks_until_next_key() {
+        let mut input = Input::new();
+        input.set_accessibility(InputAccessibility::default().with_sticky_modifiers(true));
+
+        frame(&mut input, &[Key::LeftShift], 0.1);
+        frame(&mut input, &[], 0.1);
+        assert!(input.is_key_held(Key::LeftShift));
+
+        frame(&mut input, &[Key::A], 0.1);
+        assert!(input.is_key_held(Key::LeftShift));
+        assert!(input.is_key_pressed(Key::A));
+
+        frame(&mut input, &[], 0.1);
+        frame(&mut input, &[], 0.1);
+        assert!(!input.is_key_held(Key::LeftShift));
+    }
 }
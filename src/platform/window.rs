@@ -1,42 +1,336 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
 use crate::errors::ApparatusError;
 use crate::platform::framebuffer::FrameBuffer;
 
+/// How the native window is decorated and composited.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct WindowOptions {
+    pub(crate) borderless: bool,
+    pub(crate) topmost: bool,
+    /// Let pixels with zero alpha show the desktop behind the window. Implies borderless.
+    pub(crate) transparent: bool,
+    /// Cover the screen as far as minifb can: borderless, topmost, in the top left corner and
+    /// scaled up to the largest whole multiple of its size that fits. Implies borderless.
+    pub(crate) fullscreen: bool,
+}
+
+impl From<WindowOptions> for minifb::WindowOptions {
+    fn from(options: WindowOptions) -> Self {
+        let borderless = options.borderless || options.transparent || options.fullscreen;
+        minifb::WindowOptions {
+            borderless,
+            title: !borderless,
+            topmost: options.topmost || options.fullscreen,
+            transparency: options.transparent,
+            scale: if options.fullscreen {
+                minifb::Scale::FitScreen
+            } else {
+                minifb::Scale::X1
+            },
+            // Windows only supports transparency on undecorated windows.
+            none: options.transparent && cfg!(target_os = "windows"),
+            ..minifb::WindowOptions::default()
+        }
+    }
+}
+
+/// Collects characters typed into the window between frames.
+struct TypedText(Rc<RefCell<String>>);
+
+impl minifb::InputCallback for TypedText {
+    fn add_char(&mut self, uni_char: u32) {
+        if let Some(c) = char::from_u32(uni_char).filter(|c| !c.is_control()) {
+            self.0.borrow_mut().push(c);
+        }
+    }
+}
+
+/// Notices the window losing focus between frames.
+#[derive(Debug)]
+pub(crate) struct FocusTracker {
+    was_active: bool,
+}
+
+impl Default for FocusTracker {
+    fn default() -> Self {
+        Self { was_active: true }
+    }
+}
+
+impl FocusTracker {
+    /// Whether the window has lost focus since the last update.
+    pub(crate) fn lost_focus(&mut self, is_active: bool) -> bool {
+        let lost_focus = self.was_active && !is_active;
+        self.was_active = is_active;
+        lost_focus
+    }
+
+    /// Forget the previous focus, e.g. for a reopened window that starts inactive, so only a
+    /// later loss of focus counts.
+    pub(crate) fn reset(&mut self) {
+        self.was_active = false;
+    }
+}
+
 pub struct Window {
+    name: String,
     width: f32,
     height: f32,
-    native_window: minifb::Window,
+    options: WindowOptions,
+    /// The last position set, restored when the window is reopened. minifb can't report where
+    /// the window is, so moves made by the player aren't known.
+    position: Option<(isize, isize)>,
+    /// None when running headless, so nothing is shown and no input is read.
+    native_window: Option<minifb::Window>,
+    typed_text: Rc<RefCell<String>>,
 }
 
 impl Window {
-    pub(crate) fn new(name: &str, width: f32, height: f32) -> Result<Self, ApparatusError> {
-        let native_window = minifb::Window::new(
-            name,
-            width as usize,
-            height as usize,
-            minifb::WindowOptions::default(),
-        )
-        .map_err(|e| ApparatusError::Window(e.into()))?;
+    pub(crate) fn new(
+        name: &str,
+        width: f32,
+        height: f32,
+        options: WindowOptions,
+    ) -> Result<Self, ApparatusError> {
+        let typed_text = Rc::new(RefCell::new(String::new()));
+        let native_window = open_native_window(name, width, height, options, &typed_text)?;
 
         let window = Self {
+            name: name.to_string(),
             width,
             height,
-            native_window,
+            options,
+            position: None,
+            native_window: Some(native_window),
+            typed_text,
         };
 
         Ok(window)
     }
 
-    pub(crate) fn native_window(&self) -> &minifb::Window {
-        &self.native_window
+    /// A window that is never opened, e.g. to run a game on a build server.
+    pub(crate) fn headless(name: &str, width: f32, height: f32) -> Self {
+        Self {
+            name: name.to_string(),
+            width,
+            height,
+            options: WindowOptions::default(),
+            position: None,
+            native_window: None,
+            typed_text: Rc::new(RefCell::new(String::new())),
+        }
+    }
+
+    /// Change the size of the window's client area. The native window can't be resized in
+    /// place, so it is reopened with the same title, options and position.
+    pub(crate) fn resize(&mut self, width: f32, height: f32) -> Result<(), ApparatusError> {
+        if let Some(native_window) = &mut self.native_window {
+            *native_window =
+                open_native_window(&self.name, width, height, self.options, &self.typed_text)?;
+            if let Some((x, y)) = self.position {
+                native_window.set_position(x, y);
+            }
+        }
+        self.width = width;
+        self.height = height;
+
+        Ok(())
+    }
+
+    pub(crate) fn is_key_down(&self, key: minifb::Key) -> bool {
+        self.native_window
+            .as_ref()
+            .is_some_and(|native_window| native_window.is_key_down(key))
+    }
+
+    pub(crate) fn is_mouse_down(&self, button: minifb::MouseButton) -> bool {
+        self.native_window
+            .as_ref()
+            .is_some_and(|native_window| native_window.get_mouse_down(button))
+    }
+
+    /// The mouse position in frame pixels from the top left of the window.
+    pub(crate) fn mouse_pos(&self) -> (f32, f32) {
+        self.native_window
+            .as_ref()
+            .and_then(|native_window| native_window.get_mouse_pos(minifb::MouseMode::Pass))
+            .unwrap_or_default()
+    }
+
+    /// The size of the frame shown in the window, which mouse positions are given in even when
+    /// the window is scaled to fit the screen.
+    pub(crate) fn size(&self) -> (f32, f32) {
+        (self.width, self.height)
+    }
+
+    pub(crate) fn take_typed_text(&self) -> String {
+        self.typed_text.take()
     }
 
     pub(crate) fn display(&mut self, buffer: &FrameBuffer) -> Result<(), ApparatusError> {
+        match &mut self.native_window {
+            Some(native_window) => native_window
+                .update_with_buffer(&buffer.data, self.width as usize, self.height as usize)
+                .map_err(|e| ApparatusError::Window(e.into())),
+            None => Ok(()),
+        }
+    }
+
+    /// Handle window events without presenting a new frame.
+    pub(crate) fn poll_events(&mut self) {
+        if let Some(native_window) = &mut self.native_window {
+            native_window.update();
+        }
+    }
+
+    /// Whether the window has focus. A headless window always does, so it never pauses.
+    pub(crate) fn is_active(&mut self) -> bool {
         self.native_window
-            .update_with_buffer(&buffer.data, self.width as usize, self.height as usize)
-            .map_err(|e| ApparatusError::Window(e.into()))
+            .as_mut()
+            .is_none_or(|native_window| native_window.is_active())
+    }
+
+    pub(crate) fn set_position(&mut self, x: isize, y: isize) {
+        self.position = Some((x, y));
+        if let Some(native_window) = &mut self.native_window {
+            native_window.set_position(x, y);
+        }
     }
 
     pub(crate) fn should_close(&self) -> bool {
-        !self.native_window.is_open()
+        self.native_window
+            .as_ref()
+            .is_some_and(|native_window| !native_window.is_open())
+    }
+}
+
+fn open_native_window(
+    name: &str,
+    width: f32,
+    height: f32,
+    options: WindowOptions,
+    typed_text: &Rc<RefCell<String>>,
+) -> Result<minifb::Window, ApparatusError> {
+    let mut native_window =
+        minifb::Window::new(name, width as usize, height as usize, options.into())
+            .map_err(|e| ApparatusError::Window(e.into()))?;
+    native_window.set_input_callback(Box::new(TypedText(typed_text.clone())));
+    if options.fullscreen {
+        native_window.set_position(0, 0);
+    }
+
+    Ok(native_window)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_options_are_a_decorated_window() {
+        let options = minifb::WindowOptions::from(WindowOptions::default());
+
+        assert!(!options.borderless);
+        assert!(options.title);
+        assert!(!options.topmost);
+        assert!(!options.transparency);
+        assert!(!options.none);
+    }
+
+    #[test]
+    fn borderless_options_hide_the_title_bar() {
+        let options = minifb::WindowOptions::from(WindowOptions {
+            borderless: true,
+            topmost: true,
+            ..WindowOptions::default()
+        });
+
+        assert!(options.borderless);
+        assert!(!options.title);
+        assert!(options.topmost);
+        assert!(!options.transparency);
+        assert!(!options.none);
+    }
+
+    #[test]
+    fn transparent_options_imply_borderless() {
+        let options = minifb::WindowOptions::from(WindowOptions {
+            transparent: true,
+            ..WindowOptions::default()
+        });
+
+        assert!(options.borderless);
+        assert!(!options.title);
+        assert!(options.transparency);
+        assert_eq!(cfg!(target_os = "windows"), options.none);
+    }
+
+    #[test]
+    fn fullscreen_options_cover_the_screen() {
+        let options = minifb::WindowOptions::from(WindowOptions {
+            fullscreen: true,
+            ..WindowOptions::default()
+        });
+
+        assert!(options.borderless);
+        assert!(!options.title);
+        assert!(options.topmost);
+        assert!(matches!(options.scale, minifb::Scale::FitScreen));
+        assert!(matches!(
+            minifb::WindowOptions::from(WindowOptions::default()).scale,
+            minifb::Scale::X1
+        ));
+    }
+
+    #[test]
+    fn headless_windows_never_close_or_lose_focus() {
+        let mut window = Window::headless("test", 64.0, 48.0);
+
+        window.display(&FrameBuffer::new(64, 48)).unwrap();
+        window.resize(128.0, 96.0).unwrap();
+
+        assert!(!window.should_close());
+        assert!(window.is_active());
+        assert!(!window.is_key_down(minifb::Key::A));
+        assert_eq!((0.0, 0.0), window.mouse_pos());
+        assert_eq!((128.0, 96.0), window.size());
+    }
+
+    #[test]
+    fn focus_loss_is_reported_once() {
+        let mut focus = FocusTracker::default();
+
+        assert!(!focus.lost_focus(true));
+        assert!(focus.lost_focus(false));
+        assert!(!focus.lost_focus(false));
+    }
+
+    #[test]
+    fn reset_focus_ignores_the_new_window_starting_inactive() {
+        let mut focus = FocusTracker::default();
+        focus.lost_focus(true);
+
+        focus.reset();
+
+        assert!(!focus.lost_focus(false));
+        assert!(!focus.lost_focus(true));
+        assert!(focus.lost_focus(false));
+    }
+
+    #[test]
+    fn resize_keeps_the_window_position() {
+        // Opening a window needs a display, which test environments often lack.
+        let mut window = match Window::new("test", 64.0, 64.0, WindowOptions::default()) {
+            Ok(window) => window,
+            Err(_) => return,
+        };
+        window.set_position(40, 30);
+
+        window.resize(128.0, 96.0).unwrap();
+
+        assert_eq!(Some((40, 30)), window.position);
+        assert_eq!((128.0, 96.0), (window.width, window.height));
     }
 }
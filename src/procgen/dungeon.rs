@@ -0,0 +1,412 @@
+use std::collections::VecDeque;
+
+use crate::engine::tilemap::Tilemap;
+use crate::procgen::rng::Rng;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Cell {
+    Wall,
+    Floor,
+}
+
+/// A rectangular room in tile coordinates.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Room {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl Room {
+    pub fn center(&self) -> (i32, i32) {
+        (self.x + self.width / 2, self.y + self.height / 2)
+    }
+
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        x >= self.x && x < self.x + self.width && y >= self.y && y < self.y + self.height
+    }
+}
+
+/// A generated level. The exit is the floor tile furthest (by walking distance) from the spawn,
+/// and is always reachable from it.
+#[derive(Clone, Debug)]
+pub struct Dungeon {
+    pub tiles: Tilemap<Cell>,
+    pub rooms: Vec<Room>,
+    pub spawn: (i32, i32),
+    pub exit: (i32, i32),
+}
+
+impl Dungeon {
+    fn new(mut tiles: Tilemap<Cell>, rooms: Vec<Room>, spawn: (i32, i32)) -> Self {
+        tiles.set(spawn.0, spawn.1, Cell::Floor);
+        let exit = furthest_floor(&tiles, spawn);
+        Self {
+            tiles,
+            rooms,
+            spawn,
+            exit,
+        }
+    }
+
+    pub fn is_floor(&self, x: i32, y: i32) -> bool {
+        self.tiles.get(x, y) == Some(&Cell::Floor)
+    }
+}
+
+/// Rooms and corridors: the map is split recursively into leaves (binary space partitioning),
+/// a room is placed in each leaf and sibling leaves are joined by L-shaped corridors, so every
+/// room is reachable.
+#[derive(Clone, Debug)]
+pub struct BspGenerator {
+    width: u32,
+    height: u32,
+    min_leaf_size: i32,
+    min_room_size: i32,
+}
+
+impl BspGenerator {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            min_leaf_size: 8,
+            min_room_size: 4,
+        }
+    }
+
+    /// Leaves smaller than twice this are not split further. Defaults to 8.
+    pub fn with_min_leaf_size(mut self, size: u32) -> Self {
+        self.min_leaf_size = size as i32;
+        self
+    }
+
+    /// Defaults to 4. Clamped so rooms always fit inside a leaf with a wall around them.
+    pub fn with_min_room_size(mut self, size: u32) -> Self {
+        self.min_room_size = size as i32;
+        self
+    }
+
+    pub fn generate(&self, seed: u64) -> Dungeon {
+        let mut rng = Rng::new(seed);
+        let mut tiles = Tilemap::new(self.width, self.height, Cell::Wall);
+        let mut rooms = Vec::new();
+
+        let bounds = Room {
+            x: 0,
+            y: 0,
+            width: self.width as i32,
+            height: self.height as i32,
+        };
+        self.split(bounds, &mut rng, &mut tiles, &mut rooms);
+
+        let spawn = rooms
+            .first()
+            .map(Room::center)
+            .unwrap_or((bounds.width / 2, bounds.height / 2));
+        Dungeon::new(tiles, rooms, spawn)
+    }
+
+    /// Split `leaf` or place a room in it. Returns the center of one of the rooms inside, for
+    /// joining to its sibling.
+    fn split(
+        &self,
+        leaf: Room,
+        rng: &mut Rng,
+        tiles: &mut Tilemap<Cell>,
+        rooms: &mut Vec<Room>,
+    ) -> Option<(i32, i32)> {
+        let min_leaf = self.min_leaf_size.max(3);
+        let can_split_x = leaf.width >= min_leaf * 2;
+        let can_split_y = leaf.height >= min_leaf * 2;
+
+        let split_x = match (can_split_x, can_split_y) {
+            (false, false) => return self.place_room(leaf, rng, tiles, rooms),
+            (true, false) => true,
+            (false, true) => false,
+            _ if leaf.width as f32 > leaf.height as f32 * 1.25 => true,
+            _ if leaf.height as f32 > leaf.width as f32 * 1.25 => false,
+            _ => rng.chance(0.5),
+        };
+
+        let (a, b) = if split_x {
+            let at = rng.range_i32(min_leaf, leaf.width - min_leaf + 1);
+            (
+                Room { width: at, ..leaf },
+                Room {
+                    x: leaf.x + at,
+                    width: leaf.width - at,
+                    ..leaf
+                },
+            )
+        } else {
+            let at = rng.range_i32(min_leaf, leaf.height - min_leaf + 1);
+            (
+                Room { height: at, ..leaf },
+                Room {
+                    y: leaf.y + at,
+                    height: leaf.height - at,
+                    ..leaf
+                },
+            )
+        };
+
+        let a = self.split(a, rng, tiles, rooms);
+        let b = self.split(b, rng, tiles, rooms);
+        match (a, b) {
+            (Some(a), Some(b)) => {
+                carve_corridor(tiles, a, b, rng.chance(0.5));
+                Some(if rng.chance(0.5) { a } else { b })
+            }
+            (a, b) => a.or(b),
+        }
+    }
+
+    fn place_room(
+        &self,
+        leaf: Room,
+        rng: &mut Rng,
+        tiles: &mut Tilemap<Cell>,
+        rooms: &mut Vec<Room>,
+    ) -> Option<(i32, i32)> {
+        // Leave a wall between the room and the leaf's edges.
+        let (max_width, max_height) = (leaf.width - 2, leaf.height - 2);
+        if max_width < 1 || max_height < 1 {
+            return None;
+        }
+
+        let width = rng.range_i32(self.min_room_size.clamp(1, max_width), max_width + 1);
+        let height = rng.range_i32(self.min_room_size.clamp(1, max_height), max_height + 1);
+        let room = Room {
+            x: leaf.x + 1 + rng.range_i32(0, max_width - width + 1),
+            y: leaf.y + 1 + rng.range_i32(0, max_height - height + 1),
+            width,
+            height,
+        };
+
+        for y in room.y..room.y + room.height {
+            for x in room.x..room.x + room.width {
+                tiles.set(x, y, Cell::Floor);
+            }
+        }
+        rooms.push(room);
+
+        Some(room.center())
+    }
+}
+
+fn carve_corridor(tiles: &mut Tilemap<Cell>, from: (i32, i32), to: (i32, i32), x_first: bool) {
+    let corner = if x_first {
+        (to.0, from.1)
+    } else {
+        (from.0, to.1)
+    };
+
+    for (a, b) in [(from, corner), (corner, to)] {
+        for x in a.0.min(b.0)..=a.0.max(b.0) {
+            for y in a.1.min(b.1)..=a.1.max(b.1) {
+                tiles.set(x, y, Cell::Floor);
+            }
+        }
+    }
+}
+
+/// Organic caves from cellular automata: the map starts as random noise and is smoothed by
+/// turning cells with many wall neighbours into walls and few into floor. Only the largest
+/// connected cave is kept.
+#[derive(Clone, Debug)]
+pub struct CaveGenerator {
+    width: u32,
+    height: u32,
+    fill_chance: f32,
+    iterations: u32,
+}
+
+impl CaveGenerator {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            fill_chance: 0.45,
+            iterations: 5,
+        }
+    }
+
+    /// Chance of each cell starting as a wall. Defaults to 0.45; higher gives tighter caves.
+    pub fn with_fill_chance(mut self, chance: f32) -> Self {
+        self.fill_chance = chance;
+        self
+    }
+
+    /// Number of smoothing passes. Defaults to 5.
+    pub fn with_iterations(mut self, iterations: u32) -> Self {
+        self.iterations = iterations;
+        self
+    }
+
+    pub fn generate(&self, seed: u64) -> Dungeon {
+        let mut rng = Rng::new(seed);
+        let (width, height) = (self.width as i32, self.height as i32);
+        let is_border = |x: i32, y: i32| x == 0 || y == 0 || x == width - 1 || y == height - 1;
+
+        let mut tiles = Tilemap::new(self.width, self.height, Cell::Wall).map(|x, y, _| {
+            if is_border(x, y) || rng.chance(self.fill_chance) {
+                Cell::Wall
+            } else {
+                Cell::Floor
+            }
+        });
+
+        for _ in 0..self.iterations {
+            tiles = tiles.map(|x, y, &cell| {
+                let walls = (-1..=1)
+                    .flat_map(|dy| (-1..=1).map(move |dx| (dx, dy)))
+                    .filter(|&offset| offset != (0, 0))
+                    .filter(|&(dx, dy)| tiles.get(x + dx, y + dy) != Some(&Cell::Floor))
+                    .count();
+
+                match walls {
+                    _ if is_border(x, y) => Cell::Wall,
+                    5.. => Cell::Wall,
+                    0..=3 => Cell::Floor,
+                    _ => cell,
+                }
+            });
+        }
+
+        let cave = largest_region(&tiles);
+        let tiles = tiles.map(|x, y, _| {
+            if cave.binary_search(&(x, y)).is_ok() {
+                Cell::Floor
+            } else {
+                Cell::Wall
+            }
+        });
+
+        let spawn = cave.first().copied().unwrap_or((width / 2, height / 2));
+        Dungeon::new(tiles, Vec::new(), spawn)
+    }
+}
+
+/// Walking distances from `from` over floor tiles, or `None` for unreachable tiles.
+fn distances(tiles: &Tilemap<Cell>, from: (i32, i32)) -> Tilemap<Option<u32>> {
+    let mut distances = Tilemap::new(tiles.width(), tiles.height(), None);
+    let mut queue = VecDeque::new();
+
+    if tiles.get(from.0, from.1) == Some(&Cell::Floor) {
+        distances.set(from.0, from.1, Some(0));
+        queue.push_back(from);
+    }
+
+    while let Some((x, y)) = queue.pop_front() {
+        let distance = distances.get(x, y).copied().flatten().unwrap_or(0);
+        for (dx, dy) in [(0, 1), (1, 0), (0, -1), (-1, 0)] {
+            let (nx, ny) = (x + dx, y + dy);
+            if tiles.get(nx, ny) == Some(&Cell::Floor) && distances.get(nx, ny) == Some(&None) {
+                distances.set(nx, ny, Some(distance + 1));
+                queue.push_back((nx, ny));
+            }
+        }
+    }
+
+    distances
+}
+
+fn furthest_floor(tiles: &Tilemap<Cell>, from: (i32, i32)) -> (i32, i32) {
+    distances(tiles, from)
+        .iter()
+        .filter_map(|(x, y, distance)| distance.map(|distance| (distance, (x, y))))
+        .max_by_key(|&(distance, _)| distance)
+        .map_or(from, |(_, position)| position)
+}
+
+/// The floor tiles of the largest connected region, sorted by position.
+fn largest_region(tiles: &Tilemap<Cell>) -> Vec<(i32, i32)> {
+    let mut visited = Tilemap::new(tiles.width(), tiles.height(), false);
+    let mut largest = Vec::new();
+
+    for (x, y, &cell) in tiles.iter() {
+        if cell != Cell::Floor || visited.get(x, y) == Some(&true) {
+            continue;
+        }
+
+        let region: Vec<(i32, i32)> = distances(tiles, (x, y))
+            .iter()
+            .filter(|(_, _, distance)| distance.is_some())
+            .map(|(x, y, _)| (x, y))
+            .collect();
+        for &(x, y) in &region {
+            visited.set(x, y, true);
+        }
+
+        if region.len() > largest.len() {
+            largest = region;
+        }
+    }
+
+    largest.sort_unstable();
+    largest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn floor_count(dungeon: &Dungeon) -> usize {
+        dungeon
+            .tiles
+            .iter()
+            .filter(|(_, _, &cell)| cell == Cell::Floor)
+            .count()
+    }
+
+    fn reachable_count(dungeon: &Dungeon) -> usize {
+        distances(&dungeon.tiles, dungeon.spawn)
+            .iter()
+            .filter(|(_, _, distance)| distance.is_some())
+            .count()
+    }
+
+    #[test]
+    fn bsp_rooms_do_not_overlap_and_are_all_connected() {
+        let dungeon = BspGenerator::new(64, 48).generate(12);
+
+        assert!(dungeon.rooms.len() > 4);
+        for (i, a) in dungeon.rooms.iter().enumerate() {
+            for b in &dungeon.rooms[i + 1..] {
+                let separate = a.x + a.width <= b.x
+                    || b.x + b.width <= a.x
+                    || a.y + a.height <= b.y
+                    || b.y + b.height <= a.y;
+                assert!(separate, "{:?} overlaps {:?}", a, b);
+            }
+        }
+        assert_eq!(floor_count(&dungeon), reachable_count(&dungeon));
+        assert!(dungeon.rooms[0].contains(dungeon.spawn.0, dungeon.spawn.1));
+        assert!(dungeon.is_floor(dungeon.exit.0, dungeon.exit.1));
+        assert_ne!(dungeon.spawn, dungeon.exit);
+    }
+
+    #[test]
+    fn cave_keeps_only_one_connected_region_inside_solid_border() {
+        let dungeon = CaveGenerator::new(48, 32).generate(5);
+
+        assert_eq!(floor_count(&dungeon), reachable_count(&dungeon));
+        assert!(floor_count(&dungeon) > 100);
+        for (x, y, &cell) in dungeon.tiles.iter() {
+            if x == 0 || y == 0 || x == 47 || y == 31 {
+                assert_eq!(Cell::Wall, cell);
+            }
+        }
+    }
+
+    #[test]
+    fn same_seed_generates_same_level() {
+        let a = CaveGenerator::new(32, 32).generate(3);
+        let b = CaveGenerator::new(32, 32).generate(3);
+
+        assert_eq!(a.tiles, b.tiles);
+        assert_eq!((a.spawn, a.exit), (b.spawn, b.exit));
+    }
+}
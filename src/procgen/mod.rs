@@ -0,0 +1,6 @@
+pub mod dungeon;
+pub mod noise;
+pub mod preview;
+pub mod rng;
+pub mod seed;
+pub mod wfc;
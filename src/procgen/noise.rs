@@ -0,0 +1,108 @@
+use crate::maths::lerp;
+use crate::procgen::rng::Rng;
+
+/// Random values in [0, 1) to build noise from.
+pub fn noise_seed(rng: &mut Rng, count: usize) -> Vec<f32> {
+    (0..count).map(|_| rng.next_f32()).collect()
+}
+
+/// Fill `output` with 1D fractal noise in [0, 1] built from `seed`, which must be at least as long
+/// as `output`. Each octave halves the sample spacing and its influence is divided by `bias`, so
+/// a higher bias gives smoother noise.
+pub fn perlin_noise_1d(octaves: usize, bias: f32, seed: &[f32], output: &mut [f32]) {
+    let count = output.len();
+    let bias = 1.0 / bias;
+
+    for (i, x) in output.iter_mut().enumerate() {
+        let mut noise = 0.0;
+        let mut scale = 1.0;
+        let mut scale_accumulator = 0.0;
+
+        for octave in 0..octaves {
+            let pitch = (count >> octave).max(1);
+            let sample_1 = (i / pitch) * pitch;
+            let sample_2 = (sample_1 + pitch) % count;
+
+            let blend = (i - sample_1) as f32 / pitch as f32;
+            let sample = lerp(seed[sample_2], seed[sample_1], blend);
+            noise += sample * scale;
+            scale_accumulator += scale;
+            scale *= bias;
+        }
+
+        *x = noise / scale_accumulator;
+    }
+}
+
+/// Like `perlin_noise_1d` for a `width` by `height` grid stored row by row.
+pub fn perlin_noise_2d(
+    width: usize,
+    height: usize,
+    octaves: usize,
+    bias: f32,
+    seed: &[f32],
+    output: &mut [f32],
+) {
+    let bias = 1.0 / bias;
+
+    for x in 0..width {
+        for y in 0..height {
+            let mut noise = 0.0;
+            let mut scale = 1.0;
+            let mut scale_accumulator = 0.0;
+
+            for octave in 0..octaves {
+                let pitch = (width >> octave).max(1);
+
+                let sample_x1 = (x / pitch) * pitch;
+                let sample_y1 = (y / pitch) * pitch;
+
+                let sample_x2 = (sample_x1 + pitch) % width;
+                let sample_y2 = (sample_y1 + pitch) % height;
+
+                let blend_x = (x - sample_x1) as f32 / pitch as f32;
+                let blend_y = (y - sample_y1) as f32 / pitch as f32;
+
+                let sample_t = lerp(
+                    seed[sample_y1 * width + sample_x2],
+                    seed[sample_y1 * width + sample_x1],
+                    blend_x,
+                );
+                let sample_b = lerp(
+                    seed[sample_y2 * width + sample_x2],
+                    seed[sample_y2 * width + sample_x1],
+                    blend_x,
+                );
+
+                noise += (blend_y * (sample_b - sample_t) + sample_t) * scale;
+                scale_accumulator += scale;
+                scale *= bias;
+            }
+
+            output[y * width + x] = noise / scale_accumulator;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn noise_stays_in_range_and_is_smoother_with_more_bias() {
+        let seed = noise_seed(&mut Rng::new(8), 256);
+        let mut rough = vec![0.0; 256];
+        let mut smooth = vec![0.0; 256];
+
+        perlin_noise_1d(8, 0.5, &seed, &mut rough);
+        perlin_noise_1d(8, 4.0, &seed, &mut smooth);
+
+        let bumpiness =
+            |noise: &[f32]| -> f32 { noise.windows(2).map(|pair| (pair[1] - pair[0]).abs()).sum() };
+        assert!(rough
+            .iter()
+            .chain(&smooth)
+            .all(|&n| (0.0..=1.0).contains(&n)));
+        assert!(bumpiness(&smooth) < bumpiness(&rough));
+    }
+}
@@ -0,0 +1,207 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+use image::codecs::gif::GifEncoder;
+use image::{Frame, ImageError, Rgba, RgbaImage};
+use thiserror::Error;
+
+use crate::color::Color;
+use crate::engine::tilemap::Tilemap;
+use crate::renderer::software_2d::Origin;
+
+#[derive(Debug, Error)]
+pub enum MapPreviewError {
+    #[error("unable to write map preview")]
+    Io(#[from] std::io::Error),
+    #[error("unable to encode map preview")]
+    Image(#[from] ImageError),
+    #[error("a GIF holds at most 256 colors but the map uses {0}")]
+    TooManyColors(usize),
+    #[error("a {0} x {1} preview is too large for a GIF")]
+    TooLarge(u32, u32),
+}
+
+/// A picture of a whole map, one color per cell, e.g. to check what a generator made or to share
+/// a generated world. Saved as a GIF, the map's colors are kept exactly.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MapPreview {
+    width: u32,
+    height: u32,
+    cells: Vec<Color>,
+    scale: u32,
+    origin: Origin,
+}
+
+impl MapPreview {
+    /// Color each cell of a `width` x `height` map with `color_at(x, y)`.
+    pub fn render(width: u32, height: u32, mut color_at: impl FnMut(u32, u32) -> Color) -> Self {
+        let cells = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| color_at(x, y))
+            .collect();
+
+        Self {
+            width,
+            height,
+            cells,
+            scale: 1,
+            origin: Origin::default(),
+        }
+    }
+
+    pub fn from_tilemap<T>(tiles: &Tilemap<T>, mut color_of: impl FnMut(&T) -> Color) -> Self {
+        Self::render(tiles.width(), tiles.height(), |x, y| {
+            tiles
+                .get(x as i32, y as i32)
+                .map(&mut color_of)
+                .unwrap_or(Color::rgba(0, 0, 0, 0))
+        })
+    }
+
+    /// Draw each cell as a `scale` x `scale` square. Defaults to 1.
+    pub fn with_scale(mut self, scale: u32) -> Self {
+        self.scale = scale.max(1);
+        self
+    }
+
+    /// Where cell (0, 0) goes: the bottom left corner of the image, or the top left. Defaults to
+    /// bottom left, as the engine draws.
+    pub fn with_origin(mut self, origin: Origin) -> Self {
+        self.origin = origin;
+        self
+    }
+
+    /// Recolor a cell, e.g. to mark the spawn point. Cells outside the map are ignored.
+    pub fn set(&mut self, x: u32, y: u32, color: Color) {
+        if x < self.width && y < self.height {
+            self.cells[(y * self.width + x) as usize] = color;
+        }
+    }
+
+    pub fn get(&self, x: u32, y: u32) -> Option<Color> {
+        (x < self.width && y < self.height).then(|| self.cells[(y * self.width + x) as usize])
+    }
+
+    /// The distinct colors in the map, in the order they first appear.
+    pub fn palette(&self) -> Vec<Color> {
+        let mut seen = HashMap::new();
+        for &color in &self.cells {
+            let next = seen.len();
+            seen.entry(u32::from(color)).or_insert((next, color));
+        }
+
+        let mut palette: Vec<(usize, Color)> = seen.into_values().collect();
+        palette.sort_by_key(|(index, _)| *index);
+        palette.into_iter().map(|(_, color)| color).collect()
+    }
+
+    /// The preview at its scale.
+    pub fn to_image(&self) -> RgbaImage {
+        let scale = self.scale;
+        RgbaImage::from_fn(self.width * scale, self.height * scale, |x, y| {
+            let row = match self.origin {
+                Origin::BottomLeft => self.height - 1 - y / scale,
+                Origin::TopLeft => y / scale,
+            };
+            let color = self.cells[(row * self.width + x / scale) as usize];
+            Rgba([color.r(), color.g(), color.b(), color.a()])
+        })
+    }
+
+    /// Save the preview as a GIF. Cells with an alpha of zero are transparent and the rest are
+    /// saved opaque.
+    pub fn save_gif(&self, path: impl AsRef<Path>) -> Result<(), MapPreviewError> {
+        let colors = self.palette().len();
+        if colors > 256 {
+            return Err(MapPreviewError::TooManyColors(colors));
+        }
+        let (width, height) = (self.width * self.scale, self.height * self.scale);
+        if width > u16::MAX as u32 || height > u16::MAX as u32 {
+            return Err(MapPreviewError::TooLarge(width, height));
+        }
+
+        if let Some(directory) = path.as_ref().parent() {
+            std::fs::create_dir_all(directory)?;
+        }
+        let mut encoder = GifEncoder::new(BufWriter::new(File::create(path)?));
+        encoder.encode_frame(Frame::new(self.to_image()))?;
+        Ok(())
+    }
+
+    pub fn save_png(&self, path: impl AsRef<Path>) -> Result<(), MapPreviewError> {
+        if let Some(directory) = path.as_ref().parent() {
+            std::fs::create_dir_all(directory)?;
+        }
+        self.to_image().save(path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color;
+
+    fn checkerboard() -> MapPreview {
+        MapPreview::render(3, 2, |x, y| {
+            if (x + y) % 2 == 0 {
+                color::css::GREEN
+            } else {
+                color::css::BLUE
+            }
+        })
+    }
+
+    #[test]
+    fn previews_are_scaled_with_the_origin_at_the_bottom() {
+        let mut preview = checkerboard().with_scale(2);
+        preview.set(0, 0, color::css::RED);
+
+        let image = preview.to_image();
+
+        assert_eq!((6, 4), image.dimensions());
+        let red = Rgba([255, 0, 0, 255]);
+        assert_eq!(&red, image.get_pixel(1, 3));
+        assert_ne!(&red, image.get_pixel(1, 1));
+        assert_eq!(
+            vec![color::css::RED, color::css::BLUE, color::css::GREEN],
+            preview.palette()
+        );
+    }
+
+    #[test]
+    fn gifs_keep_the_map_colors_exactly() {
+        use image::codecs::gif::GifDecoder;
+        use image::AnimationDecoder;
+
+        let path = std::env::temp_dir().join(format!(
+            "apparatus-map-preview-test-{}.gif",
+            std::process::id()
+        ));
+        let preview = checkerboard().with_origin(Origin::TopLeft);
+        preview.save_gif(&path).unwrap();
+
+        let file = File::open(&path).unwrap();
+        let frame = GifDecoder::new(file)
+            .unwrap()
+            .into_frames()
+            .next()
+            .unwrap()
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(&preview.to_image(), frame.buffer());
+    }
+
+    #[test]
+    fn gifs_need_a_palette_of_256_colors_at_most() {
+        let preview = MapPreview::render(20, 20, |x, y| Color::rgba(x as u8, y as u8, 0, 255));
+
+        assert!(matches!(
+            preview.save_gif(std::env::temp_dir().join("unused.gif")),
+            Err(MapPreviewError::TooManyColors(400))
+        ));
+    }
+}
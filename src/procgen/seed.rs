@@ -0,0 +1,135 @@
+use std::fmt::{Display, Formatter};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::procgen::rng::Rng;
+
+/// The one number everything in a generated world is derived from. Each generator asks for its
+/// own named stream, so the same seed reproduces the same world on any machine, and adding a new
+/// generator doesn't change what the existing ones produce.
+///
+/// Seeds display, and save, as decimal numbers that players can share.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct WorldSeed(u64);
+
+impl WorldSeed {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    /// A seed typed by a player: a number is used as is, anything else is hashed.
+    pub fn from_phrase(phrase: &str) -> Self {
+        let phrase = phrase.trim();
+        match phrase.parse() {
+            Ok(seed) => Self(seed),
+            Err(_) => Self(fnv1a(phrase.as_bytes())),
+        }
+    }
+
+    /// A fresh seed from the system clock.
+    pub fn random() -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_nanos() as u64);
+        Self(Rng::new(nanos).next_u64())
+    }
+
+    pub fn value(&self) -> u64 {
+        self.0
+    }
+
+    /// A seed for the named stream, e.g. `"terrain"` or `"loot"`, for generators that take a
+    /// plain `u64` seed.
+    pub fn derive(&self, stream: &str) -> u64 {
+        Rng::new(self.0 ^ fnv1a(stream.as_bytes())).next_u64()
+    }
+
+    /// A random number generator for the named stream.
+    pub fn rng(&self, stream: &str) -> Rng {
+        Rng::new(self.derive(stream))
+    }
+}
+
+impl From<u64> for WorldSeed {
+    fn from(seed: u64) -> Self {
+        Self(seed)
+    }
+}
+
+impl Display for WorldSeed {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+// Saved as a string: TOML integers can't hold every u64.
+impl Serialize for WorldSeed {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for WorldSeed {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let phrase = String::deserialize(deserializer)?;
+        Ok(Self::from_phrase(&phrase))
+    }
+}
+
+/// FNV-1a, chosen because it is stable across platforms and Rust versions, unlike `std`'s hasher.
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xCBF2_9CE4_8422_2325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x0000_0100_0000_01B3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn streams_are_reproducible_and_independent() {
+        let seed = WorldSeed::new(1234);
+
+        assert_eq!(
+            seed.derive("terrain"),
+            WorldSeed::new(1234).derive("terrain")
+        );
+        assert_ne!(seed.derive("terrain"), seed.derive("loot"));
+        // Reference FNV-1a values; changing the hash would alter every world.
+        assert_eq!(0xAF63_DC4C_8601_EC8C, fnv1a(b"a"));
+    }
+
+    #[test]
+    fn phrases_parse_numbers_and_hash_words() {
+        assert_eq!(WorldSeed::new(42), WorldSeed::from_phrase(" 42 "));
+        assert_eq!(
+            WorldSeed::from_phrase("banana"),
+            WorldSeed::from_phrase("banana")
+        );
+        assert_ne!(
+            WorldSeed::from_phrase("banana"),
+            WorldSeed::from_phrase("apple")
+        );
+    }
+
+    #[test]
+    fn seed_saves_as_a_string() {
+        #[derive(Serialize, Deserialize)]
+        struct Save {
+            seed: WorldSeed,
+        }
+
+        let save = toml::to_string(&Save {
+            seed: WorldSeed::new(u64::MAX),
+        })
+        .unwrap();
+
+        assert_eq!("seed = \"18446744073709551615\"\n", save);
+        assert_eq!(
+            WorldSeed::new(u64::MAX),
+            toml::from_str::<Save>(&save).unwrap().seed
+        );
+    }
+}
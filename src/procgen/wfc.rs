@@ -0,0 +1,376 @@
+use thiserror::Error;
+
+use crate::engine::tilemap::Tilemap;
+use crate::procgen::rng::Rng;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum WfcError {
+    #[error("unable to satisfy the adjacency rules")]
+    Contradiction,
+}
+
+/// A direction between neighbouring tiles. Up is towards increasing y.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Right,
+        Direction::Down,
+        Direction::Left,
+    ];
+
+    pub fn opposite(&self) -> Direction {
+        match self {
+            Direction::Up => Direction::Down,
+            Direction::Right => Direction::Left,
+            Direction::Down => Direction::Up,
+            Direction::Left => Direction::Right,
+        }
+    }
+
+    pub fn offset(&self) -> (i32, i32) {
+        match self {
+            Direction::Up => (0, 1),
+            Direction::Right => (1, 0),
+            Direction::Down => (0, -1),
+            Direction::Left => (-1, 0),
+        }
+    }
+
+    fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
+/// Which tiles may sit next to each other, and how common each tile should be.
+/// Tiles are numbered from 0.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AdjacencyRules {
+    weights: Vec<f32>,
+    allowed: Vec<[Vec<bool>; 4]>,
+}
+
+impl AdjacencyRules {
+    /// Rules for `tile_count` tiles of equal weight, with no adjacencies allowed yet.
+    pub fn new(tile_count: usize) -> Self {
+        let none = || vec![false; tile_count];
+        Self {
+            weights: vec![1.0; tile_count],
+            allowed: (0..tile_count)
+                .map(|_| [none(), none(), none(), none()])
+                .collect(),
+        }
+    }
+
+    /// Learn the rules from an example map: every pair of neighbouring tiles becomes allowed and
+    /// each tile is weighted by how often it appears.
+    pub fn from_example(example: &Tilemap<usize>) -> Self {
+        let tile_count = example
+            .iter()
+            .map(|(_, _, &tile)| tile + 1)
+            .max()
+            .unwrap_or(0);
+        let mut rules = Self::new(tile_count);
+        rules.weights = vec![0.0; tile_count];
+
+        for (x, y, &tile) in example.iter() {
+            rules.weights[tile] += 1.0;
+            for direction in Direction::ALL {
+                let (dx, dy) = direction.offset();
+                if let Some(&neighbour) = example.get(x + dx, y + dy) {
+                    rules.allow(tile, direction, neighbour);
+                }
+            }
+        }
+
+        rules
+    }
+
+    pub fn tile_count(&self) -> usize {
+        self.weights.len()
+    }
+
+    /// Allow `neighbour` to be in `direction` from `tile`, and so `tile` in the opposite direction
+    /// from `neighbour`.
+    pub fn allow(&mut self, tile: usize, direction: Direction, neighbour: usize) {
+        self.allowed[tile][direction.index()][neighbour] = true;
+        self.allowed[neighbour][direction.opposite().index()][tile] = true;
+    }
+
+    /// Allow the two tiles to be neighbours in every direction.
+    pub fn allow_all_directions(&mut self, tile: usize, neighbour: usize) {
+        for direction in Direction::ALL {
+            self.allow(tile, direction, neighbour);
+        }
+    }
+
+    pub fn is_allowed(&self, tile: usize, direction: Direction, neighbour: usize) -> bool {
+        self.allowed[tile][direction.index()][neighbour]
+    }
+
+    /// How likely a tile is to be picked relative to the others. Defaults to 1.
+    pub fn set_weight(&mut self, tile: usize, weight: f32) {
+        self.weights[tile] = weight;
+    }
+}
+
+/// Generates tilemaps that obey a set of adjacency rules by wave function collapse: repeatedly
+/// fixing the most constrained cell to a random allowed tile and propagating what that rules out
+/// to its neighbours.
+pub struct Wfc<'a> {
+    rules: &'a AdjacencyRules,
+    width: u32,
+    height: u32,
+    constraints: Vec<(i32, i32, Vec<bool>)>,
+    attempts: u32,
+}
+
+impl<'a> Wfc<'a> {
+    pub fn new(rules: &'a AdjacencyRules, width: u32, height: u32) -> Self {
+        Self {
+            rules,
+            width,
+            height,
+            constraints: Vec::new(),
+            attempts: 10,
+        }
+    }
+
+    /// Force the tile at (x, y).
+    pub fn with_fixed(mut self, x: i32, y: i32, tile: usize) -> Self {
+        let mut allowed = vec![false; self.rules.tile_count()];
+        allowed[tile] = true;
+        self.constraints.push((x, y, allowed));
+        self
+    }
+
+    /// Make the edge facing `side` fit seamlessly against an already generated chunk of the same
+    /// height (for left/right) or width (for up/down) lying in that direction.
+    pub fn with_neighbour(mut self, neighbour: &Tilemap<usize>, side: Direction) -> Self {
+        let (width, height) = (self.width as i32, self.height as i32);
+        let edge: Vec<((i32, i32), (i32, i32))> = match side {
+            Direction::Right => (0..height).map(|y| ((width - 1, y), (0, y))).collect(),
+            Direction::Left => (0..height)
+                .map(|y| ((0, y), (neighbour.width() as i32 - 1, y)))
+                .collect(),
+            Direction::Up => (0..width).map(|x| ((x, height - 1), (x, 0))).collect(),
+            Direction::Down => (0..width)
+                .map(|x| ((x, 0), (x, neighbour.height() as i32 - 1)))
+                .collect(),
+        };
+
+        for ((x, y), (nx, ny)) in edge {
+            if let Some(&other) = neighbour.get(nx, ny) {
+                let allowed = (0..self.rules.tile_count())
+                    .map(|tile| self.rules.is_allowed(tile, side, other))
+                    .collect();
+                self.constraints.push((x, y, allowed));
+            }
+        }
+        self
+    }
+
+    /// How many seeds to try before giving up on contradictions. Defaults to 10.
+    pub fn with_attempts(mut self, attempts: u32) -> Self {
+        self.attempts = attempts.max(1);
+        self
+    }
+
+    /// Generate a map. The same seed and rules always give the same map.
+    pub fn generate(&self, seed: u64) -> Result<Tilemap<usize>, WfcError> {
+        (0..self.attempts as u64)
+            .find_map(|attempt| self.try_generate(&mut Rng::new(seed.wrapping_add(attempt))))
+            .ok_or(WfcError::Contradiction)
+    }
+
+    fn try_generate(&self, rng: &mut Rng) -> Option<Tilemap<usize>> {
+        let tile_count = self.rules.tile_count();
+        if tile_count == 0 {
+            return None;
+        }
+
+        let mut wave = Tilemap::new(self.width, self.height, vec![true; tile_count]);
+        let mut pending = Vec::new();
+        for (x, y, allowed) in &self.constraints {
+            let cell = wave.get_mut(*x, *y)?;
+            for (possible, allowed) in cell.iter_mut().zip(allowed) {
+                *possible &= *allowed;
+            }
+            pending.push((*x, *y));
+        }
+        self.propagate(&mut wave, pending)?;
+
+        while let Some((x, y)) = self.least_uncertain(&wave, rng) {
+            let cell = wave.get_mut(x, y)?;
+            let weights: Vec<f32> = cell
+                .iter()
+                .zip(&self.rules.weights)
+                .map(|(&possible, &weight)| if possible { weight.max(1e-6) } else { 0.0 })
+                .collect();
+            let chosen = rng.weighted_index(&weights)?;
+            for (tile, possible) in cell.iter_mut().enumerate() {
+                *possible = tile == chosen;
+            }
+            self.propagate(&mut wave, vec![(x, y)])?;
+        }
+
+        Some(wave.map(|_, _, cell| cell.iter().position(|&possible| possible).unwrap_or(0)))
+    }
+
+    /// The undecided cell with the lowest entropy, with a little noise to break ties.
+    fn least_uncertain(&self, wave: &Tilemap<Vec<bool>>, rng: &mut Rng) -> Option<(i32, i32)> {
+        let mut best = None;
+        let mut best_entropy = f32::MAX;
+
+        for (x, y, cell) in wave.iter() {
+            if cell.iter().filter(|&&possible| possible).count() <= 1 {
+                continue;
+            }
+
+            let weights = cell
+                .iter()
+                .zip(&self.rules.weights)
+                .filter(|(&possible, _)| possible)
+                .map(|(_, &weight)| weight.max(1e-6));
+            let total: f32 = weights.clone().sum();
+            let entropy = total.ln()
+                - weights.map(|weight| weight * weight.ln()).sum::<f32>() / total
+                + rng.next_f32() * 1e-3;
+
+            if entropy < best_entropy {
+                best_entropy = entropy;
+                best = Some((x, y));
+            }
+        }
+
+        best
+    }
+
+    /// Remove tiles from neighbours of changed cells until nothing else changes. Returns `None`
+    /// if a cell runs out of possible tiles.
+    fn propagate(&self, wave: &mut Tilemap<Vec<bool>>, mut pending: Vec<(i32, i32)>) -> Option<()> {
+        while let Some((x, y)) = pending.pop() {
+            let cell = wave.get(x, y)?.clone();
+            if !cell.contains(&true) {
+                return None;
+            }
+
+            for direction in Direction::ALL {
+                let (dx, dy) = direction.offset();
+                let neighbour = match wave.get_mut(x + dx, y + dy) {
+                    Some(neighbour) => neighbour,
+                    None => continue,
+                };
+
+                let mut changed = false;
+                for (tile, possible) in neighbour.iter_mut().enumerate() {
+                    if *possible
+                        && !cell.iter().enumerate().any(|(source, &source_possible)| {
+                            source_possible && self.rules.is_allowed(source, direction, tile)
+                        })
+                    {
+                        *possible = false;
+                        changed = true;
+                    }
+                }
+
+                if changed {
+                    if !neighbour.contains(&true) {
+                        return None;
+                    }
+                    pending.push((x + dx, y + dy));
+                }
+            }
+        }
+
+        Some(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WATER: usize = 0;
+    const SAND: usize = 1;
+    const GRASS: usize = 2;
+
+    fn coast_rules() -> AdjacencyRules {
+        let mut rules = AdjacencyRules::new(3);
+        for tile in [WATER, SAND, GRASS] {
+            rules.allow_all_directions(tile, tile);
+        }
+        rules.allow_all_directions(WATER, SAND);
+        rules.allow_all_directions(SAND, GRASS);
+        rules
+    }
+
+    fn obeys(map: &Tilemap<usize>, rules: &AdjacencyRules) -> bool {
+        map.iter().all(|(x, y, &tile)| {
+            Direction::ALL.into_iter().all(|direction| {
+                let (dx, dy) = direction.offset();
+                map.get(x + dx, y + dy)
+                    .is_none_or(|&neighbour| rules.is_allowed(tile, direction, neighbour))
+            })
+        })
+    }
+
+    #[test]
+    fn generated_map_obeys_rules_and_fixed_tiles() {
+        let rules = coast_rules();
+
+        let map = Wfc::new(&rules, 12, 8)
+            .with_fixed(0, 0, WATER)
+            .with_fixed(11, 7, GRASS)
+            .generate(3)
+            .unwrap();
+
+        assert!(obeys(&map, &rules));
+        assert_eq!(Some(&WATER), map.get(0, 0));
+        assert_eq!(Some(&GRASS), map.get(11, 7));
+    }
+
+    #[test]
+    fn same_seed_generates_same_map() {
+        let rules = coast_rules();
+        let wfc = Wfc::new(&rules, 10, 10);
+
+        assert_eq!(wfc.generate(9).unwrap(), wfc.generate(9).unwrap());
+    }
+
+    #[test]
+    fn rules_learned_from_example() {
+        let mut example = Tilemap::new(3, 1, WATER);
+        example.set(1, 0, SAND);
+        example.set(2, 0, GRASS);
+
+        let rules = AdjacencyRules::from_example(&example);
+
+        assert!(rules.is_allowed(WATER, Direction::Right, SAND));
+        assert!(rules.is_allowed(SAND, Direction::Left, WATER));
+        assert!(!rules.is_allowed(WATER, Direction::Right, GRASS));
+    }
+
+    #[test]
+    fn chunks_join_seamlessly() {
+        let rules = coast_rules();
+        let left = Wfc::new(&rules, 6, 6).generate(1).unwrap();
+
+        let right = Wfc::new(&rules, 6, 6)
+            .with_neighbour(&left, Direction::Left)
+            .generate(2)
+            .unwrap();
+
+        for y in 0..6 {
+            let (&a, &b) = (left.get(5, y).unwrap(), right.get(0, y).unwrap());
+            assert!(rules.is_allowed(a, Direction::Right, b));
+        }
+    }
+}
@@ -0,0 +1,253 @@
+use std::ops::Range;
+
+use crate::color::Color;
+use crate::engine::camera::Camera2D;
+use crate::maths::{Rect, Vec2};
+use crate::platform::framebuffer::FrameBuffer;
+use crate::renderer::software_2d::{BlendMode, Interlace, Origin};
+
+/// A custom rasterizer, e.g. a raycaster's column renderer, drawn with `Apparatus::draw_custom`
+/// so it shares the engine's framebuffer, camera and clipping instead of bypassing them.
+pub trait DrawCommand {
+    fn draw(&self, context: &mut DrawContext);
+}
+
+/// The framebuffer a `DrawCommand` draws into, with the state the built-in draw calls use.
+/// Positions are in framebuffer pixels, columns from the left and rows from the top, unless
+/// stated otherwise.
+pub struct DrawContext<'a> {
+    buffer: &'a mut FrameBuffer,
+    width: usize,
+    height: usize,
+    pixel_width: usize,
+    pixel_height: usize,
+    origin: Origin,
+    camera: Option<Camera2D>,
+    columns: Range<usize>,
+    rows: Range<usize>,
+    interlace: Interlace,
+    field: usize,
+    blend_mode: BlendMode,
+}
+
+impl<'a> DrawContext<'a> {
+    /// `clip` is in virtual pixels, placed like a rectangle drawn with the same origin.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn new(
+        buffer: &'a mut FrameBuffer,
+        width: usize,
+        height: usize,
+        pixel_width: usize,
+        pixel_height: usize,
+        origin: Origin,
+        camera: Option<Camera2D>,
+        clip: Option<Rect>,
+        interlace: Interlace,
+        field: usize,
+        blend_mode: BlendMode,
+    ) -> Self {
+        let (columns, rows) = match clip {
+            Some(clip) => {
+                let span = |start: f32, end: f32, len: usize| {
+                    let (start, end) = (start.max(0.0) as usize, end.max(0.0) as usize);
+                    start.min(len)..end.clamp(start.min(len), len)
+                };
+                let (top, bottom) = match origin {
+                    Origin::TopLeft => (clip.y, clip.y + clip.height),
+                    Origin::BottomLeft => (
+                        height as f32 / pixel_height as f32 - clip.y - clip.height,
+                        height as f32 / pixel_height as f32 - clip.y,
+                    ),
+                };
+                (
+                    span(
+                        clip.x * pixel_width as f32,
+                        (clip.x + clip.width) * pixel_width as f32,
+                        width,
+                    ),
+                    span(
+                        top * pixel_height as f32,
+                        bottom * pixel_height as f32,
+                        height,
+                    ),
+                )
+            }
+            None => (0..width, 0..height),
+        };
+
+        Self {
+            buffer,
+            width,
+            height,
+            pixel_width,
+            pixel_height,
+            origin,
+            camera,
+            columns,
+            rows,
+            interlace,
+            field,
+            blend_mode,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The size of a virtual pixel in framebuffer pixels.
+    pub fn pixel_size(&self) -> (usize, usize) {
+        (self.pixel_width, self.pixel_height)
+    }
+
+    pub fn origin(&self) -> Origin {
+        self.origin
+    }
+
+    /// The camera the game set with `Apparatus::set_camera`, if any.
+    pub fn camera(&self) -> Option<Camera2D> {
+        self.camera
+    }
+
+    /// The columns and rows the command may draw into.
+    pub fn clip(&self) -> (Range<usize>, Range<usize>) {
+        (self.columns.clone(), self.rows.clone())
+    }
+
+    /// The framebuffer position of a screen position in virtual pixels.
+    pub fn screen_to_buffer(&self, position: Vec2) -> Vec2 {
+        let x = position.x * self.pixel_width as f32;
+        let y = position.y * self.pixel_height as f32;
+        match self.origin {
+            Origin::TopLeft => Vec2::new(x, y),
+            Origin::BottomLeft => Vec2::new(x, self.height as f32 - y),
+        }
+    }
+
+    /// The framebuffer position of a world position, through the camera if one is set.
+    pub fn world_to_buffer(&self, position: Vec2) -> Vec2 {
+        let position = match &self.camera {
+            Some(camera) => {
+                let screen = Vec2::new(
+                    self.width as f32 / self.pixel_width as f32,
+                    self.height as f32 / self.pixel_height as f32,
+                );
+                camera.world_to_screen(position, screen)
+            }
+            None => position,
+        };
+        self.screen_to_buffer(position)
+    }
+
+    /// Blend `color` into the pixel at column `x`, row `y` with the current blend mode. Pixels
+    /// outside the clip or not in this frame's interlaced field are skipped.
+    pub fn blend_pixel(&mut self, x: i64, y: i64, color: Color) {
+        if !self.is_drawable(x, y) {
+            return;
+        }
+
+        let index = y as usize * self.width + x as usize;
+        let dst = self.buffer.data[index].into();
+        self.buffer.data[index] = self.blend_mode.blend(color, dst).into();
+    }
+
+    /// Fill rows `top` to `bottom` (exclusive) of column `x`, e.g. a wall slice.
+    pub fn fill_column(&mut self, x: i64, top: i64, bottom: i64, color: Color) {
+        for y in top.max(self.rows.start as i64)..bottom.min(self.rows.end as i64) {
+            self.blend_pixel(x, y, color);
+        }
+    }
+
+    fn is_drawable(&self, x: i64, y: i64) -> bool {
+        x >= self.columns.start as i64
+            && x < self.columns.end as i64
+            && y >= self.rows.start as i64
+            && y < self.rows.end as i64
+            && self.interlace.covers(self.field, x as usize, y as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color;
+    use crate::renderer::software_2d::Renderer;
+
+    struct FillClip(Color);
+
+    impl DrawCommand for FillClip {
+        fn draw(&self, context: &mut DrawContext) {
+            let (columns, rows) = context.clip();
+            for x in columns {
+                context.fill_column(x as i64, rows.start as i64, rows.end as i64, self.0);
+            }
+        }
+    }
+
+    fn filled_rows(renderer: &Renderer) -> Vec<String> {
+        let red: u32 = color::css::RED.into();
+        renderer
+            .buffer()
+            .data
+            .chunks(4)
+            .map(|row| {
+                row.iter()
+                    .map(|&p| if p == red { '#' } else { '.' })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn commands_are_clipped() {
+        for (origin, expected) in [
+            (Origin::TopLeft, ["....", ".##.", ".##.", "...."]),
+            (Origin::BottomLeft, ["....", ".##.", ".##.", "...."]),
+        ] {
+            let mut renderer = Renderer::new(4.0, 4.0, 1, 1, FrameBuffer::new(4, 4));
+            renderer.set_origin(origin);
+
+            renderer.draw_custom(
+                &FillClip(color::css::RED),
+                Some(Rect::new(1.0, 1.0, 2.0, 2.0)),
+            );
+
+            assert_eq!(expected.to_vec(), filled_rows(&renderer));
+        }
+
+        let mut renderer = Renderer::new(4.0, 4.0, 1, 1, FrameBuffer::new(4, 4));
+        renderer.set_origin(Origin::BottomLeft);
+        renderer.draw_custom(
+            &FillClip(color::css::RED),
+            Some(Rect::new(0.0, 0.0, 4.0, 1.0)),
+        );
+        assert_eq!(vec!["....", "....", "....", "####"], filled_rows(&renderer));
+    }
+
+    #[test]
+    fn world_positions_go_through_the_camera() {
+        let mut buffer = FrameBuffer::new(8, 8);
+        let context = DrawContext::new(
+            &mut buffer,
+            8,
+            8,
+            2,
+            2,
+            Origin::BottomLeft,
+            Some(Camera2D::new(Vec2::new(12.0, 2.0))),
+            None,
+            Interlace::Off,
+            0,
+            BlendMode::Alpha,
+        );
+
+        assert_eq!(
+            Vec2::new(2.0, 6.0),
+            context.world_to_buffer(Vec2::new(11.0, 1.0))
+        );
+    }
+}
@@ -1,2 +1,4 @@
 pub mod bresenham;
+pub mod custom;
 pub mod software_2d;
+pub mod upscale;
@@ -1,11 +1,176 @@
+use image::RgbaImage;
+use log::warn;
+
 use crate::color::Color;
+use crate::engine::camera::Camera2D;
+use crate::engine::palette::Palette;
 use crate::engine::sprite::Sprite;
+use crate::engine::vector_model::VectorModel;
 use crate::engine::Point;
 use crate::font;
-use crate::font::Font;
-use crate::maths::clamp;
+use crate::font::{BitmapFont, Font, GlyphAtlas};
+use crate::maths::spline::Spline;
+use crate::maths::{clamp, Obb, Rect, Vec2};
 use crate::platform::framebuffer::FrameBuffer;
 use crate::renderer::bresenham::BresenhamLine;
+use crate::renderer::custom::{DrawCommand, DrawContext};
+use crate::renderer::upscale::{self, RenderScale, UpscaleFilter};
+use crate::ui::text_grid::TextGrid;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+use std::ops::{BitOr, BitOrAssign, Range};
+
+/// Where (0, 0) is on the screen and which way y increases.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Origin {
+    /// y increases upwards from the bottom of the screen.
+    #[default]
+    BottomLeft,
+    /// y increases downwards from the top of the screen.
+    TopLeft,
+}
+
+/// Which pixels are drawn each frame. Interlaced modes draw half the pixels each frame,
+/// alternating between two fields, and leave the other half showing the previous frame.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Interlace {
+    #[default]
+    Off,
+    /// Alternate rows.
+    Scanlines,
+    /// Alternate pixels, offset on each row.
+    Checkerboard,
+}
+
+impl Interlace {
+    pub(crate) fn covers(&self, field: usize, x: usize, row: usize) -> bool {
+        match self {
+            Interlace::Off => true,
+            Interlace::Scanlines => row % 2 == field,
+            Interlace::Checkerboard => (x + row) % 2 == field,
+        }
+    }
+}
+
+/// How drawn colors are combined with what's already on screen.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum BlendMode {
+    /// Overwrite the screen, ignoring alpha, e.g. for opaque backgrounds.
+    Replace,
+    /// Mix with the screen by alpha. The screen ends up opaque.
+    #[default]
+    Alpha,
+    /// Composite over the screen by alpha, keeping the combined alpha, e.g. to draw into a
+    /// surface that is composited later or onto a transparent window.
+    Over,
+    /// As `Over` for colors already multiplied by their alpha, see `Color::premultiplied`.
+    Premultiplied,
+    /// Brighten the screen by the color scaled by alpha, e.g. for glowing explosions.
+    Add,
+    /// Darken the screen by the color, faded by alpha, e.g. for shadows and lighting.
+    Multiply,
+    /// Darken the screen by subtracting the color scaled by alpha.
+    Subtract,
+}
+
+impl BlendMode {
+    pub(crate) fn blend(&self, src: Color, dst: Color) -> Color {
+        let amount = src.a() as f32 / 255.0;
+        let channels = |f: &dyn Fn(f32, f32) -> f32| {
+            let channel = |src: u8, dst: u8| f(src as f32, dst as f32).round().clamp(0.0, 255.0);
+            Color::rgba(
+                channel(src.r(), dst.r()) as u8,
+                channel(src.g(), dst.g()) as u8,
+                channel(src.b(), dst.b()) as u8,
+                255,
+            )
+        };
+
+        match self {
+            BlendMode::Replace => src,
+            BlendMode::Alpha => Color::linear_blend(src, dst),
+            BlendMode::Over => Color::blend_over(src, dst),
+            BlendMode::Premultiplied => Color::blend_premultiplied(src, dst),
+            BlendMode::Add => channels(&|src, dst| dst + src * amount),
+            BlendMode::Multiply => channels(&|src, dst| dst + (dst * src / 255.0 - dst) * amount),
+            BlendMode::Subtract => channels(&|src, dst| dst - src * amount),
+        }
+    }
+
+    /// Whether a pixel with `alpha` simply replaces what's on screen, so it can be copied.
+    pub(crate) fn overwrites(&self, alpha: u8) -> bool {
+        match self {
+            BlendMode::Replace => true,
+            BlendMode::Alpha | BlendMode::Over | BlendMode::Premultiplied => alpha == 255,
+            _ => false,
+        }
+    }
+
+    /// Whether a pixel with `alpha` leaves the screen unchanged, so it can be skipped.
+    /// Premultiplied pixels with zero alpha can still add light.
+    pub(crate) fn skips(&self, alpha: u8) -> bool {
+        alpha == 0 && !matches!(self, BlendMode::Replace | BlendMode::Premultiplied)
+    }
+}
+
+/// Which ways to mirror a sprite when drawing, combined with `|`, e.g.
+/// `Flip::HORIZONTAL | Flip::VERTICAL`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Flip(u8);
+
+impl Flip {
+    pub const NONE: Flip = Flip(0);
+    /// Mirror left to right, e.g. to face the other way.
+    pub const HORIZONTAL: Flip = Flip(1);
+    /// Mirror top to bottom.
+    pub const VERTICAL: Flip = Flip(2);
+
+    pub fn contains(self, other: Flip) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl BitOr for Flip {
+    type Output = Flip;
+
+    fn bitor(self, rhs: Flip) -> Self::Output {
+        Flip(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for Flip {
+    fn bitor_assign(&mut self, rhs: Flip) {
+        self.0 |= rhs.0;
+    }
+}
+
+/// Decoration drawn behind text to keep it readable over busy backgrounds.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct TextStyle {
+    outline: Option<(Color, f32)>,
+    shadow: Option<(Vec2, Color)>,
+}
+
+impl TextStyle {
+    /// Surround each glyph with `color`, `width` window pixels thick.
+    pub fn with_outline(mut self, color: Color, width: f32) -> Self {
+        self.outline = Some((color, width));
+        self
+    }
+
+    /// Draw a copy of the text in `color` behind it, moved by `offset` window pixels in screen
+    /// axes.
+    pub fn with_shadow(mut self, offset: Vec2, color: Color) -> Self {
+        self.shadow = Some((offset, color));
+        self
+    }
+}
+
+/// A layer not being drawn to, with the columns and rows drawn since it was last composited.
+struct Layer {
+    buffer: FrameBuffer,
+    drawn: Option<(Range<usize>, Range<usize>)>,
+}
 
 pub struct Renderer {
     width: f32,
@@ -14,6 +179,30 @@ pub struct Renderer {
     pixel_height: usize,
     buffer: FrameBuffer,
     default_font: Font,
+    glyph_atlas: GlyphAtlas,
+    origin: Origin,
+    window_width: f32,
+    window_height: f32,
+    window_pixel_width: usize,
+    window_pixel_height: usize,
+    render_scale: RenderScale,
+    upscale_filter: UpscaleFilter,
+    output: FrameBuffer,
+    interlace: Interlace,
+    field: usize,
+    blend_mode: BlendMode,
+    /// The blend mode drawing uses, which differs from the chosen one on layers above 0.
+    active_blend_mode: BlendMode,
+    layer: u8,
+    /// The buffers of the layers not being drawn to, kept for compositing.
+    layers: BTreeMap<u8, Layer>,
+    /// Columns and rows of the current layer drawn since it was last composited, if above 0.
+    drawn: Option<(Range<usize>, Range<usize>)>,
+    bitmap_fonts: HashMap<(usize, usize), BitmapFont>,
+    dirty_tracking: bool,
+    /// Columns and rows of the buffer drawn since the last present, when tracked.
+    dirty: Option<(Range<usize>, Range<usize>)>,
+    camera: Option<Camera2D>,
 }
 
 impl Renderer {
@@ -33,36 +222,470 @@ impl Renderer {
             pixel_height,
             buffer,
             default_font,
+            glyph_atlas: GlyphAtlas::default(),
+            origin: Origin::default(),
+            window_width: width,
+            window_height: height,
+            window_pixel_width: pixel_width,
+            window_pixel_height: pixel_height,
+            render_scale: RenderScale::default(),
+            upscale_filter: UpscaleFilter::default(),
+            output: FrameBuffer::new(0, 0),
+            interlace: Interlace::default(),
+            blend_mode: BlendMode::default(),
+            active_blend_mode: BlendMode::default(),
+            layer: 0,
+            layers: BTreeMap::new(),
+            drawn: None,
+            field: 0,
+            bitmap_fonts: HashMap::new(),
+            dirty_tracking: false,
+            dirty: None,
+            camera: None,
         }
     }
 
+    /// The buffer being drawn into, at the current render scale.
     pub fn buffer(&self) -> &FrameBuffer {
         &self.buffer
     }
 
+    /// The most recently presented frame, at window resolution.
+    pub fn frame(&self) -> &FrameBuffer {
+        match self.render_scale {
+            RenderScale::Full => &self.buffer,
+            _ => &self.output,
+        }
+    }
+
+    /// The most recently presented frame as an image, e.g. to save as a PNG or compare against a
+    /// reference image in tests.
+    pub fn to_image(&self) -> RgbaImage {
+        let data = self
+            .frame()
+            .data
+            .iter()
+            .flat_map(|pixel| {
+                let [a, r, g, b] = pixel.to_be_bytes();
+                [r, g, b, a]
+            })
+            .collect();
+
+        RgbaImage::from_raw(self.window_width as u32, self.window_height as u32, data)
+            .expect("frame should be the window size")
+    }
+
+    /// Upscale the buffer to window resolution for display.
+    pub(crate) fn present(&mut self) -> &FrameBuffer {
+        self.composite_layers();
+
+        let divisor = self.render_scale.divisor();
+        let rows = match (self.dirty_tracking, self.dirty.take()) {
+            (false, _) => 0..self.height as usize,
+            (true, Some((_, rows))) => rows,
+            (true, None) => 0..0,
+        };
+        if divisor > 1 && !rows.is_empty() {
+            upscale::upscale(
+                self.upscale_filter,
+                &self.buffer.data,
+                self.width as usize,
+                self.height as usize,
+                divisor,
+                rows,
+                &mut self.output.data,
+            );
+        }
+
+        if self.interlace != Interlace::Off {
+            self.field = 1 - self.field;
+        }
+
+        self.frame()
+    }
+
+    pub fn interlace(&self) -> Interlace {
+        self.interlace
+    }
+
+    pub fn dirty_tracking(&self) -> bool {
+        self.dirty_tracking
+    }
+
+    /// Track the area drawn each frame, so a frame where nothing was drawn isn't displayed and
+    /// only the changed rows are upscaled. Best for mostly static screens that aren't cleared
+    /// every frame, e.g. board games.
+    pub fn set_dirty_tracking(&mut self, enabled: bool) {
+        self.dirty_tracking = enabled;
+        self.mark_all_dirty();
+    }
+
+    /// The area of the buffer drawn since the last present, in rendered pixels with rows counted
+    /// down from the top, or `None` if nothing was drawn or tracking is off.
+    pub fn dirty_rect(&self) -> Option<Rect> {
+        self.dirty.as_ref().map(|(columns, rows)| {
+            Rect::new(
+                columns.start as f32,
+                rows.start as f32,
+                columns.len() as f32,
+                rows.len() as f32,
+            )
+        })
+    }
+
+    /// Whether anything may have been drawn since the last present, always so when untracked.
+    pub(crate) fn is_dirty(&self) -> bool {
+        !self.dirty_tracking || self.dirty.is_some()
+    }
+
+    fn mark_dirty(&mut self, columns: Range<usize>, rows: Range<usize>) {
+        if columns.is_empty() || rows.is_empty() {
+            return;
+        }
+
+        // Layers above 0 reach the screen when composited, which marks what they drew.
+        let region = match (self.layer, self.dirty_tracking) {
+            (0, false) => return,
+            (0, true) => &mut self.dirty,
+            _ => &mut self.drawn,
+        };
+        *region = Some(match region.take() {
+            Some((c, r)) => (
+                c.start.min(columns.start)..c.end.max(columns.end),
+                r.start.min(rows.start)..r.end.max(rows.end),
+            ),
+            None => (columns, rows),
+        });
+    }
+
+    fn mark_all_dirty(&mut self) {
+        self.mark_dirty(0..self.width as usize, 0..self.height as usize);
+    }
+
+    pub fn blend_mode(&self) -> BlendMode {
+        self.blend_mode
+    }
+
+    /// Set how everything drawn from now on is combined with the screen.
+    pub fn set_blend_mode(&mut self, blend_mode: BlendMode) {
+        self.blend_mode = blend_mode;
+        self.update_blend_mode();
+    }
+
+    /// Layers above 0 start out transparent, so alpha blending keeps their alpha for compositing.
+    fn update_blend_mode(&mut self) {
+        self.active_blend_mode = match self.blend_mode {
+            BlendMode::Alpha if self.layer > 0 => BlendMode::Over,
+            chosen => chosen,
+        };
+    }
+
+    pub fn layer(&self) -> u8 {
+        self.layer
+    }
+
+    pub fn camera(&self) -> Option<Camera2D> {
+        self.camera
+    }
+
+    /// Draw world-space shapes and sprites through `camera` from now on. Text, text grids,
+    /// tiled sprites and custom draw commands are still drawn in screen space, and lines and
+    /// points stay one pixel wide when zoomed.
+    pub fn set_camera(&mut self, camera: &Camera2D) {
+        self.camera = Some(*camera);
+    }
+
+    /// Draw in screen space again, e.g. for the HUD.
+    pub fn clear_camera(&mut self) {
+        self.camera = None;
+    }
+
+    /// Where a world position is drawn on screen, in virtual pixels, through the current camera.
+    /// With no camera set the position is already on screen.
+    pub fn world_to_screen(&self, point: Vec2) -> Vec2 {
+        match &self.camera {
+            Some(camera) => camera.world_to_screen(point, self.screen_size()),
+            None => point,
+        }
+    }
+
+    /// The world position drawn at a screen position through the current camera.
+    pub fn screen_to_world(&self, point: Vec2) -> Vec2 {
+        match &self.camera {
+            Some(camera) => camera.screen_to_world(point, self.screen_size()),
+            None => point,
+        }
+    }
+
+    /// The screen size in virtual pixels.
+    fn screen_size(&self) -> Vec2 {
+        Vec2::new(
+            self.width / self.pixel_width as f32,
+            self.height / self.pixel_height as f32,
+        )
+    }
+
+    /// If a camera is set, run `draw` with it suspended, so the draw calls it makes with the
+    /// positions from `to_screen` aren't moved again, and return true.
+    fn through_camera(
+        &mut self,
+        draw: impl FnOnce(&mut Self, &Camera2D, &dyn Fn(f32, f32) -> Vec2),
+    ) -> bool {
+        let camera = match self.camera.take() {
+            Some(camera) => camera,
+            None => return false,
+        };
+
+        let screen = self.screen_size();
+        draw(self, &camera, &|x, y| {
+            camera.world_to_screen(Vec2::new(x, y), screen)
+        });
+        self.camera = Some(camera);
+        true
+    }
+
+    /// The rotation to pass to `draw_sprite_ex` to turn a sprite with the camera's view.
+    fn view_rotation(&self, camera: &Camera2D) -> f32 {
+        match self.origin {
+            Origin::BottomLeft => -camera.rotation(),
+            Origin::TopLeft => camera.rotation(),
+        }
+    }
+
+    /// Draw into `layer` from now on. Layers are stacked in order on top of layer 0 when the frame
+    /// is presented, so they can be drawn in any order, e.g. the UI before the entities under it.
+    /// Layers above 0 are emptied after each frame and drawing returns to layer 0. When
+    /// interlaced, layers are drawn and stacked a field at a time like layer 0, so the other field
+    /// keeps the previous frame's picture, layers included.
+    pub fn set_layer(&mut self, layer: u8) {
+        if layer == self.layer {
+            return;
+        }
+
+        let (width, height) = (self.width as usize, self.height as usize);
+        let next = self.layers.remove(&layer).unwrap_or_else(|| Layer {
+            buffer: FrameBuffer::new(width, height),
+            drawn: None,
+        });
+        let previous = Layer {
+            buffer: std::mem::replace(&mut self.buffer, next.buffer),
+            drawn: std::mem::replace(&mut self.drawn, next.drawn),
+        };
+        self.layers.insert(self.layer, previous);
+        self.layer = layer;
+        self.update_blend_mode();
+    }
+
+    /// Stack the layers drawn this frame onto layer 0, empty them and go back to drawing into
+    /// layer 0. Only the part of each layer that was drawn is visited and marked dirty.
+    pub(crate) fn composite_layers(&mut self) {
+        self.set_layer(0);
+
+        let width = self.width as usize;
+        let mut composited = Vec::new();
+        for layer in self.layers.values_mut() {
+            let (columns, rows) = match layer.drawn.take() {
+                Some(drawn) => drawn,
+                None => continue,
+            };
+            for row in rows.clone() {
+                let span = row * width + columns.start..row * width + columns.end;
+                let (dst, src) = (
+                    &mut self.buffer.data[span.clone()],
+                    &mut layer.buffer.data[span],
+                );
+                for (dst, src) in dst.iter_mut().zip(src.iter_mut()) {
+                    let color: Color = std::mem::take(src).into();
+                    if color.a() > 0 {
+                        *dst = Color::blend_over(color, (*dst).into()).into();
+                    }
+                }
+            }
+            composited.push((columns, rows));
+        }
+        for (columns, rows) in composited {
+            self.mark_dirty(columns, rows);
+        }
+    }
+
+    /// Draw only one field of pixels each frame, alternating when presented, so full-screen
+    /// effects cost half as much to fill at the expense of some combing on movement.
+    pub fn set_interlace(&mut self, interlace: Interlace) {
+        self.interlace = interlace;
+    }
+
+    pub fn render_scale(&self) -> RenderScale {
+        self.render_scale
+    }
+
+    pub fn upscale_filter(&self) -> UpscaleFilter {
+        self.upscale_filter
+    }
+
+    /// Draw at a fraction of the window resolution, upscaled with `filter` when presented. Each
+    /// virtual pixel has to cover a whole number of rendered pixels, so the scale is raised if
+    /// the pixel size doesn't divide by it; with a pixel size of 1 every scale falls back to
+    /// `RenderScale::Full`. Returns the scale in effect. The current image is kept, resampled to
+    /// the new resolution.
+    pub fn set_render_scale(&mut self, scale: RenderScale, filter: UpscaleFilter) -> RenderScale {
+        // Layers are sized to the buffer, so they are flattened into it before it is resampled.
+        self.composite_layers();
+        self.layers.clear();
+
+        let mut divisor = scale.divisor();
+        while divisor > 1
+            && (!self.window_pixel_width.is_multiple_of(divisor)
+                || !self.window_pixel_height.is_multiple_of(divisor))
+        {
+            divisor /= 2;
+        }
+        if divisor != scale.divisor() {
+            warn!(
+                "render scale {:?} needs a pixel size divisible by {}, using {:?}",
+                scale,
+                scale.divisor(),
+                RenderScale::from_divisor(divisor)
+            );
+        }
+
+        let (old_width, old_height) = (self.width as usize, self.height as usize);
+        let (width, height) = (
+            self.window_width as usize / divisor,
+            self.window_height as usize / divisor,
+        );
+        let mut buffer = FrameBuffer::new(width, height);
+        for (i, pixel) in buffer.data.iter_mut().enumerate() {
+            let x = (i % width) * old_width / width;
+            let y = (i / width) * old_height / height;
+            *pixel = self.buffer.data[y * old_width + x];
+        }
+
+        self.width = width as f32;
+        self.height = height as f32;
+        self.pixel_width = self.window_pixel_width / divisor;
+        self.pixel_height = self.window_pixel_height / divisor;
+        self.buffer = buffer;
+        self.render_scale = RenderScale::from_divisor(divisor);
+        self.upscale_filter = filter;
+        self.output = match self.render_scale {
+            RenderScale::Full => FrameBuffer::new(0, 0),
+            _ => FrameBuffer::new(self.window_width as usize, self.window_height as usize),
+        };
+        self.dirty = None;
+        self.mark_all_dirty();
+
+        self.render_scale
+    }
+
+    /// Change the window size and virtual pixel size, keeping the current render scale where the
+    /// new pixel size allows it. The current image is kept, resampled to the new resolution.
+    pub fn resize(&mut self, width: f32, height: f32, pixel_width: usize, pixel_height: usize) {
+        self.window_width = width;
+        self.window_height = height;
+        self.window_pixel_width = pixel_width;
+        self.window_pixel_height = pixel_height;
+        self.set_render_scale(self.render_scale, self.upscale_filter);
+    }
+
+    /// Convert a window pixel measurement to rendered pixels.
+    fn window_to_render(&self, value: f32) -> f32 {
+        value / self.render_scale.divisor() as f32
+    }
+
+    /// The buffer to draw into directly, which counts as drawing over all of it.
+    pub(crate) fn buffer_mut(&mut self) -> &mut FrameBuffer {
+        self.mark_all_dirty();
+        &mut self.buffer
+    }
+
+    pub fn origin(&self) -> Origin {
+        self.origin
+    }
+
+    pub fn set_origin(&mut self, origin: Origin) {
+        self.origin = origin;
+    }
+
+    /// The framebuffer row for window pixel row `y`.
+    fn buffer_row(&self, y: f32) -> f32 {
+        match self.origin {
+            Origin::BottomLeft => self.height - y,
+            Origin::TopLeft => y,
+        }
+    }
+
+    /// Move `distance` up the screen from `y`.
+    fn up(&self, y: f32, distance: f32) -> f32 {
+        match self.origin {
+            Origin::BottomLeft => y + distance,
+            Origin::TopLeft => y - distance,
+        }
+    }
+
+    /// The offset of image row `row` from the corner it's drawn at. Images are stored top row
+    /// first, so they are drawn from the bottom left corner upwards or the top left corner
+    /// downwards.
+    fn image_row_offset(&self, height: usize, row: usize) -> f32 {
+        match self.origin {
+            Origin::BottomLeft => (height - row) as f32,
+            Origin::TopLeft => row as f32,
+        }
+    }
+
     fn put_pixel(&mut self, x: f32, y: f32, color: Color) {
-        let y = self.height - y;
+        let y = self.buffer_row(y);
 
         // TODO: transmute?
-        if x >= 0.0 && x < self.width && y >= 0.0 && y < self.height {
+        if x >= 0.0
+            && x < self.width
+            && y >= 0.0
+            && y < self.height
+            && self.interlace.covers(self.field, x as usize, y as usize)
+        {
             let buffer_idx = y as usize * self.width as usize + x as usize;
+            self.mark_dirty(x as usize..x as usize + 1, y as usize..y as usize + 1);
 
-            let dst = self.buffer.data[buffer_idx];
-            let dst_a = ((dst >> 24) & 255) as u8;
-            let dst_r = ((dst >> 16) & 255) as u8;
-            let dst_g = ((dst >> 8) & 255) as u8;
-            let dst_b = (dst & 255) as u8;
-            let dst = Color::rgba(dst_r, dst_g, dst_b, dst_a);
+            let dst = self.buffer.data[buffer_idx].into();
 
-            self.buffer.data[buffer_idx] = Color::linear_blend(color, dst).into();
+            self.buffer.data[buffer_idx] = self.active_blend_mode.blend(color, dst).into();
         }
     }
 
     pub fn clear(&mut self, color: Color) {
-        self.buffer.data = vec![color.into(); self.width as usize * self.height as usize];
+        self.mark_all_dirty();
+        if self.interlace == Interlace::Off {
+            self.buffer.data.fill(color.into());
+            return;
+        }
+
+        // Only visit the pixels in this frame's field, so interlacing halves the cost of a clear.
+        let (width, height, field) = (self.width as usize, self.height as usize, self.field);
+        let color: u32 = color.into();
+        match self.interlace {
+            Interlace::Off => {}
+            Interlace::Scanlines => {
+                for row in (field..height).step_by(2) {
+                    self.buffer.data[row * width..(row + 1) * width].fill(color);
+                }
+            }
+            Interlace::Checkerboard => {
+                for row in 0..height {
+                    for x in ((field + row) % 2..width).step_by(2) {
+                        self.buffer.data[row * width + x] = color;
+                    }
+                }
+            }
+        }
     }
 
     pub fn draw(&mut self, x: f32, y: f32, color: Color) {
+        if self.through_camera(|renderer, _, to_screen| {
+            let point = to_screen(x, y);
+            renderer.draw(point.x, point.y, color);
+        }) {
+            return;
+        }
+
         let x = x * self.pixel_width as f32;
         let y = y * self.pixel_height as f32;
 
@@ -80,6 +703,13 @@ impl Renderer {
 
     /// Draw a line from (x0, y0) to (x1, y1) using Bresenham's line algorithm.
     pub fn draw_line(&mut self, x0: f32, y0: f32, x1: f32, y1: f32, color: Color) {
+        if self.through_camera(|renderer, _, to_screen| {
+            let (from, to) = (to_screen(x0, y0), to_screen(x1, y1));
+            renderer.draw_line(from.x, from.y, to.x, to.y, color);
+        }) {
+            return;
+        }
+
         let x0 = (clamp(0.0, x0.floor(), self.width) + 0.5) as u32;
         let y0 = (clamp(0.0, y0.floor(), self.height) + 0.5) as u32;
         let x1 = (clamp(0.0, x1.floor(), self.width) + 0.5) as u32;
@@ -118,6 +748,13 @@ impl Renderer {
         y2: f32,
         color: Color,
     ) {
+        if self.through_camera(|renderer, _, to_screen| {
+            let [a, b, c] = [(x0, y0), (x1, y1), (x2, y2)].map(|(x, y)| to_screen(x, y));
+            renderer.draw_filled_triangle(a.x, a.y, b.x, b.y, c.x, c.y, color);
+        }) {
+            return;
+        }
+
         let (mut x0, mut y0, mut x1, mut y1, mut x2, mut y2) = (x0, y0, x1, y1, x2, y2);
         // Sort vertices by y so that y0 <= y1 <= y2.
         if y1 < y0 {
@@ -261,7 +898,41 @@ impl Renderer {
         self.draw_line(x, y1, x1, y1, color);
     }
 
+    pub fn draw_wireframe_obb(&mut self, obb: &Obb, color: Color) {
+        let corners = obb.corners();
+        for i in 0..corners.len() {
+            let (a, b) = (corners[i], corners[(i + 1) % corners.len()]);
+            self.draw_line(a.x, a.y, b.x, b.y, color);
+        }
+    }
+
+    /// Stroke the spline with straight lines roughly two pixels long.
+    pub fn draw_spline(&mut self, spline: &Spline, color: Color) {
+        for pair in spline.points(2.0).windows(2) {
+            self.draw_line(pair[0].x, pair[0].y, pair[1].x, pair[1].y, color);
+        }
+    }
+
+    pub fn draw_filled_obb(&mut self, obb: &Obb, color: Color) {
+        let [a, b, c, d] = obb.corners();
+        self.draw_filled_triangle(a.x, a.y, b.x, b.y, c.x, c.y, color);
+        self.draw_filled_triangle(a.x, a.y, c.x, c.y, d.x, d.y, color);
+    }
+
     pub fn draw_filled_rectangle(&mut self, x: f32, y: f32, width: f32, height: f32, color: Color) {
+        if self.through_camera(|renderer, camera, to_screen| {
+            let (a, c) = (to_screen(x, y), to_screen(x + width, y + height));
+            if camera.rotation() == 0.0 {
+                renderer.draw_filled_rectangle(a.x, a.y, c.x - a.x, c.y - a.y, color);
+            } else {
+                let (b, d) = (to_screen(x + width, y), to_screen(x, y + height));
+                renderer.draw_filled_triangle(a.x, a.y, b.x, b.y, c.x, c.y, color);
+                renderer.draw_filled_triangle(a.x, a.y, c.x, c.y, d.x, d.y, color);
+            }
+        }) {
+            return;
+        }
+
         let x1 = x + width;
         let y1 = y + height;
 
@@ -289,6 +960,13 @@ impl Renderer {
     /// Draw a wireframe circle centered on (x, y) with radius using Bresenham's algorithm.
     /// See https://www.geeksforgeeks.org/bresenhams-circle-drawing-algorithm/?ref=lbp
     pub fn draw_wireframe_circle(&mut self, x: f32, y: f32, radius: f32, color: Color) {
+        if self.through_camera(|renderer, camera, to_screen| {
+            let center = to_screen(x, y);
+            renderer.draw_wireframe_circle(center.x, center.y, radius * camera.zoom(), color);
+        }) {
+            return;
+        }
+
         let (x, y) = (x as i32, y as i32);
         let radius = radius as i32;
 
@@ -318,6 +996,13 @@ impl Renderer {
 
     /// Draw a filled circle centered on (x, y) with radius using Bresenham's algorithm.
     pub fn draw_filled_circle(&mut self, x: f32, y: f32, radius: f32, color: Color) {
+        if self.through_camera(|renderer, camera, to_screen| {
+            let center = to_screen(x, y);
+            renderer.draw_filled_circle(center.x, center.y, radius * camera.zoom(), color);
+        }) {
+            return;
+        }
+
         let (x, y) = (x as i32, y as i32);
         let radius = radius as i32;
 
@@ -365,6 +1050,86 @@ impl Renderer {
         }
     }
 
+    /// Draw the outline of an arc centered on (x, y) between two angles.
+    /// Angles are in radians, measured counter-clockwise from the positive x-axis.
+    pub fn draw_wireframe_arc(
+        &mut self,
+        x: f32,
+        y: f32,
+        radius: f32,
+        start_angle: f32,
+        end_angle: f32,
+        color: Color,
+    ) {
+        let sweep = end_angle - start_angle;
+        let segments = ((sweep.abs() * radius).ceil() as u32).max(1);
+
+        let mut previous = (
+            x + radius * start_angle.cos(),
+            y + radius * start_angle.sin(),
+        );
+        for segment in 1..=segments {
+            let angle = start_angle + sweep * segment as f32 / segments as f32;
+            let next = (x + radius * angle.cos(), y + radius * angle.sin());
+            self.draw_line(previous.0, previous.1, next.0, next.1, color);
+            previous = next;
+        }
+    }
+
+    /// Fill the ring segment centered on (x, y) between two radii and two angles.
+    /// An inner radius of zero draws a pie slice.
+    /// Angles are in radians, measured counter-clockwise from the positive x-axis.
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_filled_arc(
+        &mut self,
+        x: f32,
+        y: f32,
+        inner_radius: f32,
+        outer_radius: f32,
+        start_angle: f32,
+        end_angle: f32,
+        color: Color,
+    ) {
+        if self.through_camera(|renderer, camera, to_screen| {
+            let center = to_screen(x, y);
+            renderer.draw_filled_arc(
+                center.x,
+                center.y,
+                inner_radius * camera.zoom(),
+                outer_radius * camera.zoom(),
+                start_angle - camera.rotation(),
+                end_angle - camera.rotation(),
+                color,
+            );
+        }) {
+            return;
+        }
+
+        let (start_angle, sweep) = if end_angle < start_angle {
+            (end_angle, start_angle - end_angle)
+        } else {
+            (start_angle, end_angle - start_angle)
+        };
+        let full_circle = sweep >= std::f32::consts::TAU;
+
+        let (x, y) = (x.floor(), y.floor());
+        let extent = outer_radius.ceil() as i32;
+        for offset_y in -extent..=extent {
+            for offset_x in -extent..=extent {
+                let (dx, dy) = (offset_x as f32, offset_y as f32);
+                let distance = (dx * dx + dy * dy).sqrt();
+                if distance < inner_radius || distance > outer_radius {
+                    continue;
+                }
+
+                let angle = (dy.atan2(dx) - start_angle).rem_euclid(std::f32::consts::TAU);
+                if full_circle || angle <= sweep {
+                    self.draw(x + dx, y + dy, color);
+                }
+            }
+        }
+    }
+
     /// Draw a wireframe outline of a model at a given position (translation), rotation (radians) and scale.
     pub fn draw_wireframe_model(
         &mut self,
@@ -374,28 +1139,85 @@ impl Renderer {
         model: &[Point],
         color: Color,
     ) {
-        let vertices: Vec<Point> = model
-            .iter()
-            .map(|vertex| {
-                let (x, y) = (vertex.x(), vertex.y());
+        let vertices = transform_model(position, rotation, scale, model);
+        self.draw_outline(&vertices, true, color);
+    }
 
-                let (x, y) = (x * scale, y * scale); // Scale.
+    /// Draw a vector model's shapes, filled then stroked, at a given position (translation),
+    /// rotation (radians) and scale.
+    pub fn draw_vector_model(
+        &mut self,
+        position: Point,
+        rotation: f32,
+        scale: f32,
+        model: &VectorModel,
+    ) {
+        for shape in &model.shapes {
+            let vertices = transform_model(position, rotation, scale, &shape.points);
+            if let Some(fill) = shape.fill {
+                self.draw_filled_polygon(&vertices, fill);
+            }
+            if let Some(stroke) = shape.stroke {
+                self.draw_outline(&vertices, shape.closed, stroke);
+            }
+        }
+    }
 
-                // y-axis is up, but we draw as if it is down, which means the rotation is in the wrong direction, so flip it.
-                let rotation = -rotation;
-                let (x, y) = (
-                    x * rotation.cos() - y * rotation.sin(),
-                    y * rotation.cos() + x * rotation.sin(),
-                ); // Rotate.
+    /// Fill the inside of a polygon, using the even-odd rule where its edges cross.
+    pub fn draw_filled_polygon(&mut self, vertices: &[Point], color: Color) {
+        if self.through_camera(|renderer, _, to_screen| {
+            let vertices: Vec<Point> = vertices
+                .iter()
+                .map(|vertex| {
+                    let point = to_screen(vertex.x(), vertex.y());
+                    Point::new(point.x, point.y)
+                })
+                .collect();
+            renderer.draw_filled_polygon(&vertices, color);
+        }) {
+            return;
+        }
 
-                let (x, y) = (x + position.x(), y + position.y()); // Translate
+        if vertices.len() < 3 {
+            return;
+        }
 
-                (x, y).into()
-            })
-            .collect();
+        let (min_y, max_y) = vertices.iter().fold((f32::MAX, f32::MIN), |(min, max), v| {
+            (min.min(v.y()), max.max(v.y()))
+        });
+
+        let mut crossings = Vec::new();
+        for row in min_y.floor() as i64..=max_y.ceil() as i64 {
+            // Sample through the middle of the row, so vertices never sit exactly on it.
+            let y = row as f32 + 0.5;
+            crossings.clear();
+            for i in 0..vertices.len() {
+                let (a, b) = (vertices[i], vertices[(i + 1) % vertices.len()]);
+                if (a.y() <= y) != (b.y() <= y) {
+                    crossings.push(a.x() + (y - a.y()) / (b.y() - a.y()) * (b.x() - a.x()));
+                }
+            }
+            crossings.sort_by(|a, b| a.total_cmp(b));
+
+            for span in crossings.chunks_exact(2) {
+                let (start, end) = ((span[0] - 0.5).ceil(), (span[1] - 0.5).floor());
+                let mut x = start;
+                while x <= end {
+                    self.draw(x, row as f32, color);
+                    x += 1.0;
+                }
+            }
+        }
+    }
 
+    fn draw_outline(&mut self, vertices: &[Point], closed: bool, color: Color) {
         let count = vertices.len();
-        for i in 0..count {
+        let edges = if closed {
+            count
+        } else {
+            count.saturating_sub(1)
+        };
+        for i in 0..edges {
             let a = &vertices[i];
             let b = &vertices[(i + 1) % count];
             self.draw_line(a.x(), a.y(), b.x(), b.y(), color);
@@ -403,45 +1225,907 @@ impl Renderer {
     }
 
     pub fn draw_string(&mut self, value: impl AsRef<str>, x: f32, y: f32, color: Color, size: f32) {
-        let mut character_offset_x = 0.0;
-        for c in value.as_ref().chars() {
-            let rasterized = font::rasterize(c, &self.default_font, size);
-
-            for rasterized_y in 0..rasterized.height {
-                for rasterized_x in 0..rasterized.width {
-                    let font_color = Color::rgba(
-                        color.r(),
-                        color.g(),
-                        color.b(),
-                        rasterized.data[rasterized_y * rasterized.width + rasterized_x],
-                    );
-                    self.put_pixel(
-                        x + character_offset_x + rasterized.xmin as f32 + rasterized_x as f32,
-                        y + rasterized.ymin as f32 + (rasterized.height - rasterized_y) as f32,
-                        font_color,
-                    );
-                }
-            }
-
-            character_offset_x += rasterized.advance_width;
-        }
+        self.draw_styled_string(value, x, y, color, size, &TextStyle::default());
     }
 
-    pub fn draw_sprite(&mut self, x: f32, y: f32, sprite: &Sprite) {
-        for sprite_y in 0..sprite.height() as usize {
-            for sprite_x in 0..sprite.width() as usize {
-                let x = x + sprite_x as f32;
-                let y = y + (sprite.height() as usize - sprite_y) as f32;
+    /// Draw a string with an outline and/or drop shadow.
+    pub fn draw_styled_string(
+        &mut self,
+        value: impl AsRef<str>,
+        x: f32,
+        y: f32,
+        color: Color,
+        size: f32,
+        style: &TextStyle,
+    ) {
+        let (x, y, size) = (
+            self.window_to_render(x),
+            self.window_to_render(y),
+            self.window_to_render(size),
+        );
+        let glyphs = self.rasterize_string(value.as_ref(), x, y, size);
 
-                let offset = (sprite_y * sprite.width() as usize + sprite_x) * 4;
-                let sprite_data = sprite.data();
-                let r = sprite_data[offset];
-                let g = sprite_data[offset + 1];
-                let b = sprite_data[offset + 2];
-                let a = sprite_data[offset + 3];
-                let color = Color::rgba(r, g, b, a);
+        if let Some((offset, shadow)) = style.shadow {
+            let offset_x = self.window_to_render(offset.x).round() as i64;
+            let offset_row = (self.buffer_row(self.window_to_render(offset.y))
+                - self.buffer_row(0.0))
+            .round() as i64;
+            for &(x, y, coverage) in &glyphs {
+                let shadow = with_coverage(shadow, coverage);
+                self.put_buffer_pixel(x + offset_x, y + offset_row, shadow);
+            }
+        }
 
-                self.draw(x, y, color);
+        if let Some((outline, width)) = style.outline {
+            for (x, y, coverage) in dilate(&glyphs, self.window_to_render(width)) {
+                self.put_buffer_pixel(x, y, with_coverage(outline, coverage));
+            }
+        }
+
+        for (x, y, coverage) in glyphs {
+            let font_color = Color::rgba(color.r(), color.g(), color.b(), coverage);
+            self.put_buffer_pixel(x, y, font_color);
+        }
+    }
+
+    /// Rasterize `characters` at each text size in `sizes`, as passed to `draw_string`.
+    pub fn bake_glyph_atlas(&self, sizes: &[f32], characters: &str) -> GlyphAtlas {
+        let sizes: Vec<f32> = sizes
+            .iter()
+            .map(|&size| self.window_to_render(size))
+            .collect();
+        GlyphAtlas::bake(&self.default_font, &sizes, characters)
+    }
+
+    /// Draw strings with glyphs from `atlas` where it has them, rather than rasterizing them.
+    pub fn add_glyph_atlas(&mut self, atlas: GlyphAtlas) {
+        self.glyph_atlas.extend(atlas);
+    }
+
+    /// How wide `value` is drawn at `size`, both in window pixels.
+    pub fn string_width(&self, value: &str, size: f32) -> f32 {
+        let size = self.window_to_render(size);
+        let width: f32 = value
+            .chars()
+            .map(|c| match self.glyph_atlas.glyph(c, size) {
+                Some(glyph) => glyph.advance_width,
+                None => font::advance_width(c, &self.default_font, size),
+            })
+            .sum();
+
+        width * self.render_scale.divisor() as f32
+    }
+
+    /// The framebuffer position and coverage of every pixel of `value` drawn at (x, y).
+    fn rasterize_string(&self, value: &str, x: f32, y: f32, size: f32) -> Vec<(i64, i64, u8)> {
+        let mut pixels = Vec::new();
+        let mut character_offset_x = 0.0;
+        for c in value.chars() {
+            let rasterized = match self.glyph_atlas.glyph(c, size) {
+                Some(glyph) => Cow::Borrowed(glyph),
+                None => Cow::Owned(font::rasterize(c, &self.default_font, size)),
+            };
+
+            for rasterized_y in 0..rasterized.height {
+                for rasterized_x in 0..rasterized.width {
+                    let coverage = rasterized.data[rasterized_y * rasterized.width + rasterized_x];
+                    let glyph_y =
+                        rasterized.ymin as f32 + (rasterized.height - rasterized_y) as f32;
+                    let pixel_x =
+                        x + character_offset_x + rasterized.xmin as f32 + rasterized_x as f32;
+                    let pixel_y = self.buffer_row(self.up(y, glyph_y));
+
+                    pixels.push((pixel_x.floor() as i64, pixel_y.floor() as i64, coverage));
+                }
+            }
+
+            character_offset_x += rasterized.advance_width;
+        }
+
+        pixels
+    }
+
+    /// Draw a text grid with its top left cell in the corner given by the origin, as for sprites.
+    pub fn draw_text_grid(&mut self, x: f32, y: f32, grid: &TextGrid) {
+        let (cell_width, cell_height) = (grid.cell_width(), grid.cell_height());
+        if cell_width == 0 || cell_height == 0 {
+            return;
+        }
+
+        let key = (cell_width, cell_height);
+        let mut font = self
+            .bitmap_fonts
+            .remove(&key)
+            .unwrap_or_else(|| BitmapFont::new(&self.default_font, cell_width, cell_height));
+        let grid_height = grid.rows() * cell_height;
+
+        for row in 0..grid.rows() {
+            for column in 0..grid.columns() {
+                let cell = match grid.get(column, row) {
+                    Some(cell) => *cell,
+                    None => continue,
+                };
+                let glyph = font.glyph(&self.default_font, cell.character);
+
+                for glyph_y in 0..cell_height {
+                    let y = y + self.image_row_offset(grid_height, row * cell_height + glyph_y);
+                    for glyph_x in 0..cell_width {
+                        let x = x + (column * cell_width + glyph_x) as f32;
+                        let coverage = glyph[glyph_y * cell_width + glyph_x];
+
+                        if coverage < 255 {
+                            self.draw_sprite_pixel(x, y, cell.background, false);
+                        }
+                        if coverage > 0 {
+                            let foreground = with_coverage(cell.foreground, coverage);
+                            self.draw_sprite_pixel(x, y, foreground, false);
+                        }
+                    }
+                }
+            }
+        }
+
+        self.bitmap_fonts.insert(key, font);
+    }
+
+    /// Blend `color` into the framebuffer at column `x`, row `y`.
+    fn put_buffer_pixel(&mut self, x: i64, y: i64, color: Color) {
+        if x < 0
+            || y < 0
+            || x >= self.width as i64
+            || y >= self.height as i64
+            || !self.interlace.covers(self.field, x as usize, y as usize)
+        {
+            return;
+        }
+
+        let buffer_idx = y as usize * self.width as usize + x as usize;
+        self.mark_dirty(x as usize..x as usize + 1, y as usize..y as usize + 1);
+        let dst = self.buffer.data[buffer_idx].into();
+        self.buffer.data[buffer_idx] = self.active_blend_mode.blend(color, dst).into();
+    }
+
+    /// Draw a sprite with its bottom left corner at (x, y), or its top left corner when the
+    /// origin is top left.
+    ///
+    /// Sprites with no transparency are written straight into the framebuffer. Otherwise, each
+    /// pixel is blended onto the framebuffer exactly once using its own alpha, with fully
+    /// transparent pixels skipped. Pixels that fall outside the framebuffer are discarded.
+    /// Trimmed sprites are offset so they land where the untrimmed image would have.
+    pub fn draw_sprite(&mut self, x: f32, y: f32, sprite: &Sprite) {
+        self.draw_sprite_flipped(x, y, sprite, Flip::NONE);
+    }
+
+    /// Draw a sprite mirrored in place, so it covers the same pixels as `draw_sprite`.
+    pub fn draw_sprite_flipped(&mut self, x: f32, y: f32, sprite: &Sprite, flip: Flip) {
+        if self.through_camera(|renderer, camera, to_screen| {
+            let position = to_screen(x, y);
+            if camera.is_translation() {
+                renderer.draw_sprite_flipped(position.x, position.y, sprite, flip);
+            } else {
+                let opaque = sprite.is_opaque();
+                renderer.draw_sprite_in_view(
+                    camera,
+                    position,
+                    sprite,
+                    flip,
+                    Vec2::new(1.0, 1.0),
+                    opaque,
+                    |color| color,
+                );
+            }
+        }) {
+            return;
+        }
+
+        let offset = sprite.trim_offset(self.origin, flip);
+        let (x, y) = (x + offset.x, y + offset.y);
+        let (width, height) = (sprite.width() as usize, sprite.height() as usize);
+        let (columns, rows) = self.visible_area(x, y, width, height);
+        if columns.is_empty() || rows.is_empty() {
+            return;
+        }
+
+        // Clip the sprite to the framebuffer once, then build each sprite row at the pixel size
+        // and copy it into every framebuffer row it covers.
+        let (pixel_width, pixel_height) = (self.pixel_width as i64, self.pixel_height as i64);
+        let (buffer_width, buffer_height) = (self.width as i64, self.height as i64);
+        let left = x.floor() as i64;
+        let window_x0 = ((left + columns.start as i64) * pixel_width).max(0);
+        let window_x1 = ((left + columns.end as i64) * pixel_width).min(buffer_width);
+        if window_x0 >= window_x1 {
+            return;
+        }
+
+        let mut row = vec![0_u32; (window_x1 - window_x0) as usize];
+        for sprite_y in rows {
+            let source_y = if flip.contains(Flip::VERTICAL) {
+                height - 1 - sprite_y
+            } else {
+                sprite_y
+            };
+
+            let mut row_is_opaque = true;
+            for (i, pixel) in row.iter_mut().enumerate() {
+                let sprite_x = ((window_x0 + i as i64) / pixel_width - left) as usize;
+                let source_x = if flip.contains(Flip::HORIZONTAL) {
+                    width - 1 - sprite_x
+                } else {
+                    sprite_x
+                };
+                let color = sprite_color(sprite, source_x, source_y);
+
+                row_is_opaque &= color.a() == 255;
+                *pixel = color.into();
+            }
+
+            let window_y =
+                (y + self.image_row_offset(height, sprite_y)).floor() as i64 * pixel_height;
+            for sub_y in 0..pixel_height {
+                let buffer_y = match self.origin {
+                    Origin::BottomLeft => buffer_height - (window_y + sub_y),
+                    Origin::TopLeft => window_y + sub_y,
+                };
+                if buffer_y < 0 || buffer_y >= buffer_height {
+                    continue;
+                }
+
+                let start = (buffer_y * buffer_width + window_x0) as usize;
+                self.mark_dirty(
+                    window_x0 as usize..window_x0 as usize + row.len(),
+                    buffer_y as usize..buffer_y as usize + 1,
+                );
+                let (interlace, field, blend_mode) =
+                    (self.interlace, self.field, self.active_blend_mode);
+                let dst = &mut self.buffer.data[start..start + row.len()];
+                if row_is_opaque && interlace == Interlace::Off && blend_mode.overwrites(255) {
+                    dst.copy_from_slice(&row);
+                    continue;
+                }
+
+                for (i, (dst, &src)) in dst.iter_mut().zip(row.iter()).enumerate() {
+                    if !interlace.covers(field, window_x0 as usize + i, buffer_y as usize) {
+                        continue;
+                    }
+
+                    let src: Color = src.into();
+                    if blend_mode.overwrites(src.a()) {
+                        *dst = src.into();
+                    } else if !blend_mode.skips(src.a()) {
+                        *dst = blend_mode.blend(src, (*dst).into()).into();
+                    }
+                }
+            }
+        }
+    }
+
+    /// Draw the `src_width` x `src_height` region of a sprite sheet whose top left corner is at
+    /// (src_x, src_y) in sprite pixels, as if it were a sprite of its own. The region is clipped
+    /// to the sprite, and any trim is ignored.
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_sprite_region(
+        &mut self,
+        x: f32,
+        y: f32,
+        sprite: &Sprite,
+        src_x: u32,
+        src_y: u32,
+        src_width: u32,
+        src_height: u32,
+    ) {
+        if self.through_camera(|renderer, camera, to_screen| {
+            let position = to_screen(x, y);
+            if camera.is_translation() {
+                renderer.draw_sprite_region(
+                    position.x, position.y, sprite, src_x, src_y, src_width, src_height,
+                );
+            } else {
+                let region = sprite.region(src_x, src_y, src_width, src_height);
+                let opaque = region.is_opaque();
+                let scale = Vec2::new(1.0, 1.0);
+                renderer.draw_sprite_in_view(
+                    camera,
+                    position,
+                    &region,
+                    Flip::NONE,
+                    scale,
+                    opaque,
+                    |color| color,
+                );
+            }
+        }) {
+            return;
+        }
+
+        let opaque = sprite.is_opaque();
+        let src_x = src_x.min(sprite.width()) as usize;
+        let src_y = src_y.min(sprite.height()) as usize;
+        let width = (src_width as usize).min(sprite.width() as usize - src_x);
+        let height = (src_height as usize).min(sprite.height() as usize - src_y);
+        let (columns, rows) = self.visible_area(x, y, width, height);
+
+        for region_y in rows {
+            let y = y + self.image_row_offset(height, region_y);
+
+            for region_x in columns.clone() {
+                let color = sprite_color(sprite, src_x + region_x, src_y + region_y);
+
+                self.draw_sprite_pixel(x + region_x as f32, y, color, opaque);
+            }
+        }
+    }
+
+    /// Draw a sprite with its colors swapped through `palette`.
+    pub fn draw_sprite_with_palette(&mut self, x: f32, y: f32, sprite: &Sprite, palette: &Palette) {
+        if self.through_camera(|renderer, camera, to_screen| {
+            let position = to_screen(x, y);
+            if camera.is_translation() {
+                renderer.draw_sprite_with_palette(position.x, position.y, sprite, palette);
+            } else {
+                let opaque = sprite.is_opaque();
+                renderer.draw_sprite_in_view(
+                    camera,
+                    position,
+                    sprite,
+                    Flip::NONE,
+                    Vec2::new(1.0, 1.0),
+                    opaque,
+                    |color| palette.apply(color),
+                );
+            }
+        }) {
+            return;
+        }
+
+        let offset = sprite.trim_offset(self.origin, Flip::NONE);
+        let (x, y) = (x + offset.x, y + offset.y);
+        let opaque = sprite.is_opaque();
+
+        for sprite_y in 0..sprite.height() as usize {
+            for sprite_x in 0..sprite.width() as usize {
+                let x = x + sprite_x as f32;
+                let y = y + self.image_row_offset(sprite.height() as usize, sprite_y);
+
+                let color = palette.apply(sprite_color(sprite, sprite_x, sprite_y));
+
+                self.draw_sprite_pixel(x, y, color, opaque);
+            }
+        }
+    }
+
+    /// Draw a sprite with its colors moved towards `tint` by the tint's alpha and its opacity
+    /// multiplied by `alpha`, from 0 (invisible) to 1. A fully opaque white tint flashes the
+    /// sprite white, and an alpha below 1 fades it out.
+    pub fn draw_sprite_tinted(&mut self, x: f32, y: f32, sprite: &Sprite, tint: Color, alpha: f32) {
+        let alpha = alpha.clamp(0.0, 1.0);
+        let opaque = sprite.is_opaque() && alpha == 1.0;
+        let amount = tint.a() as f32 / 255.0;
+        let mix = move |channel: u8, tint: u8| {
+            (channel as f32 + (tint as f32 - channel as f32) * amount).round() as u8
+        };
+        let shade = move |color: Color| {
+            Color::rgba(
+                mix(color.r(), tint.r()),
+                mix(color.g(), tint.g()),
+                mix(color.b(), tint.b()),
+                (color.a() as f32 * alpha).round() as u8,
+            )
+        };
+
+        if self.through_camera(|renderer, camera, to_screen| {
+            let position = to_screen(x, y);
+            if camera.is_translation() {
+                renderer.draw_sprite_tinted(position.x, position.y, sprite, tint, alpha);
+            } else {
+                renderer.draw_sprite_in_view(
+                    camera,
+                    position,
+                    sprite,
+                    Flip::NONE,
+                    Vec2::new(1.0, 1.0),
+                    opaque,
+                    shade,
+                );
+            }
+        }) {
+            return;
+        }
+
+        let offset = sprite.trim_offset(self.origin, Flip::NONE);
+        let (x, y) = (x + offset.x, y + offset.y);
+
+        for sprite_y in 0..sprite.height() as usize {
+            for sprite_x in 0..sprite.width() as usize {
+                let x = x + sprite_x as f32;
+                let y = y + self.image_row_offset(sprite.height() as usize, sprite_y);
+
+                let color = shade(sprite_color(sprite, sprite_x, sprite_y));
+
+                self.draw_sprite_pixel(x, y, color, opaque);
+            }
+        }
+    }
+
+    /// Run a custom draw command against the framebuffer. The current camera is passed on for the
+    /// command to place world positions with, and `clip`, in virtual pixels, limits where it can
+    /// draw.
+    pub fn draw_custom(&mut self, command: &dyn DrawCommand, clip: Option<Rect>) {
+        let (width, height) = (self.width as usize, self.height as usize);
+        // Commands write to the buffer directly, so count all of it as drawn.
+        self.mark_all_dirty();
+        let mut context = DrawContext::new(
+            &mut self.buffer,
+            width,
+            height,
+            self.pixel_width,
+            self.pixel_height,
+            self.origin,
+            self.camera,
+            clip,
+            self.interlace,
+            self.field,
+            self.active_blend_mode,
+        );
+        command.draw(&mut context);
+    }
+
+    /// Draw a sprite scaled by (scale_x, scale_y) using nearest neighbour sampling.
+    /// Only the part of the scaled sprite that is on screen is visited, so large scale factors
+    /// stay cheap. Shrunk sprites sample the nearest of their mipmaps, if generated.
+    pub fn draw_sprite_scaled(
+        &mut self,
+        x: f32,
+        y: f32,
+        sprite: &Sprite,
+        scale_x: f32,
+        scale_y: f32,
+    ) {
+        if scale_x <= 0.0 || scale_y <= 0.0 {
+            return;
+        }
+
+        if self.through_camera(|renderer, camera, to_screen| {
+            let position = to_screen(x, y);
+            if camera.is_translation() {
+                renderer.draw_sprite_scaled(position.x, position.y, sprite, scale_x, scale_y);
+            } else {
+                let opaque = sprite.is_opaque();
+                let scale = Vec2::new(scale_x, scale_y);
+                renderer.draw_sprite_in_view(
+                    camera,
+                    position,
+                    sprite,
+                    Flip::NONE,
+                    scale,
+                    opaque,
+                    |color| color,
+                );
+            }
+        }) {
+            return;
+        }
+
+        let offset = sprite.trim_offset(self.origin, Flip::NONE);
+        let (x, y) = (x + offset.x * scale_x, y + offset.y * scale_y);
+
+        let level = sprite.mipmap_for_scale(scale_x.max(scale_y));
+        if !std::ptr::eq(level, sprite) {
+            let scale_x = scale_x * sprite.width() as f32 / level.width() as f32;
+            let scale_y = scale_y * sprite.height() as f32 / level.height() as f32;
+            return self.draw_sprite_scaled(x, y, level, scale_x, scale_y);
+        }
+
+        let opaque = sprite.is_opaque();
+        let whole = scale_x.fract() == 0.0 && scale_y.fract() == 0.0;
+        let (sprite_width, sprite_height) = (sprite.width() as usize, sprite.height() as usize);
+        let scaled_width = (sprite_width as f32 * scale_x).round() as usize;
+        let scaled_height = (sprite_height as f32 * scale_y).round() as usize;
+        let (columns, rows) = self.visible_area(x, y, scaled_width, scaled_height);
+
+        for scaled_y in rows {
+            let sprite_y = if whole {
+                scaled_y / scale_y as usize
+            } else {
+                ((scaled_y as f32 / scale_y) as usize).min(sprite_height - 1)
+            };
+            let y = y + self.image_row_offset(scaled_height, scaled_y);
+
+            for scaled_x in columns.clone() {
+                let sprite_x = if whole {
+                    scaled_x / scale_x as usize
+                } else {
+                    ((scaled_x as f32 / scale_x) as usize).min(sprite_width - 1)
+                };
+                let color = sprite_color(sprite, sprite_x, sprite_y);
+
+                self.draw_sprite_pixel(x + scaled_x as f32, y, color, opaque);
+            }
+        }
+    }
+
+    /// Draw a sprite rotated counter-clockwise by `rotation` radians and scaled by
+    /// (scale_x, scale_y), using nearest neighbour sampling. `origin` is the point in the sprite,
+    /// in sprite pixels from the corner `draw_sprite` anchors, that is placed at (x, y) and
+    /// rotated and scaled about; for trimmed sprites it is measured in the untrimmed image.
+    /// Negative scales mirror the sprite.
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_sprite_ex(
+        &mut self,
+        x: f32,
+        y: f32,
+        sprite: &Sprite,
+        rotation: f32,
+        scale_x: f32,
+        scale_y: f32,
+        origin: Vec2,
+    ) {
+        if self.through_camera(|renderer, camera, to_screen| {
+            let position = to_screen(x, y);
+            renderer.draw_sprite_ex(
+                position.x,
+                position.y,
+                sprite,
+                rotation + renderer.view_rotation(camera),
+                scale_x * camera.zoom(),
+                scale_y * camera.zoom(),
+                origin,
+            );
+        }) {
+            return;
+        }
+
+        let opaque = sprite.is_opaque();
+        self.draw_sprite_mapped(
+            x,
+            y,
+            sprite,
+            rotation,
+            Vec2::new(scale_x, scale_y),
+            origin,
+            opaque,
+            |color| color,
+        );
+    }
+
+    /// Draw a sprite through a camera that zooms or rotates, mirrored in place by `flip` and
+    /// with its colors passed through `map`.
+    #[allow(clippy::too_many_arguments)]
+    fn draw_sprite_in_view(
+        &mut self,
+        camera: &Camera2D,
+        position: Vec2,
+        sprite: &Sprite,
+        flip: Flip,
+        scale: Vec2,
+        opaque: bool,
+        map: impl Fn(Color) -> Color,
+    ) {
+        // Mirroring about the far edge of the untrimmed image keeps the sprite in place.
+        let (width, height) = sprite.source_size();
+        let (horizontal, vertical) = (
+            flip.contains(Flip::HORIZONTAL),
+            flip.contains(Flip::VERTICAL),
+        );
+        let origin = Vec2::new(
+            if horizontal { width as f32 } else { 0.0 },
+            if vertical { height as f32 } else { 0.0 },
+        );
+        let mirror = |mirrored: bool, scale: f32| if mirrored { -scale } else { scale };
+        let scale = Vec2::new(
+            mirror(horizontal, scale.x * camera.zoom()),
+            mirror(vertical, scale.y * camera.zoom()),
+        );
+
+        let rotation = self.view_rotation(camera);
+        self.draw_sprite_mapped(
+            position.x, position.y, sprite, rotation, scale, origin, opaque, map,
+        );
+    }
+
+    /// The body of `draw_sprite_ex`, with each color passed through `map` before it's drawn.
+    #[allow(clippy::too_many_arguments)]
+    fn draw_sprite_mapped(
+        &mut self,
+        x: f32,
+        y: f32,
+        sprite: &Sprite,
+        rotation: f32,
+        scale: Vec2,
+        origin: Vec2,
+        opaque: bool,
+        map: impl Fn(Color) -> Color,
+    ) {
+        let (scale_x, scale_y) = (scale.x, scale.y);
+        if scale_x.abs() < f32::EPSILON || scale_y.abs() < f32::EPSILON {
+            return;
+        }
+
+        let (width, height) = (sprite.width() as f32, sprite.height() as f32);
+        let origin = origin - sprite.trim_offset(self.origin, Flip::NONE);
+        // Counter-clockwise on screen is clockwise in y-down coordinates.
+        let rotation = match self.origin {
+            Origin::BottomLeft => rotation,
+            Origin::TopLeft => -rotation,
+        };
+        let (sin, cos) = rotation.sin_cos();
+        let anchor = Vec2::new(x, y);
+
+        let (min, max) = self.sprite_bounds(x, y, sprite, rotation, scale, origin);
+
+        // Only visit the part of the bounds that is on screen.
+        let visible_width = (self.width / self.pixel_width as f32).ceil();
+        let visible_height = (self.height / self.pixel_height as f32).ceil() + 1.0;
+        let columns = min.x.floor().max(0.0) as i64..max.x.ceil().min(visible_width) as i64 + 1;
+        let rows = min.y.floor().max(0.0) as i64..max.y.ceil().min(visible_height) as i64 + 1;
+
+        for row in rows {
+            // A bottom left row covers the pixel below it.
+            let center_y = match self.origin {
+                Origin::BottomLeft => row as f32 - 0.5,
+                Origin::TopLeft => row as f32 + 0.5,
+            };
+
+            for column in columns.clone() {
+                let d = Vec2::new(column as f32 + 0.5, center_y) - anchor;
+                let a = origin.x + (d.x * cos + d.y * sin) / scale_x;
+                let b = origin.y + (-d.x * sin + d.y * cos) / scale_y;
+                if a < 0.0 || a >= width || b < 0.0 || b >= height {
+                    continue;
+                }
+
+                let sprite_x = a as usize;
+                let sprite_y = match self.origin {
+                    Origin::BottomLeft => sprite.height() as usize - 1 - b as usize,
+                    Origin::TopLeft => b as usize,
+                };
+                let color = map(sprite_color(sprite, sprite_x, sprite_y));
+
+                self.draw_sprite_pixel(column as f32, row as f32, color, opaque);
+            }
+        }
+    }
+
+    /// The columns and rows of a `width` by `height` image drawn at (x, y) that land on screen.
+    fn visible_area(
+        &self,
+        x: f32,
+        y: f32,
+        width: usize,
+        height: usize,
+    ) -> (Range<usize>, Range<usize>) {
+        let visible_width =
+            (self.width as i64 + self.pixel_width as i64 - 1) / self.pixel_width as i64;
+        let visible_height =
+            (self.height as i64 + self.pixel_height as i64 - 1) / self.pixel_height as i64;
+        let (x, y) = (x.floor() as i64, y.floor() as i64);
+        let (width, height) = (width as i64, height as i64);
+
+        let (first_row, end_row) = match self.origin {
+            Origin::BottomLeft => (y + height - visible_height, y + height + 1),
+            Origin::TopLeft => (-y, visible_height - y),
+        };
+        let clamp = |start: i64, end: i64, len: i64| {
+            let (start, end) = (start.clamp(0, len), end.clamp(0, len));
+            start as usize..end.max(start) as usize
+        };
+
+        (
+            clamp(-x, visible_width - x, width),
+            clamp(first_row, end_row, height),
+        )
+    }
+
+    /// Whether any of a `width` x `height` sprite drawn at (x, y) would be on screen.
+    /// The corners of the box around a sprite drawn by `draw_sprite_mapped`, with `rotation`
+    /// already in framebuffer coordinates and `origin` already trimmed.
+    fn sprite_bounds(
+        &self,
+        x: f32,
+        y: f32,
+        sprite: &Sprite,
+        rotation: f32,
+        scale: Vec2,
+        origin: Vec2,
+    ) -> (Vec2, Vec2) {
+        let (width, height) = (sprite.width() as f32, sprite.height() as f32);
+        let (sin, cos) = rotation.sin_cos();
+        let anchor = Vec2::new(x, y);
+
+        [(0.0, 0.0), (width, 0.0), (0.0, height), (width, height)]
+            .iter()
+            .map(|&(a, b)| {
+                let offset = Vec2::new((a - origin.x) * scale.x, (b - origin.y) * scale.y);
+                anchor
+                    + Vec2::new(
+                        offset.x * cos - offset.y * sin,
+                        offset.x * sin + offset.y * cos,
+                    )
+            })
+            .fold(
+                (Vec2::new(f32::MAX, f32::MAX), Vec2::new(f32::MIN, f32::MIN)),
+                |(min, max), corner| {
+                    (
+                        Vec2::new(min.x.min(corner.x), min.y.min(corner.y)),
+                        Vec2::new(max.x.max(corner.x), max.y.max(corner.y)),
+                    )
+                },
+            )
+    }
+
+    /// The bounds `draw_sprite_ex` draws a sprite in, as the corner nearest the origin and a size.
+    pub(crate) fn sprite_ex_bounds(
+        &self,
+        x: f32,
+        y: f32,
+        sprite: &Sprite,
+        rotation: f32,
+        scale: Vec2,
+        origin: Vec2,
+    ) -> Rect {
+        let origin = origin - sprite.trim_offset(self.origin, Flip::NONE);
+        let rotation = match self.origin {
+            Origin::BottomLeft => rotation,
+            Origin::TopLeft => -rotation,
+        };
+        let (min, max) = self.sprite_bounds(x, y, sprite, rotation, scale, origin);
+        Rect::new(min.x, min.y, max.x - min.x, max.y - min.y)
+    }
+
+    /// Whether any of `area`, in the world, is on screen through the current camera.
+    pub(crate) fn is_area_visible(&self, area: Rect) -> bool {
+        if self.camera.is_none() {
+            return self.is_screen_area_visible(area);
+        }
+
+        let corners = [
+            Vec2::new(area.x, area.y),
+            Vec2::new(area.x + area.width, area.y),
+            Vec2::new(area.x, area.y + area.height),
+            Vec2::new(area.x + area.width, area.y + area.height),
+        ]
+        .map(|corner| self.world_to_screen(corner));
+        let min = corners.iter().fold(corners[0], |min, corner| {
+            Vec2::new(min.x.min(corner.x), min.y.min(corner.y))
+        });
+        let max = corners.iter().fold(corners[0], |max, corner| {
+            Vec2::new(max.x.max(corner.x), max.y.max(corner.y))
+        });
+        self.is_screen_area_visible(Rect::new(min.x, min.y, max.x - min.x, max.y - min.y))
+    }
+
+    /// Whether any of `area`, which may be fractional, is on screen once widened to whole pixels.
+    pub(crate) fn is_screen_area_visible(&self, area: Rect) -> bool {
+        let (x, y) = (area.x.floor(), area.y.floor());
+        let width = (area.x + area.width.abs() - x).ceil() as usize;
+        let height = (area.y + area.height.abs() - y).ceil() as usize;
+        self.is_sprite_visible(x, y, width, height)
+    }
+
+    pub(crate) fn is_sprite_visible(&self, x: f32, y: f32, width: usize, height: usize) -> bool {
+        let (columns, rows) = self.visible_area(x, y, width, height);
+        !columns.is_empty() && !rows.is_empty()
+    }
+
+    /// Write a single sprite pixel at virtual pixel (x, y), expanded to the pixel size.
+    fn draw_sprite_pixel(&mut self, x: f32, y: f32, color: Color, opaque: bool) {
+        if !opaque && self.active_blend_mode.skips(color.a()) {
+            return;
+        }
+
+        let (buffer_width, buffer_height) = (self.width as i64, self.height as i64);
+        let window_x = x.floor() as i64 * self.pixel_width as i64;
+        let window_y = y.floor() as i64 * self.pixel_height as i64;
+
+        for pixel_y in 0..self.pixel_height as i64 {
+            let buffer_y = match self.origin {
+                Origin::BottomLeft => buffer_height - (window_y + pixel_y),
+                Origin::TopLeft => window_y + pixel_y,
+            };
+            if buffer_y < 0 || buffer_y >= buffer_height {
+                continue;
+            }
+
+            for pixel_x in 0..self.pixel_width as i64 {
+                let buffer_x = window_x + pixel_x;
+                if buffer_x < 0 || buffer_x >= buffer_width {
+                    continue;
+                }
+
+                if !self
+                    .interlace
+                    .covers(self.field, buffer_x as usize, buffer_y as usize)
+                {
+                    continue;
+                }
+
+                let buffer_idx = (buffer_y * buffer_width + buffer_x) as usize;
+                self.mark_dirty(
+                    buffer_x as usize..buffer_x as usize + 1,
+                    buffer_y as usize..buffer_y as usize + 1,
+                );
+                if self
+                    .blend_mode
+                    .overwrites(if opaque { 255 } else { color.a() })
+                {
+                    self.buffer.data[buffer_idx] = color.into();
+                } else {
+                    let dst = self.buffer.data[buffer_idx].into();
+                    self.buffer.data[buffer_idx] = self.active_blend_mode.blend(color, dst).into();
+                }
+            }
+        }
+    }
+
+    /// Fill `rect` by repeating the sprite, with `offset` (in sprite pixels) scrolling the pattern.
+    /// Each row of the sprite is expanded once and then copied into every framebuffer row it covers;
+    /// rows containing translucent pixels are blended instead of copied. Any trim is ignored.
+    pub fn draw_sprite_tiled(&mut self, rect: Rect, sprite: &Sprite, offset: Vec2) {
+        let (sprite_width, sprite_height) = (sprite.width() as i64, sprite.height() as i64);
+        if sprite_width == 0 || sprite_height == 0 || rect.width <= 0.0 || rect.height <= 0.0 {
+            return;
+        }
+
+        let (pixel_width, pixel_height) = (self.pixel_width as i64, self.pixel_height as i64);
+        let (buffer_width, buffer_height) = (self.width as i64, self.height as i64);
+
+        let (rect_x, rect_y) = (rect.x.floor() as i64, rect.y.floor() as i64);
+        let (rect_width, rect_height) = (rect.width as i64, rect.height as i64);
+        let offset_x = (offset.x.floor() as i64).rem_euclid(sprite_width);
+        let offset_y = (offset.y.floor() as i64).rem_euclid(sprite_height);
+
+        let window_x0 = (rect_x * pixel_width).max(0);
+        let window_x1 = ((rect_x + rect_width) * pixel_width).min(buffer_width);
+        if window_x0 >= window_x1 {
+            return;
+        }
+
+        let mut row = vec![0_u32; (window_x1 - window_x0) as usize];
+        for tile_y in 0..rect_height {
+            let sprite_y = ((tile_y + offset_y) % sprite_height) as usize;
+
+            let mut row_is_opaque = true;
+            for (i, pixel) in row.iter_mut().enumerate() {
+                let tile_x = (window_x0 + i as i64) / pixel_width - rect_x;
+                let sprite_x = ((tile_x + offset_x) % sprite_width) as usize;
+                let color = sprite_color(sprite, sprite_x, sprite_y);
+
+                row_is_opaque &= color.a() == 255;
+                *pixel = color.into();
+            }
+
+            for sub_y in 0..pixel_height {
+                let buffer_y = match self.origin {
+                    Origin::BottomLeft => {
+                        buffer_height - ((rect_y + rect_height - tile_y) * pixel_height + sub_y)
+                    }
+                    Origin::TopLeft => (rect_y + tile_y) * pixel_height + sub_y,
+                };
+                if buffer_y < 0 || buffer_y >= buffer_height {
+                    continue;
+                }
+
+                let start = (buffer_y * buffer_width + window_x0) as usize;
+                self.mark_dirty(
+                    window_x0 as usize..window_x0 as usize + row.len(),
+                    buffer_y as usize..buffer_y as usize + 1,
+                );
+                let (interlace, field, blend_mode) =
+                    (self.interlace, self.field, self.active_blend_mode);
+                let dst = &mut self.buffer.data[start..start + row.len()];
+                if interlace == Interlace::Checkerboard {
+                    let first = (field + window_x0 as usize + buffer_y as usize) % 2;
+                    for i in (first..row.len()).step_by(2) {
+                        dst[i] = blend_mode.blend(row[i].into(), dst[i].into()).into();
+                    }
+                } else if !interlace.covers(field, 0, buffer_y as usize) {
+                    continue;
+                } else if row_is_opaque && blend_mode.overwrites(255) {
+                    dst.copy_from_slice(&row);
+                } else {
+                    for (dst, &src) in dst.iter_mut().zip(row.iter()) {
+                        *dst = blend_mode.blend(src.into(), (*dst).into()).into();
+                    }
+                }
             }
         }
     }
@@ -454,6 +2138,8 @@ impl Renderer {
         height: f32,
         color: Color,
     ) {
+        let (x, y) = (self.window_to_render(x), self.window_to_render(y));
+        let (width, height) = (self.window_to_render(width), self.window_to_render(height));
         let x1 = x + width;
         let y1 = y + height;
 
@@ -478,3 +2164,872 @@ impl Renderer {
         }
     }
 }
+
+/// Scale, rotate (radians) and then translate a model's vertices.
+fn transform_model(position: Point, rotation: f32, scale: f32, model: &[Point]) -> Vec<Point> {
+    model
+        .iter()
+        .map(|vertex| {
+            let (x, y) = (vertex.x(), vertex.y());
+
+            let (x, y) = (x * scale, y * scale); // Scale.
+
+            // y-axis is up, but we draw as if it is down, which means the rotation is in the wrong direction, so flip it.
+            let rotation = -rotation;
+            let (x, y) = (
+                x * rotation.cos() - y * rotation.sin(),
+                y * rotation.cos() + x * rotation.sin(),
+            ); // Rotate.
+
+            let (x, y) = (x + position.x(), y + position.y()); // Translate
+
+            (x, y).into()
+        })
+        .collect()
+}
+
+/// `color` with its alpha scaled by a glyph's `coverage`.
+fn with_coverage(color: Color, coverage: u8) -> Color {
+    let alpha = (color.a() as u32 * coverage as u32 / 255) as u8;
+    Color::rgba(color.r(), color.g(), color.b(), alpha)
+}
+
+/// Grow glyph pixels by `radius`, each pixel taking the highest coverage within reach.
+fn dilate(pixels: &[(i64, i64, u8)], radius: f32) -> Vec<(i64, i64, u8)> {
+    let reach = radius.ceil() as i64;
+    if pixels.is_empty() || reach <= 0 {
+        return Vec::new();
+    }
+
+    let min_x = pixels.iter().map(|p| p.0).min().unwrap_or(0) - reach;
+    let max_x = pixels.iter().map(|p| p.0).max().unwrap_or(0) + reach;
+    let min_y = pixels.iter().map(|p| p.1).min().unwrap_or(0) - reach;
+    let max_y = pixels.iter().map(|p| p.1).max().unwrap_or(0) + reach;
+    let width = (max_x - min_x + 1) as usize;
+    let height = (max_y - min_y + 1) as usize;
+
+    let mut coverage = vec![0_u8; width * height];
+    for &(x, y, c) in pixels {
+        let i = (y - min_y) as usize * width + (x - min_x) as usize;
+        coverage[i] = coverage[i].max(c);
+    }
+
+    let mut dilated = Vec::new();
+    for y in min_y..=max_y {
+        for x in min_x..=max_x {
+            let mut max = 0;
+            for dy in -reach..=reach {
+                for dx in -reach..=reach {
+                    let (nx, ny) = (x + dx, y + dy);
+                    if (dx * dx + dy * dy) as f32 > radius * radius
+                        || nx < min_x
+                        || nx > max_x
+                        || ny < min_y
+                        || ny > max_y
+                    {
+                        continue;
+                    }
+                    max = max.max(coverage[(ny - min_y) as usize * width + (nx - min_x) as usize]);
+                }
+            }
+            if max > 0 {
+                dilated.push((x, y, max));
+            }
+        }
+    }
+
+    dilated
+}
+
+fn sprite_color(sprite: &Sprite, x: usize, y: usize) -> Color {
+    let offset = (y * sprite.width() as usize + x) * 4;
+    let sprite_data = sprite.data();
+    let r = sprite_data[offset];
+    let g = sprite_data[offset + 1];
+    let b = sprite_data[offset + 2];
+    let a = sprite_data[offset + 3];
+
+    Color::rgba(r, g, b, a)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color;
+
+    fn renderer(origin: Origin) -> Renderer {
+        let mut renderer = Renderer::new(4.0, 4.0, 1, 1, FrameBuffer::new(4, 4));
+        renderer.set_origin(origin);
+        renderer
+    }
+
+    fn row_of(renderer: &Renderer, color: Color) -> Option<usize> {
+        let color: u32 = color.into();
+        renderer
+            .buffer()
+            .data
+            .iter()
+            .position(|&pixel| pixel == color)
+            .map(|i| i / 4)
+    }
+
+    #[test]
+    fn bottom_left_origin_draws_low_y_near_the_bottom() {
+        let mut renderer = renderer(Origin::BottomLeft);
+
+        renderer.draw(1.0, 1.0, color::css::RED);
+
+        assert_eq!(Some(3), row_of(&renderer, color::css::RED));
+    }
+
+    #[test]
+    fn top_left_origin_draws_low_y_near_the_top() {
+        let mut renderer = renderer(Origin::TopLeft);
+
+        renderer.draw(1.0, 1.0, color::css::RED);
+
+        assert_eq!(Some(1), row_of(&renderer, color::css::RED));
+    }
+
+    #[test]
+    fn sprites_keep_their_top_row_at_the_top_in_both_origins() {
+        let red: u32 = color::css::RED.into();
+        let [_, r, g, b] = red.to_be_bytes();
+        let mut data = vec![r, g, b, 255];
+        data.extend_from_slice(&[0, 0, 255, 255]);
+        let sprite = Sprite::from_raw_rgba(1, 2, data);
+
+        let mut bottom_left = renderer(Origin::BottomLeft);
+        bottom_left.draw_sprite(0.0, 0.0, &sprite);
+        let mut top_left = renderer(Origin::TopLeft);
+        top_left.draw_sprite(0.0, 0.0, &sprite);
+
+        assert_eq!(Some(2), row_of(&bottom_left, color::css::RED));
+        assert_eq!(Some(0), row_of(&top_left, color::css::RED));
+    }
+
+    #[test]
+    fn cameras_move_world_draws_to_the_screen() {
+        let red = color::css::RED;
+        let sprite = one_pixel_sprite(red);
+        // The view is centered on (10, 10), so (9, 8) lands at (1, 0) on the 4 x 4 screen.
+        let camera = Camera2D::new(Vec2::new(10.0, 10.0));
+
+        for origin in [Origin::BottomLeft, Origin::TopLeft] {
+            let mut expected = renderer(origin);
+            expected.draw_line(1.0, 0.0, 3.0, 2.0, red);
+            expected.draw_sprite(0.0, 1.0, &sprite);
+            let mut actual = renderer(origin);
+            actual.set_camera(&camera);
+            actual.draw_line(9.0, 8.0, 11.0, 10.0, red);
+            actual.draw_sprite(8.0, 9.0, &sprite);
+
+            assert_eq!(expected.buffer().data, actual.buffer().data);
+        }
+    }
+
+    #[test]
+    fn zoomed_cameras_magnify_sprites_and_shapes() {
+        let red = color::css::RED;
+        let camera = Camera2D::new(Vec2::new(1.0, 1.0)).with_zoom(2.0);
+        let mut renderer = renderer(Origin::TopLeft);
+        renderer.set_camera(&camera);
+
+        renderer.draw_sprite(0.0, 0.0, &one_pixel_sprite(red));
+        let sprite: Vec<u32> = renderer.buffer().data.clone();
+        renderer.clear(color::css::BLACK);
+        renderer.draw_filled_rectangle(0.0, 0.0, 0.5, 0.5, red);
+
+        let red: u32 = red.into();
+        let covered =
+            |data: &[u32]| -> Vec<usize> { (0..data.len()).filter(|&i| data[i] == red).collect() };
+        assert_eq!(vec![0, 1, 4, 5], covered(&sprite));
+        assert_eq!(vec![0, 1, 4, 5], covered(&renderer.buffer().data));
+    }
+
+    #[test]
+    fn trimmed_sprites_draw_where_the_untrimmed_image_would() {
+        let mut data = vec![0; 3 * 3 * 4];
+        data[4..8].copy_from_slice(&[255, 0, 0, 255]);
+        data[20..24].copy_from_slice(&[0, 0, 255, 255]);
+        let sprite = Sprite::from_raw_rgba(3, 3, data);
+        let trimmed = sprite.trimmed();
+        assert_eq!((2, 2), (trimmed.width(), trimmed.height()));
+
+        for origin in [Origin::BottomLeft, Origin::TopLeft] {
+            for flip in [Flip::NONE, Flip::HORIZONTAL, Flip::VERTICAL] {
+                let mut expected = renderer(origin);
+                expected.draw_sprite_flipped(1.0, 1.0, &sprite, flip);
+                let mut actual = renderer(origin);
+                actual.draw_sprite_flipped(1.0, 1.0, &trimmed, flip);
+
+                assert_eq!(expected.buffer().data, actual.buffer().data);
+            }
+
+            let mut expected = renderer(origin);
+            expected.draw_sprite_ex(1.0, 1.0, &sprite, 0.0, 1.0, 1.0, Vec2::new(1.0, 1.0));
+            let mut actual = renderer(origin);
+            actual.draw_sprite_ex(1.0, 1.0, &trimmed, 0.0, 1.0, 1.0, Vec2::new(1.0, 1.0));
+
+            assert_eq!(expected.buffer().data, actual.buffer().data);
+        }
+    }
+
+    #[test]
+    fn half_render_scale_presents_the_same_image_for_whole_pixels() {
+        let draw = |renderer: &mut Renderer| {
+            renderer.clear(color::css::BLACK);
+            renderer.draw(1.0, 2.0, color::css::RED);
+            renderer.draw_filled_rectangle(0.0, 0.0, 1.0, 1.0, color::css::BLUE);
+            renderer.draw_wireframe_rectangle(1.0, 1.0, 2.0, 2.0, color::css::GREEN);
+        };
+        let mut full = Renderer::new(8.0, 8.0, 2, 2, FrameBuffer::new(8, 8));
+        let mut half = Renderer::new(8.0, 8.0, 2, 2, FrameBuffer::new(8, 8));
+        full.set_origin(Origin::TopLeft);
+        half.set_origin(Origin::TopLeft);
+
+        let scale = half.set_render_scale(RenderScale::Half, UpscaleFilter::Nearest);
+        draw(&mut full);
+        draw(&mut half);
+
+        assert_eq!(RenderScale::Half, scale);
+        assert_eq!(16, half.buffer().data.len());
+        assert_eq!(full.present().data, half.present().data);
+    }
+
+    #[test]
+    fn render_scale_is_limited_by_pixel_size() {
+        let mut renderer = Renderer::new(6.0, 6.0, 2, 2, FrameBuffer::new(6, 6));
+
+        let scale = renderer.set_render_scale(RenderScale::Quarter, UpscaleFilter::Scale2x);
+
+        assert_eq!(RenderScale::Half, scale);
+    }
+
+    #[test]
+    fn string_width_adds_up_each_character() {
+        let renderer = renderer(Origin::TopLeft);
+        let (a, b) = (
+            renderer.string_width("a", 12.0),
+            renderer.string_width("b", 12.0),
+        );
+
+        assert!(a > 0.0);
+        assert_eq!(a + b, renderer.string_width("ab", 12.0));
+    }
+
+    #[test]
+    fn clear_reuses_the_buffer() {
+        let mut renderer = renderer(Origin::TopLeft);
+        let data = renderer.buffer().data.as_ptr();
+
+        renderer.clear(color::css::RED);
+
+        assert_eq!(data, renderer.buffer().data.as_ptr());
+        let red: u32 = color::css::RED.into();
+        assert!(renderer.buffer().data.iter().all(|&pixel| pixel == red));
+    }
+
+    #[test]
+    fn scanlines_fill_alternate_rows_each_frame() {
+        let mut renderer = renderer(Origin::TopLeft);
+        renderer.set_interlace(Interlace::Scanlines);
+        let red: u32 = color::css::RED.into();
+
+        renderer.clear(color::css::RED);
+        let first = renderer.present().data.clone();
+        renderer.clear(color::css::RED);
+        let second = renderer.present().data.clone();
+
+        assert_eq!(red, first[0]);
+        assert_ne!(red, first[4]);
+        assert!(second.iter().all(|&pixel| pixel == red));
+    }
+
+    #[test]
+    fn resize_changes_resolution_and_keeps_render_scale() {
+        let mut renderer = Renderer::new(8.0, 8.0, 2, 2, FrameBuffer::new(8, 8));
+        renderer.set_render_scale(RenderScale::Half, UpscaleFilter::Nearest);
+
+        renderer.resize(12.0, 6.0, 2, 2);
+        renderer.draw(5.0, 2.0, color::css::RED);
+
+        assert_eq!(RenderScale::Half, renderer.render_scale());
+        assert_eq!(18, renderer.buffer().data.len());
+        assert_eq!(72, renderer.present().data.len());
+    }
+
+    #[test]
+    fn palette_recolors_sprite_while_drawing() {
+        let mut renderer = renderer(Origin::TopLeft);
+        let sprite = Sprite::from_raw_rgba(1, 1, vec![255, 0, 0, 255]);
+        let palette = Palette::new().with_swap(color::css::RED, color::css::BLUE);
+
+        renderer.draw_sprite_with_palette(0.0, 0.0, &sprite, &palette);
+
+        assert_eq!(Some(0), row_of(&renderer, color::css::BLUE));
+        assert_eq!(None, row_of(&renderer, color::css::RED));
+    }
+
+    #[test]
+    fn tint_moves_sprite_colors_towards_the_tint() {
+        let mut renderer = renderer(Origin::TopLeft);
+        let sprite = Sprite::from_raw_rgba(1, 1, vec![255, 0, 0, 255]);
+
+        renderer.draw_sprite_tinted(0.0, 0.0, &sprite, Color::rgba(0, 0, 255, 255), 1.0);
+        assert_eq!(Some(0), row_of(&renderer, color::css::BLUE));
+
+        renderer.draw_sprite_tinted(0.0, 1.0, &sprite, Color::rgba(0, 0, 255, 0), 1.0);
+        assert_eq!(Some(1), row_of(&renderer, color::css::RED));
+    }
+
+    #[test]
+    fn tinted_sprites_fade_with_alpha() {
+        let mut renderer = renderer(Origin::TopLeft);
+        renderer.clear(color::css::BLACK);
+        let sprite = Sprite::from_raw_rgba(1, 1, vec![255, 255, 255, 255]);
+
+        renderer.draw_sprite_tinted(0.0, 0.0, &sprite, Color::rgba(0, 0, 0, 0), 0.0);
+        assert_eq!(u32::from(color::css::BLACK), renderer.buffer().data[0]);
+
+        renderer.draw_sprite_tinted(0.0, 0.0, &sprite, Color::rgba(0, 0, 0, 0), 0.5);
+        let pixel: Color = renderer.buffer().data[0].into();
+        assert!((120..=136).contains(&pixel.r()), "{:?}", pixel);
+    }
+
+    #[test]
+    fn outline_surrounds_text() {
+        let count = |renderer: &Renderer, color: Color| {
+            let color: u32 = color.into();
+            renderer
+                .buffer()
+                .data
+                .iter()
+                .filter(|&&p| p == color)
+                .count()
+        };
+        let mut plain = Renderer::new(32.0, 32.0, 1, 1, FrameBuffer::new(32, 32));
+        let mut outlined = Renderer::new(32.0, 32.0, 1, 1, FrameBuffer::new(32, 32));
+        let style = TextStyle::default().with_outline(color::css::RED, 1.0);
+
+        plain.draw_string("I", 8.0, 8.0, color::css::WHITE, 16.0);
+        outlined.draw_styled_string("I", 8.0, 8.0, color::css::WHITE, 16.0, &style);
+
+        assert_eq!(0, count(&plain, color::css::RED));
+        assert!(count(&outlined, color::css::RED) > 0);
+    }
+
+    #[test]
+    fn strings_are_drawn_with_baked_glyphs() {
+        let mut renderer = Renderer::new(32.0, 32.0, 1, 1, FrameBuffer::new(32, 32));
+        let atlas = GlyphAtlas::load_str(
+            "[[glyphs]]\nsize = 16.0\ncharacter = \"I\"\nwidth = 1\nheight = 1\nxmin = 0\n\
+             ymin = 0\nadvance_width = 4.0\ndata = [255]\n",
+        )
+        .unwrap();
+        renderer.add_glyph_atlas(atlas);
+
+        renderer.draw_string("I", 8.0, 8.0, color::css::WHITE, 16.0);
+
+        let white: u32 = color::css::WHITE.into();
+        let drawn = renderer.buffer().data.iter().filter(|&&p| p == white);
+        assert_eq!(1, drawn.count());
+    }
+
+    #[test]
+    fn text_grid_draws_backgrounds_and_glyphs() {
+        let mut renderer = Renderer::new(16.0, 16.0, 1, 1, FrameBuffer::new(16, 16));
+        let mut grid = TextGrid::new(2, 1, 8, 16);
+        grid.set(0, 0, ' ', color::css::WHITE, color::css::BLUE);
+        grid.set(1, 0, '#', color::css::WHITE, color::css::RED);
+
+        renderer.draw_text_grid(0.0, 0.0, &grid);
+
+        let blue: u32 = color::css::BLUE.into();
+        let white: u32 = color::css::WHITE.into();
+        assert_eq!(blue, renderer.buffer().data[8 * 16]);
+        assert!(renderer.buffer().data.contains(&white));
+    }
+
+    #[test]
+    fn filled_polygon_covers_its_inside_only() {
+        let mut renderer = renderer(Origin::TopLeft);
+        let square = [
+            Point::new(1.0, 1.0),
+            Point::new(3.0, 1.0),
+            Point::new(3.0, 3.0),
+            Point::new(1.0, 3.0),
+        ];
+
+        renderer.draw_filled_polygon(&square, color::css::RED);
+
+        let red: u32 = color::css::RED.into();
+        let filled: Vec<usize> = (0..16)
+            .filter(|&i| renderer.buffer().data[i] == red)
+            .collect();
+        assert_eq!(vec![5, 6, 9, 10], filled);
+    }
+
+    #[test]
+    fn scaled_sprite_replicates_each_pixel_into_a_block() {
+        let mut renderer = renderer(Origin::TopLeft);
+        let (red, blue) = (color::css::RED, color::css::BLUE);
+        let data = [red, blue, blue, red]
+            .iter()
+            .flat_map(|c| [c.r(), c.g(), c.b(), c.a()])
+            .collect();
+        let sprite = Sprite::from_raw_rgba(2, 2, data);
+
+        renderer.draw_sprite_scaled(0.0, 0.0, &sprite, 2.0, 2.0);
+
+        let (red, blue): (u32, u32) = (red.into(), blue.into());
+        let expected = [
+            red, red, blue, blue, //
+            red, red, blue, blue, //
+            blue, blue, red, red, //
+            blue, blue, red, red,
+        ];
+        assert_eq!(&expected[..], &renderer.buffer().data[..]);
+    }
+
+    #[test]
+    fn images_are_converted_from_argb_to_rgba() {
+        let mut renderer = Renderer::new(1.0, 1.0, 1, 1, FrameBuffer::new(1, 1));
+        renderer.clear(Color::rgba(0x11, 0x22, 0x33, 0x80));
+
+        let image = renderer.to_image();
+
+        assert_eq!((1, 1), image.dimensions());
+        assert_eq!([0x11, 0x22, 0x33, 0x80], image.get_pixel(0, 0).0);
+    }
+
+    #[test]
+    fn blend_modes_combine_with_the_screen() {
+        let dst = Color::rgba(100, 100, 100, 255);
+        let src = Color::rgba(200, 50, 0, 128);
+        let blend = |mode: BlendMode| mode.blend(src, dst);
+
+        assert_eq!(src, blend(BlendMode::Replace));
+        assert_eq!(Color::linear_blend(src, dst), blend(BlendMode::Alpha));
+        assert_eq!(Color::blend_over(src, dst), blend(BlendMode::Over));
+        assert_eq!(
+            Color::blend_premultiplied(src, dst),
+            blend(BlendMode::Premultiplied)
+        );
+        assert_eq!(Color::rgba(200, 125, 100, 255), blend(BlendMode::Add));
+        assert_eq!(Color::rgba(89, 60, 50, 255), blend(BlendMode::Multiply));
+        assert_eq!(Color::rgba(0, 75, 100, 255), blend(BlendMode::Subtract));
+    }
+
+    #[test]
+    fn opaque_sprites_are_blended_outside_alpha_mode() {
+        let mut renderer = renderer(Origin::TopLeft);
+        renderer.clear(Color::rgba(100, 0, 0, 255));
+        renderer.set_blend_mode(BlendMode::Add);
+
+        renderer.draw_sprite(0.0, 0.0, &one_pixel_sprite(Color::rgba(100, 50, 0, 255)));
+
+        assert_eq!(
+            u32::from(Color::rgba(200, 50, 0, 255)),
+            renderer.buffer().data[0]
+        );
+    }
+
+    #[test]
+    fn shrunk_sprites_sample_their_mipmaps() {
+        let mut renderer = renderer(Origin::TopLeft);
+        let mut sprite = two_by_two_sprite();
+        sprite.generate_mipmaps();
+
+        renderer.draw_sprite_scaled(0.0, 0.0, &sprite, 0.5, 0.5);
+
+        let average: u32 = sprite.mipmap(1).unwrap().pixel(0, 0).unwrap().into();
+        assert_eq!(average, renderer.buffer().data[0]);
+        assert_eq!(0, renderer.buffer().data[1]);
+    }
+
+    #[test]
+    fn huge_scale_factors_only_visit_the_screen() {
+        for origin in [Origin::BottomLeft, Origin::TopLeft] {
+            let mut renderer = renderer(origin);
+            let red = color::css::RED;
+            let sprite = Sprite::from_raw_rgba(1, 1, vec![red.r(), red.g(), red.b(), red.a()]);
+
+            renderer.draw_sprite_scaled(-1.0e6, -1.0e6, &sprite, 1.0e7, 1.0e7);
+
+            let red: u32 = red.into();
+            assert!(renderer.buffer().data.iter().all(|&pixel| pixel == red));
+        }
+    }
+
+    fn two_by_two_sprite() -> Sprite {
+        let data = [
+            color::css::RED,
+            color::css::BLUE,
+            color::css::GREEN,
+            color::css::WHITE,
+        ]
+        .iter()
+        .flat_map(|c| [c.r(), c.g(), c.b(), c.a()])
+        .collect();
+        Sprite::from_raw_rgba(2, 2, data)
+    }
+
+    #[test]
+    fn sprites_are_clipped_to_the_buffer_at_the_pixel_size() {
+        let mut renderer = Renderer::new(6.0, 4.0, 2, 2, FrameBuffer::new(6, 4));
+        renderer.set_origin(Origin::TopLeft);
+
+        renderer.draw_sprite(-1.0, 0.0, &two_by_two_sprite());
+
+        let (blue, white): (u32, u32) = (color::css::BLUE.into(), color::css::WHITE.into());
+        let rows: Vec<&[u32]> = renderer.buffer().data.chunks(6).collect();
+        assert_eq!(&[blue, blue, 0, 0, 0, 0], rows[0]);
+        assert_eq!(&[blue, blue, 0, 0, 0, 0], rows[1]);
+        assert_eq!(&[white, white, 0, 0, 0, 0], rows[2]);
+        assert_eq!(&[white, white, 0, 0, 0, 0], rows[3]);
+    }
+
+    #[test]
+    fn flipped_sprites_are_mirrored_in_place() {
+        for origin in [Origin::BottomLeft, Origin::TopLeft] {
+            let (red, blue, green, white): (u32, u32, u32, u32) = (
+                color::css::RED.into(),
+                color::css::BLUE.into(),
+                color::css::GREEN.into(),
+                color::css::WHITE.into(),
+            );
+            // The sprite covers rows 1 and 2 of the buffer in both origins.
+            let draw = |flip: Flip| {
+                let mut renderer = renderer(origin);
+                renderer.draw_sprite_flipped(1.0, 1.0, &two_by_two_sprite(), flip);
+                let data = &renderer.buffer().data;
+                [data[5], data[6], data[9], data[10]]
+            };
+
+            assert_eq!([red, blue, green, white], draw(Flip::NONE));
+            assert_eq!([blue, red, white, green], draw(Flip::HORIZONTAL));
+            assert_eq!([green, white, red, blue], draw(Flip::VERTICAL));
+            assert_eq!(
+                [white, green, blue, red],
+                draw(Flip::HORIZONTAL | Flip::VERTICAL)
+            );
+        }
+    }
+
+    #[test]
+    fn sprite_region_draws_only_the_region() {
+        for origin in [Origin::BottomLeft, Origin::TopLeft] {
+            let mut region = renderer(origin);
+            region.draw_sprite_region(1.0, 1.0, &two_by_two_sprite(), 1, 0, 1, 2);
+
+            let right_column = [color::css::BLUE, color::css::WHITE]
+                .iter()
+                .flat_map(|c| [c.r(), c.g(), c.b(), c.a()])
+                .collect();
+            let mut plain = renderer(origin);
+            plain.draw_sprite(1.0, 1.0, &Sprite::from_raw_rgba(1, 2, right_column));
+
+            assert_eq!(plain.buffer().data, region.buffer().data, "{:?}", origin);
+        }
+    }
+
+    #[test]
+    fn sprite_region_is_clipped_to_the_sprite() {
+        let mut renderer = renderer(Origin::TopLeft);
+
+        renderer.draw_sprite_region(0.0, 0.0, &two_by_two_sprite(), 1, 1, 10, 10);
+        renderer.draw_sprite_region(2.0, 2.0, &two_by_two_sprite(), 5, 5, 1, 1);
+
+        let white: u32 = color::css::WHITE.into();
+        let filled: Vec<usize> = (0..16)
+            .filter(|&i| renderer.buffer().data[i] != 0)
+            .collect();
+        assert_eq!(vec![0], filled);
+        assert_eq!(white, renderer.buffer().data[0]);
+    }
+
+    #[test]
+    fn untransformed_sprite_ex_matches_draw_sprite() {
+        for origin in [Origin::BottomLeft, Origin::TopLeft] {
+            let sprite = two_by_two_sprite();
+            let mut plain = renderer(origin);
+            plain.draw_sprite(1.0, 1.0, &sprite);
+            let mut ex = renderer(origin);
+
+            ex.draw_sprite_ex(1.0, 1.0, &sprite, 0.0, 1.0, 1.0, Vec2::new(0.0, 0.0));
+
+            assert_eq!(plain.buffer().data, ex.buffer().data, "{:?}", origin);
+        }
+    }
+
+    #[test]
+    fn sprite_ex_rotates_counter_clockwise_about_its_origin() {
+        for origin in [Origin::BottomLeft, Origin::TopLeft] {
+            let mut renderer = renderer(origin);
+
+            renderer.draw_sprite_ex(
+                2.0,
+                2.0,
+                &two_by_two_sprite(),
+                std::f32::consts::FRAC_PI_2,
+                1.0,
+                1.0,
+                Vec2::new(1.0, 1.0),
+            );
+
+            // A quarter turn counter-clockwise moves the top left pixel to the bottom left. The
+            // sprite covers rows 1 and 2 of the buffer in both origins.
+            let (red, blue, green, white): (u32, u32, u32, u32) = (
+                color::css::RED.into(),
+                color::css::BLUE.into(),
+                color::css::GREEN.into(),
+                color::css::WHITE.into(),
+            );
+            let at = |row: usize, column: usize| renderer.buffer().data[(row + 1) * 4 + column + 1];
+            assert_eq!(
+                [blue, white, red, green],
+                [at(0, 0), at(0, 1), at(1, 0), at(1, 1)]
+            );
+        }
+    }
+
+    #[test]
+    fn sprite_ex_scales_about_its_origin() {
+        let mut renderer = renderer(Origin::TopLeft);
+
+        renderer.draw_sprite_ex(
+            2.0,
+            2.0,
+            &one_pixel_sprite(color::css::RED),
+            0.0,
+            2.0,
+            2.0,
+            Vec2::new(0.5, 0.5),
+        );
+
+        let red: u32 = color::css::RED.into();
+        let filled: Vec<usize> = (0..16)
+            .filter(|&i| renderer.buffer().data[i] == red)
+            .collect();
+        assert_eq!(vec![5, 6, 9, 10], filled);
+    }
+
+    fn one_pixel_sprite(color: Color) -> Sprite {
+        Sprite::from_raw_rgba(1, 1, vec![color.r(), color.g(), color.b(), color.a()])
+    }
+
+    #[test]
+    fn layers_stack_in_order_whatever_order_they_are_drawn_in() {
+        let mut renderer = renderer(Origin::TopLeft);
+        let (red, blue, black): (u32, u32, u32) = (
+            color::css::RED.into(),
+            color::css::BLUE.into(),
+            color::css::BLACK.into(),
+        );
+
+        renderer.set_layer(2);
+        renderer.draw_sprite(1.0, 1.0, &one_pixel_sprite(color::css::RED));
+        renderer.set_layer(1);
+        assert_eq!(BlendMode::Alpha, renderer.blend_mode());
+        renderer.draw_sprite(1.0, 1.0, &one_pixel_sprite(color::css::BLUE));
+        renderer.draw_sprite(2.0, 2.0, &one_pixel_sprite(color::css::BLUE));
+        renderer.set_layer(0);
+        renderer.clear(color::css::BLACK);
+
+        let frame = renderer.present().data.clone();
+        assert_eq!((red, blue, black), (frame[5], frame[10], frame[0]));
+
+        // Layers are emptied once presented.
+        renderer.clear(color::css::BLACK);
+        assert!(renderer.present().data.iter().all(|&pixel| pixel == black));
+    }
+
+    #[test]
+    fn compositing_only_marks_what_layers_drew() {
+        let mut renderer = renderer(Origin::TopLeft);
+        renderer.set_dirty_tracking(true);
+        renderer.present();
+
+        renderer.set_layer(3);
+        renderer.draw_sprite(2.0, 1.0, &one_pixel_sprite(color::css::RED));
+        renderer.set_layer(1);
+        renderer.composite_layers();
+
+        assert_eq!(Some(Rect::new(2.0, 1.0, 1.0, 1.0)), renderer.dirty_rect());
+        let red: u32 = color::css::RED.into();
+        assert_eq!(red, renderer.buffer().data[6]);
+    }
+
+    #[test]
+    fn interlaced_layers_keep_the_other_field() {
+        let mut renderer = renderer(Origin::TopLeft);
+        renderer.set_interlace(Interlace::Scanlines);
+        let red: u32 = color::css::RED.into();
+
+        for _ in 0..2 {
+            renderer.set_layer(1);
+            renderer.draw_filled_rectangle(0.0, 0.0, 1.0, 4.0, color::css::RED);
+            renderer.present();
+        }
+
+        let column: Vec<u32> = (0..4).map(|row| renderer.buffer().data[row * 4]).collect();
+        assert_eq!(vec![red; 4], column);
+    }
+
+    #[test]
+    fn dirty_tracking_bounds_what_was_drawn_since_present() {
+        let mut renderer = renderer(Origin::TopLeft);
+        assert!(renderer.is_dirty());
+
+        renderer.set_dirty_tracking(true);
+        renderer.present();
+        assert!(!renderer.is_dirty());
+
+        renderer.draw_sprite(1.0, 2.0, &one_pixel_sprite(color::css::RED));
+        renderer.draw_sprite(2.0, 1.0, &one_pixel_sprite(color::css::RED));
+        assert_eq!(Some(Rect::new(1.0, 1.0, 2.0, 2.0)), renderer.dirty_rect());
+
+        renderer.present();
+        assert_eq!(None, renderer.dirty_rect());
+    }
+
+    #[test]
+    fn translucent_sprite_pixels_blend_with_the_background() {
+        let mut renderer = renderer(Origin::TopLeft);
+        renderer.clear(color::css::BLUE);
+        let sprite = one_pixel_sprite(Color::rgba(255, 0, 0, 128));
+        assert!(!sprite.is_opaque());
+
+        renderer.draw_sprite(0.0, 0.0, &sprite);
+
+        let expected = Color::linear_blend(Color::rgba(255, 0, 0, 128), color::css::BLUE);
+        assert_eq!(u32::from(expected), renderer.buffer().data[0]);
+        assert_ne!(u32::from(color::css::BLUE), renderer.buffer().data[0]);
+    }
+
+    #[test]
+    fn fully_transparent_sprite_pixels_leave_the_background() {
+        let mut renderer = renderer(Origin::TopLeft);
+        renderer.clear(color::css::BLUE);
+
+        renderer.draw_sprite(0.0, 0.0, &one_pixel_sprite(Color::rgba(255, 0, 0, 0)));
+
+        assert_eq!(u32::from(color::css::BLUE), renderer.buffer().data[0]);
+    }
+
+    #[test]
+    fn opaque_sprites_overwrite_the_background() {
+        let mut renderer = renderer(Origin::TopLeft);
+        renderer.clear(color::css::BLUE);
+        let sprite = one_pixel_sprite(color::css::RED);
+        assert!(sprite.is_opaque());
+
+        renderer.draw_sprite(0.0, 0.0, &sprite);
+
+        assert_eq!(u32::from(color::css::RED), renderer.buffer().data[0]);
+        assert_eq!(u32::from(color::css::BLUE), renderer.buffer().data[1]);
+    }
+
+    fn interlaced_rows(draw: impl Fn(&mut Renderer)) -> Vec<bool> {
+        let mut renderer = renderer(Origin::TopLeft);
+        renderer.set_interlace(Interlace::Scanlines);
+        draw(&mut renderer);
+
+        let red: u32 = color::css::RED.into();
+        renderer
+            .buffer()
+            .data
+            .chunks_exact(4)
+            .map(|row| row.iter().all(|&pixel| pixel == red))
+            .collect()
+    }
+
+    #[test]
+    fn interlaced_sprite_blits_skip_odd_rows() {
+        let red = color::css::RED;
+        let data = [red.r(), red.g(), red.b(), red.a()].repeat(16);
+        let sprite = Sprite::from_raw_rgba(4, 4, data);
+
+        let rows = interlaced_rows(|renderer| renderer.draw_sprite(0.0, 0.0, &sprite));
+
+        assert_eq!(vec![true, false, true, false], rows);
+    }
+
+    #[test]
+    fn interlaced_tiled_blits_skip_odd_rows() {
+        let red = color::css::RED;
+        let sprite = Sprite::from_raw_rgba(1, 1, vec![red.r(), red.g(), red.b(), red.a()]);
+        let rect = Rect::new(0.0, 0.0, 4.0, 4.0);
+
+        let rows =
+            interlaced_rows(|renderer| renderer.draw_sprite_tiled(rect, &sprite, Vec2::default()));
+
+        assert_eq!(vec![true, false, true, false], rows);
+    }
+
+    #[test]
+    fn sprites_entirely_off_screen_are_not_visible() {
+        for origin in [Origin::BottomLeft, Origin::TopLeft] {
+            let renderer = renderer(origin);
+
+            assert!(renderer.is_sprite_visible(-1.0, 0.0, 2, 2));
+            assert!(renderer.is_sprite_visible(3.0, 0.0, 2, 2));
+            assert!(!renderer.is_sprite_visible(-2.0, 0.0, 2, 2));
+            assert!(!renderer.is_sprite_visible(4.0, 0.0, 2, 2));
+            assert!(!renderer.is_sprite_visible(0.0, 8.0, 2, 2));
+        }
+    }
+
+    #[test]
+    fn scaled_and_rotated_sprites_are_visible_by_their_drawn_bounds() {
+        let sprite = Sprite::from_raw_rgba(4, 1, vec![255; 16]);
+        for origin in [Origin::BottomLeft, Origin::TopLeft] {
+            let renderer = renderer(origin);
+
+            assert!(!renderer.is_area_visible(Rect::new(-4.5, 0.0, 4.0, 1.0)));
+            assert!(renderer.is_area_visible(Rect::new(-4.5, 0.0, 8.0, 2.0)));
+
+            let scale = Vec2::new(1.0, 1.0);
+            let unrotated =
+                renderer.sprite_ex_bounds(4.5, 0.0, &sprite, 0.0, scale, Vec2::default());
+            let turned = renderer.sprite_ex_bounds(
+                4.5,
+                0.0,
+                &sprite,
+                std::f32::consts::PI,
+                scale,
+                Vec2::default(),
+            );
+            assert!(!renderer.is_area_visible(unrotated));
+            assert!(renderer.is_area_visible(turned));
+        }
+    }
+
+    #[test]
+    fn screen_positions_map_back_to_the_world_through_the_camera() {
+        let mut renderer = renderer(Origin::BottomLeft);
+        assert_eq!(
+            Vec2::new(1.0, 3.0),
+            renderer.screen_to_world(Vec2::new(1.0, 3.0))
+        );
+
+        renderer.set_camera(&Camera2D::new(Vec2::new(102.0, 52.0)).with_zoom(2.0));
+        let world = renderer.screen_to_world(Vec2::new(1.0, 3.0));
+
+        assert_eq!(Vec2::new(101.5, 52.5), world);
+        assert_eq!(Vec2::new(1.0, 3.0), renderer.world_to_screen(world));
+    }
+
+    #[test]
+    fn sprites_are_visible_through_the_camera() {
+        for origin in [Origin::BottomLeft, Origin::TopLeft] {
+            let mut renderer = renderer(origin);
+            renderer.set_camera(&Camera2D::new(Vec2::new(12.0, 2.0)));
+
+            assert!(renderer.is_area_visible(Rect::new(10.0, 0.0, 2.0, 2.0)));
+            assert!(!renderer.is_area_visible(Rect::new(0.0, 0.0, 2.0, 2.0)));
+            assert!(renderer.is_screen_area_visible(Rect::new(0.0, 0.0, 2.0, 2.0)));
+        }
+    }
+}
@@ -0,0 +1,231 @@
+use std::ops::Range;
+
+/// The fraction of the window's resolution the renderer draws at. Lower scales fill fewer pixels
+/// each frame and are upscaled to the window when displayed. A scale only takes effect when the
+/// virtual pixel size divides by its divisor, so with the default pixel size of 1 everything is
+/// drawn at `Full`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum RenderScale {
+    #[default]
+    Full,
+    Half,
+    Quarter,
+}
+
+impl RenderScale {
+    /// How many window pixels wide and high each rendered pixel is.
+    pub fn divisor(&self) -> usize {
+        match self {
+            RenderScale::Full => 1,
+            RenderScale::Half => 2,
+            RenderScale::Quarter => 4,
+        }
+    }
+
+    pub(crate) fn from_divisor(divisor: usize) -> Self {
+        match divisor {
+            4 => RenderScale::Quarter,
+            2 => RenderScale::Half,
+            _ => RenderScale::Full,
+        }
+    }
+}
+
+/// How a reduced render scale is enlarged to fill the window.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum UpscaleFilter {
+    /// Blocky pixel replication. The cheapest, and faithful to pixel art.
+    #[default]
+    Nearest,
+    /// Scale2x (EPX): rounds off diagonal edges while keeping flat areas and colors crisp, in the
+    /// spirit of 2xSaI and hq2x.
+    Scale2x,
+    /// Smooth blending between neighbouring pixels.
+    Bilinear,
+}
+
+/// Enlarge a `width` by `height` image by `factor` into `dst`, which must hold
+/// `width * factor` by `height * factor` pixels. Only the source `rows` that changed are redone
+/// where the filter allows it; filters that blend neighbouring pixels redo the whole image.
+pub(crate) fn upscale(
+    filter: UpscaleFilter,
+    src: &[u32],
+    width: usize,
+    height: usize,
+    factor: usize,
+    rows: Range<usize>,
+    dst: &mut [u32],
+) {
+    match filter {
+        UpscaleFilter::Nearest => nearest(src, width, rows, factor, dst),
+        UpscaleFilter::Scale2x if factor.is_power_of_two() => {
+            let (mut image, mut image_width, mut image_height) = (src.to_vec(), width, height);
+            let mut remaining = factor;
+            while remaining > 1 {
+                image = scale2x(&image, image_width, image_height);
+                image_width *= 2;
+                image_height *= 2;
+                remaining /= 2;
+            }
+            dst.copy_from_slice(&image);
+        }
+        UpscaleFilter::Scale2x => nearest(src, width, 0..height, factor, dst),
+        UpscaleFilter::Bilinear => bilinear(src, width, height, factor, dst),
+    }
+}
+
+fn nearest(src: &[u32], width: usize, rows: Range<usize>, factor: usize, dst: &mut [u32]) {
+    let dst_width = width * factor;
+    for y in rows {
+        let row = &src[y * width..(y + 1) * width];
+        let first = y * factor * dst_width;
+        for (x, &pixel) in row.iter().enumerate() {
+            dst[first + x * factor..first + (x + 1) * factor].fill(pixel);
+        }
+        for copy in 1..factor {
+            dst.copy_within(first..first + dst_width, first + copy * dst_width);
+        }
+    }
+}
+
+/// Each pixel becomes four, each taking the color of the two neighbours it sits between when
+/// they agree, which fills in the steps of diagonal edges.
+fn scale2x(src: &[u32], width: usize, height: usize) -> Vec<u32> {
+    let mut dst = vec![0; width * height * 4];
+    let at = |x: isize, y: isize| {
+        let x = x.clamp(0, width as isize - 1) as usize;
+        let y = y.clamp(0, height as isize - 1) as usize;
+        src[y * width + x]
+    };
+
+    for y in 0..height as isize {
+        for x in 0..width as isize {
+            let p = at(x, y);
+            let (a, b, c, d) = (at(x, y - 1), at(x + 1, y), at(x - 1, y), at(x, y + 1));
+
+            let mut out = [p; 4];
+            if c == a && c != d && a != b {
+                out[0] = a;
+            }
+            if a == b && a != c && b != d {
+                out[1] = b;
+            }
+            if d == c && d != b && c != a {
+                out[2] = c;
+            }
+            if b == d && b != a && d != c {
+                out[3] = d;
+            }
+
+            let (dx, dy) = (x as usize * 2, y as usize * 2);
+            dst[dy * width * 2 + dx] = out[0];
+            dst[dy * width * 2 + dx + 1] = out[1];
+            dst[(dy + 1) * width * 2 + dx] = out[2];
+            dst[(dy + 1) * width * 2 + dx + 1] = out[3];
+        }
+    }
+
+    dst
+}
+
+fn bilinear(src: &[u32], width: usize, height: usize, factor: usize, dst: &mut [u32]) {
+    let dst_width = width * factor;
+    let sample = |x: usize, y: usize| -> [f32; 4] {
+        src[y.min(height - 1) * width + x.min(width - 1)]
+            .to_be_bytes()
+            .map(|channel| channel as f32)
+    };
+
+    for (i, pixel) in dst.iter_mut().enumerate() {
+        let (dst_x, dst_y) = (i % dst_width, i / dst_width);
+        let x = ((dst_x as f32 + 0.5) / factor as f32 - 0.5).max(0.0);
+        let y = ((dst_y as f32 + 0.5) / factor as f32 - 0.5).max(0.0);
+        let (x0, y0) = (x as usize, y as usize);
+        let (tx, ty) = (x.fract(), y.fract());
+
+        let (p00, p10) = (sample(x0, y0), sample(x0 + 1, y0));
+        let (p01, p11) = (sample(x0, y0 + 1), sample(x0 + 1, y0 + 1));
+        let mut channels = [0; 4];
+        for c in 0..4 {
+            let top = p00[c] + (p10[c] - p00[c]) * tx;
+            let bottom = p01[c] + (p11[c] - p01[c]) * tx;
+            channels[c] = (top + (bottom - top) * ty).round() as u8;
+        }
+
+        *pixel = u32::from_be_bytes(channels);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nearest_replicates_pixels_into_blocks() {
+        let mut dst = vec![0; 16];
+
+        upscale(
+            UpscaleFilter::Nearest,
+            &[1, 2, 3, 4],
+            2,
+            2,
+            2,
+            0..2,
+            &mut dst,
+        );
+
+        assert_eq!(vec![1, 1, 2, 2, 1, 1, 2, 2, 3, 3, 4, 4, 3, 3, 4, 4], dst);
+    }
+
+    #[test]
+    fn nearest_only_redoes_changed_rows() {
+        let mut dst = vec![0; 16];
+
+        upscale(
+            UpscaleFilter::Nearest,
+            &[1, 2, 3, 4],
+            2,
+            2,
+            2,
+            1..2,
+            &mut dst,
+        );
+
+        assert_eq!(vec![0; 8], dst[..8]);
+        assert_eq!(vec![3, 3, 4, 4, 3, 3, 4, 4], dst[8..]);
+    }
+
+    #[test]
+    fn scale2x_rounds_diagonal_corners() {
+        // A diagonal edge: the top left pixel's corner towards its matching neighbours fills in.
+        let (x, o) = (0xFFFF_FFFF, 0xFF00_0000);
+        let src = [o, x, x, x];
+        let mut dst = vec![0; 16];
+
+        upscale(UpscaleFilter::Scale2x, &src, 2, 2, 2, 0..2, &mut dst);
+
+        assert_eq!(o, dst[0]);
+        assert_eq!(x, dst[5]);
+        assert_eq!(x, dst[15]);
+    }
+
+    #[test]
+    fn bilinear_blends_between_pixels() {
+        let (black, white) = (0xFF00_0000, 0xFFFF_FFFF);
+        let mut dst = vec![0; 8];
+
+        upscale(
+            UpscaleFilter::Bilinear,
+            &[black, white],
+            2,
+            1,
+            2,
+            0..1,
+            &mut dst,
+        );
+
+        let middle = dst[1].to_be_bytes();
+        assert!(middle[1] > 0 && middle[1] < 255);
+        assert_eq!(white, dst[3]);
+    }
+}
@@ -0,0 +1,138 @@
+use crate::color::Color;
+use crate::engine::apparatus::Apparatus;
+use crate::maths::Vec2;
+
+/// Where an anchored widget should be drawn this frame.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AnchorPlacement {
+    /// The widget's anchor point on screen, in virtual pixels.
+    pub position: Vec2,
+    /// Whether the anchor is on screen, rather than clamped to its edge.
+    pub on_screen: bool,
+    /// The direction from the center of the screen to the anchor, of length 1, e.g. to point an
+    /// off-screen indicator with.
+    pub direction: Vec2,
+}
+
+/// Attaches a widget to a point in the world, e.g. a name tag or a health bar above a unit, or
+/// an indicator at the screen edge pointing to something off screen.
+/// All positions are in virtual pixels.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WorldAnchor {
+    offset: Vec2,
+    clamp_margin: Option<f32>,
+}
+
+impl WorldAnchor {
+    /// Anchor `offset` away from the world position, e.g. a few pixels above a unit's head.
+    pub fn new(offset: Vec2) -> Self {
+        Self {
+            offset,
+            clamp_margin: None,
+        }
+    }
+
+    /// Keep the anchor on screen, at least `margin` from each edge, moving it towards the center
+    /// of the screen when its world position is off screen. Defaults to hiding it instead.
+    pub fn with_clamp_to_screen(mut self, margin: f32) -> Self {
+        self.clamp_margin = Some(margin.max(0.0));
+        self
+    }
+
+    /// Place the anchor for a point at `screen_position`, already through the camera, on a
+    /// screen of `screen_size`. `None` if it is off screen and not clamped.
+    pub fn place(&self, screen_position: Vec2, screen_size: Vec2) -> Option<AnchorPlacement> {
+        let position = screen_position + self.offset;
+        let center = screen_size * 0.5;
+        let direction = (position - center).normalized();
+        let margin = self.clamp_margin.unwrap_or(0.0);
+        let within = |value: f32, size: f32| value >= margin && value <= size - margin;
+
+        if within(position.x, screen_size.x) && within(position.y, screen_size.y) {
+            return Some(AnchorPlacement {
+                position,
+                on_screen: true,
+                direction,
+            });
+        }
+        self.clamp_margin?;
+
+        // Slide back along the line to the center until both axes are inside the margin.
+        let half = Vec2::new((center.x - margin).max(0.0), (center.y - margin).max(0.0));
+        let to_anchor = position - center;
+        let scale = |half: f32, distance: f32| {
+            if distance.abs() > half {
+                half / distance.abs()
+            } else {
+                1.0
+            }
+        };
+        let t = scale(half.x, to_anchor.x).min(scale(half.y, to_anchor.y));
+
+        Some(AnchorPlacement {
+            position: center + to_anchor * t,
+            on_screen: false,
+            direction,
+        })
+    }
+
+    /// Place the anchor for `world_position`, seen through the engine's camera.
+    pub fn place_in_world(&self, app: &Apparatus, world_position: Vec2) -> Option<AnchorPlacement> {
+        let screen_size = Vec2::new(app.screen_width() as f32, app.screen_height() as f32);
+        self.place(app.world_to_screen(world_position), screen_size)
+    }
+}
+
+/// Draw a filled arrow of `size` at `placement`, pointing towards the anchor's world position,
+/// e.g. for off-screen indicators. It is drawn on screen whether or not a camera is set.
+pub fn draw_indicator_arrow(
+    app: &mut Apparatus,
+    placement: &AnchorPlacement,
+    size: f32,
+    color: Color,
+) {
+    let forward = placement.direction;
+    if forward == Vec2::default() {
+        return;
+    }
+
+    let side = Vec2::new(-forward.y, forward.x) * (size * 0.5);
+    let tip = placement.position + forward * (size * 0.5);
+    let back = placement.position - forward * (size * 0.5);
+    let (left, right) = (back + side, back - side);
+    app.in_screen_space(|app| {
+        app.draw_filled_triangle(tip.x, tip.y, left.x, left.y, right.x, right.y, color)
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCREEN: Vec2 = Vec2 { x: 100.0, y: 50.0 };
+
+    #[test]
+    fn on_screen_anchors_are_offset() {
+        let anchor = WorldAnchor::new(Vec2::new(0.0, 8.0));
+
+        let placement = anchor.place(Vec2::new(20.0, 10.0), SCREEN).unwrap();
+
+        assert_eq!(Vec2::new(20.0, 18.0), placement.position);
+        assert!(placement.on_screen);
+        assert_eq!(None, anchor.place(Vec2::new(120.0, 10.0), SCREEN));
+    }
+
+    #[test]
+    fn off_screen_anchors_are_clamped_towards_the_center() {
+        let anchor = WorldAnchor::new(Vec2::default()).with_clamp_to_screen(5.0);
+
+        let placement = anchor.place(Vec2::new(250.0, 25.0), SCREEN).unwrap();
+        assert_eq!(Vec2::new(95.0, 25.0), placement.position);
+        assert_eq!(Vec2::new(1.0, 0.0), placement.direction);
+        assert!(!placement.on_screen);
+
+        // Up and to the right hits the top edge first, keeping the line to the center.
+        let placement = anchor.place(Vec2::new(110.0, 85.0), SCREEN).unwrap();
+        assert_eq!(Vec2::new(70.0, 45.0), placement.position);
+    }
+}
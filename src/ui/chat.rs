@@ -0,0 +1,156 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use crate::color;
+use crate::color::Color;
+use crate::engine::apparatus::Apparatus;
+use crate::engine::key::Key;
+use crate::net::chat::ChatMessage;
+use crate::ui::text_input::TextInput;
+
+struct Line {
+    message: ChatMessage,
+    age: Duration,
+}
+
+/// Scrollback of recent chat messages drawn over the game, with a text entry line. Enter opens
+/// the entry line and sends, Escape cancels. Messages fade out after a while unless the entry line
+/// is open. Positions are in window pixels, measured from the bottom left.
+pub struct ChatOverlay {
+    name: String,
+    lines: VecDeque<Line>,
+    input: TextInput,
+    open: bool,
+    capacity: usize,
+    visible_lines: usize,
+    fade_after: Duration,
+    text_size: f32,
+    color: Color,
+}
+
+impl ChatOverlay {
+    /// `name` is sent as the sender of messages typed here.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            lines: VecDeque::new(),
+            input: TextInput::default().with_max_length(120),
+            open: false,
+            capacity: 100,
+            visible_lines: 6,
+            fade_after: Duration::from_secs(8),
+            text_size: 12.0,
+            color: color::css::WHITE,
+        }
+    }
+
+    /// Number of messages shown at once. Defaults to 6.
+    pub fn with_visible_lines(mut self, lines: usize) -> Self {
+        self.visible_lines = lines;
+        self
+    }
+
+    /// How long messages stay on screen while chat is closed. Defaults to 8 seconds.
+    pub fn with_fade_after(mut self, duration: Duration) -> Self {
+        self.fade_after = duration;
+        self
+    }
+
+    /// Defaults to white.
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Whether the entry line is open. Games should ignore gameplay keys while it is.
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Add a message to the scrollback, e.g. one received from the network.
+    pub fn push(&mut self, message: ChatMessage) {
+        self.lines.push_back(Line {
+            message,
+            age: Duration::ZERO,
+        });
+        while self.lines.len() > self.capacity {
+            self.lines.pop_front();
+        }
+    }
+
+    pub fn messages(&self) -> impl Iterator<Item = &ChatMessage> {
+        self.lines.iter().map(|line| &line.message)
+    }
+
+    /// Handle input and age messages. Returns a message the player sent, which has already been
+    /// added to the scrollback and should be broadcast to the other players.
+    pub fn update(&mut self, app: &Apparatus) -> Option<ChatMessage> {
+        let dt = app.elapsed_time();
+        for line in &mut self.lines {
+            line.age += dt;
+        }
+
+        if !self.open {
+            if app.is_key_pressed(Key::Enter) {
+                self.open = true;
+            }
+            return None;
+        }
+
+        if app.is_key_pressed(Key::Escape) {
+            self.open = false;
+            self.input.clear();
+            return None;
+        }
+
+        let text = self.input.update(app)?;
+        self.open = false;
+
+        let text = text.trim();
+        if text.is_empty() {
+            return None;
+        }
+
+        let message = ChatMessage::new(self.name.clone(), text);
+        self.push(message.clone());
+        Some(message)
+    }
+
+    pub fn draw(&self, app: &mut Apparatus, x: f32, y: f32) {
+        let line_height = self.text_size + 2.0;
+        let mut y = y;
+
+        if self.open {
+            // The backdrop is drawn in virtual pixels.
+            let scale_x = app.window_width() / app.screen_width() as f32;
+            let scale_y = app.window_height() / app.screen_height() as f32;
+            app.draw_filled_rectangle(
+                (x - 2.0) / scale_x,
+                (y - 2.0) / scale_y,
+                (app.window_width() - 2.0 * x + 4.0) / scale_x,
+                line_height / scale_y,
+                Color::rgba(0, 0, 0, 160),
+            );
+            app.draw_string(
+                format!("> {}_", self.input.text()),
+                x,
+                y,
+                self.color,
+                self.text_size,
+            );
+        }
+        y += line_height;
+
+        let visible = self
+            .lines
+            .iter()
+            .rev()
+            .take(self.visible_lines)
+            .filter(|line| self.open || line.age < self.fade_after);
+        for line in visible {
+            let text = format!("{}: {}", line.message.sender, line.message.text);
+            app.draw_string(text, x, y, self.color, self.text_size);
+            y += line_height;
+        }
+    }
+}
@@ -0,0 +1,195 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use crate::color;
+use crate::color::Color;
+use crate::engine::apparatus::Apparatus;
+
+/// A line of the event log made of spans of text, each in its own color or the log's, e.g.
+/// "Alice" in red, " fragged " and "Bob" in blue.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LogEntry {
+    spans: Vec<(String, Option<Color>)>,
+}
+
+impl LogEntry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add text in the log's color.
+    pub fn with_text(mut self, text: impl Into<String>) -> Self {
+        self.spans.push((text.into(), None));
+        self
+    }
+
+    /// Add text in `color`.
+    pub fn with_colored_text(mut self, text: impl Into<String>, color: Color) -> Self {
+        self.spans.push((text.into(), Some(color)));
+        self
+    }
+
+    /// The entry's text without colors.
+    pub fn text(&self) -> String {
+        self.spans.iter().map(|(text, _)| text.as_str()).collect()
+    }
+}
+
+impl From<&str> for LogEntry {
+    fn from(text: &str) -> Self {
+        LogEntry::new().with_text(text)
+    }
+}
+
+impl From<String> for LogEntry {
+    fn from(text: String) -> Self {
+        LogEntry::new().with_text(text)
+    }
+}
+
+struct Line {
+    entry: LogEntry,
+    age: Duration,
+}
+
+/// A scrolling list of recent events drawn over the game, e.g. a kill feed. New entries appear at
+/// the bottom, pushing older ones up, and each fades out once it has been shown for a while.
+/// Positions are in window pixels, measured from the bottom left.
+pub struct EventLog {
+    lines: VecDeque<Line>,
+    visible_lines: usize,
+    lifetime: Duration,
+    fade: Duration,
+    text_size: f32,
+    color: Color,
+}
+
+impl Default for EventLog {
+    fn default() -> Self {
+        Self {
+            lines: VecDeque::new(),
+            visible_lines: 5,
+            lifetime: Duration::from_secs(6),
+            fade: Duration::from_secs(1),
+            text_size: 12.0,
+            color: color::css::WHITE,
+        }
+    }
+}
+
+impl EventLog {
+    /// Number of entries shown at once; older entries are dropped. Defaults to 5.
+    pub fn with_visible_lines(mut self, lines: usize) -> Self {
+        self.visible_lines = lines;
+        self
+    }
+
+    /// How long entries are shown for, including fading out over the last `fade` of it. Defaults
+    /// to 6 seconds, fading over 1.
+    pub fn with_lifetime(mut self, lifetime: Duration, fade: Duration) -> Self {
+        self.lifetime = lifetime;
+        self.fade = fade.min(lifetime);
+        self
+    }
+
+    /// Defaults to 12.
+    pub fn with_text_size(mut self, size: f32) -> Self {
+        self.text_size = size;
+        self
+    }
+
+    /// The color of text without its own. Defaults to white.
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn push(&mut self, entry: impl Into<LogEntry>) {
+        self.lines.push_back(Line {
+            entry: entry.into(),
+            age: Duration::ZERO,
+        });
+        while self.lines.len() > self.visible_lines {
+            self.lines.pop_front();
+        }
+    }
+
+    /// The entries still shown, oldest first.
+    pub fn entries(&self) -> impl Iterator<Item = &LogEntry> {
+        self.lines.iter().map(|line| &line.entry)
+    }
+
+    pub fn clear(&mut self) {
+        self.lines.clear();
+    }
+
+    /// Age entries by the frame's elapsed time, dropping those that have faded out.
+    pub fn update(&mut self, app: &Apparatus) {
+        self.advance(app.elapsed_time());
+    }
+
+    fn advance(&mut self, dt: Duration) {
+        for line in &mut self.lines {
+            line.age += dt;
+        }
+        let lifetime = self.lifetime;
+        self.lines.retain(|line| line.age < lifetime);
+    }
+
+    /// How opaque an entry of `age` is, from 1 until it starts fading down to 0 at its lifetime.
+    fn opacity(&self, age: Duration) -> f32 {
+        let remaining = self.lifetime.saturating_sub(age);
+        if self.fade.is_zero() || remaining >= self.fade {
+            1.0
+        } else {
+            remaining.as_secs_f32() / self.fade.as_secs_f32()
+        }
+    }
+
+    /// Draw the entries with the newest one's bottom left corner at (x, y).
+    pub fn draw(&self, app: &mut Apparatus, x: f32, y: f32) {
+        let line_height = self.text_size + 2.0;
+        let mut y = y;
+
+        for line in self.lines.iter().rev() {
+            let opacity = self.opacity(line.age);
+            let mut x = x;
+            for (text, color) in &line.entry.spans {
+                let color = color.unwrap_or(self.color);
+                let alpha = (color.a() as f32 * opacity).round() as u8;
+                let color = Color::rgba(color.r(), color.g(), color.b(), alpha);
+                app.draw_string(text, x, y, color, self.text_size);
+                x += app.string_width(text, self.text_size);
+            }
+            y += line_height;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries_fade_out_and_expire() {
+        let mut log = EventLog::default()
+            .with_lifetime(Duration::from_secs(4), Duration::from_secs(2))
+            .with_visible_lines(2);
+        log.push("first");
+        log.advance(Duration::from_secs(3));
+        log.push(
+            LogEntry::new()
+                .with_colored_text("Alice", color::css::RED)
+                .with_text(" joined"),
+        );
+        log.push("third");
+
+        let texts: Vec<String> = log.entries().map(LogEntry::text).collect();
+        assert_eq!(vec!["Alice joined", "third"], texts);
+        assert_eq!(1.0, log.opacity(Duration::from_secs(1)));
+        assert_eq!(0.5, log.opacity(Duration::from_secs(3)));
+
+        log.advance(Duration::from_secs(4));
+        assert_eq!(0, log.entries().count());
+    }
+}
@@ -0,0 +1,247 @@
+use crate::color::Color;
+use crate::engine::apparatus::Apparatus;
+use crate::engine::key::Key;
+use crate::maths::Rect;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct WidgetId(pub u32);
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum NavigationCommand {
+    Next,
+    Previous,
+    Up,
+    Down,
+    Left,
+    Right,
+    Activate,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct FocusStyle {
+    pub color: Color,
+    /// Gap in virtual pixels between a widget and its focus outline.
+    pub padding: f32,
+}
+
+struct Focusable {
+    id: WidgetId,
+    rect: Rect,
+}
+
+/// Tracks which widget has keyboard focus so menus can be used without a mouse.
+///
+/// Widgets are registered in tab order. Tab and Shift+Tab move through them in that order, the
+/// arrow keys move to the nearest widget in that direction and Enter or Space activates the
+/// focused widget.
+#[derive(Default)]
+pub struct FocusModel {
+    widgets: Vec<Focusable>,
+    focused: Option<usize>,
+}
+
+impl FocusModel {
+    /// Register a widget, appending it to the tab order. Re-registering an id updates its rect.
+    /// The first widget registered receives focus.
+    pub fn add(&mut self, id: WidgetId, rect: Rect) {
+        match self.widgets.iter_mut().find(|widget| widget.id == id) {
+            Some(widget) => widget.rect = rect,
+            None => self.widgets.push(Focusable { id, rect }),
+        }
+
+        if self.focused.is_none() {
+            self.focused = Some(0);
+        }
+    }
+
+    pub fn remove(&mut self, id: WidgetId) {
+        let focused = self.focused();
+        self.widgets.retain(|widget| widget.id != id);
+        self.focused = match focused {
+            Some(focused) if focused != id => self.index_of(focused),
+            _ if self.widgets.is_empty() => None,
+            _ => Some(0),
+        };
+    }
+
+    pub fn clear(&mut self) {
+        self.widgets.clear();
+        self.focused = None;
+    }
+
+    pub fn focused(&self) -> Option<WidgetId> {
+        self.focused.map(|index| self.widgets[index].id)
+    }
+
+    pub fn is_focused(&self, id: WidgetId) -> bool {
+        self.focused() == Some(id)
+    }
+
+    pub fn set_focus(&mut self, id: WidgetId) {
+        if let Some(index) = self.index_of(id) {
+            self.focused = Some(index);
+        }
+    }
+
+    /// Move focus or activate the focused widget, returning the widget that was activated.
+    pub fn navigate(&mut self, command: NavigationCommand) -> Option<WidgetId> {
+        let focused = self.focused?;
+        let count = self.widgets.len();
+
+        match command {
+            NavigationCommand::Next => self.focused = Some((focused + 1) % count),
+            NavigationCommand::Previous => self.focused = Some((focused + count - 1) % count),
+            NavigationCommand::Activate => return self.focused(),
+            direction => {
+                if let Some(index) = self.nearest_in_direction(focused, direction) {
+                    self.focused = Some(index);
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Apply this frame's keyboard input, returning the widget that was activated, if any.
+    pub fn update(&mut self, app: &Apparatus) -> Option<WidgetId> {
+        let shift = app.is_key_pressed(Key::LeftShift)
+            || app.is_key_held(Key::LeftShift)
+            || app.is_key_pressed(Key::RightShift)
+            || app.is_key_held(Key::RightShift);
+
+        let command = if app.is_key_pressed(Key::Tab) && shift {
+            NavigationCommand::Previous
+        } else if app.is_key_pressed(Key::Tab) {
+            NavigationCommand::Next
+        } else if app.is_key_pressed(Key::Up) {
+            NavigationCommand::Up
+        } else if app.is_key_pressed(Key::Down) {
+            NavigationCommand::Down
+        } else if app.is_key_pressed(Key::Left) {
+            NavigationCommand::Left
+        } else if app.is_key_pressed(Key::Right) {
+            NavigationCommand::Right
+        } else if app.is_key_pressed(Key::Enter) || app.is_key_pressed(Key::Space) {
+            NavigationCommand::Activate
+        } else {
+            return None;
+        };
+
+        self.navigate(command)
+    }
+
+    /// Outline the focused widget.
+    pub fn draw(&self, app: &mut Apparatus, style: FocusStyle) {
+        if let Some(index) = self.focused {
+            let rect = self.widgets[index].rect;
+            app.draw_wireframe_rectangle(
+                rect.x - style.padding - 1.0,
+                rect.y - style.padding - 1.0,
+                rect.width + 2.0 * style.padding + 1.0,
+                rect.height + 2.0 * style.padding + 1.0,
+                style.color,
+            );
+        }
+    }
+
+    fn index_of(&self, id: WidgetId) -> Option<usize> {
+        self.widgets.iter().position(|widget| widget.id == id)
+    }
+
+    /// Find the closest widget whose center lies in `direction` from the focused widget's center,
+    /// preferring widgets that are aligned with the direction of travel.
+    fn nearest_in_direction(&self, from: usize, direction: NavigationCommand) -> Option<usize> {
+        let center = |rect: Rect| (rect.x + rect.width / 2.0, rect.y + rect.height / 2.0);
+        let (from_x, from_y) = center(self.widgets[from].rect);
+
+        self.widgets
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| *index != from)
+            .filter_map(|(index, widget)| {
+                let (x, y) = center(widget.rect);
+                let (dx, dy) = (x - from_x, y - from_y);
+
+                // y is up, so "up" is towards larger y.
+                let (along, across) = match direction {
+                    NavigationCommand::Up => (dy, dx),
+                    NavigationCommand::Down => (-dy, dx),
+                    NavigationCommand::Left => (-dx, dy),
+                    NavigationCommand::Right => (dx, dy),
+                    _ => return None,
+                };
+
+                (along > 0.0).then(|| (index, along + 2.0 * across.abs()))
+            })
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(index, _)| index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn menu() -> FocusModel {
+        // A column of three buttons with a fourth to the right of the middle one.
+        let mut focus = FocusModel::default();
+        focus.add(WidgetId(1), Rect::new(0.0, 100.0, 50.0, 10.0));
+        focus.add(WidgetId(2), Rect::new(0.0, 80.0, 50.0, 10.0));
+        focus.add(WidgetId(3), Rect::new(0.0, 60.0, 50.0, 10.0));
+        focus.add(WidgetId(4), Rect::new(80.0, 80.0, 50.0, 10.0));
+        focus
+    }
+
+    #[test]
+    fn first_widget_is_focused() {
+        assert_eq!(Some(WidgetId(1)), menu().focused());
+    }
+
+    #[test]
+    fn tab_order_wraps() {
+        let mut focus = menu();
+
+        focus.navigate(NavigationCommand::Previous);
+
+        assert_eq!(Some(WidgetId(4)), focus.focused());
+
+        focus.navigate(NavigationCommand::Next);
+
+        assert_eq!(Some(WidgetId(1)), focus.focused());
+    }
+
+    #[test]
+    fn arrows_move_to_nearest_widget_in_direction() {
+        let mut focus = menu();
+
+        focus.navigate(NavigationCommand::Down);
+        assert_eq!(Some(WidgetId(2)), focus.focused());
+
+        focus.navigate(NavigationCommand::Right);
+        assert_eq!(Some(WidgetId(4)), focus.focused());
+
+        focus.navigate(NavigationCommand::Right);
+        assert_eq!(Some(WidgetId(4)), focus.focused());
+    }
+
+    #[test]
+    fn activate_returns_focused_widget() {
+        let mut focus = menu();
+        focus.set_focus(WidgetId(3));
+
+        assert_eq!(
+            Some(WidgetId(3)),
+            focus.navigate(NavigationCommand::Activate)
+        );
+    }
+
+    #[test]
+    fn removing_focused_widget_moves_focus_to_first() {
+        let mut focus = menu();
+        focus.set_focus(WidgetId(3));
+
+        focus.remove(WidgetId(3));
+
+        assert_eq!(Some(WidgetId(1)), focus.focused());
+    }
+}
@@ -0,0 +1,102 @@
+use crate::engine::sprite::Sprite;
+use crate::maths::Vec2;
+use crate::renderer::software_2d::{Flip, Origin};
+
+/// Whether `point` lands on a visible pixel of `sprite` drawn at `position`, as `draw_sprite`
+/// places it for `origin`. Pixels with an alpha of zero don't count, so clicks through the
+/// transparent parts of a sprite reach whatever is behind it. Both are in virtual pixels.
+pub fn sprite_contains(sprite: &Sprite, position: Vec2, origin: Origin, point: Vec2) -> bool {
+    let position = position + sprite.trim_offset(origin, Flip::NONE);
+    let column = (point.x - position.x).floor();
+    let offset = (point.y - position.y).floor();
+    let row = match origin {
+        // Drawn upwards from just above the anchor, top row highest.
+        Origin::BottomLeft => sprite.height() as f32 - offset,
+        Origin::TopLeft => offset,
+    };
+
+    if column < 0.0 || row < 0.0 {
+        return false;
+    }
+
+    sprite
+        .alpha(column as u32, row as u32)
+        .is_some_and(|alpha| alpha > 0)
+}
+
+/// The topmost item whose sprite is visible at `point`. Items are expected in draw order, so
+/// later items are on top.
+pub fn sprite_at<'a, T>(
+    items: impl IntoIterator<Item = (T, &'a Sprite, Vec2)>,
+    origin: Origin,
+    point: Vec2,
+) -> Option<T> {
+    items
+        .into_iter()
+        .filter(|(_, sprite, position)| sprite_contains(sprite, *position, origin, point))
+        .last()
+        .map(|(item, _, _)| item)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 2 x 2 sprite with a transparent top right pixel.
+    fn notched() -> Sprite {
+        let (solid, clear) = ([255, 0, 0, 255], [0, 0, 0, 0]);
+        Sprite::from_raw_rgba(2, 2, [solid, clear, solid, solid].concat())
+    }
+
+    #[test]
+    fn transparent_pixels_are_not_hit() {
+        let sprite = notched();
+        let position = Vec2::new(10.0, 10.0);
+
+        assert!(sprite_contains(
+            &sprite,
+            position,
+            Origin::TopLeft,
+            Vec2::new(10.5, 10.5)
+        ));
+        assert!(!sprite_contains(
+            &sprite,
+            position,
+            Origin::TopLeft,
+            Vec2::new(11.5, 10.5)
+        ));
+        assert!(!sprite_contains(
+            &sprite,
+            position,
+            Origin::BottomLeft,
+            Vec2::new(11.5, 12.5)
+        ));
+        assert!(sprite_contains(
+            &sprite,
+            position,
+            Origin::BottomLeft,
+            Vec2::new(11.5, 11.5)
+        ));
+        assert!(!sprite_contains(
+            &sprite,
+            position,
+            Origin::TopLeft,
+            Vec2::new(12.5, 10.5)
+        ));
+    }
+
+    #[test]
+    fn topmost_sprite_wins() {
+        let sprite = notched();
+        let items = vec![
+            ("back", &sprite, Vec2::new(0.0, 0.0)),
+            ("front", &sprite, Vec2::new(1.0, 0.0)),
+        ];
+
+        let hit = sprite_at(items.clone(), Origin::TopLeft, Vec2::new(1.5, 1.5));
+        let through_notch = sprite_at(items, Origin::TopLeft, Vec2::new(2.5, 0.5));
+
+        assert_eq!(Some("front"), hit);
+        assert_eq!(None, through_notch);
+    }
+}
@@ -0,0 +1,117 @@
+use crate::color;
+use crate::color::Color;
+use crate::engine::apparatus::Apparatus;
+use crate::maths::Vec2;
+use crate::ui::anchor::{self, AnchorPlacement, WorldAnchor};
+
+/// An arrow at the edge of the screen pointing towards something off screen, e.g. a projectile
+/// that has flown out of view, optionally labelled with how far away it is. Nothing is drawn
+/// while the target is on screen. Positions and sizes are in virtual pixels.
+#[derive(Clone, Debug)]
+pub struct OffscreenIndicator {
+    anchor: WorldAnchor,
+    arrow_size: f32,
+    color: Color,
+    distance: Option<(Color, f32)>,
+    distance_scale: f32,
+}
+
+impl OffscreenIndicator {
+    /// Create an indicator kept `margin` from the edges of the screen.
+    pub fn new(margin: f32) -> Self {
+        Self {
+            anchor: WorldAnchor::new(Vec2::default()).with_clamp_to_screen(margin),
+            arrow_size: 6.0,
+            color: color::css::YELLOW,
+            distance: None,
+            distance_scale: 1.0,
+        }
+    }
+
+    /// Defaults to a yellow arrow 6 pixels long.
+    pub fn with_arrow(mut self, size: f32, color: Color) -> Self {
+        self.arrow_size = size;
+        self.color = color;
+        self
+    }
+
+    /// Label the arrow with the distance from the center of the screen to the target, drawn
+    /// `size` window pixels high. Defaults to none.
+    pub fn with_distance(mut self, color: Color, size: f32) -> Self {
+        self.distance = Some((color, size));
+        self
+    }
+
+    /// Multiply distances by `scale` before they're shown, e.g. to convert pixels to metres.
+    /// Defaults to 1.
+    pub fn with_distance_scale(mut self, scale: f32) -> Self {
+        self.distance_scale = scale;
+        self
+    }
+
+    /// Where the arrow goes for a target at `screen_position`, already through the camera,
+    /// on a screen of `screen_size`, or `None` if the target is on screen.
+    pub fn placement(&self, screen_position: Vec2, screen_size: Vec2) -> Option<AnchorPlacement> {
+        self.anchor
+            .place(screen_position, screen_size)
+            .filter(|placement| !placement.on_screen)
+    }
+
+    /// The label shown for a target `distance` pixels from the center of the screen.
+    pub fn distance_label(&self, distance: f32) -> String {
+        format!("{:.0}", distance * self.distance_scale)
+    }
+
+    /// Draw the indicator for `world_position`, seen through the engine's camera. Returns
+    /// whether it was drawn, i.e. the target is off screen.
+    pub fn draw(&self, app: &mut Apparatus, world_position: Vec2) -> bool {
+        let screen_size = Vec2::new(app.screen_width() as f32, app.screen_height() as f32);
+        let screen_position = app.world_to_screen(world_position);
+        let placement = match self.placement(screen_position, screen_size) {
+            Some(placement) => placement,
+            None => return false,
+        };
+
+        anchor::draw_indicator_arrow(app, &placement, self.arrow_size, self.color);
+
+        if let Some((color, size)) = self.distance {
+            let label = self.distance_label((screen_position - screen_size * 0.5).length());
+            // Step back from the arrow towards the center, then left by roughly the label's
+            // width when near the right edge so it stays on screen.
+            let inward = placement.position - placement.direction * (self.arrow_size * 1.5);
+            let (pixel_width, pixel_height) = (app.pixel_width() as f32, app.pixel_height() as f32);
+            let label_width = label.len() as f32 * size * 0.5;
+            let x = inward.x * pixel_width - label_width * placement.direction.x.max(0.0);
+            let y = inward.y * pixel_height - size * 0.5;
+            app.draw_string(label, x, y, color, size);
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn indicators_only_show_off_screen_targets() {
+        let indicator = OffscreenIndicator::new(4.0);
+        let screen = Vec2::new(80.0, 60.0);
+
+        assert_eq!(None, indicator.placement(Vec2::new(40.0, 30.0), screen));
+
+        let placement = indicator
+            .placement(Vec2::new(40.0, -100.0), screen)
+            .unwrap();
+        assert_eq!(Vec2::new(40.0, 4.0), placement.position);
+        assert_eq!(Vec2::new(0.0, -1.0), placement.direction);
+    }
+
+    #[test]
+    fn distances_are_scaled_and_rounded() {
+        let indicator = OffscreenIndicator::new(4.0).with_distance_scale(0.1);
+
+        assert_eq!("13", indicator.distance_label(126.0));
+    }
+}
@@ -0,0 +1,223 @@
+use crate::engine::apparatus::Apparatus;
+use crate::maths::Rect;
+
+/// A length in virtual pixels, or as a percentage of the space available.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Size {
+    Pixels(f32),
+    Percent(f32),
+}
+
+impl Size {
+    pub fn resolve(&self, available: f32) -> f32 {
+        match self {
+            Size::Pixels(pixels) => *pixels,
+            Size::Percent(percent) => available * percent / 100.0,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Anchor {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+/// The whole screen in virtual pixels, for use as the outermost layout container.
+pub fn screen_rect(app: &Apparatus) -> Rect {
+    Rect::new(
+        0.0,
+        0.0,
+        app.screen_width() as f32,
+        app.screen_height() as f32,
+    )
+}
+
+/// Place a rect of the given size against an edge, corner or the center of `container`,
+/// inset by `margin` from any edge it touches.
+pub fn anchor(container: Rect, width: Size, height: Size, anchor: Anchor, margin: f32) -> Rect {
+    let width = width.resolve(container.width);
+    let height = height.resolve(container.height);
+
+    let left = container.x + margin;
+    let center_x = container.x + (container.width - width) / 2.0;
+    let right = container.x + container.width - width - margin;
+    let bottom = container.y + margin;
+    let center_y = container.y + (container.height - height) / 2.0;
+    let top = container.y + container.height - height - margin;
+
+    let (x, y) = match anchor {
+        Anchor::TopLeft => (left, top),
+        Anchor::Top => (center_x, top),
+        Anchor::TopRight => (right, top),
+        Anchor::Left => (left, center_y),
+        Anchor::Center => (center_x, center_y),
+        Anchor::Right => (right, center_y),
+        Anchor::BottomLeft => (left, bottom),
+        Anchor::Bottom => (center_x, bottom),
+        Anchor::BottomRight => (right, bottom),
+    };
+
+    Rect::new(x, y, width, height)
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Axis {
+    Horizontal,
+    Vertical,
+}
+
+/// Lays children out one after another, left to right or top to bottom.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Stack {
+    axis: Axis,
+    spacing: f32,
+    padding: f32,
+}
+
+impl Stack {
+    pub fn horizontal(spacing: f32) -> Self {
+        Self {
+            axis: Axis::Horizontal,
+            spacing,
+            padding: 0.0,
+        }
+    }
+
+    pub fn vertical(spacing: f32) -> Self {
+        Self {
+            axis: Axis::Vertical,
+            spacing,
+            padding: 0.0,
+        }
+    }
+
+    /// Inset children from every edge of the container. Defaults to 0.
+    pub fn with_padding(mut self, padding: f32) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Compute a rect for each child from its (width, height). Percentages are relative to the
+    /// container after padding. Children are aligned to the left or top of the container.
+    pub fn layout(&self, container: Rect, children: &[(Size, Size)]) -> Vec<Rect> {
+        let inner = Rect::new(
+            container.x + self.padding,
+            container.y + self.padding,
+            container.width - 2.0 * self.padding,
+            container.height - 2.0 * self.padding,
+        );
+        let top = inner.y + inner.height;
+
+        let mut offset = 0.0;
+        children
+            .iter()
+            .map(|(width, height)| {
+                let width = width.resolve(inner.width);
+                let height = height.resolve(inner.height);
+
+                let rect = match self.axis {
+                    Axis::Horizontal => Rect::new(inner.x + offset, top - height, width, height),
+                    Axis::Vertical => Rect::new(inner.x, top - offset - height, width, height),
+                };
+
+                offset += self.spacing
+                    + match self.axis {
+                        Axis::Horizontal => width,
+                        Axis::Vertical => height,
+                    };
+
+                rect
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCREEN: Rect = Rect {
+        x: 0.0,
+        y: 0.0,
+        width: 200.0,
+        height: 100.0,
+    };
+
+    #[test]
+    fn percent_size_is_relative_to_available_space() {
+        assert_eq!(50.0, Size::Percent(25.0).resolve(200.0));
+        assert_eq!(12.0, Size::Pixels(12.0).resolve(200.0));
+    }
+
+    #[test]
+    fn anchor_top_right_is_inset_by_margin() {
+        let rect = anchor(
+            SCREEN,
+            Size::Pixels(20.0),
+            Size::Pixels(10.0),
+            Anchor::TopRight,
+            2.0,
+        );
+
+        assert_eq!(Rect::new(178.0, 88.0, 20.0, 10.0), rect);
+    }
+
+    #[test]
+    fn anchor_center_ignores_margin() {
+        let rect = anchor(
+            SCREEN,
+            Size::Percent(50.0),
+            Size::Percent(50.0),
+            Anchor::Center,
+            5.0,
+        );
+
+        assert_eq!(Rect::new(50.0, 25.0, 100.0, 50.0), rect);
+    }
+
+    #[test]
+    fn vertical_stack_lays_out_top_to_bottom() {
+        let rects = Stack::vertical(5.0).with_padding(10.0).layout(
+            SCREEN,
+            &[
+                (Size::Percent(100.0), Size::Pixels(20.0)),
+                (Size::Pixels(40.0), Size::Pixels(10.0)),
+            ],
+        );
+
+        assert_eq!(
+            vec![
+                Rect::new(10.0, 70.0, 180.0, 20.0),
+                Rect::new(10.0, 55.0, 40.0, 10.0)
+            ],
+            rects
+        );
+    }
+
+    #[test]
+    fn horizontal_stack_lays_out_left_to_right() {
+        let rects = Stack::horizontal(4.0).layout(
+            SCREEN,
+            &[
+                (Size::Pixels(30.0), Size::Pixels(10.0)),
+                (Size::Percent(10.0), Size::Pixels(10.0)),
+            ],
+        );
+
+        assert_eq!(
+            vec![
+                Rect::new(0.0, 90.0, 30.0, 10.0),
+                Rect::new(34.0, 90.0, 20.0, 10.0)
+            ],
+            rects
+        );
+    }
+}
@@ -0,0 +1,13 @@
+pub mod anchor;
+pub mod chat;
+pub mod event_log;
+pub mod focus;
+pub mod hit_test;
+pub mod indicator;
+pub mod layout;
+pub mod progress_bar;
+pub mod radial_gauge;
+pub mod save_slots;
+pub mod selection;
+pub mod text_grid;
+pub mod text_input;
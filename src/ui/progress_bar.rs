@@ -0,0 +1,254 @@
+use crate::color;
+use crate::color::Color;
+use crate::engine::apparatus::Apparatus;
+use crate::maths::{clamp, lerp, Rect};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FillDirection {
+    LeftToRight,
+    RightToLeft,
+    BottomToTop,
+    TopToBottom,
+}
+
+impl FillDirection {
+    fn is_horizontal(&self) -> bool {
+        matches!(
+            self,
+            FillDirection::LeftToRight | FillDirection::RightToLeft
+        )
+    }
+}
+
+/// A bar showing `value` out of `max`, e.g. a health bar or power meter.
+/// All positions and sizes are in virtual pixels.
+#[derive(Clone, Debug)]
+pub struct ProgressBar {
+    rect: Rect,
+    direction: FillDirection,
+    fill_start: Color,
+    fill_end: Color,
+    background: Option<Color>,
+    border: Option<Color>,
+    segments: usize,
+    segment_color: Color,
+}
+
+impl ProgressBar {
+    pub fn new(rect: Rect) -> Self {
+        Self {
+            rect,
+            direction: FillDirection::LeftToRight,
+            fill_start: color::css::LIME,
+            fill_end: color::css::LIME,
+            background: None,
+            border: None,
+            segments: 1,
+            segment_color: color::css::BLACK,
+        }
+    }
+
+    pub fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    pub fn set_rect(&mut self, rect: Rect) {
+        self.rect = rect;
+    }
+
+    /// Defaults to `FillDirection::LeftToRight`.
+    pub fn with_direction(mut self, direction: FillDirection) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    /// Fill with a single color. Defaults to lime.
+    pub fn with_fill(mut self, color: Color) -> Self {
+        self.fill_start = color;
+        self.fill_end = color;
+        self
+    }
+
+    /// Fill with a gradient running from the empty end of the bar to the full end.
+    pub fn with_gradient(mut self, start: Color, end: Color) -> Self {
+        self.fill_start = start;
+        self.fill_end = end;
+        self
+    }
+
+    /// Color of the unfilled part of the bar. Defaults to none.
+    pub fn with_background(mut self, color: Color) -> Self {
+        self.background = Some(color);
+        self
+    }
+
+    /// Draw a one pixel border around the outside of the bar. Defaults to none.
+    pub fn with_border(mut self, color: Color) -> Self {
+        self.border = Some(color);
+        self
+    }
+
+    /// Divide the bar into `count` segments separated by tick marks. Defaults to 1.
+    pub fn with_segments(mut self, count: usize, color: Color) -> Self {
+        self.segments = count.max(1);
+        self.segment_color = color;
+        self
+    }
+
+    /// The filled portion of the bar for `value` out of `max`.
+    pub fn fill_rect(&self, value: f32, max: f32) -> Rect {
+        let fraction = if max > 0.0 {
+            clamp(0.0, value / max, 1.0)
+        } else {
+            0.0
+        };
+        let Rect {
+            x,
+            y,
+            width,
+            height,
+        } = self.rect;
+
+        match self.direction {
+            FillDirection::LeftToRight => Rect::new(x, y, (width * fraction).round(), height),
+            FillDirection::RightToLeft => {
+                let filled = (width * fraction).round();
+                Rect::new(x + width - filled, y, filled, height)
+            }
+            FillDirection::BottomToTop => Rect::new(x, y, width, (height * fraction).round()),
+            FillDirection::TopToBottom => {
+                let filled = (height * fraction).round();
+                Rect::new(x, y + height - filled, width, filled)
+            }
+        }
+    }
+
+    pub fn draw(&self, app: &mut Apparatus, value: f32, max: f32) {
+        let Rect {
+            x,
+            y,
+            width,
+            height,
+        } = self.rect;
+
+        if let Some(background) = self.background {
+            self.draw_strips(app, self.rect, |_| background);
+        }
+
+        let fill = self.fill_rect(value, max);
+        let length = if self.direction.is_horizontal() {
+            width
+        } else {
+            height
+        };
+        self.draw_strips(app, fill, |offset| {
+            let t = match self.direction {
+                FillDirection::LeftToRight | FillDirection::BottomToTop => offset / length,
+                FillDirection::RightToLeft | FillDirection::TopToBottom => 1.0 - offset / length,
+            };
+            lerp_color(self.fill_start, self.fill_end, t)
+        });
+
+        for segment in 1..self.segments {
+            let t = segment as f32 / self.segments as f32;
+            if self.direction.is_horizontal() {
+                let tick_x = (x + width * t).round();
+                app.draw_line(tick_x, y, tick_x, y + height - 1.0, self.segment_color);
+            } else {
+                let tick_y = (y + height * t).round();
+                app.draw_line(x, tick_y, x + width - 1.0, tick_y, self.segment_color);
+            }
+        }
+
+        if let Some(border) = self.border {
+            app.draw_wireframe_rectangle(x - 1.0, y - 1.0, width + 1.0, height + 1.0, border);
+        }
+    }
+
+    /// Fill `rect` one pixel wide strip at a time along the fill direction, coloring each strip
+    /// by its offset from the start of the whole bar.
+    fn draw_strips(&self, app: &mut Apparatus, rect: Rect, color: impl Fn(f32) -> Color) {
+        if self.direction.is_horizontal() {
+            for i in 0..rect.width as u32 {
+                let strip_x = rect.x + i as f32;
+                app.draw_line(
+                    strip_x,
+                    rect.y,
+                    strip_x,
+                    rect.y + rect.height - 1.0,
+                    color(strip_x - self.rect.x),
+                );
+            }
+        } else {
+            for i in 0..rect.height as u32 {
+                let strip_y = rect.y + i as f32;
+                app.draw_line(
+                    rect.x,
+                    strip_y,
+                    rect.x + rect.width - 1.0,
+                    strip_y,
+                    color(strip_y - self.rect.y),
+                );
+            }
+        }
+    }
+}
+
+fn lerp_color(start: Color, end: Color, t: f32) -> Color {
+    let channel = |start: u8, end: u8| lerp(end as f32, start as f32, t).round() as u8;
+
+    Color::rgba(
+        channel(start.r(), end.r()),
+        channel(start.g(), end.g()),
+        channel(start.b(), end.b()),
+        channel(start.a(), end.a()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn left_to_right_fill_grows_from_left_edge() {
+        let bar = ProgressBar::new(Rect::new(10.0, 5.0, 100.0, 4.0));
+
+        assert_eq!(Rect::new(10.0, 5.0, 25.0, 4.0), bar.fill_rect(25.0, 100.0));
+    }
+
+    #[test]
+    fn right_to_left_fill_grows_from_right_edge() {
+        let bar = ProgressBar::new(Rect::new(10.0, 5.0, 100.0, 4.0))
+            .with_direction(FillDirection::RightToLeft);
+
+        assert_eq!(Rect::new(85.0, 5.0, 25.0, 4.0), bar.fill_rect(1.0, 4.0));
+    }
+
+    #[test]
+    fn top_to_bottom_fill_grows_from_top_edge() {
+        let bar = ProgressBar::new(Rect::new(0.0, 0.0, 4.0, 50.0))
+            .with_direction(FillDirection::TopToBottom);
+
+        assert_eq!(Rect::new(0.0, 40.0, 4.0, 10.0), bar.fill_rect(20.0, 100.0));
+    }
+
+    #[test]
+    fn fill_is_clamped_to_bar() {
+        let bar = ProgressBar::new(Rect::new(0.0, 0.0, 10.0, 2.0));
+
+        assert_eq!(Rect::new(0.0, 0.0, 10.0, 2.0), bar.fill_rect(150.0, 100.0));
+        assert_eq!(Rect::new(0.0, 0.0, 0.0, 2.0), bar.fill_rect(-5.0, 100.0));
+        assert_eq!(Rect::new(0.0, 0.0, 0.0, 2.0), bar.fill_rect(5.0, 0.0));
+    }
+
+    #[test]
+    fn gradient_interpolates_between_colors() {
+        let color = lerp_color(
+            Color::rgba(0, 0, 0, 255),
+            Color::rgba(200, 100, 50, 255),
+            0.5,
+        );
+
+        assert_eq!(Color::rgba(100, 50, 25, 255), color);
+    }
+}
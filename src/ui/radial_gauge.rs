@@ -0,0 +1,137 @@
+use std::f32::consts::PI;
+
+use crate::color;
+use crate::color::Color;
+use crate::engine::apparatus::Apparatus;
+use crate::maths::{clamp, Vec2};
+
+/// A dial showing `value` out of `max` as a filled arc, e.g. a speedometer or cooldown indicator.
+/// Angles are in radians, counter-clockwise from the positive x-axis, and the arc fills from
+/// `start_angle` towards `end_angle`. Positions and sizes are in virtual pixels.
+#[derive(Clone, Debug)]
+pub struct RadialGauge {
+    center: Vec2,
+    inner_radius: f32,
+    outer_radius: f32,
+    start_angle: f32,
+    end_angle: f32,
+    fill: Color,
+    background: Option<Color>,
+    needle: Option<Color>,
+}
+
+impl RadialGauge {
+    /// Create a gauge that sweeps clockwise from bottom left to bottom right, like a speedometer.
+    pub fn new(center: Vec2, inner_radius: f32, outer_radius: f32) -> Self {
+        Self {
+            center,
+            inner_radius,
+            outer_radius,
+            start_angle: 1.25 * PI,
+            end_angle: -0.25 * PI,
+            fill: color::css::ORANGE,
+            background: None,
+            needle: None,
+        }
+    }
+
+    pub fn set_center(&mut self, center: Vec2) {
+        self.center = center;
+    }
+
+    pub fn with_angles(mut self, start_angle: f32, end_angle: f32) -> Self {
+        self.start_angle = start_angle;
+        self.end_angle = end_angle;
+        self
+    }
+
+    /// Defaults to orange.
+    pub fn with_fill(mut self, color: Color) -> Self {
+        self.fill = color;
+        self
+    }
+
+    /// Color of the unfilled part of the arc. Defaults to none.
+    pub fn with_background(mut self, color: Color) -> Self {
+        self.background = Some(color);
+        self
+    }
+
+    /// Draw a needle from the center to the current value. Defaults to none.
+    pub fn with_needle(mut self, color: Color) -> Self {
+        self.needle = Some(color);
+        self
+    }
+
+    /// The angle of the arc's leading edge for `value` out of `max`.
+    pub fn value_angle(&self, value: f32, max: f32) -> f32 {
+        let fraction = if max > 0.0 {
+            clamp(0.0, value / max, 1.0)
+        } else {
+            0.0
+        };
+
+        self.start_angle + (self.end_angle - self.start_angle) * fraction
+    }
+
+    pub fn draw(&self, app: &mut Apparatus, value: f32, max: f32) {
+        let Vec2 { x, y } = self.center;
+        let value_angle = self.value_angle(value, max);
+
+        if let Some(background) = self.background {
+            app.draw_filled_arc(
+                x,
+                y,
+                self.inner_radius,
+                self.outer_radius,
+                self.start_angle,
+                self.end_angle,
+                background,
+            );
+        }
+
+        if value_angle != self.start_angle {
+            app.draw_filled_arc(
+                x,
+                y,
+                self.inner_radius,
+                self.outer_radius,
+                self.start_angle,
+                value_angle,
+                self.fill,
+            );
+        }
+
+        if let Some(needle) = self.needle {
+            app.draw_line(
+                x,
+                y,
+                x + self.outer_radius * value_angle.cos(),
+                y + self.outer_radius * value_angle.sin(),
+                needle,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn value_angle_interpolates_between_start_and_end() {
+        let gauge = RadialGauge::new(Vec2::new(0.0, 0.0), 5.0, 10.0).with_angles(0.0, PI);
+
+        assert_eq!(0.0, gauge.value_angle(0.0, 10.0));
+        assert_eq!(PI / 2.0, gauge.value_angle(5.0, 10.0));
+        assert_eq!(PI, gauge.value_angle(20.0, 10.0));
+    }
+
+    #[test]
+    fn default_gauge_sweeps_clockwise() {
+        let gauge = RadialGauge::new(Vec2::new(0.0, 0.0), 5.0, 10.0);
+
+        assert!(gauge.value_angle(1.0, 2.0) < gauge.value_angle(0.0, 2.0));
+        assert!((PI / 2.0 - gauge.value_angle(1.0, 2.0)).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,400 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use image::imageops::{self, FilterType};
+use image::ImageError;
+use thiserror::Error;
+
+use crate::color;
+use crate::color::Color;
+use crate::engine::apparatus::Apparatus;
+use crate::engine::key::Key;
+use crate::engine::sprite::Sprite;
+
+/// How wide thumbnails are saved, in window pixels.
+const THUMBNAIL_WIDTH: u32 = 96;
+
+const SAVE_TITLE: (&str, &str) = ("apparatus.slots.save_title", "Save Game");
+const LOAD_TITLE: (&str, &str) = ("apparatus.slots.load_title", "Load Game");
+const SLOT: (&str, &str) = ("apparatus.slots.slot", "Slot {number}");
+const EMPTY: (&str, &str) = ("apparatus.slots.empty", "Empty");
+const CONFIRM_OVERWRITE: (&str, &str) =
+    ("apparatus.slots.confirm_overwrite", "Overwrite this save?");
+const CONFIRM_DELETE: (&str, &str) = ("apparatus.slots.confirm_delete", "Delete this save?");
+const CONFIRM_HINT: (&str, &str) = (
+    "apparatus.slots.confirm_hint",
+    "Enter to confirm, Escape to cancel",
+);
+const HINT: (&str, &str) = (
+    "apparatus.slots.hint",
+    "Enter to choose, Backspace to delete, Escape to go back",
+);
+
+#[derive(Debug, Error)]
+pub enum SaveSlotError {
+    #[error("unable to access save slot")]
+    Io(#[from] std::io::Error),
+    #[error("unable to write save slot thumbnail")]
+    Image(#[from] ImageError),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SlotMode {
+    Save,
+    Load,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SlotCommand {
+    Up,
+    Down,
+    Select,
+    Delete,
+    Back,
+}
+
+/// What the player chose. The game does the saving and loading itself, using
+/// `SaveSlotScreen::save_path`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SlotAction {
+    Save(usize),
+    Load(usize),
+    Delete(usize),
+    Back,
+}
+
+/// What's in a save slot, as last read by `SaveSlotScreen::refresh`.
+#[derive(Clone, Default)]
+pub struct SlotInfo {
+    /// When the slot was last saved, or `None` if it's empty.
+    pub modified: Option<SystemTime>,
+    pub thumbnail: Option<Sprite>,
+}
+
+impl SlotInfo {
+    pub fn is_empty(&self) -> bool {
+        self.modified.is_none()
+    }
+}
+
+/// A ready-made screen listing save slots with when they were saved and a thumbnail of the game
+/// at the time, asking before a save is overwritten or deleted. Slot `n` is saved by the game to
+/// `slot-n.<extension>` in the directory, with its thumbnail alongside as `slot-n.png`.
+/// Text is looked up in the game's localization under `apparatus.slots.*`, falling back to
+/// English. Positions are in window pixels, measured from the bottom left.
+pub struct SaveSlotScreen {
+    directory: PathBuf,
+    extension: String,
+    slots: Vec<SlotInfo>,
+    mode: SlotMode,
+    selected: usize,
+    confirming: Option<SlotAction>,
+}
+
+impl SaveSlotScreen {
+    pub fn new(directory: impl Into<PathBuf>, count: usize) -> Self {
+        Self {
+            directory: directory.into(),
+            extension: String::from("toml"),
+            slots: vec![SlotInfo::default(); count.max(1)],
+            mode: SlotMode::Load,
+            selected: 0,
+            confirming: None,
+        }
+    }
+
+    /// The extension of the files the game saves slots to. Defaults to `toml`.
+    pub fn with_extension(mut self, extension: impl Into<String>) -> Self {
+        self.extension = extension.into();
+        self
+    }
+
+    /// Where the game should save or load `slot`.
+    pub fn save_path(&self, slot: usize) -> PathBuf {
+        self.directory
+            .join(format!("slot-{}.{}", slot, self.extension))
+    }
+
+    pub fn thumbnail_path(&self, slot: usize) -> PathBuf {
+        self.directory.join(format!("slot-{}.png", slot))
+    }
+
+    pub fn slots(&self) -> &[SlotInfo] {
+        &self.slots
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    pub fn mode(&self) -> SlotMode {
+        self.mode
+    }
+
+    /// Show the screen for saving or loading, re-reading the slots from disk.
+    pub fn open(&mut self, mode: SlotMode) {
+        self.mode = mode;
+        self.selected = 0;
+        self.confirming = None;
+        self.refresh();
+    }
+
+    /// Re-read when each slot was saved and its thumbnail. Slots that can't be read are shown as
+    /// empty and thumbnails that can't be read are left out.
+    pub fn refresh(&mut self) {
+        for slot in 0..self.slots.len() {
+            let modified = std::fs::metadata(self.save_path(slot))
+                .and_then(|metadata| metadata.modified())
+                .ok();
+            let thumbnail =
+                modified.and_then(|_| Sprite::from_file(self.thumbnail_path(slot)).ok());
+            self.slots[slot] = SlotInfo {
+                modified,
+                thumbnail,
+            };
+        }
+    }
+
+    /// Save a small copy of the last presented frame as `slot`'s thumbnail. Call it after saving
+    /// the game, and `refresh` to show it.
+    pub fn write_thumbnail(&self, app: &Apparatus, slot: usize) -> Result<(), SaveSlotError> {
+        std::fs::create_dir_all(&self.directory)?;
+
+        let frame = app.frame_image();
+        let height = (frame.height() * THUMBNAIL_WIDTH / frame.width().max(1)).max(1);
+        imageops::resize(&frame, THUMBNAIL_WIDTH, height, FilterType::Triangle)
+            .save(self.thumbnail_path(slot))?;
+        Ok(())
+    }
+
+    /// Remove `slot`'s save and thumbnail, e.g. on `SlotAction::Delete`.
+    pub fn delete(&mut self, slot: usize) -> Result<(), SaveSlotError> {
+        remove_if_present(&self.save_path(slot))?;
+        remove_if_present(&self.thumbnail_path(slot))?;
+        if let Some(info) = self.slots.get_mut(slot) {
+            *info = SlotInfo::default();
+        }
+        Ok(())
+    }
+
+    /// Apply a navigation command, returning an action once the player has made a choice.
+    /// Saving over or deleting a saved slot asks for confirmation first; loading or deleting an
+    /// empty slot does nothing.
+    pub fn handle(&mut self, command: SlotCommand) -> Option<SlotAction> {
+        if let Some(action) = self.confirming {
+            return match command {
+                SlotCommand::Select => {
+                    self.confirming = None;
+                    Some(action)
+                }
+                SlotCommand::Back => {
+                    self.confirming = None;
+                    None
+                }
+                _ => None,
+            };
+        }
+
+        let count = self.slots.len();
+        let empty = self.slots[self.selected].is_empty();
+        match command {
+            SlotCommand::Up => {
+                self.selected = (self.selected + count - 1) % count;
+                None
+            }
+            SlotCommand::Down => {
+                self.selected = (self.selected + 1) % count;
+                None
+            }
+            SlotCommand::Select => match self.mode {
+                SlotMode::Save if empty => Some(SlotAction::Save(self.selected)),
+                SlotMode::Save => {
+                    self.confirming = Some(SlotAction::Save(self.selected));
+                    None
+                }
+                SlotMode::Load if empty => None,
+                SlotMode::Load => Some(SlotAction::Load(self.selected)),
+            },
+            SlotCommand::Delete if empty => None,
+            SlotCommand::Delete => {
+                self.confirming = Some(SlotAction::Delete(self.selected));
+                None
+            }
+            SlotCommand::Back => Some(SlotAction::Back),
+        }
+    }
+
+    /// Handle this frame's key presses: arrows to move, Enter to choose, Backspace to delete and
+    /// Escape to go back.
+    pub fn update(&mut self, app: &Apparatus) -> Option<SlotAction> {
+        let commands = [
+            (Key::Up, SlotCommand::Up),
+            (Key::Down, SlotCommand::Down),
+            (Key::Enter, SlotCommand::Select),
+            (Key::Backspace, SlotCommand::Delete),
+            (Key::Escape, SlotCommand::Back),
+        ];
+
+        commands
+            .iter()
+            .filter(|(key, _)| app.is_key_pressed(*key))
+            .find_map(|&(_, command)| self.handle(command))
+    }
+
+    pub fn draw(&self, app: &mut Apparatus) {
+        let localization = app.localization();
+        let text = |(key, default)| localization.get_or(key, default).to_string();
+        let title = text(match self.mode {
+            SlotMode::Save => SAVE_TITLE,
+            SlotMode::Load => LOAD_TITLE,
+        });
+        let confirm = self.confirming.map(|action| match action {
+            SlotAction::Delete(_) => text(CONFIRM_DELETE),
+            _ => text(CONFIRM_OVERWRITE),
+        });
+        let hint = text(if confirm.is_some() {
+            CONFIRM_HINT
+        } else {
+            HINT
+        });
+        let (slot_label, empty_label) = (text(SLOT), text(EMPTY));
+
+        let (width, height) = (app.window_width(), app.window_height());
+        let scale_x = width / app.screen_width() as f32;
+        let scale_y = height / app.screen_height() as f32;
+        app.draw_filled_rectangle(
+            0.0,
+            0.0,
+            width / scale_x,
+            height / scale_y,
+            Color::rgba(0, 0, 0, 200),
+        );
+
+        let x = 40.0;
+        let mut y = height - 50.0;
+        app.draw_string(title, x, y, color::css::WHITE, 24.0);
+        y -= 20.0;
+
+        let row_height = 60.0;
+        let now = SystemTime::now();
+        for (i, slot) in self.slots.iter().enumerate() {
+            y -= row_height;
+            let color = if i == self.selected {
+                color::css::YELLOW
+            } else {
+                color::css::WHITE
+            };
+
+            if let Some(thumbnail) = &slot.thumbnail {
+                // Fit the thumbnail to the row, converting to virtual pixels.
+                let scale = (row_height - 8.0) / scale_y / thumbnail.height().max(1) as f32;
+                app.draw_sprite_scaled(x / scale_x, y / scale_y, thumbnail, scale, scale);
+            }
+
+            let label_x = x + THUMBNAIL_WIDTH as f32 + 16.0;
+            let marker = if i == self.selected { "> " } else { "  " };
+            let name = slot_label.replace("{number}", &(i + 1).to_string());
+            app.draw_string(
+                format!("{}{}", marker, name),
+                label_x,
+                y + 30.0,
+                color,
+                16.0,
+            );
+            let saved = match slot.modified {
+                Some(modified) => age_label(now.duration_since(modified).unwrap_or_default()),
+                None => empty_label.clone(),
+            };
+            app.draw_string(saved, label_x + 16.0, y + 12.0, color::css::SILVER, 12.0);
+        }
+
+        if let Some(confirm) = confirm {
+            app.draw_string(confirm, x, 50.0, color::css::WHITE, 16.0);
+        }
+        app.draw_string(hint, x, 30.0, color::css::SILVER, 12.0);
+    }
+}
+
+fn remove_if_present(path: &Path) -> std::io::Result<()> {
+    match std::fs::remove_file(path) {
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        result => result,
+    }
+}
+
+/// How long ago a slot was saved, e.g. "5 minutes ago".
+fn age_label(age: Duration) -> String {
+    let minutes = age.as_secs() / 60;
+    let (amount, unit) = match minutes {
+        0 => return String::from("Just now"),
+        1..=59 => (minutes, "minute"),
+        60..=1439 => (minutes / 60, "hour"),
+        _ => (minutes / 1440, "day"),
+    };
+
+    if amount == 1 {
+        format!("1 {} ago", unit)
+    } else {
+        format!("{} {}s ago", amount, unit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn screen(mode: SlotMode) -> SaveSlotScreen {
+        let mut screen = SaveSlotScreen::new("saves", 3);
+        screen.mode = mode;
+        screen.slots[1].modified = Some(SystemTime::UNIX_EPOCH);
+        screen
+    }
+
+    #[test]
+    fn saving_over_a_slot_needs_confirmation() {
+        let mut screen = screen(SlotMode::Save);
+
+        assert_eq!(
+            Some(SlotAction::Save(0)),
+            screen.handle(SlotCommand::Select)
+        );
+
+        screen.handle(SlotCommand::Down);
+        assert_eq!(None, screen.handle(SlotCommand::Select));
+        assert_eq!(None, screen.handle(SlotCommand::Back));
+        assert_eq!(None, screen.handle(SlotCommand::Select));
+        assert_eq!(
+            Some(SlotAction::Save(1)),
+            screen.handle(SlotCommand::Select)
+        );
+    }
+
+    #[test]
+    fn only_saved_slots_load_or_delete() {
+        let mut screen = screen(SlotMode::Load);
+
+        assert_eq!(None, screen.handle(SlotCommand::Select));
+        assert_eq!(None, screen.handle(SlotCommand::Delete));
+
+        screen.handle(SlotCommand::Up);
+        screen.handle(SlotCommand::Up);
+        assert_eq!(
+            Some(SlotAction::Load(1)),
+            screen.handle(SlotCommand::Select)
+        );
+        assert_eq!(None, screen.handle(SlotCommand::Delete));
+        assert_eq!(
+            Some(SlotAction::Delete(1)),
+            screen.handle(SlotCommand::Select)
+        );
+        assert_eq!(Some(SlotAction::Back), screen.handle(SlotCommand::Back));
+    }
+
+    #[test]
+    fn ages_are_shown_in_the_largest_whole_unit() {
+        assert_eq!("Just now", age_label(Duration::from_secs(59)));
+        assert_eq!("1 minute ago", age_label(Duration::from_secs(60)));
+        assert_eq!("3 hours ago", age_label(Duration::from_secs(3 * 3600 + 10)));
+        assert_eq!("2 days ago", age_label(Duration::from_secs(2 * 86400)));
+    }
+}
@@ -0,0 +1,185 @@
+use crate::color;
+use crate::color::Color;
+use crate::engine::apparatus::Apparatus;
+use crate::engine::mouse::MouseButton;
+use crate::maths::{Rect, Vec2};
+
+/// Click and drag with the mouse to select a rectangle in world space, e.g. to pick units in an
+/// RTS or objects in an editor. Positions are in the world, seen through the camera set with
+/// `Apparatus::set_camera`.
+#[derive(Clone, Debug)]
+pub struct DragSelection {
+    button: MouseButton,
+    start: Option<Vec2>,
+    current: Vec2,
+    color: Color,
+    fill: Option<Color>,
+}
+
+impl Default for DragSelection {
+    fn default() -> Self {
+        Self {
+            button: MouseButton::Left,
+            start: None,
+            current: Vec2::default(),
+            color: color::css::LIME,
+            fill: Some(Color::rgba(0, 255, 0, 48)),
+        }
+    }
+}
+
+impl DragSelection {
+    /// Defaults to the left mouse button.
+    pub fn with_button(mut self, button: MouseButton) -> Self {
+        self.button = button;
+        self
+    }
+
+    /// Defaults to lime.
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Translucent color drawn inside the marquee, or `None` for an outline only.
+    pub fn with_fill(mut self, fill: Option<Color>) -> Self {
+        self.fill = fill;
+        self
+    }
+
+    pub fn is_dragging(&self) -> bool {
+        self.start.is_some()
+    }
+
+    /// Track the mouse for this frame. Returns the selected rectangle on the frame the button
+    /// is released.
+    pub fn update(&mut self, app: &Apparatus) -> Option<Rect> {
+        let point = app.mouse_world_pos();
+
+        if app.is_mouse_button_held(self.button) {
+            self.drag_to(point);
+            None
+        } else if app.was_mouse_button_released(self.button) {
+            self.drag_to(point);
+            self.finish()
+        } else {
+            self.start = None;
+            None
+        }
+    }
+
+    /// Start a drag at `point` if one isn't in progress, otherwise extend it to `point`.
+    pub fn drag_to(&mut self, point: Vec2) {
+        self.start.get_or_insert(point);
+        self.current = point;
+    }
+
+    /// End the drag, returning the selected rectangle.
+    pub fn finish(&mut self) -> Option<Rect> {
+        let rect = self.marquee();
+        self.start = None;
+
+        rect
+    }
+
+    /// The rectangle being dragged out in world space.
+    pub fn marquee(&self) -> Option<Rect> {
+        let start = self.start?;
+        let (min_x, max_x) = (start.x.min(self.current.x), start.x.max(self.current.x));
+        let (min_y, max_y) = (start.y.min(self.current.y), start.y.max(self.current.y));
+
+        Some(Rect::new(min_x, min_y, max_x - min_x, max_y - min_y))
+    }
+
+    pub fn draw(&self, app: &mut Apparatus) {
+        let Rect {
+            x,
+            y,
+            width,
+            height,
+        } = match self.marquee() {
+            Some(rect) => rect,
+            None => return,
+        };
+
+        if let Some(fill) = self.fill {
+            app.draw_filled_rectangle(x, y, width, height, fill);
+        }
+        app.draw_wireframe_rectangle(x, y, width, height, self.color);
+    }
+}
+
+/// Whether `point` lies inside `rect`, including its edges.
+pub fn contains(rect: &Rect, point: Vec2) -> bool {
+    point.x >= rect.x
+        && point.x <= rect.x + rect.width
+        && point.y >= rect.y
+        && point.y <= rect.y + rect.height
+}
+
+/// The items whose positions lie inside `rect`. A selection smaller than `click_radius` in both
+/// directions is treated as a click and picks the single nearest item within that radius.
+pub fn select<T>(
+    rect: &Rect,
+    items: impl IntoIterator<Item = (T, Vec2)>,
+    click_radius: f32,
+) -> Vec<T> {
+    if rect.width >= click_radius || rect.height >= click_radius {
+        return items
+            .into_iter()
+            .filter(|(_, position)| contains(rect, *position))
+            .map(|(item, _)| item)
+            .collect();
+    }
+
+    let center = Vec2::new(rect.x + rect.width / 2.0, rect.y + rect.height / 2.0);
+    items
+        .into_iter()
+        .map(|(item, position)| (item, (position - center).length()))
+        .filter(|(_, distance)| *distance <= click_radius)
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(item, _)| vec![item])
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dragging_in_any_direction_gives_normalized_rect() {
+        let mut selection = DragSelection::default();
+        selection.drag_to(Vec2::new(50.0, 40.0));
+        selection.drag_to(Vec2::new(10.0, 60.0));
+
+        assert!(selection.is_dragging());
+        assert_eq!(Some(Rect::new(10.0, 40.0, 40.0, 20.0)), selection.finish());
+        assert!(!selection.is_dragging());
+    }
+
+    #[test]
+    fn select_returns_items_inside_the_box() {
+        let units = vec![
+            ("archer", Vec2::new(15.0, 45.0)),
+            ("knight", Vec2::new(80.0, 45.0)),
+            ("peasant", Vec2::new(30.0, 50.0)),
+        ];
+
+        let selected = select(&Rect::new(10.0, 40.0, 40.0, 20.0), units, 4.0);
+
+        assert_eq!(vec!["archer", "peasant"], selected);
+    }
+
+    #[test]
+    fn tiny_selection_picks_nearest_item() {
+        let units = vec![
+            ("archer", Vec2::new(12.0, 10.0)),
+            ("knight", Vec2::new(11.0, 10.0)),
+            ("peasant", Vec2::new(80.0, 80.0)),
+        ];
+
+        let selected = select(&Rect::new(10.0, 10.0, 1.0, 0.0), units, 4.0);
+
+        assert_eq!(vec!["knight"], selected);
+    }
+}
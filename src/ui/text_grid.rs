@@ -0,0 +1,163 @@
+use crate::color;
+use crate::color::Color;
+
+/// One character cell of a `TextGrid`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct GridCell {
+    pub character: char,
+    pub foreground: Color,
+    pub background: Color,
+}
+
+impl Default for GridCell {
+    fn default() -> Self {
+        Self {
+            character: ' ',
+            foreground: color::css::WHITE,
+            background: color::css::BLACK,
+        }
+    }
+}
+
+/// A console-style grid of fixed size character cells, e.g. for a roguelike map or a debug
+/// console. Cells are addressed by column and row from the top left, and sized in virtual
+/// pixels. Glyphs are rendered once per cell size and reused, so redrawing a full grid every
+/// frame is cheap.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextGrid {
+    columns: usize,
+    rows: usize,
+    cell_width: usize,
+    cell_height: usize,
+    cells: Vec<GridCell>,
+}
+
+impl TextGrid {
+    pub fn new(columns: usize, rows: usize, cell_width: usize, cell_height: usize) -> Self {
+        Self {
+            columns,
+            rows,
+            cell_width,
+            cell_height,
+            cells: vec![GridCell::default(); columns * rows],
+        }
+    }
+
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cell_width(&self) -> usize {
+        self.cell_width
+    }
+
+    pub fn cell_height(&self) -> usize {
+        self.cell_height
+    }
+
+    pub fn get(&self, column: usize, row: usize) -> Option<&GridCell> {
+        if column >= self.columns || row >= self.rows {
+            return None;
+        }
+
+        self.cells.get(row * self.columns + column)
+    }
+
+    /// Set a cell. Cells outside the grid are ignored.
+    pub fn set(
+        &mut self,
+        column: usize,
+        row: usize,
+        character: char,
+        foreground: Color,
+        background: Color,
+    ) {
+        if column < self.columns && row < self.rows {
+            self.cells[row * self.columns + column] = GridCell {
+                character,
+                foreground,
+                background,
+            };
+        }
+    }
+
+    /// Write `text` along a row from `column`, clipped at the right edge. Returns the number of
+    /// cells written.
+    pub fn print(
+        &mut self,
+        column: usize,
+        row: usize,
+        text: &str,
+        foreground: Color,
+        background: Color,
+    ) -> usize {
+        let mut written = 0;
+        for (offset, character) in text.chars().enumerate() {
+            if column + offset >= self.columns || row >= self.rows {
+                break;
+            }
+            self.set(column + offset, row, character, foreground, background);
+            written += 1;
+        }
+
+        written
+    }
+
+    /// Blank every cell to `background`.
+    pub fn clear(&mut self, background: Color) {
+        self.cells.fill(GridCell {
+            background,
+            ..GridCell::default()
+        });
+    }
+
+    /// Move every row up by `lines`, blanking the rows revealed at the bottom, e.g. to add a line
+    /// to a console log.
+    pub fn scroll_up(&mut self, lines: usize, background: Color) {
+        let lines = lines.min(self.rows);
+        self.cells.rotate_left(lines * self.columns);
+        let blank = GridCell {
+            background,
+            ..GridCell::default()
+        };
+        let revealed = (self.rows - lines) * self.columns;
+        self.cells[revealed..].fill(blank);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn print_clips_at_the_right_edge() {
+        let mut grid = TextGrid::new(4, 2, 8, 8);
+
+        let written = grid.print(2, 1, "hello", color::css::RED, color::css::BLACK);
+
+        assert_eq!(2, written);
+        assert_eq!(Some('h'), grid.get(2, 1).map(|cell| cell.character));
+        assert_eq!(Some('e'), grid.get(3, 1).map(|cell| cell.character));
+        assert_eq!(None, grid.get(4, 1));
+    }
+
+    #[test]
+    fn scrolling_moves_rows_up_and_blanks_the_bottom() {
+        let mut grid = TextGrid::new(2, 3, 8, 8);
+        grid.print(0, 1, "ab", color::css::WHITE, color::css::BLACK);
+        grid.print(0, 2, "cd", color::css::WHITE, color::css::BLACK);
+
+        grid.scroll_up(1, color::css::BLUE);
+
+        assert_eq!(Some('a'), grid.get(0, 0).map(|cell| cell.character));
+        assert_eq!(Some('c'), grid.get(0, 1).map(|cell| cell.character));
+        assert_eq!(
+            Some(color::css::BLUE),
+            grid.get(1, 2).map(|cell| cell.background)
+        );
+    }
+}
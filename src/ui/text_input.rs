@@ -0,0 +1,77 @@
+use crate::engine::apparatus::Apparatus;
+use crate::engine::key::Key;
+
+/// A single line of editable text, fed from the characters typed each frame.
+#[derive(Clone, Debug, Default)]
+pub struct TextInput {
+    text: String,
+    max_length: Option<usize>,
+}
+
+impl TextInput {
+    /// Limit the number of characters. Defaults to unlimited.
+    pub fn with_max_length(mut self, max_length: usize) -> Self {
+        self.max_length = Some(max_length);
+        self
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn set_text(&mut self, text: impl Into<String>) {
+        self.text = text.into();
+        self.truncate();
+    }
+
+    pub fn clear(&mut self) {
+        self.text.clear();
+    }
+
+    /// Apply this frame's typing. Returns the text, and clears it, when Enter is pressed.
+    pub fn update(&mut self, app: &Apparatus) -> Option<String> {
+        self.edit(
+            app.typed_text(),
+            app.is_key_pressed(Key::Backspace),
+            app.is_key_pressed(Key::Enter),
+        )
+    }
+
+    fn edit(&mut self, typed: &str, backspace: bool, enter: bool) -> Option<String> {
+        if backspace {
+            self.text.pop();
+        }
+        self.text.push_str(typed);
+        self.truncate();
+
+        if enter {
+            Some(std::mem::take(&mut self.text))
+        } else {
+            None
+        }
+    }
+
+    fn truncate(&mut self) {
+        if let Some(max_length) = self.max_length {
+            if let Some((index, _)) = self.text.char_indices().nth(max_length) {
+                self.text.truncate(index);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn typing_backspace_and_submit() {
+        let mut input = TextInput::default().with_max_length(5);
+
+        assert_eq!(None, input.edit("héllo world", false, false));
+        assert_eq!("héllo", input.text());
+        assert_eq!(None, input.edit("", true, false));
+        assert_eq!(Some("héll!".to_string()), input.edit("!", false, true));
+        assert_eq!("", input.text());
+    }
+}
@@ -1,13 +1,16 @@
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::Duration;
 
 use thiserror::Error;
 
 use crate::engine::clock::Clock;
 
-static mut SLEEP_TOLERANCE: Duration = Duration::from_micros(0);
+/// How much earlier than asked `sleep` wakes the thread, in microseconds, to make up for the
+/// platform oversleeping. Grows each time a sleep overshoots.
+static SLEEP_TOLERANCE_MICROS: AtomicU64 = AtomicU64::new(0);
 
 pub(crate) fn get_sleep_tolerance() -> Duration {
-    unsafe { SLEEP_TOLERANCE }
+    Duration::from_micros(SLEEP_TOLERANCE_MICROS.load(Ordering::Relaxed))
 }
 
 #[derive(Debug, Error)]
@@ -20,7 +23,7 @@ pub fn sleep(duration: Duration) -> Result<(), SleepError> {
     let mut clock = Clock::default();
     clock.tick();
 
-    let tolerance = unsafe { SLEEP_TOLERANCE };
+    let tolerance = get_sleep_tolerance();
 
     if tolerance < duration {
         if duration - tolerance > Duration::from_secs_f32(0.0) {
@@ -29,9 +32,7 @@ pub fn sleep(duration: Duration) -> Result<(), SleepError> {
 
         let elapsed = clock.elapsed();
         if elapsed > duration {
-            unsafe {
-                SLEEP_TOLERANCE += Duration::from_micros(100);
-            }
+            SLEEP_TOLERANCE_MICROS.fetch_add(100, Ordering::Relaxed);
             return Err(SleepError::TargetDurationExceeded(elapsed - duration));
         }
     }